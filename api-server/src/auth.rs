@@ -1,6 +1,7 @@
 use axum::{
-    extract::State,
-    http::StatusCode,
+    async_trait,
+    extract::{FromRequestParts, State},
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -60,6 +61,35 @@ pub fn verify_token(token: &str, secret: &str) -> Result<Claims, AuthError> {
     Ok(token_data.claims)
 }
 
+/// Identity of the caller, from a bearer token or a `token` query parameter
+/// (browsers can't set headers on WebSocket requests). Missing or invalid
+/// tokens yield `None`; this attributes actions and does not gate routes.
+pub struct MaybeUser(pub Option<String>);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for MaybeUser {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        let header = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        let query = parts
+            .uri
+            .query()
+            .and_then(|q| q.split('&').find_map(|pair| pair.strip_prefix("token=")));
+
+        let user = header
+            .or(query)
+            .and_then(|token| verify_token(token, &state.jwt_secret).ok())
+            .map(|claims| claims.sub);
+
+        Ok(MaybeUser(user))
+    }
+}
+
 #[derive(Debug)]
 pub enum AuthError {
     InvalidCredentials,
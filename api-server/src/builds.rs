@@ -0,0 +1,156 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use server_manager::{get_newer_builds, BuildChange, Progress, ServerConfig};
+use std::sync::Arc;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+use crate::{db, jars, state::AppState, upgrades::UpgradeStatus};
+
+const DEFAULT_CHECK_HOURS: u64 = 6;
+/// A year; longer periods would overflow the timer's deadline
+const MAX_CHECK_HOURS: u64 = 24 * 365;
+
+/// Newer builds of the version a server runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildUpdate {
+    /// Newest build offered
+    pub build: String,
+    pub channel: Option<String>,
+    /// Builds released since the installed one
+    pub builds_behind: usize,
+    /// Commits since the installed build, oldest first
+    pub changes: Vec<BuildChange>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BuildTracking {
+    /// Upstream build installed; unknown for uploaded jars
+    pub build: Option<String>,
+    /// Release channel of `build`, known once it has been checked
+    pub channel: Option<String>,
+    /// Build of the jar kept for rollback
+    pub previous_build: Option<String>,
+    /// Stay on `build` even with `auto_update`
+    pub pinned: bool,
+    /// Install the update when the server next starts
+    pub auto_update: bool,
+    pub update: Option<BuildUpdate>,
+    pub checked_at: Option<i64>,
+}
+
+/// Read `BUILD_CHECK_HOURS`, how often to look for newer builds. Zero,
+/// which the timer can't take, and values over a year fall back to the
+/// default.
+pub fn check_interval_from_env() -> Duration {
+    let hours = std::env::var("BUILD_CHECK_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|hours| (1..=MAX_CHECK_HOURS).contains(hours))
+        .unwrap_or(DEFAULT_CHECK_HOURS);
+    Duration::from_secs(hours * 3600)
+}
+
+/// Record the build of a jar just put in place, keeping the one it replaced
+pub async fn installed(state: &AppState, server_id: Uuid, build: Option<String>) -> Result<()> {
+    let mut tracking = db::get_server_build(&state.db, server_id).await?;
+    tracking.previous_build = std::mem::replace(&mut tracking.build, build);
+    tracking.channel = None;
+    tracking.update = None;
+    tracking.checked_at = None;
+    db::set_server_build(&state.db, server_id, &tracking).await
+}
+
+/// The jar was swapped with the one it replaced
+pub async fn rolled_back(state: &AppState, server_id: Uuid) -> Result<()> {
+    let mut tracking = db::get_server_build(&state.db, server_id).await?;
+    std::mem::swap(&mut tracking.build, &mut tracking.previous_build);
+    tracking.channel = None;
+    tracking.update = None;
+    tracking.checked_at = None;
+    db::set_server_build(&state.db, server_id, &tracking).await
+}
+
+/// Ask upstream for builds newer than the server's own
+pub async fn check(state: &AppState, config: &ServerConfig) -> Result<BuildTracking> {
+    let mut tracking = db::get_server_build(&state.db, config.id).await?;
+    let Some(build) = &tracking.build else {
+        return Ok(tracking);
+    };
+
+    let newer = get_newer_builds(&state.upstreams, config.server_type, &config.minecraft_version, build).await?;
+    tracking.update = newer.builds.last().map(|latest| BuildUpdate {
+        build: latest.id.clone(),
+        channel: latest.channel.clone(),
+        builds_behind: newer.builds.len(),
+        changes: newer.builds.iter().flat_map(|b| b.changes.iter().cloned()).collect(),
+    });
+    tracking.channel = newer.channel.or(tracking.channel);
+    tracking.checked_at = Some(chrono::Utc::now().timestamp());
+
+    db::set_server_build(&state.db, config.id, &tracking).await?;
+    Ok(tracking)
+}
+
+/// The build a server that opted in to updates should get as it starts.
+/// None while an upgrade awaits its first start, since updating would
+/// replace the jar that upgrade rolls back to.
+pub async fn pending_update(state: &AppState, server_id: Uuid) -> Result<Option<BuildUpdate>> {
+    let tracking = db::get_server_build(&state.db, server_id).await?;
+    if !tracking.auto_update || tracking.pinned {
+        return Ok(None);
+    }
+    let upgrade = db::get_server_upgrade(&state.db, server_id).await?;
+    if upgrade.is_some_and(|u| u.status == UpgradeStatus::Installed) {
+        return Ok(None);
+    }
+    Ok(tracking.update)
+}
+
+/// Install `update` on a stopped server
+pub async fn apply_update(
+    state: &AppState,
+    config: &ServerConfig,
+    update: &BuildUpdate,
+    progress: &Progress,
+) -> Result<()> {
+    progress.log(format!("Downloading {} build {}", config.server_type.as_str(), update.build));
+    jars::download(state, config, &config.minecraft_version, Some(&update.build), progress).await?;
+    tracing::info!(
+        "Updated server {} to {} build {}",
+        config.id,
+        config.server_type.as_str(),
+        update.build
+    );
+    Ok(())
+}
+
+/// Check every server for newer builds
+pub async fn run(state: Arc<AppState>, interval: Duration) {
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        interval.tick().await;
+
+        let configs = match db::list_servers(&state.db).await {
+            Ok(configs) => configs,
+            Err(e) => {
+                tracing::error!("Failed to list servers for build checks: {}", e);
+                continue;
+            }
+        };
+        for config in configs {
+            match check(&state, &config).await {
+                Ok(BuildTracking { update: Some(update), .. }) => tracing::info!(
+                    "Server {} is {} builds behind {} build {}",
+                    config.id,
+                    update.builds_behind,
+                    config.server_type.as_str(),
+                    update.build
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to check builds for server {}: {:#}", config.id, e),
+            }
+        }
+    }
+}
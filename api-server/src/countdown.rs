@@ -0,0 +1,231 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, State};
+use serde::{Deserialize, Serialize};
+use server_manager::parse_duration;
+use std::sync::Arc;
+use tokio::task::AbortHandle;
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::{routes, state::AppState};
+
+/// Longest countdown a restart can be scheduled with
+const MAX_COUNTDOWN_SECS: u64 = 24 * 3600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartCountdown {
+    /// Times before the restart at which to warn players, e.g. "10m", "30s"
+    #[serde(default = "default_warnings")]
+    pub warnings: Vec<String>,
+    /// Chat message, with `{time}` replaced by the remaining time
+    #[serde(default = "default_message")]
+    pub message: String,
+    /// Also show the warning as an on-screen title
+    #[serde(default)]
+    pub title: bool,
+    /// Restart immediately when nobody is online
+    #[serde(default)]
+    pub skip_if_empty: bool,
+}
+
+impl Default for RestartCountdown {
+    fn default() -> Self {
+        Self {
+            warnings: default_warnings(),
+            message: default_message(),
+            title: false,
+            skip_if_empty: false,
+        }
+    }
+}
+
+fn default_warnings() -> Vec<String> {
+    ["10m", "5m", "1m", "30s", "10s", "5s", "4s", "3s", "2s", "1s"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_message() -> String {
+    "Server restarting in {time}".to_string()
+}
+
+pub struct PendingRestart {
+    pub restart_at: i64,
+    abort: AbortHandle,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CountdownStatus {
+    pub restart_at: i64,
+    pub seconds_remaining: i64,
+}
+
+fn format_remaining(secs: u64) -> String {
+    let (value, unit) = if secs >= 3600 && secs.is_multiple_of(3600) {
+        (secs / 3600, "hour")
+    } else if secs >= 60 && secs.is_multiple_of(60) {
+        (secs / 60, "minute")
+    } else {
+        (secs, "second")
+    };
+
+    if value == 1 {
+        format!("{} {}", value, unit)
+    } else {
+        format!("{} {}s", value, unit)
+    }
+}
+
+pub fn status(pending: &PendingRestart) -> CountdownStatus {
+    CountdownStatus {
+        restart_at: pending.restart_at,
+        seconds_remaining: (pending.restart_at - chrono::Utc::now().timestamp()).max(0),
+    }
+}
+
+/// Start a countdown restart in the background, returning when it will happen
+pub async fn begin(
+    state: &Arc<AppState>,
+    server_id: Uuid,
+    options: RestartCountdown,
+) -> Result<CountdownStatus> {
+    let mut warnings = options
+        .warnings
+        .iter()
+        .map(|w| parse_duration(w))
+        .collect::<Result<Vec<_>>>()?;
+    warnings.sort_unstable_by(|a, b| b.cmp(a));
+    warnings.dedup();
+
+    let total = warnings.first().copied().unwrap_or(0);
+    if total > MAX_COUNTDOWN_SECS {
+        anyhow::bail!("Countdowns can't be longer than {}", format_remaining(MAX_COUNTDOWN_SECS));
+    }
+
+    // Hold the lock while spawning so the task can't remove its entry before
+    // it has been inserted
+    let mut pending = state.pending_restarts.write().await;
+    if pending.contains_key(&server_id) {
+        anyhow::bail!("A restart is already pending for this server");
+    }
+
+    let restart_at = chrono::Utc::now().timestamp() + total as i64;
+
+    let task_state = state.clone();
+    let handle = tokio::spawn(async move {
+        run(&task_state, server_id, options, warnings).await;
+    });
+
+    let entry = PendingRestart {
+        restart_at,
+        abort: handle.abort_handle(),
+    };
+    let status = status(&entry);
+    pending.insert(server_id, entry);
+
+    Ok(status)
+}
+
+/// Cancel a pending countdown restart and tell players about it
+pub async fn cancel(state: &Arc<AppState>, server_id: Uuid) -> Result<()> {
+    let pending = state
+        .pending_restarts
+        .write()
+        .await
+        .remove(&server_id)
+        .context("No restart is pending for this server")?;
+
+    pending.abort.abort();
+    let _ = broadcast(state, server_id, "Scheduled restart cancelled", false).await;
+
+    Ok(())
+}
+
+/// Drop a pending countdown without telling anyone, for when the server is
+/// stopped, restarted, deleted or upgraded in the meantime
+pub async fn abandon(state: &AppState, server_id: Uuid) {
+    if let Some(pending) = state.pending_restarts.write().await.remove(&server_id) {
+        pending.abort.abort();
+        tracing::info!("Dropped the pending restart of {}", server_id);
+    }
+}
+
+async fn run(state: &Arc<AppState>, server_id: Uuid, options: RestartCountdown, warnings: Vec<u64>) {
+    let skip = options.skip_if_empty && player_count(state, server_id).await == Some(0);
+
+    if !skip {
+        let total = warnings.first().copied().unwrap_or(0);
+        let start = Instant::now();
+
+        for remaining in warnings {
+            tokio::time::sleep_until(start + Duration::from_secs(total - remaining)).await;
+
+            let message = options.message.replace("{time}", &format_remaining(remaining));
+            if let Err(e) = broadcast(state, server_id, &message, options.title).await {
+                tracing::warn!("Failed to send restart warning for {}: {}", server_id, e);
+            }
+        }
+
+        tokio::time::sleep_until(start + Duration::from_secs(total)).await;
+    }
+
+    state.pending_restarts.write().await.remove(&server_id);
+
+    // A server that has gone down since was stopped for a reason; restarting
+    // would bring it back up
+    let process = state.processes.read().await.get(&server_id).cloned();
+    let running = match process {
+        Some(process) => process.is_running().await,
+        None => false,
+    };
+    if !running {
+        tracing::info!("{} is no longer running; skipping its countdown restart", server_id);
+        return;
+    }
+
+    if let Err(e) = routes::servers::restart_server(State(state.clone()), Path(server_id)).await {
+        tracing::error!("Countdown restart of {} failed: {:?}", server_id, e);
+    }
+}
+
+async fn player_count(state: &Arc<AppState>, server_id: Uuid) -> Option<u32> {
+    let process = state.processes.read().await.get(&server_id).cloned()?;
+    let count = process.query_player_count().await.ok()?;
+
+    if let Some(instance) = state.servers.write().await.get_mut(&server_id) {
+        instance.players_online = count;
+    }
+    Some(count)
+}
+
+async fn broadcast(state: &Arc<AppState>, server_id: Uuid, message: &str, title: bool) -> Result<()> {
+    let process = state
+        .processes
+        .read()
+        .await
+        .get(&server_id)
+        .cloned()
+        .context("Server is not running")?;
+
+    let message = server_manager::sanitize_console_arg(message);
+    process.send_command(format!("say {}", message)).await?;
+
+    if title {
+        let bedrock = state
+            .servers
+            .read()
+            .await
+            .get(&server_id)
+            .is_some_and(|i| i.config.server_type.is_bedrock());
+        // Bedrock's title takes plain text rather than a JSON component
+        let text = if bedrock {
+            message
+        } else {
+            serde_json::json!({ "text": message }).to_string()
+        };
+        process.send_command(format!("title @a title {}", text)).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{db, state::AppState};
+
+/// Index any crash reports or JVM fatal error logs not yet recorded for a
+/// server, returning how many new ones were found
+pub async fn index(state: &Arc<AppState>, server_id: Uuid) -> Result<usize> {
+    let config = db::get_server(&state.db, server_id)
+        .await?
+        .context("Server not found")?;
+    let server_dir = config.server_dir(&state.servers_dir);
+
+    let mut added = 0;
+    for (path, kind) in server_manager::find_crash_files(&server_dir).await? {
+        let summary = match server_manager::parse_crash_file(&server_dir, &path, kind).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                tracing::warn!("Failed to parse crash file {:?}: {}", path, e);
+                continue;
+            }
+        };
+
+        if db::insert_crash(&state.db, server_id, &summary).await? {
+            tracing::warn!(
+                "Server {} crashed: {}",
+                server_id,
+                summary.exception.as_deref().unwrap_or("unknown cause")
+            );
+            added += 1;
+        }
+    }
+
+    Ok(added)
+}
+
+/// Called when a server process exits; indexes crashes unless it exited cleanly
+pub fn on_exit(state: Arc<AppState>, server_id: Uuid, clean: bool) {
+    if clean {
+        return;
+    }
+
+    tokio::spawn(async move {
+        if let Err(e) = index(&state, server_id).await {
+            tracing::error!("Failed to index crashes for {}: {}", server_id, e);
+        }
+    });
+}
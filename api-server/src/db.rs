@@ -1,10 +1,20 @@
 use anyhow::{Context, Result};
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
 use sqlx::Row;
-use server_manager::{ServerConfig, ServerType};
+use server_manager::{CrashKind, CrashSummary, ServerConfig, ServerType};
 use uuid::Uuid;
 use std::str::FromStr;
 
+use crate::builds::BuildTracking;
+use crate::history::{HistoryEntry, HistoryKind, HistorySearch};
+use crate::jars::{JarRecord, JarSource, ServerJars};
+use crate::jobs::{Job, JobKind, JobLogLine, JobStatus};
+use crate::networks::{Network, NetworkMember};
+use crate::scheduler::{Schedule, ScheduledTask};
+use crate::triggers::{Trigger, TriggerAction};
+use crate::upgrades::{UpgradeRecord, UpgradeStatus};
+use crate::watchdog::{HangAction, WatchdogPolicy};
+
 pub async fn init_db(database_url: &str) -> Result<SqlitePool> {
     let connection_options = SqliteConnectOptions::from_str(database_url)
         .context("Invalid database URL")?
@@ -35,15 +45,275 @@ pub async fn init_db(database_url: &str) -> Result<SqlitePool> {
     .await
     .context("Failed to create servers table")?;
 
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schedules (
+            id TEXT PRIMARY KEY,
+            server_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            cron TEXT NOT NULL,
+            timezone TEXT NOT NULL,
+            task TEXT NOT NULL,
+            enabled INTEGER NOT NULL,
+            last_run_at INTEGER,
+            last_status TEXT,
+            last_error TEXT,
+            next_run_at INTEGER,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create schedules table")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS watchdog_policies (
+            server_id TEXT PRIMARY KEY,
+            enabled INTEGER NOT NULL,
+            hang_timeout_secs INTEGER NOT NULL,
+            startup_grace_secs INTEGER NOT NULL,
+            action TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create watchdog_policies table")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS crashes (
+            id TEXT PRIMARY KEY,
+            server_id TEXT NOT NULL,
+            file TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            description TEXT,
+            exception TEXT,
+            culprit TEXT,
+            occurred_at INTEGER NOT NULL,
+            indexed_at INTEGER NOT NULL,
+            UNIQUE(server_id, file)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create crashes table")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS triggers (
+            id TEXT PRIMARY KEY,
+            server_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            pattern TEXT NOT NULL,
+            threshold INTEGER NOT NULL,
+            window_secs INTEGER NOT NULL,
+            cooldown_secs INTEGER NOT NULL,
+            action TEXT NOT NULL,
+            enabled INTEGER NOT NULL,
+            last_fired_at INTEGER,
+            last_status TEXT,
+            last_error TEXT,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create triggers table")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS console_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            server_id TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            level TEXT,
+            source TEXT,
+            user TEXT,
+            line TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create console_history table")?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_console_history_server_time ON console_history (server_id, timestamp)",
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create console_history index")?;
+
+    // External-content FTS index over the line text, kept in sync by triggers
+    sqlx::query(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS console_history_fts
+        USING fts5(line, content='console_history', content_rowid='id')
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create console_history_fts table")?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS console_history_ai AFTER INSERT ON console_history BEGIN
+            INSERT INTO console_history_fts (rowid, line) VALUES (new.id, new.line);
+        END
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create console_history insert trigger")?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS console_history_ad AFTER DELETE ON console_history BEGIN
+            INSERT INTO console_history_fts (console_history_fts, rowid, line) VALUES ('delete', old.id, old.line);
+        END
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create console_history delete trigger")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS jobs (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            server_id TEXT,
+            status TEXT NOT NULL,
+            progress REAL NOT NULL DEFAULT 0,
+            message TEXT,
+            error TEXT,
+            result TEXT,
+            created_at INTEGER NOT NULL,
+            started_at INTEGER,
+            finished_at INTEGER
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create jobs table")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS job_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            job_id TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            line TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create job_logs table")?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_job_logs_job ON job_logs (job_id, id)")
+        .execute(&pool)
+        .await
+        .context("Failed to create job_logs index")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS networks (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            proxy_id TEXT NOT NULL UNIQUE,
+            forwarding_secret TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create networks table")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS network_servers (
+            server_id TEXT PRIMARY KEY,
+            network_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            UNIQUE(network_id, name)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create network_servers table")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS server_jars (
+            server_id TEXT NOT NULL,
+            slot TEXT NOT NULL,
+            source TEXT NOT NULL,
+            file_name TEXT,
+            version TEXT NOT NULL,
+            sha256 TEXT NOT NULL,
+            main_class TEXT,
+            uploaded_by TEXT,
+            installed_at INTEGER NOT NULL,
+            PRIMARY KEY (server_id, slot)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create server_jars table")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS server_upgrades (
+            server_id TEXT PRIMARY KEY,
+            from_version TEXT NOT NULL,
+            to_version TEXT NOT NULL,
+            jar_sha256 TEXT NOT NULL,
+            backups TEXT NOT NULL,
+            status TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create server_upgrades table")?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS server_builds (
+            server_id TEXT PRIMARY KEY,
+            build TEXT,
+            channel TEXT,
+            previous_build TEXT,
+            pinned INTEGER NOT NULL DEFAULT 0,
+            auto_update INTEGER NOT NULL DEFAULT 0,
+            update_available TEXT,
+            checked_at INTEGER
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .context("Failed to create server_builds table")?;
+
     Ok(pool)
 }
 
 pub async fn create_server(pool: &SqlitePool, config: &ServerConfig) -> Result<()> {
     let properties_json = serde_json::to_string(&config.properties)?;
-    let server_type_str = match config.server_type {
-        ServerType::Paper => "paper",
-        ServerType::Spigot => "spigot",
-    };
+    let server_type_str = config.server_type.as_str();
 
     sqlx::query(
         r#"
@@ -81,11 +351,7 @@ pub async fn get_server(pool: &SqlitePool, id: Uuid) -> Result<Option<ServerConf
     .await?;
 
     if let Some(row) = row {
-        let server_type = match row.get::<String, _>("server_type").as_str() {
-            "paper" => ServerType::Paper,
-            "spigot" => ServerType::Spigot,
-            _ => ServerType::Paper,
-        };
+        let server_type: ServerType = row.get::<String, _>("server_type").parse()?;
 
         let properties: std::collections::HashMap<String, String> =
             serde_json::from_str(row.get("properties"))?;
@@ -120,10 +386,13 @@ pub async fn list_servers(pool: &SqlitePool) -> Result<Vec<ServerConfig>> {
     let mut servers = Vec::new();
 
     for row in rows {
-        let server_type = match row.get::<String, _>("server_type").as_str() {
-            "paper" => ServerType::Paper,
-            "spigot" => ServerType::Spigot,
-            _ => ServerType::Paper,
+        // One server of a type this build doesn't know shouldn't hide the rest
+        let server_type: ServerType = match row.get::<String, _>("server_type").parse() {
+            Ok(server_type) => server_type,
+            Err(e) => {
+                tracing::warn!("Skipping server {}: {}", row.get::<String, _>("id"), e);
+                continue;
+            }
         };
 
         let properties: std::collections::HashMap<String, String> =
@@ -147,10 +416,7 @@ pub async fn list_servers(pool: &SqlitePool) -> Result<Vec<ServerConfig>> {
 
 pub async fn update_server(pool: &SqlitePool, config: &ServerConfig) -> Result<()> {
     let properties_json = serde_json::to_string(&config.properties)?;
-    let server_type_str = match config.server_type {
-        ServerType::Paper => "paper",
-        ServerType::Spigot => "spigot",
-    };
+    let server_type_str = config.server_type.as_str();
 
     sqlx::query(
         r#"
@@ -184,3 +450,1073 @@ pub async fn delete_server(pool: &SqlitePool, id: Uuid) -> Result<()> {
 
     Ok(())
 }
+
+fn schedule_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Schedule> {
+    let task: ScheduledTask = serde_json::from_str(row.get("task"))?;
+
+    Ok(Schedule {
+        id: Uuid::parse_str(row.get("id"))?,
+        server_id: Uuid::parse_str(row.get("server_id"))?,
+        name: row.get("name"),
+        cron: row.get("cron"),
+        timezone: row.get("timezone"),
+        task,
+        enabled: row.get::<i64, _>("enabled") != 0,
+        last_run_at: row.get("last_run_at"),
+        last_status: row.get("last_status"),
+        last_error: row.get("last_error"),
+        next_run_at: row.get("next_run_at"),
+    })
+}
+
+pub async fn create_schedule(pool: &SqlitePool, schedule: &Schedule) -> Result<()> {
+    let task_json = serde_json::to_string(&schedule.task)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO schedules (id, server_id, name, cron, timezone, task, enabled, next_run_at, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(schedule.id.to_string())
+    .bind(schedule.server_id.to_string())
+    .bind(&schedule.name)
+    .bind(&schedule.cron)
+    .bind(&schedule.timezone)
+    .bind(task_json)
+    .bind(schedule.enabled as i64)
+    .bind(schedule.next_run_at)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(pool)
+    .await
+    .context("Failed to insert schedule")?;
+
+    Ok(())
+}
+
+pub async fn get_schedule(pool: &SqlitePool, id: Uuid) -> Result<Option<Schedule>> {
+    let row = sqlx::query("SELECT * FROM schedules WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(schedule_from_row).transpose()
+}
+
+pub async fn list_schedules(pool: &SqlitePool, server_id: Uuid) -> Result<Vec<Schedule>> {
+    let rows = sqlx::query("SELECT * FROM schedules WHERE server_id = ? ORDER BY created_at")
+        .bind(server_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(schedule_from_row).collect()
+}
+
+pub async fn list_enabled_schedules(pool: &SqlitePool) -> Result<Vec<Schedule>> {
+    let rows = sqlx::query("SELECT * FROM schedules WHERE enabled = 1")
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(schedule_from_row).collect()
+}
+
+pub async fn update_schedule(pool: &SqlitePool, schedule: &Schedule) -> Result<()> {
+    let task_json = serde_json::to_string(&schedule.task)?;
+
+    sqlx::query(
+        r#"
+        UPDATE schedules
+        SET name = ?, cron = ?, timezone = ?, task = ?, enabled = ?, next_run_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&schedule.name)
+    .bind(&schedule.cron)
+    .bind(&schedule.timezone)
+    .bind(task_json)
+    .bind(schedule.enabled as i64)
+    .bind(schedule.next_run_at)
+    .bind(schedule.id.to_string())
+    .execute(pool)
+    .await
+    .context("Failed to update schedule")?;
+
+    Ok(())
+}
+
+pub async fn set_schedule_next_run(pool: &SqlitePool, id: Uuid, next_run_at: Option<i64>) -> Result<()> {
+    sqlx::query("UPDATE schedules SET next_run_at = ? WHERE id = ?")
+        .bind(next_run_at)
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to update schedule next run")?;
+
+    Ok(())
+}
+
+pub async fn record_schedule_run(
+    pool: &SqlitePool,
+    id: Uuid,
+    run_at: i64,
+    status: &str,
+    error: Option<&str>,
+) -> Result<()> {
+    sqlx::query("UPDATE schedules SET last_run_at = ?, last_status = ?, last_error = ? WHERE id = ?")
+        .bind(run_at)
+        .bind(status)
+        .bind(error)
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to record schedule run")?;
+
+    Ok(())
+}
+
+pub async fn delete_schedule(pool: &SqlitePool, id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM schedules WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to delete schedule")?;
+
+    Ok(())
+}
+
+pub async fn delete_server_schedules(pool: &SqlitePool, server_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM schedules WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to delete server schedules")?;
+
+    Ok(())
+}
+
+pub async fn get_watchdog_policy(pool: &SqlitePool, server_id: Uuid) -> Result<Option<WatchdogPolicy>> {
+    let row = sqlx::query("SELECT * FROM watchdog_policies WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|row| {
+        let action = match row.get::<String, _>("action").as_str() {
+            "stop" => HangAction::Stop,
+            "none" => HangAction::None,
+            _ => HangAction::Restart,
+        };
+
+        WatchdogPolicy {
+            enabled: row.get::<i64, _>("enabled") != 0,
+            hang_timeout_secs: row.get::<i64, _>("hang_timeout_secs") as u64,
+            startup_grace_secs: row.get::<i64, _>("startup_grace_secs") as u64,
+            action,
+        }
+    }))
+}
+
+pub async fn set_watchdog_policy(pool: &SqlitePool, server_id: Uuid, policy: &WatchdogPolicy) -> Result<()> {
+    let action = match policy.action {
+        HangAction::Restart => "restart",
+        HangAction::Stop => "stop",
+        HangAction::None => "none",
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO watchdog_policies (server_id, enabled, hang_timeout_secs, startup_grace_secs, action)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(server_id) DO UPDATE SET
+            enabled = excluded.enabled,
+            hang_timeout_secs = excluded.hang_timeout_secs,
+            startup_grace_secs = excluded.startup_grace_secs,
+            action = excluded.action
+        "#,
+    )
+    .bind(server_id.to_string())
+    .bind(policy.enabled as i64)
+    .bind(policy.hang_timeout_secs as i64)
+    .bind(policy.startup_grace_secs as i64)
+    .bind(action)
+    .execute(pool)
+    .await
+    .context("Failed to save watchdog policy")?;
+
+    Ok(())
+}
+
+pub async fn delete_watchdog_policy(pool: &SqlitePool, server_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM watchdog_policies WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to delete watchdog policy")?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CrashRecord {
+    pub id: Uuid,
+    pub server_id: Uuid,
+    #[serde(flatten)]
+    pub summary: CrashSummary,
+    pub indexed_at: i64,
+}
+
+fn crash_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<CrashRecord> {
+    let kind = match row.get::<String, _>("kind").as_str() {
+        "jvm_fatal" => CrashKind::JvmFatal,
+        _ => CrashKind::CrashReport,
+    };
+
+    Ok(CrashRecord {
+        id: Uuid::parse_str(row.get("id"))?,
+        server_id: Uuid::parse_str(row.get("server_id"))?,
+        summary: CrashSummary {
+            file: row.get("file"),
+            kind,
+            description: row.get("description"),
+            exception: row.get("exception"),
+            culprit: row.get("culprit"),
+            occurred_at: row.get("occurred_at"),
+        },
+        indexed_at: row.get("indexed_at"),
+    })
+}
+
+/// Insert a crash summary unless the same file is already indexed.
+/// Returns whether a new record was created.
+pub async fn insert_crash(pool: &SqlitePool, server_id: Uuid, summary: &CrashSummary) -> Result<bool> {
+    let kind = match summary.kind {
+        CrashKind::CrashReport => "crash_report",
+        CrashKind::JvmFatal => "jvm_fatal",
+    };
+
+    let result = sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO crashes (id, server_id, file, kind, description, exception, culprit, occurred_at, indexed_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(server_id.to_string())
+    .bind(&summary.file)
+    .bind(kind)
+    .bind(&summary.description)
+    .bind(&summary.exception)
+    .bind(&summary.culprit)
+    .bind(summary.occurred_at)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(pool)
+    .await
+    .context("Failed to insert crash")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn list_crashes(pool: &SqlitePool, server_id: Uuid) -> Result<Vec<CrashRecord>> {
+    let rows = sqlx::query("SELECT * FROM crashes WHERE server_id = ? ORDER BY occurred_at DESC")
+        .bind(server_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(crash_from_row).collect()
+}
+
+pub async fn get_crash(pool: &SqlitePool, id: Uuid) -> Result<Option<CrashRecord>> {
+    let row = sqlx::query("SELECT * FROM crashes WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(crash_from_row).transpose()
+}
+
+pub async fn delete_server_crashes(pool: &SqlitePool, server_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM crashes WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to delete server crashes")?;
+
+    Ok(())
+}
+
+fn trigger_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Trigger> {
+    let action: TriggerAction = serde_json::from_str(row.get("action"))?;
+
+    Ok(Trigger {
+        id: Uuid::parse_str(row.get("id"))?,
+        server_id: Uuid::parse_str(row.get("server_id"))?,
+        name: row.get("name"),
+        pattern: row.get("pattern"),
+        threshold: row.get::<i64, _>("threshold") as u32,
+        window_secs: row.get::<i64, _>("window_secs") as u64,
+        cooldown_secs: row.get::<i64, _>("cooldown_secs") as u64,
+        action,
+        enabled: row.get::<i64, _>("enabled") != 0,
+        last_fired_at: row.get("last_fired_at"),
+        last_status: row.get("last_status"),
+        last_error: row.get("last_error"),
+    })
+}
+
+pub async fn create_trigger(pool: &SqlitePool, trigger: &Trigger) -> Result<()> {
+    let action_json = serde_json::to_string(&trigger.action)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO triggers (id, server_id, name, pattern, threshold, window_secs, cooldown_secs, action, enabled, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(trigger.id.to_string())
+    .bind(trigger.server_id.to_string())
+    .bind(&trigger.name)
+    .bind(&trigger.pattern)
+    .bind(trigger.threshold as i64)
+    .bind(trigger.window_secs as i64)
+    .bind(trigger.cooldown_secs as i64)
+    .bind(action_json)
+    .bind(trigger.enabled as i64)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(pool)
+    .await
+    .context("Failed to insert trigger")?;
+
+    Ok(())
+}
+
+pub async fn get_trigger(pool: &SqlitePool, id: Uuid) -> Result<Option<Trigger>> {
+    let row = sqlx::query("SELECT * FROM triggers WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(trigger_from_row).transpose()
+}
+
+pub async fn list_triggers(pool: &SqlitePool, server_id: Uuid) -> Result<Vec<Trigger>> {
+    let rows = sqlx::query("SELECT * FROM triggers WHERE server_id = ? ORDER BY created_at")
+        .bind(server_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(trigger_from_row).collect()
+}
+
+pub async fn update_trigger(pool: &SqlitePool, trigger: &Trigger) -> Result<()> {
+    let action_json = serde_json::to_string(&trigger.action)?;
+
+    sqlx::query(
+        r#"
+        UPDATE triggers
+        SET name = ?, pattern = ?, threshold = ?, window_secs = ?, cooldown_secs = ?, action = ?, enabled = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&trigger.name)
+    .bind(&trigger.pattern)
+    .bind(trigger.threshold as i64)
+    .bind(trigger.window_secs as i64)
+    .bind(trigger.cooldown_secs as i64)
+    .bind(action_json)
+    .bind(trigger.enabled as i64)
+    .bind(trigger.id.to_string())
+    .execute(pool)
+    .await
+    .context("Failed to update trigger")?;
+
+    Ok(())
+}
+
+pub async fn record_trigger_fired(
+    pool: &SqlitePool,
+    id: Uuid,
+    fired_at: i64,
+    status: &str,
+    error: Option<&str>,
+) -> Result<()> {
+    sqlx::query("UPDATE triggers SET last_fired_at = ?, last_status = ?, last_error = ? WHERE id = ?")
+        .bind(fired_at)
+        .bind(status)
+        .bind(error)
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to record trigger run")?;
+
+    Ok(())
+}
+
+pub async fn delete_trigger(pool: &SqlitePool, id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM triggers WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to delete trigger")?;
+
+    Ok(())
+}
+
+pub async fn delete_server_triggers(pool: &SqlitePool, server_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM triggers WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to delete server triggers")?;
+
+    Ok(())
+}
+
+pub async fn insert_history(pool: &SqlitePool, entries: &[HistoryEntry]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for entry in entries {
+        sqlx::query(
+            r#"
+            INSERT INTO console_history (server_id, timestamp, kind, level, source, user, line)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(entry.server_id.to_string())
+        .bind(entry.timestamp)
+        .bind(entry.kind.as_str())
+        .bind(&entry.level)
+        .bind(&entry.source)
+        .bind(&entry.user)
+        .bind(&entry.line)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to insert console history")?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn search_history(pool: &SqlitePool, search: &HistorySearch) -> Result<Vec<HistoryEntry>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM console_history
+        WHERE (?1 IS NULL OR id IN (SELECT rowid FROM console_history_fts WHERE console_history_fts MATCH ?1))
+          AND (?2 IS NULL OR server_id = ?2)
+          AND (?3 IS NULL OR timestamp >= ?3)
+          AND (?4 IS NULL OR timestamp <= ?4)
+          AND (?5 IS NULL OR kind = ?5)
+          AND (?6 IS NULL OR user = ?6)
+          AND (?7 IS NULL OR id < ?7)
+        ORDER BY id DESC
+        LIMIT ?8
+        "#,
+    )
+    .bind(&search.fts)
+    .bind(search.server_id.map(|id| id.to_string()))
+    .bind(search.from)
+    .bind(search.to)
+    .bind(search.kind.map(|k| k.as_str()))
+    .bind(&search.user)
+    .bind(search.before_id)
+    .bind(search.limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to search console history")?;
+
+    rows.iter()
+        .map(|row| {
+            let kind = match row.get::<String, _>("kind").as_str() {
+                "command" => HistoryKind::Command,
+                _ => HistoryKind::Output,
+            };
+
+            Ok(HistoryEntry {
+                id: row.get("id"),
+                server_id: Uuid::parse_str(row.get("server_id"))?,
+                timestamp: row.get("timestamp"),
+                kind,
+                level: row.get("level"),
+                source: row.get("source"),
+                user: row.get("user"),
+                line: row.get("line"),
+            })
+        })
+        .collect()
+}
+
+/// Delete history older than `cutoff` and trim each server to its newest
+/// `max_per_server` lines, returning how many lines were removed
+pub async fn prune_history(pool: &SqlitePool, cutoff: i64, max_per_server: i64) -> Result<u64> {
+    let mut removed = sqlx::query("DELETE FROM console_history WHERE timestamp < ?")
+        .bind(cutoff)
+        .execute(pool)
+        .await
+        .context("Failed to prune console history")?
+        .rows_affected();
+
+    let server_ids: Vec<String> = sqlx::query("SELECT DISTINCT server_id FROM console_history")
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| row.get("server_id"))
+        .collect();
+
+    for server_id in server_ids {
+        removed += sqlx::query(
+            r#"
+            DELETE FROM console_history
+            WHERE server_id = ?1 AND id <= (
+                SELECT id FROM console_history WHERE server_id = ?1
+                ORDER BY id DESC LIMIT 1 OFFSET ?2
+            )
+            "#,
+        )
+        .bind(&server_id)
+        .bind(max_per_server)
+        .execute(pool)
+        .await
+        .context("Failed to trim console history")?
+        .rows_affected();
+    }
+
+    Ok(removed)
+}
+
+pub async fn delete_server_history(pool: &SqlitePool, server_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM console_history WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to delete server console history")?;
+
+    Ok(())
+}
+
+fn job_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Job> {
+    let kind: String = row.get("kind");
+    let status: String = row.get("status");
+    let server_id: Option<String> = row.get("server_id");
+    let result: Option<String> = row.get("result");
+
+    Ok(Job {
+        id: Uuid::parse_str(row.get("id"))?,
+        kind: JobKind::parse(&kind).with_context(|| format!("Unknown job kind '{}'", kind))?,
+        server_id: server_id.as_deref().map(Uuid::parse_str).transpose()?,
+        status: JobStatus::parse(&status).with_context(|| format!("Unknown job status '{}'", status))?,
+        progress: row.get::<f64, _>("progress") as f32,
+        message: row.get("message"),
+        error: row.get("error"),
+        result: result.as_deref().map(serde_json::from_str).transpose()?,
+        created_at: row.get("created_at"),
+        started_at: row.get("started_at"),
+        finished_at: row.get("finished_at"),
+    })
+}
+
+pub async fn create_job(pool: &SqlitePool, job: &Job) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO jobs (id, kind, server_id, status, progress, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(job.id.to_string())
+    .bind(job.kind.as_str())
+    .bind(job.server_id.map(|id| id.to_string()))
+    .bind(job.status.as_str())
+    .bind(job.progress as f64)
+    .bind(job.created_at)
+    .execute(pool)
+    .await
+    .context("Failed to insert job")?;
+
+    Ok(())
+}
+
+pub async fn get_job(pool: &SqlitePool, id: Uuid) -> Result<Option<Job>> {
+    let row = sqlx::query("SELECT * FROM jobs WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(job_from_row).transpose()
+}
+
+pub async fn list_jobs(
+    pool: &SqlitePool,
+    server_id: Option<Uuid>,
+    status: Option<JobStatus>,
+    limit: i64,
+) -> Result<Vec<Job>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM jobs
+        WHERE (?1 IS NULL OR server_id = ?1) AND (?2 IS NULL OR status = ?2)
+        ORDER BY created_at DESC, rowid DESC
+        LIMIT ?3
+        "#,
+    )
+    .bind(server_id.map(|id| id.to_string()))
+    .bind(status.map(|s| s.as_str()))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(job_from_row).collect()
+}
+
+pub async fn set_job_running(pool: &SqlitePool, id: Uuid, started_at: i64) -> Result<()> {
+    sqlx::query("UPDATE jobs SET status = 'running', started_at = ? WHERE id = ?")
+        .bind(started_at)
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to update job")?;
+
+    Ok(())
+}
+
+pub async fn set_job_progress(pool: &SqlitePool, id: Uuid, progress: f32) -> Result<()> {
+    sqlx::query("UPDATE jobs SET progress = ? WHERE id = ?")
+        .bind(progress as f64)
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to update job progress")?;
+
+    Ok(())
+}
+
+/// Append a log line and make it the job's current message
+pub async fn append_job_log(pool: &SqlitePool, job_id: Uuid, timestamp: i64, line: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("INSERT INTO job_logs (job_id, timestamp, line) VALUES (?, ?, ?)")
+        .bind(job_id.to_string())
+        .bind(timestamp)
+        .bind(line)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to insert job log")?;
+
+    sqlx::query("UPDATE jobs SET message = ? WHERE id = ?")
+        .bind(line)
+        .bind(job_id.to_string())
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update job message")?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn list_job_logs(pool: &SqlitePool, job_id: Uuid, after_id: i64, limit: i64) -> Result<Vec<JobLogLine>> {
+    let rows = sqlx::query(
+        "SELECT id, timestamp, line FROM job_logs WHERE job_id = ? AND id > ? ORDER BY id LIMIT ?",
+    )
+    .bind(job_id.to_string())
+    .bind(after_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| JobLogLine {
+            id: row.get("id"),
+            timestamp: row.get("timestamp"),
+            line: row.get("line"),
+        })
+        .collect())
+}
+
+pub async fn finish_job(
+    pool: &SqlitePool,
+    id: Uuid,
+    status: JobStatus,
+    error: Option<&str>,
+    result: Option<&serde_json::Value>,
+    finished_at: i64,
+) -> Result<()> {
+    let result_json = result.map(serde_json::to_string).transpose()?;
+
+    sqlx::query(
+        r#"
+        UPDATE jobs
+        SET status = ?, error = ?, result = ?, finished_at = ?,
+            progress = CASE WHEN ? = 'succeeded' THEN 100 ELSE progress END
+        WHERE id = ?
+        "#,
+    )
+    .bind(status.as_str())
+    .bind(error)
+    .bind(result_json)
+    .bind(finished_at)
+    .bind(status.as_str())
+    .bind(id.to_string())
+    .execute(pool)
+    .await
+    .context("Failed to finish job")?;
+
+    Ok(())
+}
+
+/// Jobs can't survive a restart of the API server; fail the ones it cut short
+pub async fn fail_interrupted_jobs(pool: &SqlitePool) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        UPDATE jobs
+        SET status = 'failed', error = 'Interrupted by API server restart', finished_at = ?
+        WHERE status IN ('queued', 'running')
+        "#,
+    )
+    .bind(chrono::Utc::now().timestamp())
+    .execute(pool)
+    .await
+    .context("Failed to fail interrupted jobs")?;
+
+    Ok(result.rows_affected())
+}
+
+async fn network_from_row(pool: &SqlitePool, row: &sqlx::sqlite::SqliteRow) -> Result<Network> {
+    let id = Uuid::parse_str(row.get("id"))?;
+
+    let members = sqlx::query("SELECT * FROM network_servers WHERE network_id = ? ORDER BY position")
+        .bind(id.to_string())
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|member| {
+            Ok(NetworkMember {
+                server_id: Uuid::parse_str(member.get("server_id"))?,
+                name: member.get("name"),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Network {
+        id,
+        name: row.get("name"),
+        proxy_id: Uuid::parse_str(row.get("proxy_id"))?,
+        forwarding_secret: row.get("forwarding_secret"),
+        backends: members,
+    })
+}
+
+pub async fn create_network(pool: &SqlitePool, network: &Network) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO networks (id, name, proxy_id, forwarding_secret, created_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(network.id.to_string())
+    .bind(&network.name)
+    .bind(network.proxy_id.to_string())
+    .bind(&network.forwarding_secret)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(pool)
+    .await
+    .context("Failed to insert network")?;
+
+    Ok(())
+}
+
+pub async fn get_network(pool: &SqlitePool, id: Uuid) -> Result<Option<Network>> {
+    let row = sqlx::query("SELECT * FROM networks WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+    match row {
+        Some(row) => Ok(Some(network_from_row(pool, &row).await?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn list_networks(pool: &SqlitePool) -> Result<Vec<Network>> {
+    let rows = sqlx::query("SELECT * FROM networks ORDER BY created_at")
+        .fetch_all(pool)
+        .await?;
+
+    let mut networks = Vec::with_capacity(rows.len());
+    for row in &rows {
+        networks.push(network_from_row(pool, row).await?);
+    }
+    Ok(networks)
+}
+
+/// The network a server belongs to, as its proxy or as a backend
+pub async fn find_server_network(pool: &SqlitePool, server_id: Uuid) -> Result<Option<Uuid>> {
+    let row = sqlx::query(
+        r#"
+        SELECT id FROM networks WHERE proxy_id = ?
+        UNION
+        SELECT network_id AS id FROM network_servers WHERE server_id = ?
+        "#,
+    )
+    .bind(server_id.to_string())
+    .bind(server_id.to_string())
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|row| Uuid::parse_str(row.get("id")).map_err(Into::into))
+        .transpose()
+}
+
+pub async fn add_network_server(pool: &SqlitePool, network_id: Uuid, member: &NetworkMember) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO network_servers (server_id, network_id, name, position)
+        VALUES (?, ?, ?, (SELECT COALESCE(MAX(position), -1) + 1 FROM network_servers WHERE network_id = ?))
+        "#,
+    )
+    .bind(member.server_id.to_string())
+    .bind(network_id.to_string())
+    .bind(&member.name)
+    .bind(network_id.to_string())
+    .execute(pool)
+    .await
+    .context("Failed to add server to network")?;
+
+    Ok(())
+}
+
+pub async fn remove_network_server(pool: &SqlitePool, network_id: Uuid, server_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM network_servers WHERE network_id = ? AND server_id = ?")
+        .bind(network_id.to_string())
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to remove server from network")?;
+
+    Ok(())
+}
+
+pub async fn delete_network(pool: &SqlitePool, id: Uuid) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM network_servers WHERE network_id = ?")
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete network servers")?;
+
+    sqlx::query("DELETE FROM networks WHERE id = ?")
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete network")?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+fn jar_record_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<JarRecord> {
+    let source: String = row.get("source");
+
+    Ok(JarRecord {
+        source: JarSource::parse(&source).with_context(|| format!("Unknown jar source {}", source))?,
+        file_name: row.get("file_name"),
+        version: row.get("version"),
+        sha256: row.get("sha256"),
+        main_class: row.get("main_class"),
+        uploaded_by: row.get("uploaded_by"),
+        installed_at: row.get("installed_at"),
+    })
+}
+
+pub async fn get_server_jars(pool: &SqlitePool, server_id: Uuid) -> Result<ServerJars> {
+    let rows = sqlx::query("SELECT * FROM server_jars WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+    let mut jars = ServerJars::default();
+    for row in &rows {
+        let record = jar_record_from_row(row)?;
+        match row.get::<String, _>("slot").as_str() {
+            "current" => jars.current = Some(record),
+            "previous" => jars.previous = Some(record),
+            _ => {}
+        }
+    }
+    Ok(jars)
+}
+
+pub async fn set_server_jars(pool: &SqlitePool, server_id: Uuid, jars: &ServerJars) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM server_jars WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .execute(&mut *tx)
+        .await
+        .context("Failed to clear server jars")?;
+
+    for (slot, record) in [("current", &jars.current), ("previous", &jars.previous)] {
+        let Some(record) = record else {
+            continue;
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO server_jars (server_id, slot, source, file_name, version, sha256, main_class, uploaded_by, installed_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(server_id.to_string())
+        .bind(slot)
+        .bind(record.source.as_str())
+        .bind(&record.file_name)
+        .bind(&record.version)
+        .bind(&record.sha256)
+        .bind(&record.main_class)
+        .bind(&record.uploaded_by)
+        .bind(record.installed_at)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record server jar")?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn delete_server_jars(pool: &SqlitePool, server_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM server_jars WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to delete server jars")?;
+
+    Ok(())
+}
+
+/// The server's most recent upgrade
+pub async fn get_server_upgrade(pool: &SqlitePool, server_id: Uuid) -> Result<Option<UpgradeRecord>> {
+    let row = sqlx::query("SELECT * FROM server_upgrades WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let status: String = row.get("status");
+
+    Ok(Some(UpgradeRecord {
+        from_version: row.get("from_version"),
+        to_version: row.get("to_version"),
+        jar_sha256: row.get("jar_sha256"),
+        backups: serde_json::from_str(row.get("backups"))?,
+        status: UpgradeStatus::parse(&status).with_context(|| format!("Unknown upgrade status {}", status))?,
+        created_at: row.get("created_at"),
+    }))
+}
+
+/// Record an upgrade, replacing the one before it
+pub async fn set_server_upgrade(pool: &SqlitePool, server_id: Uuid, upgrade: &UpgradeRecord) -> Result<()> {
+    let backups_json = serde_json::to_string(&upgrade.backups)?;
+
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO server_upgrades (server_id, from_version, to_version, jar_sha256, backups, status, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(server_id.to_string())
+    .bind(&upgrade.from_version)
+    .bind(&upgrade.to_version)
+    .bind(&upgrade.jar_sha256)
+    .bind(backups_json)
+    .bind(upgrade.status.as_str())
+    .bind(upgrade.created_at)
+    .execute(pool)
+    .await
+    .context("Failed to record server upgrade")?;
+
+    Ok(())
+}
+
+pub async fn delete_server_upgrade(pool: &SqlitePool, server_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM server_upgrades WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to delete server upgrade")?;
+
+    Ok(())
+}
+
+fn build_tracking_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<BuildTracking> {
+    let update: Option<String> = row.get("update_available");
+
+    Ok(BuildTracking {
+        build: row.get("build"),
+        channel: row.get("channel"),
+        previous_build: row.get("previous_build"),
+        pinned: row.get("pinned"),
+        auto_update: row.get("auto_update"),
+        update: update.map(|u| serde_json::from_str(&u)).transpose()?,
+        checked_at: row.get("checked_at"),
+    })
+}
+
+/// The server's tracked build; nothing known when it was never recorded
+pub async fn get_server_build(pool: &SqlitePool, server_id: Uuid) -> Result<BuildTracking> {
+    let row = sqlx::query("SELECT * FROM server_builds WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+    match row {
+        Some(row) => build_tracking_from_row(&row),
+        None => Ok(BuildTracking::default()),
+    }
+}
+
+pub async fn list_server_builds(pool: &SqlitePool) -> Result<std::collections::HashMap<Uuid, BuildTracking>> {
+    let rows = sqlx::query("SELECT * FROM server_builds")
+        .fetch_all(pool)
+        .await?;
+
+    let mut builds = std::collections::HashMap::new();
+    for row in rows {
+        builds.insert(Uuid::parse_str(row.get("server_id"))?, build_tracking_from_row(&row)?);
+    }
+    Ok(builds)
+}
+
+pub async fn set_server_build(pool: &SqlitePool, server_id: Uuid, tracking: &BuildTracking) -> Result<()> {
+    let update_json = tracking.update.as_ref().map(serde_json::to_string).transpose()?;
+
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO server_builds (server_id, build, channel, previous_build, pinned, auto_update, update_available, checked_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(server_id.to_string())
+    .bind(&tracking.build)
+    .bind(&tracking.channel)
+    .bind(&tracking.previous_build)
+    .bind(tracking.pinned)
+    .bind(tracking.auto_update)
+    .bind(update_json)
+    .bind(tracking.checked_at)
+    .execute(pool)
+    .await
+    .context("Failed to record server build")?;
+
+    Ok(())
+}
+
+pub async fn delete_server_build(pool: &SqlitePool, server_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM server_builds WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await
+        .context("Failed to delete server build")?;
+
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use server_manager::{ConsoleCommand, LogLine, ServerProcess};
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::Duration;
+use uuid::Uuid;
+
+use crate::{db, state::AppState};
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+const FLUSH_BATCH: usize = 500;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryKind {
+    /// A line printed by the server
+    Output,
+    /// A command written to the server's stdin
+    Command,
+}
+
+impl HistoryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryKind::Output => "output",
+            HistoryKind::Command => "command",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub server_id: Uuid,
+    pub timestamp: i64,
+    pub kind: HistoryKind,
+    pub level: Option<String>,
+    pub source: Option<String>,
+    /// Who sent a command; `None` for output and manager-issued commands
+    pub user: Option<String>,
+    pub line: String,
+}
+
+impl HistoryEntry {
+    fn output(server_id: Uuid, line: &LogLine) -> Self {
+        Self {
+            id: 0,
+            server_id,
+            timestamp: line.timestamp,
+            kind: HistoryKind::Output,
+            level: Some(line.level.as_str().to_string()),
+            source: line.source.clone(),
+            user: None,
+            line: line.to_string(),
+        }
+    }
+
+    fn command(server_id: Uuid, command: ConsoleCommand) -> Self {
+        Self {
+            id: 0,
+            server_id,
+            timestamp: command.timestamp,
+            kind: HistoryKind::Command,
+            level: None,
+            source: None,
+            user: command.user,
+            line: command.command,
+        }
+    }
+}
+
+/// Criteria for searching console history, newest first
+#[derive(Debug, Clone, Default)]
+pub struct HistorySearch {
+    /// FTS5 query, see [`fts_query`]
+    pub fts: Option<String>,
+    pub server_id: Option<Uuid>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub kind: Option<HistoryKind>,
+    pub user: Option<String>,
+    /// Only entries older than this id, for paging
+    pub before_id: Option<i64>,
+    pub limit: i64,
+}
+
+/// How much console history to keep
+#[derive(Debug, Clone, Copy)]
+pub struct HistoryRetention {
+    pub max_age_days: i64,
+    pub max_lines_per_server: i64,
+}
+
+impl HistoryRetention {
+    /// Read `CONSOLE_HISTORY_DAYS` and `CONSOLE_HISTORY_MAX_LINES`
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: i64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            max_age_days: var("CONSOLE_HISTORY_DAYS", 30),
+            max_lines_per_server: var("CONSOLE_HISTORY_MAX_LINES", 500_000),
+        }
+    }
+}
+
+/// Start recording a freshly started server's output and commands
+pub fn spawn(state: Arc<AppState>, server_id: Uuid, process: Arc<ServerProcess>) {
+    let output = process.subscribe();
+    let commands = process.subscribe_commands();
+    // Holding the process would keep its channels open after it exits
+    drop(process);
+
+    tokio::spawn(async move {
+        record(state, server_id, output, commands).await;
+    });
+}
+
+async fn record(
+    state: Arc<AppState>,
+    server_id: Uuid,
+    mut output: broadcast::Receiver<LogLine>,
+    mut commands: broadcast::Receiver<ConsoleCommand>,
+) {
+    let mut pending = Vec::new();
+    let mut commands_open = true;
+    let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+
+    loop {
+        tokio::select! {
+            line = output.recv() => match line {
+                Ok(line) => pending.push(HistoryEntry::output(server_id, &line)),
+                Err(RecvError::Lagged(n)) => {
+                    tracing::warn!("Console history for {} dropped {} lines", server_id, n);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            command = commands.recv(), if commands_open => match command {
+                Ok(command) => pending.push(HistoryEntry::command(server_id, command)),
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => commands_open = false,
+            },
+            _ = interval.tick() => {
+                flush(&state, &mut pending).await;
+                continue;
+            }
+        }
+
+        if pending.len() >= FLUSH_BATCH {
+            flush(&state, &mut pending).await;
+        }
+    }
+
+    // Commands sent right before exit, e.g. `stop`
+    while let Ok(command) = commands.try_recv() {
+        pending.push(HistoryEntry::command(server_id, command));
+    }
+    flush(&state, &mut pending).await;
+}
+
+async fn flush(state: &AppState, pending: &mut Vec<HistoryEntry>) {
+    if pending.is_empty() {
+        return;
+    }
+
+    if let Err(e) = db::insert_history(&state.db, pending).await {
+        tracing::error!("Failed to save console history: {}", e);
+    }
+    pending.clear();
+}
+
+/// Background loop that applies the retention limits
+pub async fn run_retention(state: Arc<AppState>, retention: HistoryRetention) {
+    let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let cutoff = chrono::Utc::now().timestamp() - retention.max_age_days * 86400;
+        match db::prune_history(&state.db, cutoff, retention.max_lines_per_server).await {
+            Ok(0) => {}
+            Ok(removed) => tracing::info!("Pruned {} console history lines", removed),
+            Err(e) => tracing::error!("Failed to prune console history: {}", e),
+        }
+    }
+}
+
+/// Turn free text into an FTS5 query: every word must appear, and a trailing
+/// `*` keeps its prefix meaning. Quoting stops characters such as `/` and `:`
+/// from being read as query syntax.
+pub fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|term| {
+            let (word, prefix) = match term.strip_suffix('*') {
+                Some(word) if !word.is_empty() => (word, "*"),
+                _ => (term, ""),
+            };
+            format!("\"{}\"{}", word.replace('"', "\"\""), prefix)
+        })
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
@@ -0,0 +1,227 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use server_manager::{
+    download_server_jar, inspect_jar, install_server_jar, keep_previous_jar, rollback_server_jar, server_jar_sha256,
+    JarInfo, Progress, ServerConfig, SERVER_JAR,
+};
+use std::path::Path;
+
+use crate::{builds, db, state::AppState};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JarSource {
+    /// Installed from upstream when the server was created
+    Download,
+    Upload,
+}
+
+impl JarSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JarSource::Download => "download",
+            JarSource::Upload => "upload",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "download" => Some(JarSource::Download),
+            "upload" => Some(JarSource::Upload),
+            _ => None,
+        }
+    }
+}
+
+/// Where a server jar came from
+#[derive(Debug, Clone, Serialize)]
+pub struct JarRecord {
+    pub source: JarSource,
+    pub file_name: Option<String>,
+    /// Minecraft version the jar was declared or detected as
+    pub version: String,
+    pub sha256: String,
+    pub main_class: Option<String>,
+    pub uploaded_by: Option<String>,
+    pub installed_at: i64,
+}
+
+/// A server's jar and the one it replaced, kept for rollback
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ServerJars {
+    pub current: Option<JarRecord>,
+    pub previous: Option<JarRecord>,
+}
+
+/// An uploaded jar that has been checked to be runnable
+pub struct UploadedJar {
+    pub data: Vec<u8>,
+    pub file_name: Option<String>,
+    pub info: JarInfo,
+    pub uploaded_by: Option<String>,
+}
+
+impl UploadedJar {
+    pub fn read(data: Vec<u8>, file_name: Option<String>, uploaded_by: Option<String>) -> Result<Self> {
+        let info = inspect_jar(&data)?;
+        Ok(Self {
+            data,
+            file_name,
+            info,
+            uploaded_by,
+        })
+    }
+
+    pub fn record(&self, version: &str) -> JarRecord {
+        JarRecord {
+            source: JarSource::Upload,
+            file_name: self.file_name.clone(),
+            version: version.to_string(),
+            sha256: self.info.sha256.clone(),
+            main_class: Some(self.info.main_class.clone()),
+            uploaded_by: self.uploaded_by.clone(),
+            installed_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// Put an uploaded jar in place of a stopped server's jar, keeping the
+/// current one for rollback
+pub async fn replace(state: &AppState, config: &ServerConfig, jar: &UploadedJar, version: &str) -> Result<JarRecord> {
+    let server_dir = config.server_dir(&state.servers_dir);
+    let current = current_record(state, config).await?;
+
+    install_server_jar(&server_dir, &jar.data).await?;
+
+    let record = jar.record(version);
+    let jars = ServerJars {
+        current: Some(record.clone()),
+        previous: current,
+    };
+    db::set_server_jars(&state.db, config.id, &jars).await?;
+    builds::installed(state, config.id, None).await?;
+
+    Ok(record)
+}
+
+/// Download `version` in place of a stopped server's jar, keeping the current
+/// one for rollback. The current jar stays in place if the download fails.
+pub async fn download(
+    state: &AppState,
+    config: &ServerConfig,
+    version: &str,
+    build: Option<&str>,
+    progress: &Progress,
+) -> Result<JarRecord> {
+    let server_dir = config.server_dir(&state.servers_dir);
+    let current = current_record(state, config).await?;
+
+    keep_previous_jar(&server_dir).await?;
+    let downloaded = download_server_jar(
+        config.server_type,
+        version,
+        build,
+        &server_dir.join(SERVER_JAR),
+        &state.upstreams,
+        &state.cache,
+        progress,
+    )
+    .await;
+    let build = match downloaded {
+        Ok(build) => build,
+        Err(e) => {
+            if current.is_some() {
+                rollback_server_jar(&server_dir).await?;
+            }
+            return Err(e);
+        }
+    };
+
+    let record = JarRecord {
+        source: JarSource::Download,
+        file_name: None,
+        version: version.to_string(),
+        sha256: server_jar_sha256(&server_dir).await?,
+        main_class: None,
+        uploaded_by: None,
+        installed_at: chrono::Utc::now().timestamp(),
+    };
+    let jars = ServerJars {
+        current: Some(record.clone()),
+        previous: current,
+    };
+    db::set_server_jars(&state.db, config.id, &jars).await?;
+    builds::installed(state, config.id, Some(build)).await?;
+
+    Ok(record)
+}
+
+/// Swap a stopped server's jar with the one it replaced
+pub async fn rollback(state: &AppState, config: &ServerConfig) -> Result<JarRecord> {
+    let server_dir = config.server_dir(&state.servers_dir);
+    let jars = db::get_server_jars(&state.db, config.id).await?;
+
+    rollback_server_jar(&server_dir).await?;
+
+    let current = match jars.previous {
+        Some(previous) => previous,
+        // Kept by an older version of the manager; at least record the hash
+        None => describe_installed(&server_dir, &config.minecraft_version)
+            .await?
+            .context("Restored server jar is missing")?,
+    };
+    let jars = ServerJars {
+        current: Some(current.clone()),
+        previous: jars.current,
+    };
+    db::set_server_jars(&state.db, config.id, &jars).await?;
+    builds::rolled_back(state, config.id).await?;
+
+    Ok(current)
+}
+
+/// Record the Minecraft version of the jar now in place
+pub async fn set_version(state: &AppState, config: &ServerConfig, version: &str) -> Result<()> {
+    if config.minecraft_version == version {
+        return Ok(());
+    }
+    let mut config = config.clone();
+    config.minecraft_version = version.to_string();
+
+    db::update_server(&state.db, &config).await?;
+    if let Some(instance) = state.servers.write().await.get_mut(&config.id) {
+        instance.config = config;
+    }
+    Ok(())
+}
+
+/// The jar in place, tracked or not
+async fn current_record(state: &AppState, config: &ServerConfig) -> Result<Option<JarRecord>> {
+    match db::get_server_jars(&state.db, config.id).await?.current {
+        Some(current) => Ok(Some(current)),
+        None => describe_installed(&config.server_dir(&state.servers_dir), &config.minecraft_version).await,
+    }
+}
+
+/// Provenance for a jar that was downloaded before uploads were tracked
+async fn describe_installed(server_dir: &Path, version: &str) -> Result<Option<JarRecord>> {
+    let Ok(metadata) = tokio::fs::metadata(server_dir.join(SERVER_JAR)).await else {
+        return Ok(None);
+    };
+    let installed_at = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_else(|| chrono::Utc::now().timestamp());
+
+    Ok(Some(JarRecord {
+        source: JarSource::Download,
+        file_name: None,
+        version: version.to_string(),
+        sha256: server_jar_sha256(server_dir).await?,
+        main_class: None,
+        uploaded_by: None,
+        installed_at,
+    }))
+}
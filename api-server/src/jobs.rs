@@ -0,0 +1,343 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use server_manager::{Progress, ProgressEvent};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+use crate::{db, state::AppState};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    CreateServer,
+    BackupWorld,
+    UploadWorld,
+    ImportModpack,
+    UpgradeServer,
+    RollbackUpgrade,
+    /// Install a newer build of the server's version, then start it
+    UpdateBuild,
+    /// Update one or every installed plugin or mod
+    UpdateAddons,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::CreateServer => "create_server",
+            JobKind::BackupWorld => "backup_world",
+            JobKind::UploadWorld => "upload_world",
+            JobKind::ImportModpack => "import_modpack",
+            JobKind::UpgradeServer => "upgrade_server",
+            JobKind::RollbackUpgrade => "rollback_upgrade",
+            JobKind::UpdateBuild => "update_build",
+            JobKind::UpdateAddons => "update_addons",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "create_server" => Some(JobKind::CreateServer),
+            "backup_world" => Some(JobKind::BackupWorld),
+            "upload_world" => Some(JobKind::UploadWorld),
+            "import_modpack" => Some(JobKind::ImportModpack),
+            "upgrade_server" => Some(JobKind::UpgradeServer),
+            "rollback_upgrade" => Some(JobKind::RollbackUpgrade),
+            "update_build" => Some(JobKind::UpdateBuild),
+            "update_addons" => Some(JobKind::UpdateAddons),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "queued" => Some(JobStatus::Queued),
+            "running" => Some(JobStatus::Running),
+            "succeeded" => Some(JobStatus::Succeeded),
+            "failed" => Some(JobStatus::Failed),
+            "cancelled" => Some(JobStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: JobKind,
+    pub server_id: Option<Uuid>,
+    pub status: JobStatus,
+    /// Completion from 0 to 100
+    pub progress: f32,
+    /// Most recent log line
+    pub message: Option<String>,
+    pub error: Option<String>,
+    /// Kind-specific outcome, e.g. the created server
+    pub result: Option<serde_json::Value>,
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+    pub finished_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobLogLine {
+    pub id: i64,
+    pub timestamp: i64,
+    pub line: String,
+}
+
+/// Sent to WebSocket watchers of a job
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobUpdate {
+    Progress { percent: f32 },
+    Log { timestamp: i64, line: String },
+    Status {
+        status: JobStatus,
+        error: Option<String>,
+        result: Option<serde_json::Value>,
+    },
+}
+
+/// In-memory handle on a queued or running job
+pub struct RunningJob {
+    progress: Progress,
+    /// Only used before the job starts; a running job is never aborted, as
+    /// that would skip the cleanup its own error path does
+    abort: AbortHandle,
+    started: Arc<AtomicBool>,
+    pub updates: broadcast::Sender<JobUpdate>,
+}
+
+/// Queue `work` to run on the worker pool and return the new job at once.
+/// The value `work` returns becomes the job's result.
+pub async fn submit<F, Fut>(
+    state: &Arc<AppState>,
+    kind: JobKind,
+    server_id: Option<Uuid>,
+    work: F,
+) -> Result<Job>
+where
+    F: FnOnce(Progress) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<serde_json::Value>> + Send + 'static,
+{
+    let job = Job {
+        id: Uuid::new_v4(),
+        kind,
+        server_id,
+        status: JobStatus::Queued,
+        progress: 0.0,
+        message: None,
+        error: None,
+        result: None,
+        created_at: chrono::Utc::now().timestamp(),
+        started_at: None,
+        finished_at: None,
+    };
+    db::create_job(&state.db, &job).await?;
+
+    let (progress, events) = Progress::new();
+    let (updates, _) = broadcast::channel(256);
+    let started = Arc::new(AtomicBool::new(false));
+
+    // Hold the lock while spawning so the task can't finish and remove its
+    // entry before it has been inserted
+    let mut jobs = state.jobs.write().await;
+
+    let task_state = state.clone();
+    let task_progress = progress.clone();
+    let task_started = started.clone();
+    let task_updates = updates.clone();
+    let id = job.id;
+    let handle = tokio::spawn(async move {
+        run(task_state, id, task_progress, events, task_started, task_updates, work).await;
+    });
+
+    jobs.insert(
+        job.id,
+        RunningJob {
+            progress,
+            abort: handle.abort_handle(),
+            started,
+            updates,
+        },
+    );
+
+    Ok(job)
+}
+
+async fn run<F, Fut>(
+    state: Arc<AppState>,
+    id: Uuid,
+    progress: Progress,
+    mut events: mpsc::UnboundedReceiver<ProgressEvent>,
+    started: Arc<AtomicBool>,
+    updates: broadcast::Sender<JobUpdate>,
+    work: F,
+) where
+    F: FnOnce(Progress) -> Fut,
+    Fut: Future<Output = Result<serde_json::Value>>,
+{
+    // The semaphore is never closed
+    let Ok(_permit) = state.job_slots.clone().acquire_owned().await else {
+        return;
+    };
+
+    // Under the jobs lock, so cancellation either drops the job before it
+    // starts or leaves it to wind down by itself
+    {
+        let jobs = state.jobs.read().await;
+        if !jobs.contains_key(&id) {
+            return;
+        }
+        started.store(true, Ordering::SeqCst);
+    }
+    if let Err(e) = db::set_job_running(&state.db, id, chrono::Utc::now().timestamp()).await {
+        tracing::error!("Failed to mark job {} running: {}", id, e);
+    }
+    let _ = updates.send(JobUpdate::Status {
+        status: JobStatus::Running,
+        error: None,
+        result: None,
+    });
+
+    let mut recorder = Recorder {
+        state: &state,
+        id,
+        updates: &updates,
+        last_percent: 0.0,
+    };
+
+    let work = work(progress.clone());
+    tokio::pin!(work);
+    let result = loop {
+        tokio::select! {
+            result = &mut work => break result,
+            Some(event) = events.recv() => recorder.record(event).await,
+        }
+    };
+    while let Ok(event) = events.try_recv() {
+        recorder.record(event).await;
+    }
+
+    let (status, error, value) = match result {
+        Ok(value) => (JobStatus::Succeeded, None, Some(value)),
+        Err(_) if progress.is_cancelled() => (JobStatus::Cancelled, None, None),
+        Err(e) => (JobStatus::Failed, Some(format!("{:#}", e)), None),
+    };
+
+    state.jobs.write().await.remove(&id);
+    finish(&state, id, &updates, status, error, value).await;
+}
+
+/// Persists progress events and forwards them to watchers
+struct Recorder<'a> {
+    state: &'a AppState,
+    id: Uuid,
+    updates: &'a broadcast::Sender<JobUpdate>,
+    last_percent: f32,
+}
+
+impl Recorder<'_> {
+    async fn record(&mut self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::Percent(percent) => {
+                // Only whole-percent steps are worth a database write
+                if percent.floor() == self.last_percent.floor() {
+                    return;
+                }
+                self.last_percent = percent;
+
+                if let Err(e) = db::set_job_progress(&self.state.db, self.id, percent).await {
+                    tracing::warn!("Failed to save progress of job {}: {}", self.id, e);
+                }
+                let _ = self.updates.send(JobUpdate::Progress { percent });
+            }
+            ProgressEvent::Log(line) => {
+                let timestamp = chrono::Utc::now().timestamp();
+                if let Err(e) = db::append_job_log(&self.state.db, self.id, timestamp, &line).await {
+                    tracing::warn!("Failed to save log of job {}: {}", self.id, e);
+                }
+                let _ = self.updates.send(JobUpdate::Log { timestamp, line });
+            }
+        }
+    }
+}
+
+async fn finish(
+    state: &AppState,
+    id: Uuid,
+    updates: &broadcast::Sender<JobUpdate>,
+    status: JobStatus,
+    error: Option<String>,
+    result: Option<serde_json::Value>,
+) {
+    if let Err(e) = db::finish_job(
+        &state.db,
+        id,
+        status,
+        error.as_deref(),
+        result.as_ref(),
+        chrono::Utc::now().timestamp(),
+    )
+    .await
+    {
+        tracing::error!("Failed to record outcome of job {}: {}", id, e);
+    }
+
+    let _ = updates.send(JobUpdate::Status {
+        status,
+        error,
+        result,
+    });
+}
+
+/// Cancel a queued or running job. A queued job is dropped; a running one
+/// is asked to stop and finishes as cancelled once it has cleaned up after
+/// itself.
+pub async fn cancel(state: &Arc<AppState>, id: Uuid) -> Result<()> {
+    let mut jobs = state.jobs.write().await;
+    let job = jobs.get(&id).context("Job is not running")?;
+    job.progress.cancel();
+    if job.started.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let Some(job) = jobs.remove(&id) else {
+        return Ok(());
+    };
+    drop(jobs);
+    job.abort.abort();
+    finish(state, id, &job.updates, JobStatus::Cancelled, None, None).await;
+    Ok(())
+}
@@ -1,7 +1,19 @@
 mod auth;
+mod builds;
+mod countdown;
+mod crashes;
 mod db;
+mod history;
+mod jars;
+mod jobs;
+mod networks;
+mod presence;
 mod routes;
+mod scheduler;
 mod state;
+mod triggers;
+mod upgrades;
+mod watchdog;
 
 use anyhow::{Context, Result};
 use axum::{
@@ -39,25 +51,44 @@ async fn main() -> Result<()> {
         .context("Invalid API_PORT")?;
     let servers_dir = std::env::var("SERVERS_DIR")
         .unwrap_or_else(|_| "./servers".to_string());
+    let cache_dir = std::env::var("ARTIFACT_CACHE_DIR")
+        .unwrap_or_else(|_| "./cache".to_string());
     let admin_password = std::env::var("ADMIN_PASSWORD")
         .unwrap_or_else(|_| "changeme".to_string());
     let jwt_secret = std::env::var("JWT_SECRET")
         .unwrap_or_else(|_| "your-secret-key-change-this-in-production".to_string());
+    let job_workers = std::env::var("JOB_WORKERS")
+        .unwrap_or_else(|_| "2".to_string())
+        .parse::<usize>()
+        .context("Invalid JOB_WORKERS")?;
+
+    let upstreams = server_manager::Upstreams::new(server_manager::UpstreamConfig::from_env()?)?;
 
     // Initialize database
     let db = db::init_db(&database_url).await?;
+    let interrupted = db::fail_interrupted_jobs(&db).await?;
+    if interrupted > 0 {
+        tracing::warn!("Marked {} interrupted jobs as failed", interrupted);
+    }
 
     // Create servers directory
     let servers_path = std::env::current_dir()?.join(servers_dir);
     tokio::fs::create_dir_all(&servers_path).await?;
     let servers_path = servers_path.canonicalize().context("Failed to canonicalize servers path")?;
 
+    // Create artifact cache directory
+    let cache_path = std::env::current_dir()?.join(cache_dir);
+    tokio::fs::create_dir_all(&cache_path).await?;
+
     // Create application state
     let state = Arc::new(AppState::new(
         db,
         servers_path,
+        server_manager::ArtifactCache::new(cache_path),
+        upstreams,
         admin_password,
         jwt_secret,
+        job_workers,
     ));
 
     // Recover existing processes
@@ -68,6 +99,15 @@ async fn main() -> Result<()> {
         }
     });
 
+    // Run scheduled tasks
+    tokio::spawn(scheduler::run(state.clone()));
+
+    // Apply console history retention limits
+    tokio::spawn(history::run_retention(state.clone(), history::HistoryRetention::from_env()));
+
+    // Look for newer builds of each server's version
+    tokio::spawn(builds::run(state.clone(), builds::check_interval_from_env()));
+
     // Build router
     let app = Router::new()
         // Auth routes (no auth required)
@@ -75,15 +115,55 @@ async fn main() -> Result<()> {
         // Server routes
         .route("/api/servers", get(routes::servers::list_servers))
         .route("/api/servers", post(routes::servers::create_server))
+        .route(
+            "/api/servers/import",
+            post(routes::servers::import_modpack)
+                .layer(DefaultBodyLimit::max(1024 * 1024 * 1024)), // 1GB limit
+        )
+        .route(
+            "/api/servers/upload",
+            post(routes::servers::create_server_from_jar)
+                .layer(DefaultBodyLimit::max(1024 * 1024 * 1024)), // 1GB limit
+        )
         .route("/api/servers/:id", get(routes::servers::get_server))
         .route("/api/servers/:id", delete(routes::servers::delete_server))
         .route("/api/servers/:id/start", post(routes::servers::start_server))
         .route("/api/servers/:id/stop", post(routes::servers::stop_server))
         .route("/api/servers/:id/force-stop", post(routes::servers::force_stop_server))
         .route("/api/servers/:id/restart", post(routes::servers::restart_server))
+        .route("/api/servers/:id/restart/countdown", get(routes::servers::get_restart_countdown))
+        .route("/api/servers/:id/restart/countdown", post(routes::servers::start_restart_countdown))
+        .route("/api/servers/:id/restart/countdown", delete(routes::servers::cancel_restart_countdown))
+        .route("/api/servers/:id/jar", get(routes::jars::get_jars))
+        .route(
+            "/api/servers/:id/jar",
+            put(routes::jars::replace_jar)
+                .layer(DefaultBodyLimit::max(1024 * 1024 * 1024)), // 1GB limit
+        )
+        .route("/api/servers/:id/jar/rollback", post(routes::jars::rollback_jar))
+        .route("/api/servers/:id/build", get(routes::builds::get_build))
+        .route("/api/servers/:id/build", put(routes::builds::update_build))
+        .route("/api/servers/:id/build/check", post(routes::builds::check_build))
+        .route("/api/servers/:id/upgrade", get(routes::upgrades::get_upgrade))
+        .route("/api/servers/:id/upgrade", post(routes::upgrades::upgrade_server))
+        .route("/api/servers/:id/upgrade/check", get(routes::upgrades::check_upgrade))
+        .route("/api/servers/:id/upgrade/rollback", post(routes::upgrades::rollback_upgrade))
         .route("/api/versions/:type", get(routes::servers::get_versions))
+        .route("/api/versions/:type/:version/builds", get(routes::servers::get_builds))
+        .route(
+            "/api/versions/bedrock/upload",
+            post(routes::servers::upload_bedrock_server)
+                .layer(DefaultBodyLimit::max(1024 * 1024 * 1024)), // 1GB limit
+        )
+        .route("/api/servers/:id/watchdog", get(routes::watchdog::get_policy))
+        .route("/api/servers/:id/watchdog", put(routes::watchdog::update_policy))
+        .route("/api/servers/:id/crashes", get(routes::crashes::list_crashes))
+        .route("/api/servers/:id/crashes/scan", post(routes::crashes::scan_crashes))
+        .route("/api/servers/:id/crashes/:crash_id", get(routes::crashes::get_crash))
+        .route("/api/servers/:id/crashes/:crash_id/report", get(routes::crashes::download_report))
         // Console routes
         .route("/api/servers/:id/console", get(routes::console::console_handler))
+        .route("/api/console/history", get(routes::history::search_history))
         // Config routes
         .route("/api/servers/:id/config", get(routes::config::get_config))
         .route("/api/servers/:id/config", put(routes::config::update_config))
@@ -100,7 +180,22 @@ async fn main() -> Result<()> {
         .route("/api/plugins/search", get(routes::plugins::search_plugins))
         .route("/api/servers/:id/plugins", get(routes::plugins::list_installed_plugins))
         .route("/api/servers/:id/plugins", post(routes::plugins::install_plugin))
+        .route("/api/servers/:id/plugins/versions", get(routes::plugins::list_addon_versions))
+        .route("/api/servers/:id/plugins/plan", post(routes::plugins::plan_addon_install))
+        .route("/api/servers/:id/plugins/updates", get(routes::plugins::check_addon_updates))
+        .route("/api/servers/:id/plugins/updates", post(routes::plugins::update_addons))
+        .route("/api/servers/:id/plugins/updates/rollback", post(routes::plugins::rollback_addon_update))
         .route("/api/servers/:id/plugins/:name", delete(routes::plugins::remove_plugin))
+        // Mod routes
+        .route("/api/servers/:id/mods/search", get(routes::mods::search_mods))
+        .route("/api/servers/:id/mods", get(routes::mods::list_installed_mods))
+        .route("/api/servers/:id/mods", post(routes::mods::install_mod))
+        .route("/api/servers/:id/mods/versions", get(routes::plugins::list_addon_versions))
+        .route("/api/servers/:id/mods/plan", post(routes::plugins::plan_addon_install))
+        .route("/api/servers/:id/mods/updates", get(routes::plugins::check_addon_updates))
+        .route("/api/servers/:id/mods/updates", post(routes::plugins::update_addons))
+        .route("/api/servers/:id/mods/updates/rollback", post(routes::plugins::rollback_addon_update))
+        .route("/api/servers/:id/mods/:name", delete(routes::mods::remove_mod))
         // Stats routes
         .route("/api/servers/:id/stats", get(routes::stats::get_server_stats))
         .route("/api/stats", get(routes::stats::get_system_stats))
@@ -108,6 +203,54 @@ async fn main() -> Result<()> {
         .route("/api/servers/:id/files", get(routes::files::list_files))
         .route("/api/servers/:id/files/*path", get(routes::files::read_file))
         .route("/api/servers/:id/files/*path", put(routes::files::write_file))
+        // Log routes
+        .route("/api/servers/:id/logs", get(routes::logs::list_logs))
+        .route("/api/servers/:id/logs/search", get(routes::logs::search_logs))
+        .route("/api/servers/:id/logs/tail", get(routes::logs::tail_log))
+        .route("/api/servers/:id/logs/:name", get(routes::logs::read_log))
+        .route("/api/servers/:id/logs/:name/download", get(routes::logs::download_log))
+        // Player routes
+        .route("/api/servers/:id/players", get(routes::players::list_players))
+        .route("/api/servers/:id/players/kick", post(routes::players::kick_player))
+        .route("/api/servers/:id/players/ops", post(routes::players::op_player))
+        .route("/api/servers/:id/players/ops/:name", delete(routes::players::deop_player))
+        .route("/api/servers/:id/players/whitelist", post(routes::players::whitelist_add))
+        .route("/api/servers/:id/players/whitelist/:name", delete(routes::players::whitelist_remove))
+        .route("/api/servers/:id/players/bans", post(routes::players::ban_player))
+        .route("/api/servers/:id/players/bans/:name", delete(routes::players::pardon_player))
+        .route("/api/servers/:id/players/ip-bans", post(routes::players::ban_ip))
+        .route("/api/servers/:id/players/ip-bans/:ip", delete(routes::players::pardon_ip))
+        // Schedule routes
+        .route("/api/servers/:id/schedules", get(routes::schedules::list_schedules))
+        .route("/api/servers/:id/schedules", post(routes::schedules::create_schedule))
+        .route("/api/servers/:id/schedules/:schedule_id", get(routes::schedules::get_schedule))
+        .route("/api/servers/:id/schedules/:schedule_id", put(routes::schedules::update_schedule))
+        .route("/api/servers/:id/schedules/:schedule_id", delete(routes::schedules::delete_schedule))
+        .route("/api/servers/:id/schedules/:schedule_id/run", post(routes::schedules::run_schedule))
+        // Trigger routes
+        .route("/api/servers/:id/triggers", get(routes::triggers::list_triggers))
+        .route("/api/servers/:id/triggers", post(routes::triggers::create_trigger))
+        .route("/api/servers/:id/triggers/:trigger_id", get(routes::triggers::get_trigger))
+        .route("/api/servers/:id/triggers/:trigger_id", put(routes::triggers::update_trigger))
+        .route("/api/servers/:id/triggers/:trigger_id", delete(routes::triggers::delete_trigger))
+        .route("/api/servers/:id/triggers/:trigger_id/fire", post(routes::triggers::fire_trigger))
+        .route("/api/events", get(routes::triggers::events_handler))
+        // Artifact cache routes
+        .route("/api/cache", get(routes::cache::list_artifacts))
+        .route("/api/cache/gc", post(routes::cache::collect_garbage))
+        // Job routes
+        .route("/api/jobs", get(routes::jobs::list_jobs))
+        .route("/api/jobs/:id", get(routes::jobs::get_job))
+        .route("/api/jobs/:id/logs", get(routes::jobs::get_job_logs))
+        .route("/api/jobs/:id/cancel", post(routes::jobs::cancel_job))
+        .route("/api/jobs/:id/ws", get(routes::jobs::job_ws_handler))
+        // Network routes
+        .route("/api/networks", get(routes::networks::list_networks))
+        .route("/api/networks", post(routes::networks::create_network))
+        .route("/api/networks/:id", get(routes::networks::get_network_handler))
+        .route("/api/networks/:id", delete(routes::networks::delete_network))
+        .route("/api/networks/:id/servers", post(routes::networks::add_backend))
+        .route("/api/networks/:id/servers/:server_id", delete(routes::networks::remove_backend))
         .fallback_service(tower_http::services::ServeDir::new("frontend").fallback(tower_http::services::ServeFile::new("frontend/index.html")))
         .layer(
             CorsLayer::new()
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use server_manager::{configure_proxy, join_network, NetworkBackend};
+use uuid::Uuid;
+
+use crate::{db, state::AppState};
+
+/// A proxy together with the backend servers behind it
+#[derive(Debug, Clone, Serialize)]
+pub struct Network {
+    pub id: Uuid,
+    pub name: String,
+    pub proxy_id: Uuid,
+    /// Shared by the proxy and backends for modern forwarding
+    #[serde(skip_serializing)]
+    pub forwarding_secret: String,
+    /// In the order the proxy tries them when players join
+    pub backends: Vec<NetworkMember>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkMember {
+    pub server_id: Uuid,
+    /// Name on the proxy, e.g. `lobby`
+    pub name: String,
+}
+
+/// Rewrite the proxy's server list and every backend's forwarding setup
+/// from the network as stored. Running servers pick it up on restart.
+pub async fn apply(state: &AppState, network: &Network) -> Result<()> {
+    let proxy = db::get_server(&state.db, network.proxy_id)
+        .await?
+        .context("Proxy server no longer exists")?;
+
+    let mut backends = Vec::with_capacity(network.backends.len());
+    for member in &network.backends {
+        let config = db::get_server(&state.db, member.server_id)
+            .await?
+            .with_context(|| format!("Backend {} no longer exists", member.name))?;
+
+        join_network(
+            &config.server_dir(&state.servers_dir),
+            config.server_type,
+            config.port,
+            proxy.server_type,
+            &network.forwarding_secret,
+        )
+        .await
+        .with_context(|| format!("Failed to configure backend {}", member.name))?;
+
+        backends.push(NetworkBackend {
+            name: member.name.clone(),
+            port: config.port,
+        });
+    }
+
+    configure_proxy(
+        &proxy.server_dir(&state.servers_dir),
+        proxy.server_type,
+        proxy.port,
+        &backends,
+        Some(&network.forwarding_secret),
+    )
+    .await
+    .context("Failed to configure proxy")
+}
+
+/// Default proxy name for a backend: its server name, lowercased, with
+/// anything the proxy config can't take replaced by dashes
+pub fn backend_name(server_name: &str) -> String {
+    let name: String = server_name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '-' })
+        .collect();
+    name.trim_matches('-').to_string()
+}
@@ -0,0 +1,68 @@
+use server_manager::{ConsoleEvent, LogLine, ServerProcess, ServerState, ServerType};
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::Duration;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Servers that never print a recognised ready line still count as running
+/// after this long
+const READY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Follow a freshly started server's console to tell when it has finished
+/// starting and how many players are online
+pub fn spawn(state: Arc<AppState>, server_id: Uuid, server_type: ServerType, process: Arc<ServerProcess>) {
+    let output = process.subscribe();
+    // Holding the process would keep its output channel open after it exits
+    drop(process);
+
+    tokio::spawn(async move {
+        watch(state, server_id, server_type, output).await;
+    });
+}
+
+async fn watch(
+    state: Arc<AppState>,
+    server_id: Uuid,
+    server_type: ServerType,
+    mut output: broadcast::Receiver<LogLine>,
+) {
+    let ready_timeout = tokio::time::sleep(READY_TIMEOUT);
+    tokio::pin!(ready_timeout);
+    let mut ready = false;
+
+    loop {
+        let event = tokio::select! {
+            line = output.recv() => match line {
+                Ok(line) => server_manager::parse_console_event(server_type, &line.message),
+                Err(RecvError::Lagged(_)) => None,
+                Err(RecvError::Closed) => return,
+            },
+            _ = &mut ready_timeout, if !ready => {
+                tracing::warn!("Server {} never reported it was ready", server_id);
+                Some(ConsoleEvent::Ready)
+            }
+        };
+        let Some(event) = event else {
+            continue;
+        };
+
+        let mut servers = state.servers.write().await;
+        let Some(instance) = servers.get_mut(&server_id) else {
+            return;
+        };
+        match event {
+            ConsoleEvent::Ready => {
+                ready = true;
+                if instance.state == ServerState::Starting {
+                    instance.state = ServerState::Running;
+                }
+            }
+            ConsoleEvent::PlayerJoined(_) => instance.players_online += 1,
+            ConsoleEvent::PlayerLeft(_) => {
+                instance.players_online = instance.players_online.saturating_sub(1);
+            }
+        }
+    }
+}
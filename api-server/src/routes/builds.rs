@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use server_manager::{runs_server_jar, ServerConfig};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    builds::{self, BuildTracking},
+    db, jars,
+    jobs::{self, JobKind},
+    routes::servers::ServerError,
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateBuildRequest {
+    /// Install this build of the server's version; it is pinned unless
+    /// `pinned` says otherwise
+    pub build: Option<String>,
+    pub pinned: Option<bool>,
+    pub auto_update: Option<bool>,
+}
+
+async fn get_config(state: &AppState, id: Uuid) -> Result<ServerConfig, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)
+}
+
+pub async fn get_build(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BuildTracking>, ServerError> {
+    get_config(&state, id).await?;
+
+    let tracking = db::get_server_build(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(tracking))
+}
+
+/// Look for newer builds now rather than at the next periodic check
+pub async fn check_build(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BuildTracking>, ServerError> {
+    let config = get_config(&state, id).await?;
+
+    let tracking = builds::check(&state, &config)
+        .await
+        .map_err(|e| ServerError::Internal(format!("{:#}", e)))?;
+
+    Ok(Json(tracking))
+}
+
+/// Pin a build or opt in to updates at the next start. Installing a build
+/// runs as a job, since Spigot's is compiled with BuildTools.
+pub async fn update_build(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateBuildRequest>,
+) -> Result<Response, ServerError> {
+    let config = get_config(&state, id).await?;
+    let mut tracking = db::get_server_build(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    let Some(build) = payload.build.filter(|b| tracking.build.as_ref() != Some(b)) else {
+        if let Some(pinned) = payload.pinned {
+            tracking.pinned = pinned;
+        }
+        if let Some(auto_update) = payload.auto_update {
+            tracking.auto_update = auto_update;
+        }
+        db::set_server_build(&state.db, id, &tracking)
+            .await
+            .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+        return Ok(Json(tracking).into_response());
+    };
+
+    if !runs_server_jar(config.server_type, &config.server_dir(&state.servers_dir)) {
+        return Err(ServerError::BadRequest(format!(
+            "{} servers here don't launch from server.jar",
+            config.server_type.as_str()
+        )));
+    }
+    let process = state.processes.read().await.get(&id).cloned();
+    if let Some(process) = process {
+        if process.is_running().await {
+            return Err(ServerError::BadRequest(
+                "Stop the server before changing its build".to_string(),
+            ));
+        }
+    }
+
+    let task_state = state.clone();
+    let job = jobs::submit(&state, JobKind::UpdateBuild, Some(id), move |progress| async move {
+        jars::download(&task_state, &config, &config.minecraft_version, Some(&build), &progress).await?;
+        tracing::info!("Installed build {} on server {}", build, id);
+
+        let mut tracking = db::get_server_build(&task_state.db, id).await?;
+        tracking.pinned = payload.pinned.unwrap_or(true);
+        if let Some(auto_update) = payload.auto_update {
+            tracking.auto_update = auto_update;
+        }
+        db::set_server_build(&task_state.db, id, &tracking).await?;
+        Ok(serde_json::to_value(tracking)?)
+    })
+    .await
+    .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok((StatusCode::ACCEPTED, Json(job)).into_response())
+}
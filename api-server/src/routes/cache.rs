@@ -0,0 +1,61 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use server_manager::{CacheGcReport, CachedArtifact};
+use std::sync::Arc;
+
+use crate::{routes::servers::ServerError, state::AppState};
+
+const DEFAULT_UNUSED_DAYS: u32 = 30;
+
+#[derive(Debug, Serialize)]
+pub struct CacheResponse {
+    pub artifacts: Vec<CachedArtifact>,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct GcRequest {
+    /// Remove artifacts not installed anywhere for this many days; 0 removes
+    /// everything no server uses
+    pub unused_days: Option<u32>,
+}
+
+pub async fn list_artifacts(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CacheResponse>, ServerError> {
+    let artifacts = state
+        .cache
+        .list()
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    let total_bytes = artifacts.iter().map(|a| a.size).sum();
+
+    Ok(Json(CacheResponse {
+        artifacts,
+        total_bytes,
+    }))
+}
+
+pub async fn collect_garbage(
+    State(state): State<Arc<AppState>>,
+    payload: Option<Json<GcRequest>>,
+) -> Result<Json<CacheGcReport>, ServerError> {
+    let Json(payload) = payload.unwrap_or_default();
+    let unused_days = payload.unused_days.unwrap_or(DEFAULT_UNUSED_DAYS);
+    let cutoff = chrono::Utc::now().timestamp() - unused_days as i64 * 86400;
+
+    let report = state
+        .cache
+        .gc(cutoff)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    tracing::info!(
+        "Cache GC removed {} artifacts, freed {} bytes",
+        report.removed.len(),
+        report.freed_bytes
+    );
+
+    Ok(Json(report))
+}
@@ -10,7 +10,12 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::{db, routes::servers::ServerError, state::AppState};
+use crate::{
+    db,
+    jobs::{self, Job, JobKind},
+    routes::servers::ServerError,
+    state::AppState,
+};
 
 #[derive(Debug, Serialize)]
 pub struct ConfigResponse {
@@ -98,19 +103,28 @@ pub async fn backup_world(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
     Json(payload): Json<BackupWorldRequest>,
-) -> Result<Json<BackupResponse>, ServerError> {
+) -> Result<(StatusCode, Json<Job>), ServerError> {
     let config = db::get_server(&state.db, id)
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?
         .ok_or(ServerError::NotFound)?;
 
     let server_dir = config.server_dir(&state.servers_dir);
+    if !server_dir.join(&payload.world_name).is_dir() {
+        return Err(ServerError::BadRequest(format!(
+            "World '{}' does not exist",
+            payload.world_name
+        )));
+    }
 
-    let backup_name = server_manager::backup_world(&server_dir, &payload.world_name)
-        .await
-        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    let job = jobs::submit(&state, JobKind::BackupWorld, Some(id), move |progress| async move {
+        let backup_name = server_manager::backup_world(&server_dir, &payload.world_name, &progress).await?;
+        Ok(serde_json::to_value(BackupResponse { backup_name })?)
+    })
+    .await
+    .map_err(|e| ServerError::Internal(e.to_string()))?;
 
-    Ok(Json(BackupResponse { backup_name }))
+    Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
 pub async fn delete_world(
@@ -168,14 +182,18 @@ pub async fn upload_world(
         return Err(ServerError::Internal("Missing world name or file".to_string()));
     }
 
-    tokio::task::spawn_blocking(move || {
-        server_manager::upload_world(&server_dir, &world_name, zip_data)
+    let job = jobs::submit(&state, JobKind::UploadWorld, Some(id), move |progress| async move {
+        let name = world_name.clone();
+        tokio::task::spawn_blocking(move || {
+            server_manager::upload_world(&server_dir, &name, zip_data, &progress)
+        })
+        .await??;
+        Ok(serde_json::json!({ "world_name": world_name }))
     })
     .await
-    .map_err(|e| ServerError::Internal(e.to_string()))?
     .map_err(|e| ServerError::Internal(e.to_string()))?;
 
-    Ok(StatusCode::CREATED)
+    Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
 pub async fn set_default_world(
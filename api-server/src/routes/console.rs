@@ -1,32 +1,66 @@
 use axum::{
     extract::{
         ws::WebSocket,
-        Path, State, WebSocketUpgrade,
+        Path, Query, State, WebSocketUpgrade,
     },
     response::Response,
 };
 use futures::{SinkExt, StreamExt};
 use axum::extract::ws as ax_ws;
+use serde::Deserialize;
+use server_manager::LogFilter;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::{db, routes::servers::ServerError, state::AppState};
+use crate::{auth::MaybeUser, db, routes::logs::log_filter, routes::servers::ServerError, state::AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct ConsoleQuery {
+    /// "json" sends each line as a structured record instead of plain text
+    pub format: Option<String>,
+    /// Comma-separated levels, e.g. "warn,error"
+    pub level: Option<String>,
+    pub min_level: Option<String>,
+    pub source: Option<String>,
+    pub thread: Option<String>,
+}
 
 pub async fn console_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    MaybeUser(user): MaybeUser,
+    Query(query): Query<ConsoleQuery>,
 ) -> Result<Response, ServerError> {
+    let filter = log_filter(
+        query.level.as_deref(),
+        query.min_level.as_deref(),
+        query.source.as_ref(),
+        query.thread.as_ref(),
+    )?;
+    let json = match query.format.as_deref() {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => return Err(ServerError::BadRequest(format!("Unknown format '{}'", other))),
+    };
+
     // Verify server exists
     db::get_server(&state.db, id)
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?
         .ok_or(ServerError::NotFound)?;
 
-    Ok(ws.on_upgrade(move |socket| handle_console_socket(socket, state, id)))
+    Ok(ws.on_upgrade(move |socket| handle_console_socket(socket, state, id, filter, json, user)))
 }
 
-async fn handle_console_socket(socket: WebSocket, state: Arc<AppState>, server_id: Uuid) {
+async fn handle_console_socket(
+    socket: WebSocket,
+    state: Arc<AppState>,
+    server_id: Uuid,
+    filter: LogFilter,
+    json: bool,
+    user: Option<String>,
+) {
     let (mut sender, mut receiver) = socket.split();
 
     // Get the broadcast receiver
@@ -45,7 +79,15 @@ async fn handle_console_socket(socket: WebSocket, state: Arc<AppState>, server_i
     // Task to pipe console output to WebSocket
     let mut send_task = tokio::spawn(async move {
         while let Ok(line) = rx.recv().await {
-            if let Err(_) = sender.send(ax_ws::Message::Text(line)).await {
+            if !filter.matches_line(&line) {
+                continue;
+            }
+            let text = if json {
+                serde_json::to_string(&line).unwrap_or_default()
+            } else {
+                line.to_string()
+            };
+            if sender.send(ax_ws::Message::Text(text)).await.is_err() {
                 break;
             }
         }
@@ -58,7 +100,7 @@ async fn handle_console_socket(socket: WebSocket, state: Arc<AppState>, server_i
             if let ax_ws::Message::Text(text) = msg {
                 let processes = state_clone.processes.read().await;
                 if let Some(process) = processes.get(&server_id) {
-                    let _ = process.send_command(text).await;
+                    let _ = process.send_command_as(text, user.clone()).await;
                 }
             }
         }
@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::IntoResponse,
+    Json,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{crashes, db::{self, CrashRecord}, routes::servers::ServerError, state::AppState};
+
+#[derive(Debug, Serialize)]
+pub struct CrashResponse {
+    #[serde(flatten)]
+    pub crash: CrashRecord,
+    pub download_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrashesResponse {
+    pub crashes: Vec<CrashResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanResponse {
+    pub indexed: usize,
+}
+
+fn to_response(crash: CrashRecord) -> CrashResponse {
+    let download_url = format!("/api/servers/{}/crashes/{}/report", crash.server_id, crash.id);
+    CrashResponse { crash, download_url }
+}
+
+async fn get_server_crash(state: &AppState, id: Uuid, crash_id: Uuid) -> Result<CrashRecord, ServerError> {
+    db::get_crash(&state.db, crash_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .filter(|c| c.server_id == id)
+        .ok_or(ServerError::NotFound)
+}
+
+pub async fn list_crashes(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CrashesResponse>, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let crashes = db::list_crashes(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .into_iter()
+        .map(to_response)
+        .collect();
+
+    Ok(Json(CrashesResponse { crashes }))
+}
+
+pub async fn scan_crashes(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ScanResponse>, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let indexed = crashes::index(&state, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(ScanResponse { indexed }))
+}
+
+pub async fn get_crash(
+    State(state): State<Arc<AppState>>,
+    Path((id, crash_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<CrashResponse>, ServerError> {
+    let crash = get_server_crash(&state, id, crash_id).await?;
+    Ok(Json(to_response(crash)))
+}
+
+pub async fn download_report(
+    State(state): State<Arc<AppState>>,
+    Path((id, crash_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ServerError> {
+    let crash = get_server_crash(&state, id, crash_id).await?;
+
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+    let path = config.server_dir(&state.servers_dir).join(&crash.summary.file);
+
+    let content = tokio::fs::read(&path)
+        .await
+        .map_err(|_| ServerError::NotFound)?;
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "crash.txt".to_string());
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        content,
+    ))
+}
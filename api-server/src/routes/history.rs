@@ -0,0 +1,85 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    history::{self, HistoryEntry, HistoryKind, HistorySearch},
+    routes::servers::ServerError,
+    state::AppState,
+};
+
+const DEFAULT_HISTORY_LIMIT: i64 = 200;
+const MAX_HISTORY_LIMIT: i64 = 5000;
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    /// Words that must all appear in the line; `word*` matches a prefix
+    pub q: Option<String>,
+    pub server_id: Option<Uuid>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// Shorthand for `from`, e.g. "24h"
+    pub since: Option<String>,
+    pub kind: Option<HistoryKind>,
+    pub user: Option<String>,
+    pub before_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoryResponse {
+    pub entries: Vec<HistoryEntry>,
+    /// Pass as `before_id` to fetch the next page
+    pub next_before_id: Option<i64>,
+}
+
+/// Full-text search over recorded console output and commands, newest first
+pub async fn search_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<HistoryResponse>, ServerError> {
+    let from = match &query.since {
+        Some(since) => {
+            let secs = server_manager::parse_duration(since)
+                .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+            Some(chrono::Utc::now().timestamp() - secs as i64)
+        }
+        None => query.from,
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .clamp(1, MAX_HISTORY_LIMIT);
+
+    let search = HistorySearch {
+        fts: query.q.as_deref().and_then(history::fts_query),
+        server_id: query.server_id,
+        from,
+        to: query.to,
+        kind: query.kind,
+        user: query.user,
+        before_id: query.before_id,
+        limit,
+    };
+
+    let entries = db::search_history(&state.db, &search)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    let next_before_id = if entries.len() as i64 == limit {
+        entries.last().map(|e| e.id)
+    } else {
+        None
+    };
+
+    Ok(Json(HistoryResponse {
+        entries,
+        next_before_id,
+    }))
+}
@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Multipart, Path, State},
+    Json,
+};
+use server_manager::{runs_server_jar, ServerConfig, PREVIOUS_SERVER_JAR};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    auth::MaybeUser,
+    db,
+    jars::{self, JarRecord, ServerJars, UploadedJar},
+    routes::servers::{read_upload, ServerError},
+    state::AppState,
+};
+
+async fn get_config(state: &AppState, id: Uuid) -> Result<ServerConfig, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)
+}
+
+/// The jar can't be swapped under a running JVM
+async fn ensure_stopped(state: &AppState, id: Uuid) -> Result<(), ServerError> {
+    let process = state.processes.read().await.get(&id).cloned();
+    if let Some(process) = process {
+        if process.is_running().await {
+            return Err(ServerError::BadRequest(
+                "Stop the server before changing its jar".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub async fn get_jars(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ServerJars>, ServerError> {
+    get_config(&state, id).await?;
+
+    let jars = db::get_server_jars(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(jars))
+}
+
+/// Replace a stopped server's jar with an uploaded one. Fields: `file` and
+/// optionally `version`, read from the jar or else kept when omitted.
+pub async fn replace_jar(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    Path(id): Path<Uuid>,
+    multipart: Multipart,
+) -> Result<Json<JarRecord>, ServerError> {
+    let config = get_config(&state, id).await?;
+    if !runs_server_jar(config.server_type, &config.server_dir(&state.servers_dir)) {
+        return Err(ServerError::BadRequest(format!(
+            "{} servers here don't launch from server.jar",
+            config.server_type.as_str()
+        )));
+    }
+
+    let mut upload = read_upload(multipart).await?;
+    if upload.data.is_empty() {
+        return Err(ServerError::BadRequest("Missing server jar".to_string()));
+    }
+    let jar = UploadedJar::read(std::mem::take(&mut upload.data), upload.file_name.clone(), user)
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+    let version = upload
+        .field("version")
+        .map(str::to_string)
+        .or_else(|| jar.info.version.clone())
+        .unwrap_or_else(|| config.minecraft_version.clone());
+
+    ensure_stopped(&state, id).await?;
+    let record = jars::replace(&state, &config, &jar, &version)
+        .await
+        .map_err(|e| ServerError::Internal(format!("{:#}", e)))?;
+    jars::set_version(&state, &config, &version)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    tracing::info!("Replaced jar of server {} with {} ({})", id, version, record.sha256);
+    Ok(Json(record))
+}
+
+/// Swap a stopped server's jar back to the one it replaced
+pub async fn rollback_jar(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JarRecord>, ServerError> {
+    let config = get_config(&state, id).await?;
+    if !config.server_dir(&state.servers_dir).join(PREVIOUS_SERVER_JAR).exists() {
+        return Err(ServerError::BadRequest(
+            "There is no previous jar to roll back to".to_string(),
+        ));
+    }
+    ensure_stopped(&state, id).await?;
+
+    let record = jars::rollback(&state, &config)
+        .await
+        .map_err(|e| ServerError::Internal(format!("{:#}", e)))?;
+    jars::set_version(&state, &config, &record.version)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    tracing::info!("Rolled back jar of server {} to {} ({})", id, record.version, record.sha256);
+    Ok(Json(record))
+}
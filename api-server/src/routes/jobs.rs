@@ -0,0 +1,180 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        Path, Query, State, WebSocketUpgrade,
+    },
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    jobs::{self, Job, JobLogLine, JobStatus, JobUpdate},
+    routes::servers::ServerError,
+    state::AppState,
+};
+
+const DEFAULT_JOB_LIMIT: i64 = 50;
+const MAX_JOB_LIMIT: i64 = 500;
+const JOB_LOG_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct ListJobsQuery {
+    pub server_id: Option<Uuid>,
+    pub status: Option<JobStatus>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobLogsQuery {
+    /// Only lines with a larger id, for following a running job
+    pub after_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JobSnapshot<'a> {
+    Snapshot { job: &'a Job },
+}
+
+pub async fn list_jobs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListJobsQuery>,
+) -> Result<Json<Vec<Job>>, ServerError> {
+    let limit = query.limit.unwrap_or(DEFAULT_JOB_LIMIT).clamp(1, MAX_JOB_LIMIT);
+
+    let jobs = db::list_jobs(&state.db, query.server_id, query.status, limit)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(jobs))
+}
+
+pub async fn get_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Job>, ServerError> {
+    let job = db::get_job(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    Ok(Json(job))
+}
+
+pub async fn get_job_logs(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<JobLogsQuery>,
+) -> Result<Json<Vec<JobLogLine>>, ServerError> {
+    db::get_job(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let lines = db::list_job_logs(&state.db, id, query.after_id.unwrap_or(0), JOB_LOG_LIMIT)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(lines))
+}
+
+pub async fn cancel_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ServerError> {
+    let job = db::get_job(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    if job.status.is_finished() {
+        return Err(ServerError::BadRequest(format!(
+            "Job has already {}",
+            job.status.as_str()
+        )));
+    }
+
+    jobs::cancel(&state, id)
+        .await
+        .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Stream a job's progress: a `snapshot` of the job, then `progress`, `log`
+/// and `status` updates until it finishes
+pub async fn job_ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, ServerError> {
+    db::get_job(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    Ok(ws.on_upgrade(move |socket| handle_job_socket(socket, state, id)))
+}
+
+async fn handle_job_socket(socket: WebSocket, state: Arc<AppState>, id: Uuid) {
+    let (mut sender, mut receiver) = socket.split();
+
+    // Subscribe before reading the snapshot so no update falls in between
+    let updates = state.jobs.read().await.get(&id).map(|job| job.updates.subscribe());
+
+    let Ok(Some(job)) = db::get_job(&state.db, id).await else {
+        return;
+    };
+    let text = serde_json::to_string(&JobSnapshot::Snapshot { job: &job }).unwrap_or_default();
+    if sender.send(Message::Text(text)).await.is_err() || job.status.is_finished() {
+        let _ = sender.close().await;
+        return;
+    }
+
+    let Some(mut updates) = updates else {
+        // The job finished between the two reads; its outcome is a moment
+        // away from being saved
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        if let Ok(Some(job)) = db::get_job(&state.db, id).await {
+            let text = serde_json::to_string(&JobSnapshot::Snapshot { job: &job }).unwrap_or_default();
+            let _ = sender.send(Message::Text(text)).await;
+        }
+        let _ = sender.close().await;
+        return;
+    };
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => {
+                let update = match update {
+                    Ok(update) => update,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                let finished = matches!(&update, JobUpdate::Status { status, .. } if status.is_finished());
+                let text = serde_json::to_string(&update).unwrap_or_default();
+                if sender.send(Message::Text(text)).await.is_err() {
+                    return;
+                }
+                if finished {
+                    break;
+                }
+            }
+            msg = receiver.next() => {
+                if !matches!(msg, Some(Ok(_))) {
+                    return;
+                }
+            }
+        }
+    }
+
+    let _ = sender.close().await;
+}
@@ -0,0 +1,332 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{
+        ws::{Message, WebSocket},
+        Path, Query, State, WebSocketUpgrade,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use server_manager::{parse_duration, LogFileInfo, LogFilter, LogMatch, LogPage, LogSearch, LogTail};
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+use crate::{db, routes::servers::ServerError, state::AppState};
+
+const DEFAULT_PAGE_LINES: usize = 500;
+const MAX_PAGE_LINES: usize = 5000;
+const DEFAULT_SEARCH_LIMIT: usize = 1000;
+const TAIL_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Serialize)]
+pub struct LogFilesResponse {
+    pub files: Vec<LogFileInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadLogQuery {
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchLogsQuery {
+    pub pattern: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// Shorthand for `from`, e.g. "1h" for the last hour
+    pub since: Option<String>,
+    pub limit: Option<usize>,
+    /// Comma-separated levels, e.g. "warn,error"
+    pub level: Option<String>,
+    pub min_level: Option<String>,
+    pub source: Option<String>,
+    pub thread: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchLogsResponse {
+    pub matches: Vec<LogMatch>,
+    pub truncated: bool,
+}
+
+async fn server_dir(state: &AppState, id: Uuid) -> Result<PathBuf, ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    Ok(config.server_dir(&state.servers_dir))
+}
+
+/// Build a level/thread/source filter from query parameters, shared with the console WebSocket
+pub fn log_filter(
+    level: Option<&str>,
+    min_level: Option<&str>,
+    source: Option<&String>,
+    thread: Option<&String>,
+) -> Result<LogFilter, ServerError> {
+    LogFilter::parse(level, min_level, source.cloned(), thread.cloned())
+        .map_err(|e| ServerError::BadRequest(e.to_string()))
+}
+
+fn log_path(server_dir: &std::path::Path, name: &str) -> Result<PathBuf, ServerError> {
+    server_manager::resolve_log_file(server_dir, name)
+        .map_err(|e| ServerError::BadRequest(e.to_string()))
+}
+
+pub async fn list_logs(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LogFilesResponse>, ServerError> {
+    let server_dir = server_dir(&state, id).await?;
+
+    let files = server_manager::list_log_files(&server_dir)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(LogFilesResponse { files }))
+}
+
+/// Page through a log by line, decompressing archives on the fly
+pub async fn read_log(
+    State(state): State<Arc<AppState>>,
+    Path((id, name)): Path<(Uuid, String)>,
+    Query(query): Query<ReadLogQuery>,
+) -> Result<Json<LogPage>, ServerError> {
+    let server_dir = server_dir(&state, id).await?;
+    let path = log_path(&server_dir, &name)?;
+
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_LINES).clamp(1, MAX_PAGE_LINES);
+
+    let page = tokio::task::spawn_blocking(move || server_manager::read_log_page(&path, offset, limit))
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(page))
+}
+
+/// A `Range` header resolved against a log's length
+enum ByteRange {
+    Full,
+    /// First and last byte, inclusive
+    Partial(u64, u64),
+    Unsatisfiable,
+}
+
+/// Resolve a single `bytes=` range. Anything else, including several ranges,
+/// is ignored and the whole log served.
+fn byte_range(value: &str, len: u64) -> ByteRange {
+    let Some((first, last)) = value
+        .strip_prefix("bytes=")
+        .filter(|spec| !spec.contains(','))
+        .and_then(|spec| spec.trim().split_once('-'))
+    else {
+        return ByteRange::Full;
+    };
+
+    let range = match (first.parse::<u64>(), last.parse::<u64>()) {
+        (Ok(first), Ok(last)) if first <= last => (first, last.min(len.saturating_sub(1))),
+        (Ok(first), Err(_)) if last.is_empty() => (first, len.saturating_sub(1)),
+        (Err(_), Ok(suffix)) if first.is_empty() => {
+            if suffix == 0 {
+                return ByteRange::Unsatisfiable;
+            }
+            (len.saturating_sub(suffix), len.saturating_sub(1))
+        }
+        _ => return ByteRange::Full,
+    };
+
+    if range.0 >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial(range.0, range.1)
+}
+
+/// Stream a log as plain text, decompressing archives on the fly. A `Range`
+/// header serves part of the decompressed text.
+pub async fn download_log(
+    State(state): State<Arc<AppState>>,
+    Path((id, name)): Path<(Uuid, String)>,
+    headers: HeaderMap,
+) -> Result<Response, ServerError> {
+    let server_dir = server_dir(&state, id).await?;
+    let path = log_path(&server_dir, &name)?;
+
+    let len_path = path.clone();
+    let len = tokio::task::spawn_blocking(move || server_manager::log_length(&len_path))
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .map_or(ByteRange::Full, |v| byte_range(v, len));
+    let (status, start, end) = match range {
+        ByteRange::Full => (StatusCode::OK, 0, len),
+        ByteRange::Partial(first, last) => (StatusCode::PARTIAL_CONTENT, first, last + 1),
+        ByteRange::Unsatisfiable => {
+            return Ok((
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{}", len))],
+            )
+                .into_response());
+        }
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<std::io::Result<Bytes>>(8);
+    tokio::task::spawn_blocking(move || {
+        // Reaching `start` in an archive means decompressing everything before it
+        let mut reader = match server_manager::open_log_reader_at(&path, start) {
+            Ok(reader) => reader.take(end - start),
+            Err(e) => {
+                let _ = tx.blocking_send(Err(std::io::Error::other(format!("{:#}", e))));
+                return;
+            }
+        };
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    if tx.blocking_send(Ok(Bytes::copy_from_slice(&buf[..n]))).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.blocking_send(Err(e));
+                    break;
+                }
+            }
+        }
+    });
+
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    });
+
+    let download_name = name.trim_end_matches(".gz").to_string();
+    let mut response = (
+        status,
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", download_name),
+            ),
+            (header::ACCEPT_RANGES, "bytes".to_string()),
+            (header::CONTENT_LENGTH, (end - start).to_string()),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response();
+    if status == StatusCode::PARTIAL_CONTENT {
+        let content_range = format!("bytes {}-{}/{}", start, end - 1, len);
+        if let Ok(value) = content_range.parse() {
+            response.headers_mut().insert(header::CONTENT_RANGE, value);
+        }
+    }
+    Ok(response)
+}
+
+/// Regex and level/source search across latest.log and every rotated archive
+pub async fn search_logs(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SearchLogsQuery>,
+) -> Result<Json<SearchLogsResponse>, ServerError> {
+    let server_dir = server_dir(&state, id).await?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, DEFAULT_SEARCH_LIMIT * 10);
+    let filter = log_filter(
+        query.level.as_deref(),
+        query.min_level.as_deref(),
+        query.source.as_ref(),
+        query.thread.as_ref(),
+    )?;
+
+    let from = match &query.since {
+        Some(since) => {
+            let secs = parse_duration(since)
+                .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+            Some(chrono::Utc::now().timestamp() - secs as i64)
+        }
+        None => query.from,
+    };
+
+    let search = LogSearch::new(query.pattern.as_deref(), filter, from, query.to, limit)
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+
+    let files = server_manager::list_log_files(&server_dir)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    let matches = tokio::task::spawn_blocking(move || {
+        server_manager::search_logs(&server_dir, &files, &search)
+    })
+    .await
+    .map_err(|e| ServerError::Internal(e.to_string()))?
+    .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    let truncated = matches.len() >= limit;
+    Ok(Json(SearchLogsResponse { matches, truncated }))
+}
+
+/// Follow latest.log over a WebSocket. Works for recovered servers whose
+/// stdout we don't own, since it reads the file rather than the process.
+pub async fn tail_log(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, ServerError> {
+    let server_dir = server_dir(&state, id).await?;
+    let path = server_dir.join("logs").join("latest.log");
+
+    Ok(ws.on_upgrade(move |socket| handle_tail_socket(socket, path)))
+}
+
+async fn handle_tail_socket(socket: WebSocket, path: PathBuf) {
+    let (mut sender, mut receiver) = socket.split();
+
+    let mut tail = match LogTail::from_end(path).await {
+        Ok(tail) => tail,
+        Err(e) => {
+            let _ = sender.send(Message::Text(format!("Failed to open log: {}", e))).await;
+            return;
+        }
+    };
+
+    let mut interval = tokio::time::interval(TAIL_POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let lines = match tail.poll().await {
+                    Ok(lines) => lines,
+                    Err(e) => {
+                        tracing::warn!("Failed to tail log: {}", e);
+                        continue;
+                    }
+                };
+                for line in lines {
+                    if sender.send(Message::Text(line)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            msg = receiver.next() => {
+                if !matches!(msg, Some(Ok(_))) {
+                    return;
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,20 @@
+pub mod builds;
+pub mod cache;
 pub mod console;
 pub mod config;
+pub mod crashes;
 pub mod plugins;
 pub mod servers;
 pub mod stats;
 pub mod files;
+pub mod history;
+pub mod jars;
+pub mod jobs;
+pub mod logs;
+pub mod mods;
+pub mod networks;
+pub mod players;
+pub mod schedules;
+pub mod triggers;
+pub mod upgrades;
+pub mod watchdog;
@@ -0,0 +1,122 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use server_manager::{InstallPlan, InstalledAddon, PluginInfo};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{db, routes::servers::ServerError, state::AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModsResponse {
+    pub mods: Vec<PluginInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstalledModsResponse {
+    pub mods: Vec<InstalledAddon>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstallModRequest {
+    /// Modrinth project id or slug
+    #[serde(alias = "mod_name")]
+    pub project: String,
+    /// Release to install instead of the newest for the server
+    pub version_id: Option<String>,
+}
+
+/// Mods are searched per server, since results depend on its loader and
+/// Minecraft version
+pub async fn search_mods(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<ModsResponse>, ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let mods = server_manager::search_mods(
+        &state.upstreams,
+        &query.q,
+        config.server_type,
+        &config.minecraft_version,
+    )
+        .await
+        .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+
+    Ok(Json(ModsResponse { mods }))
+}
+
+pub async fn list_installed_mods(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<InstalledModsResponse>, ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let server_dir = config.server_dir(&state.servers_dir);
+
+    let mods = server_manager::list_installed_mods(&server_dir)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(InstalledModsResponse { mods }))
+}
+
+pub async fn install_mod(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<InstallModRequest>,
+) -> Result<Json<InstallPlan>, ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let server_dir = config.server_dir(&state.servers_dir);
+
+    let plan = server_manager::install_mod(
+        &state.upstreams,
+        &server_dir,
+        &payload.project,
+        payload.version_id.as_deref(),
+        &config.minecraft_version,
+        config.server_type,
+        &state.cache,
+    )
+        .await
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+
+    Ok(Json(plan))
+}
+
+pub async fn remove_mod(
+    State(state): State<Arc<AppState>>,
+    Path((id, mod_name)): Path<(Uuid, String)>,
+) -> Result<StatusCode, ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let server_dir = config.server_dir(&state.servers_dir);
+
+    server_manager::remove_mod(&server_dir, &mod_name)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
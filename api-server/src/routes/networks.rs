@@ -0,0 +1,236 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use server_manager::{check_backend, generate_forwarding_secret, is_valid_backend_name, leave_network};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    networks::{self, Network, NetworkMember},
+    routes::servers::ServerError,
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateNetworkRequest {
+    pub name: String,
+    pub proxy_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddBackendRequest {
+    pub server_id: Uuid,
+    /// Name on the proxy; derived from the server name when omitted
+    pub name: Option<String>,
+}
+
+async fn get_network(state: &AppState, id: Uuid) -> Result<Network, ServerError> {
+    db::get_network(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)
+}
+
+async fn ensure_not_networked(state: &AppState, server_id: Uuid) -> Result<(), ServerError> {
+    let existing = db::find_server_network(&state.db, server_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    if existing.is_some() {
+        return Err(ServerError::BadRequest(
+            "Server is already part of a network".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn list_networks(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Network>>, ServerError> {
+    let networks = db::list_networks(&state.db)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(networks))
+}
+
+pub async fn get_network_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Network>, ServerError> {
+    Ok(Json(get_network(&state, id).await?))
+}
+
+pub async fn create_network(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateNetworkRequest>,
+) -> Result<Json<Network>, ServerError> {
+    let proxy = db::get_server(&state.db, payload.proxy_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    if !proxy.server_type.is_proxy() {
+        return Err(ServerError::BadRequest(format!(
+            "{} is not a proxy",
+            proxy.name
+        )));
+    }
+    ensure_not_networked(&state, proxy.id).await?;
+
+    let network = Network {
+        id: Uuid::new_v4(),
+        name: payload.name,
+        proxy_id: proxy.id,
+        forwarding_secret: generate_forwarding_secret(),
+        backends: Vec::new(),
+    };
+
+    db::create_network(&state.db, &network)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    // Don't leave a network behind whose proxy was never configured
+    if let Err(e) = networks::apply(&state, &network).await {
+        if let Err(cleanup) = db::delete_network(&state.db, network.id).await {
+            tracing::warn!("Failed to remove network {}: {}", network.id, cleanup);
+        }
+        return Err(ServerError::Internal(format!("{:#}", e)));
+    }
+
+    Ok(Json(network))
+}
+
+pub async fn delete_network(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ServerError> {
+    let network = get_network(&state, id).await?;
+
+    // Hand the backends back their own authentication and public port
+    for member in &network.backends {
+        if let Ok(Some(config)) = db::get_server(&state.db, member.server_id).await {
+            if let Err(e) = leave_network(&config.server_dir(&state.servers_dir), config.server_type).await {
+                tracing::warn!("Failed to release backend {}: {:#}", member.name, e);
+            }
+        }
+    }
+
+    db::delete_network(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn add_backend(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AddBackendRequest>,
+) -> Result<Json<Network>, ServerError> {
+    let network = get_network(&state, id).await?;
+
+    let server = db::get_server(&state.db, payload.server_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+    let proxy = db::get_server(&state.db, network.proxy_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or_else(|| ServerError::Internal("Proxy server no longer exists".to_string()))?;
+
+    check_backend(proxy.server_type, server.server_type)
+        .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+    ensure_not_networked(&state, server.id).await?;
+
+    let name = payload
+        .name
+        .unwrap_or_else(|| networks::backend_name(&server.name));
+    if !is_valid_backend_name(&name) {
+        return Err(ServerError::BadRequest(
+            "Backend names may only contain letters, digits, '-' and '_'".to_string(),
+        ));
+    }
+    if network.backends.iter().any(|b| b.name == name) {
+        return Err(ServerError::BadRequest(format!(
+            "A backend named {} already exists",
+            name
+        )));
+    }
+
+    // Backends listen on loopback, which still clashes with the proxy's
+    // wildcard bind and with each other
+    if server.port == proxy.port {
+        return Err(ServerError::BadRequest(format!(
+            "Port {} is already the proxy's port",
+            server.port
+        )));
+    }
+    for member in &network.backends {
+        let other = db::get_server(&state.db, member.server_id)
+            .await
+            .map_err(|e| ServerError::Internal(e.to_string()))?;
+        if other.is_some_and(|o| o.port == server.port) {
+            return Err(ServerError::BadRequest(format!(
+                "Port {} is already used by backend {}",
+                server.port, member.name
+            )));
+        }
+    }
+
+    let member = NetworkMember {
+        server_id: server.id,
+        name,
+    };
+    db::add_network_server(&state.db, id, &member)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    // A backend that can't be wired up would fail every later apply
+    let network = get_network(&state, id).await?;
+    if let Err(e) = networks::apply(&state, &network).await {
+        if let Err(cleanup) = db::remove_network_server(&state.db, id, server.id).await {
+            tracing::warn!("Failed to remove backend {} from network {}: {}", server.id, id, cleanup);
+        }
+        if let Err(cleanup) = leave_network(&server.server_dir(&state.servers_dir), server.server_type).await {
+            tracing::warn!("Failed to release backend {}: {:#}", server.id, cleanup);
+        }
+        return Err(ServerError::Internal(format!("{:#}", e)));
+    }
+
+    Ok(Json(network))
+}
+
+pub async fn remove_backend(
+    State(state): State<Arc<AppState>>,
+    Path((id, server_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Network>, ServerError> {
+    let network = get_network(&state, id).await?;
+    if !network.backends.iter().any(|b| b.server_id == server_id) {
+        return Err(ServerError::NotFound);
+    }
+
+    db::remove_network_server(&state.db, id, server_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    if let Some(config) = db::get_server(&state.db, server_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+    {
+        leave_network(&config.server_dir(&state.servers_dir), config.server_type)
+            .await
+            .map_err(|e| ServerError::Internal(format!("{:#}", e)))?;
+    }
+
+    let network = get_network(&state, id).await?;
+    networks::apply(&state, &network)
+        .await
+        .map_err(|e| ServerError::Internal(format!("{:#}", e)))?;
+
+    Ok(Json(network))
+}
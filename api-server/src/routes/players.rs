@@ -0,0 +1,451 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use server_manager::{
+    BannedIpEntry, BannedPlayerEntry, OpEntry, ServerConfig, ServerProcess, ServerType, WhitelistEntry,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{auth::MaybeUser, db, routes::servers::ServerError, state::AppState};
+
+#[derive(Debug, Serialize)]
+pub struct PlayerListsResponse {
+    pub ops: Vec<OpEntry>,
+    pub whitelist: Vec<WhitelistEntry>,
+    pub banned_players: Vec<BannedPlayerEntry>,
+    pub banned_ips: Vec<BannedIpEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KickRequest {
+    pub name: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BanRequest {
+    pub name: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BanIpRequest {
+    pub ip: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpRequest {
+    pub name: String,
+    pub level: Option<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WhitelistRequest {
+    pub name: String,
+}
+
+/// Where a player change should be applied
+enum Target {
+    /// Server is running: the change must go through the console, since the
+    /// server rewrites its JSON lists from memory
+    Console(Arc<ServerProcess>),
+    /// Server is stopped: edit the JSON lists directly
+    Files { server_dir: PathBuf, online_mode: bool },
+}
+
+async fn get_config(state: &AppState, id: Uuid) -> Result<ServerConfig, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)
+}
+
+/// For features Bedrock Dedicated Server lacks, such as bans
+fn reject_bedrock(server_type: ServerType, what: &str) -> Result<(), ServerError> {
+    if server_type.is_bedrock() {
+        return Err(ServerError::BadRequest(format!("Bedrock servers don't support {}", what)));
+    }
+    Ok(())
+}
+
+async fn resolve_target(state: &Arc<AppState>, config: &ServerConfig) -> Result<Target, ServerError> {
+    let process = state.processes.read().await.get(&config.id).cloned();
+    if let Some(process) = process {
+        if process.is_running().await {
+            return Ok(Target::Console(process));
+        }
+    }
+
+    let server_dir = config.server_dir(&state.servers_dir);
+    let properties = server_manager::read_server_properties(&server_dir.join("server.properties"))
+        .await
+        .unwrap_or_default();
+    let online_mode = properties
+        .get("online-mode")
+        .map(|v| v != "false")
+        .unwrap_or(true);
+
+    Ok(Target::Files {
+        server_dir,
+        online_mode,
+    })
+}
+
+async fn send(process: &ServerProcess, command: String, user: Option<String>) -> Result<(), ServerError> {
+    process
+        .send_command_as(command, user)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))
+}
+
+fn validate_name(name: &str, server_type: ServerType) -> Result<(), ServerError> {
+    let valid = if server_type.is_bedrock() {
+        server_manager::validate_gamertag(name)
+    } else {
+        server_manager::validate_player_name(name)
+    };
+    valid.map_err(|e| ServerError::BadRequest(e.to_string()))
+}
+
+/// A player name as a console argument; gamertags may contain spaces
+fn player_arg(name: &str) -> String {
+    if name.contains(' ') {
+        format!("\"{}\"", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn with_reason(command: String, reason: Option<&str>) -> String {
+    match reason.map(server_manager::sanitize_console_arg) {
+        Some(reason) if !reason.is_empty() => format!("{} {}", command, reason),
+        _ => command,
+    }
+}
+
+pub async fn list_players(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PlayerListsResponse>, ServerError> {
+    let config = get_config(&state, id).await?;
+
+    let server_dir = config.server_dir(&state.servers_dir);
+    if config.server_type.is_bedrock() {
+        return list_bedrock_players(&server_dir).await.map(Json);
+    }
+
+    let ops = server_manager::list_ops(&server_dir)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    let whitelist = server_manager::list_whitelist(&server_dir)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    let banned_players = server_manager::list_banned_players(&server_dir)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    let banned_ips = server_manager::list_banned_ips(&server_dir)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(PlayerListsResponse {
+        ops,
+        whitelist,
+        banned_players,
+        banned_ips,
+    }))
+}
+
+/// Bedrock's allowlist and operators in the Java list shapes. Operators are
+/// only known by XUID, so that stands in for their name.
+async fn list_bedrock_players(server_dir: &std::path::Path) -> Result<PlayerListsResponse, ServerError> {
+    let ops = server_manager::list_bedrock_operators(server_dir)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .into_iter()
+        .map(|entry| OpEntry {
+            uuid: entry.xuid.clone(),
+            name: entry.xuid,
+            level: 4,
+            bypasses_player_limit: false,
+        })
+        .collect();
+    let whitelist = server_manager::list_allowlist(server_dir)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .into_iter()
+        .map(|entry| WhitelistEntry {
+            uuid: entry.xuid.unwrap_or_default(),
+            name: entry.name,
+        })
+        .collect();
+
+    Ok(PlayerListsResponse {
+        ops,
+        whitelist,
+        banned_players: Vec::new(),
+        banned_ips: Vec::new(),
+    })
+}
+
+pub async fn kick_player(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<KickRequest>,
+) -> Result<StatusCode, ServerError> {
+    let config = get_config(&state, id).await?;
+    validate_name(&payload.name, config.server_type)?;
+
+    match resolve_target(&state, &config).await? {
+        Target::Console(process) => {
+            let command = with_reason(format!("kick {}", player_arg(&payload.name)), payload.reason.as_deref());
+            send(&process, command, user).await?;
+        }
+        Target::Files { .. } => return Err(ServerError::NotRunning),
+    }
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn ban_player(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<BanRequest>,
+) -> Result<StatusCode, ServerError> {
+    let config = get_config(&state, id).await?;
+    let server_type = config.server_type;
+    reject_bedrock(server_type, "bans")?;
+    validate_name(&payload.name, server_type)?;
+
+    match resolve_target(&state, &config).await? {
+        Target::Console(process) => {
+            let command = with_reason(format!("ban {}", payload.name), payload.reason.as_deref());
+            send(&process, command, user).await?;
+        }
+        Target::Files { server_dir, online_mode } => {
+            let player = server_manager::resolve_player(&payload.name, online_mode)
+                .await
+                .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+            let reason = payload.reason.as_deref().map(server_manager::sanitize_console_arg);
+
+            server_manager::ban_player(&server_dir, &player, reason.as_deref())
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+        }
+    }
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn pardon_player(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    Path((id, name)): Path<(Uuid, String)>,
+) -> Result<StatusCode, ServerError> {
+    let config = get_config(&state, id).await?;
+    let server_type = config.server_type;
+    reject_bedrock(server_type, "bans")?;
+    validate_name(&name, server_type)?;
+
+    match resolve_target(&state, &config).await? {
+        Target::Console(process) => send(&process, format!("pardon {}", name), user).await?,
+        Target::Files { server_dir, .. } => {
+            server_manager::pardon_player(&server_dir, &name)
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn ban_ip(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<BanIpRequest>,
+) -> Result<StatusCode, ServerError> {
+    let config = get_config(&state, id).await?;
+    reject_bedrock(config.server_type, "bans")?;
+    let ip = server_manager::validate_ip(&payload.ip)
+        .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+
+    match resolve_target(&state, &config).await? {
+        Target::Console(process) => {
+            let command = with_reason(format!("ban-ip {}", ip), payload.reason.as_deref());
+            send(&process, command, user).await?;
+        }
+        Target::Files { server_dir, .. } => {
+            let reason = payload.reason.as_deref().map(server_manager::sanitize_console_arg);
+
+            server_manager::ban_ip(&server_dir, ip, reason.as_deref())
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+        }
+    }
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn pardon_ip(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    Path((id, ip)): Path<(Uuid, String)>,
+) -> Result<StatusCode, ServerError> {
+    let config = get_config(&state, id).await?;
+    reject_bedrock(config.server_type, "bans")?;
+    let ip = server_manager::validate_ip(&ip)
+        .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+
+    match resolve_target(&state, &config).await? {
+        Target::Console(process) => send(&process, format!("pardon-ip {}", ip), user).await?,
+        Target::Files { server_dir, .. } => {
+            server_manager::pardon_ip(&server_dir, ip)
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn op_player(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<OpRequest>,
+) -> Result<StatusCode, ServerError> {
+    let config = get_config(&state, id).await?;
+    let server_type = config.server_type;
+    validate_name(&payload.name, server_type)?;
+
+    if let Some(level) = payload.level {
+        if !(1..=4).contains(&level) {
+            return Err(ServerError::BadRequest("Op level must be between 1 and 4".to_string()));
+        }
+    }
+
+    match resolve_target(&state, &config).await? {
+        Target::Console(process) => {
+            // The `op` command always grants the server's op-permission-level
+            if payload.level.is_some() {
+                return Err(ServerError::BadRequest(
+                    "Op level can only be set while the server is stopped".to_string(),
+                ));
+            }
+            send(&process, format!("op {}", player_arg(&payload.name)), user).await?;
+        }
+        Target::Files { .. } if server_type.is_bedrock() => {
+            return Err(ServerError::BadRequest(
+                "Bedrock operators can only be changed while the server is running".to_string(),
+            ));
+        }
+        Target::Files { server_dir, online_mode } => {
+            let player = server_manager::resolve_player(&payload.name, online_mode)
+                .await
+                .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+
+            server_manager::add_op(&server_dir, &player, payload.level.unwrap_or(4))
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+        }
+    }
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn deop_player(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    Path((id, name)): Path<(Uuid, String)>,
+) -> Result<StatusCode, ServerError> {
+    let config = get_config(&state, id).await?;
+    let server_type = config.server_type;
+    validate_name(&name, server_type)?;
+
+    match resolve_target(&state, &config).await? {
+        Target::Console(process) => send(&process, format!("deop {}", player_arg(&name)), user).await?,
+        Target::Files { .. } if server_type.is_bedrock() => {
+            return Err(ServerError::BadRequest(
+                "Bedrock operators can only be changed while the server is running".to_string(),
+            ));
+        }
+        Target::Files { server_dir, .. } => {
+            server_manager::remove_op(&server_dir, &name)
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn whitelist_add(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<WhitelistRequest>,
+) -> Result<StatusCode, ServerError> {
+    let config = get_config(&state, id).await?;
+    let server_type = config.server_type;
+    validate_name(&payload.name, server_type)?;
+
+    match resolve_target(&state, &config).await? {
+        Target::Console(process) if server_type.is_bedrock() => {
+            send(&process, format!("allowlist add {}", player_arg(&payload.name)), user).await?
+        }
+        Target::Console(process) => send(&process, format!("whitelist add {}", payload.name), user).await?,
+        Target::Files { server_dir, .. } if server_type.is_bedrock() => {
+            server_manager::add_to_allowlist(&server_dir, &payload.name)
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+        }
+        Target::Files { server_dir, online_mode } => {
+            let player = server_manager::resolve_player(&payload.name, online_mode)
+                .await
+                .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+
+            server_manager::add_to_whitelist(&server_dir, &player)
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+        }
+    }
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn whitelist_remove(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    Path((id, name)): Path<(Uuid, String)>,
+) -> Result<StatusCode, ServerError> {
+    let config = get_config(&state, id).await?;
+    let server_type = config.server_type;
+    validate_name(&name, server_type)?;
+
+    match resolve_target(&state, &config).await? {
+        Target::Console(process) if server_type.is_bedrock() => {
+            send(&process, format!("allowlist remove {}", player_arg(&name)), user).await?
+        }
+        Target::Console(process) => send(&process, format!("whitelist remove {}", name), user).await?,
+        Target::Files { server_dir, .. } if server_type.is_bedrock() => {
+            server_manager::remove_from_allowlist(&server_dir, &name)
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+        }
+        Target::Files { server_dir, .. } => {
+            server_manager::remove_from_whitelist(&server_dir, &name)
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -4,11 +4,16 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
-use server_manager::PluginInfo;
+use server_manager::{AddonUpdate, AddonVersion, AppliedAddonUpdate, InstallPlan, InstalledAddon, PluginInfo};
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::{db, routes::servers::ServerError, state::AppState};
+use crate::{
+    db,
+    jobs::{self, Job, JobKind},
+    routes::servers::ServerError,
+    state::AppState,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct SearchQuery {
@@ -20,16 +25,56 @@ pub struct PluginsResponse {
     pub plugins: Vec<PluginInfo>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct InstalledPluginsResponse {
+    pub plugins: Vec<InstalledAddon>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct InstallPluginRequest {
-    pub plugin_name: String,
+    /// Modrinth project id or slug
+    #[serde(alias = "plugin_name")]
+    pub project: String,
+    /// Release to install instead of the newest for the server
+    pub version_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VersionsQuery {
+    pub project: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionsResponse {
+    pub versions: Vec<AddonVersion>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdatesResponse {
+    pub updates: Vec<AddonUpdate>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateAddonsRequest {
+    /// Jar to update; every jar with an update when absent
+    pub file_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdateAddonsResponse {
+    pub updated: Vec<AppliedAddonUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RollbackAddonRequest {
+    pub file_name: String,
 }
 
 pub async fn search_plugins(
-    State(_state): State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
     Query(query): Query<SearchQuery>,
 ) -> Result<Json<PluginsResponse>, ServerError> {
-    let plugins = server_manager::search_plugins(&query.q, "paper")
+    let plugins = server_manager::search_plugins(&state.upstreams, &query.q, "paper")
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?;
 
@@ -39,7 +84,7 @@ pub async fn search_plugins(
 pub async fn list_installed_plugins(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
-) -> Result<Json<PluginsResponse>, ServerError> {
+) -> Result<Json<InstalledPluginsResponse>, ServerError> {
     let config = db::get_server(&state.db, id)
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?
@@ -51,14 +96,63 @@ pub async fn list_installed_plugins(
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?;
 
-    Ok(Json(PluginsResponse { plugins }))
+    Ok(Json(InstalledPluginsResponse { plugins }))
+}
+
+/// Releases of a project that run on the server, for plugins or mods
+/// alike since the server type decides which
+pub async fn list_addon_versions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<VersionsQuery>,
+) -> Result<Json<VersionsResponse>, ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let versions = server_manager::list_addon_versions(
+        &state.upstreams,
+        &query.project,
+        config.server_type,
+        &config.minecraft_version,
+    )
+        .await
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+
+    Ok(Json(VersionsResponse { versions }))
+}
+
+/// What an install would add, without installing anything
+pub async fn plan_addon_install(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<InstallPluginRequest>,
+) -> Result<Json<InstallPlan>, ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let plan = server_manager::plan_addon_install(
+        &state.upstreams,
+        &config.server_dir(&state.servers_dir),
+        &payload.project,
+        payload.version_id.as_deref(),
+        &config.minecraft_version,
+        config.server_type,
+    )
+        .await
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+
+    Ok(Json(plan))
 }
 
 pub async fn install_plugin(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
     Json(payload): Json<InstallPluginRequest>,
-) -> Result<StatusCode, ServerError> {
+) -> Result<Json<InstallPlan>, ServerError> {
     let config = db::get_server(&state.db, id)
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?
@@ -66,16 +160,19 @@ pub async fn install_plugin(
 
     let server_dir = config.server_dir(&state.servers_dir);
 
-    server_manager::install_plugin(
+    let plan = server_manager::install_plugin(
+        &state.upstreams,
         &server_dir,
-        &payload.plugin_name,
+        &payload.project,
+        payload.version_id.as_deref(),
         &config.minecraft_version,
         config.server_type,
+        &state.cache,
     )
         .await
-        .map_err(|e| ServerError::Internal(e.to_string()))?;
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
 
-    Ok(StatusCode::OK)
+    Ok(Json(plan))
 }
 
 pub async fn remove_plugin(
@@ -95,3 +192,80 @@ pub async fn remove_plugin(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Installed plugins or mods against the newest release for the server
+pub async fn check_addon_updates(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<UpdatesResponse>, ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let updates = server_manager::check_addon_updates(
+        &state.upstreams,
+        &config.server_dir(&state.servers_dir),
+        config.server_type,
+        &config.minecraft_version,
+    )
+        .await
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+
+    Ok(Json(UpdatesResponse { updates }))
+}
+
+/// Update one plugin or mod, or all of them, keeping the replaced jars. The
+/// downloads run as a job.
+pub async fn update_addons(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<UpdateAddonsRequest>>,
+) -> Result<(StatusCode, Json<Job>), ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+    let Json(payload) = payload.unwrap_or_default();
+
+    let task_state = state.clone();
+    let job = jobs::submit(&state, JobKind::UpdateAddons, Some(id), move |progress| async move {
+        let updated = server_manager::update_addons(
+            &task_state.upstreams,
+            &task_state.cache,
+            &config.server_dir(&task_state.servers_dir),
+            config.server_type,
+            &config.minecraft_version,
+            payload.file_name.as_deref(),
+            &progress,
+        )
+            .await?;
+        Ok(serde_json::to_value(UpdateAddonsResponse { updated })?)
+    })
+    .await
+    .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Put back the jar an update replaced
+pub async fn rollback_addon_update(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RollbackAddonRequest>,
+) -> Result<StatusCode, ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    server_manager::rollback_addon_update(
+        &config.server_dir(&state.servers_dir),
+        config.server_type,
+        &payload.file_name,
+    )
+        .await
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,195 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    routes::servers::ServerError,
+    scheduler::{self, Schedule, ScheduledTask},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateScheduleRequest {
+    pub name: String,
+    pub cron: String,
+    pub timezone: Option<String>,
+    pub task: ScheduledTask,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateScheduleRequest {
+    pub name: Option<String>,
+    pub cron: Option<String>,
+    pub timezone: Option<String>,
+    pub task: Option<ScheduledTask>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SchedulesResponse {
+    pub schedules: Vec<Schedule>,
+}
+
+fn validate_task(task: &ScheduledTask) -> Result<(), ServerError> {
+    let text = match task {
+        ScheduledTask::Command { command } => command,
+        ScheduledTask::Broadcast { message } => message,
+        ScheduledTask::Backup { world_name } => world_name,
+        _ => return Ok(()),
+    };
+
+    if text.trim().is_empty() || text.chars().any(|c| c.is_control()) {
+        return Err(ServerError::BadRequest(
+            "Task argument must be a single non-empty line".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate the cron expression and timezone, returning the next run time
+fn compute_next_run(schedule: &Schedule) -> Result<Option<i64>, ServerError> {
+    if !schedule.enabled {
+        return Ok(None);
+    }
+
+    scheduler::next_run_after(&schedule.cron, &schedule.timezone, chrono::Utc::now())
+        .map_err(|e| ServerError::BadRequest(e.to_string()))
+}
+
+async fn get_server_schedule(
+    state: &AppState,
+    id: Uuid,
+    schedule_id: Uuid,
+) -> Result<Schedule, ServerError> {
+    db::get_schedule(&state.db, schedule_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .filter(|s| s.server_id == id)
+        .ok_or(ServerError::NotFound)
+}
+
+pub async fn list_schedules(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SchedulesResponse>, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let schedules = db::list_schedules(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(SchedulesResponse { schedules }))
+}
+
+pub async fn create_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateScheduleRequest>,
+) -> Result<Json<Schedule>, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    validate_task(&payload.task)?;
+
+    let mut schedule = Schedule {
+        id: Uuid::new_v4(),
+        server_id: id,
+        name: payload.name,
+        cron: payload.cron,
+        timezone: payload.timezone.unwrap_or_else(|| "UTC".to_string()),
+        task: payload.task,
+        enabled: payload.enabled.unwrap_or(true),
+        last_run_at: None,
+        last_status: None,
+        last_error: None,
+        next_run_at: None,
+    };
+    schedule.next_run_at = compute_next_run(&schedule)?;
+
+    db::create_schedule(&state.db, &schedule)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    state.schedule_notify.notify_one();
+
+    Ok(Json(schedule))
+}
+
+pub async fn get_schedule(
+    State(state): State<Arc<AppState>>,
+    Path((id, schedule_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Schedule>, ServerError> {
+    let schedule = get_server_schedule(&state, id, schedule_id).await?;
+    Ok(Json(schedule))
+}
+
+pub async fn update_schedule(
+    State(state): State<Arc<AppState>>,
+    Path((id, schedule_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateScheduleRequest>,
+) -> Result<Json<Schedule>, ServerError> {
+    let mut schedule = get_server_schedule(&state, id, schedule_id).await?;
+
+    if let Some(name) = payload.name {
+        schedule.name = name;
+    }
+    if let Some(cron) = payload.cron {
+        schedule.cron = cron;
+    }
+    if let Some(timezone) = payload.timezone {
+        schedule.timezone = timezone;
+    }
+    if let Some(task) = payload.task {
+        validate_task(&task)?;
+        schedule.task = task;
+    }
+    if let Some(enabled) = payload.enabled {
+        schedule.enabled = enabled;
+    }
+    schedule.next_run_at = compute_next_run(&schedule)?;
+
+    db::update_schedule(&state.db, &schedule)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    state.schedule_notify.notify_one();
+
+    Ok(Json(schedule))
+}
+
+pub async fn delete_schedule(
+    State(state): State<Arc<AppState>>,
+    Path((id, schedule_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ServerError> {
+    get_server_schedule(&state, id, schedule_id).await?;
+
+    db::delete_schedule(&state.db, schedule_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn run_schedule(
+    State(state): State<Arc<AppState>>,
+    Path((id, schedule_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Schedule>, ServerError> {
+    let schedule = get_server_schedule(&state, id, schedule_id).await?;
+
+    scheduler::execute(&state, &schedule).await;
+
+    let schedule = get_server_schedule(&state, id, schedule_id).await?;
+    Ok(Json(schedule))
+}
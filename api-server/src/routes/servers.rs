@@ -1,25 +1,41 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Multipart, Path, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
 use server_manager::{
-    download_server_jar, get_available_versions, initialize_server_properties, ServerConfig,
-    ServerInstance, ServerMonitor, ServerProcess, ServerState, ServerType,
+    configure_proxy, download_server_jar, get_available_builds, get_available_versions, import_bedrock_server, initialize_bedrock_properties, initialize_server_properties,
+    install_modpack, install_server_jar, read_modpack, read_server_properties, write_server_properties, Modpack, SERVER_JAR, ServerBuild, ServerConfig, Progress, ServerInstance, ServerMonitor, ServerProcess, ServerState, ServerType,
 };
 use std::sync::Arc;
 use tokio::fs;
+use tokio::time::Duration;
 use uuid::Uuid;
 
-use crate::{db, state::AppState, routes::plugins::InstallPluginRequest};
+use crate::{
+    auth::MaybeUser,
+    builds::{self, BuildUpdate},
+    countdown::{self, CountdownStatus, RestartCountdown},
+    crashes,
+    db,
+    history,
+    jars::{ServerJars, UploadedJar},
+    jobs::{self, Job, JobKind},
+    presence,
+    state::AppState,
+    triggers,
+    watchdog,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct CreateServerRequest {
     pub name: String,
     pub server_type: ServerType,
     pub minecraft_version: String,
+    /// Upstream build to install instead of the latest
+    pub build: Option<String>,
     pub port: Option<u16>,
     pub max_players: Option<u32>,
     pub memory_mb: Option<u32>,
@@ -34,6 +50,10 @@ pub struct ServerResponse {
     pub port: u16,
     pub state: ServerState,
     pub players_online: u32,
+    /// Upstream build installed, when known
+    pub build: Option<String>,
+    /// Newer builds of the same version, with their changes
+    pub update: Option<BuildUpdate>,
 }
 
 #[derive(Debug, Serialize)]
@@ -41,27 +61,82 @@ pub struct VersionsResponse {
     pub versions: Vec<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct BuildsResponse {
+    pub builds: Vec<ServerBuild>,
+}
+
 pub async fn get_versions(
-    State(_state): State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
     Path(server_type): Path<String>,
 ) -> Result<Json<VersionsResponse>, ServerError> {
-    let server_type = match server_type.as_str() {
-        "paper" => ServerType::Paper,
-        "spigot" => ServerType::Spigot,
-        _ => return Err(ServerError::InvalidServerType),
+    let server_type: ServerType = server_type
+        .parse()
+        .map_err(|_| ServerError::InvalidServerType)?;
+
+    let mut versions = match get_available_versions(&state.upstreams, server_type).await {
+        Ok(versions) => versions,
+        Err(e) => {
+            // Offline: offer the versions that can be installed from the cache
+            let versions = cached_versions(&state, server_type).await;
+            if versions.is_empty() {
+                return Err(ServerError::Internal(e.to_string()));
+            }
+            tracing::warn!("Listing cached versions only: {:#}", e);
+            versions
+        }
     };
 
-    let versions = get_available_versions(server_type)
+    // Mojang only lists the current Bedrock release; uploaded ones stay installable
+    if server_type.is_bedrock() {
+        for version in cached_versions(&state, server_type).await {
+            if !versions.contains(&version) {
+                versions.push(version);
+            }
+        }
+        versions.sort_by_key(|v| v.split('.').map(|part| part.parse().unwrap_or(0)).collect::<Vec<u64>>());
+    }
+
+    Ok(Json(VersionsResponse { versions }))
+}
+
+pub async fn get_builds(
+    State(state): State<Arc<AppState>>,
+    Path((server_type, version)): Path<(String, String)>,
+) -> Result<Json<BuildsResponse>, ServerError> {
+    let server_type: ServerType = server_type
+        .parse()
+        .map_err(|_| ServerError::InvalidServerType)?;
+
+    let builds = get_available_builds(&state.upstreams, server_type, &version)
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?;
 
-    Ok(Json(VersionsResponse { versions }))
+    Ok(Json(BuildsResponse { builds }))
+}
+
+async fn cached_versions(state: &AppState, server_type: ServerType) -> Vec<String> {
+    let prefix = format!("{}/", server_type.as_str());
+
+    let mut versions: Vec<String> = state
+        .cache
+        .list()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|a| {
+            let rest = a.key.strip_prefix(&prefix)?;
+            Some(rest.split('/').next().unwrap_or(rest).to_string())
+        })
+        .collect();
+    versions.dedup();
+    versions
 }
 
 pub async fn create_server(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<CreateServerRequest>,
-) -> Result<Json<ServerResponse>, ServerError> {
+) -> Result<(StatusCode, Json<Job>), ServerError> {
     let mut config = ServerConfig::new(
         payload.name,
         payload.server_type,
@@ -78,33 +153,291 @@ pub async fn create_server(
         config.memory_mb = memory_mb;
     }
 
-    // Create server directory
-    let server_dir = config.server_dir(&state.servers_dir);
-    fs::create_dir_all(&server_dir)
-        .await
-        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    // The server only exists once its job has installed it
+    let task_state = state.clone();
+    let build = payload.build;
+    let job = jobs::submit(&state, JobKind::CreateServer, Some(config.id), move |progress| async move {
+        let server = install_server(&task_state, config, ServerSource::Download { build: build.as_deref() }, &progress).await?;
+        Ok(serde_json::to_value(server)?)
+    })
+    .await
+    .map_err(|e| ServerError::Internal(e.to_string()))?;
 
-    // Download server JAR
-    let jar_path = server_dir.join("server.jar");
-    download_server_jar(config.server_type, &config.minecraft_version, &jar_path)
-        .await
-        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
 
-    // Initialize server.properties
-    initialize_server_properties(&server_dir, config.port, config.max_players)
+/// Add a Bedrock Dedicated Server zip, e.g. `bedrock-server-1.21.51.02.zip`,
+/// to the cache so servers can be created from it without downloading.
+/// The version comes from the `version` field or else the file name.
+pub async fn upload_bedrock_server(
+    State(state): State<Arc<AppState>>,
+    multipart: Multipart,
+) -> Result<Json<serde_json::Value>, ServerError> {
+    let upload = read_upload(multipart).await?;
+    if upload.data.is_empty() {
+        return Err(ServerError::BadRequest("Missing server archive".to_string()));
+    }
+
+    let version = upload
+        .field("version")
+        .map(str::to_string)
+        .or_else(|| {
+            let name = upload.file_name.as_deref()?;
+            let version = name.strip_prefix("bedrock-server-")?.strip_suffix(".zip")?;
+            Some(version.to_string())
+        })
+        .ok_or_else(|| ServerError::BadRequest("Missing Bedrock version".to_string()))?;
+
+    import_bedrock_server(&state.cache, &version, upload.data)
         .await
-        .map_err(|e| ServerError::Internal(e.to_string()))?;
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+
+    Ok(Json(serde_json::json!({ "version": version })))
+}
+
+/// Create a server from an uploaded Modrinth `.mrpack`, with the pack's
+/// Minecraft version and loader. Optional fields: `name`, `port`,
+/// `max_players` and `memory_mb`.
+pub async fn import_modpack(
+    State(state): State<Arc<AppState>>,
+    multipart: Multipart,
+) -> Result<(StatusCode, Json<Job>), ServerError> {
+    let mut upload = read_upload(multipart).await?;
+    if upload.data.is_empty() {
+        return Err(ServerError::BadRequest("Missing modpack file".to_string()));
+    }
+    let modpack = read_modpack(std::mem::take(&mut upload.data)).map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+
+    let name = upload
+        .field("name")
+        .map(str::to_string)
+        .unwrap_or_else(|| modpack.name.clone());
+    let mut config = ServerConfig::new(name, modpack.server_type, modpack.minecraft_version.clone());
+    upload.apply_to(&mut config)?;
+
+    let task_state = state.clone();
+    let job = jobs::submit(&state, JobKind::ImportModpack, Some(config.id), move |progress| async move {
+        progress.log(format!("Importing {} {}", modpack.name, modpack.version_id));
+        let server = install_server(&task_state, config, ServerSource::Modpack(&modpack), &progress).await?;
+        Ok(serde_json::to_value(server)?)
+    })
+    .await
+    .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Create a server from an uploaded jar, such as a patched fork or an
+/// internal build. Fields: `server_type`, and optionally `version` (read
+/// from the jar when omitted), `name`, `port`, `max_players` and `memory_mb`.
+pub async fn create_server_from_jar(
+    State(state): State<Arc<AppState>>,
+    MaybeUser(user): MaybeUser,
+    multipart: Multipart,
+) -> Result<(StatusCode, Json<Job>), ServerError> {
+    let mut upload = read_upload(multipart).await?;
+    if upload.data.is_empty() {
+        return Err(ServerError::BadRequest("Missing server jar".to_string()));
+    }
+
+    let server_type: ServerType = upload
+        .field("server_type")
+        .ok_or_else(|| ServerError::BadRequest("Missing server_type".to_string()))?
+        .parse()
+        .map_err(|_| ServerError::InvalidServerType)?;
+    if server_type.is_bedrock() {
+        return Err(ServerError::BadRequest("Bedrock servers don't run from a jar".to_string()));
+    }
+
+    let jar = UploadedJar::read(std::mem::take(&mut upload.data), upload.file_name.clone(), user)
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+    let version = upload
+        .field("version")
+        .map(str::to_string)
+        .or_else(|| jar.info.version.clone())
+        .ok_or_else(|| {
+            ServerError::BadRequest("Couldn't tell the jar's Minecraft version; declare it with `version`".to_string())
+        })?;
+
+    let name = upload
+        .field("name")
+        .or(upload.file_name.as_deref().map(|n| n.trim_end_matches(".jar")))
+        .unwrap_or("Custom server")
+        .to_string();
+    let mut config = ServerConfig::new(name, server_type, version);
+    upload.apply_to(&mut config)?;
+
+    let task_state = state.clone();
+    let job = jobs::submit(&state, JobKind::CreateServer, Some(config.id), move |progress| async move {
+        let server = install_server(&task_state, config, ServerSource::Jar(&jar), &progress).await?;
+        Ok(serde_json::to_value(server)?)
+    })
+    .await
+    .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// A multipart upload: the `file` field, plus the other fields as text
+pub struct Upload {
+    pub data: Vec<u8>,
+    pub file_name: Option<String>,
+    fields: std::collections::HashMap<String, String>,
+}
+
+impl Upload {
+    /// A text field, if given and not blank
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(|v| v.trim()).filter(|v| !v.is_empty())
+    }
+
+    fn number(&self, key: &str) -> Result<Option<u32>, ServerError> {
+        self.field(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| ServerError::BadRequest(format!("Invalid {}", key)))
+            })
+            .transpose()
+    }
 
-    // Save to database
-    db::create_server(&state.db, &config)
+    /// Apply the optional `port`, `max_players` and `memory_mb` fields
+    fn apply_to(&self, config: &mut ServerConfig) -> Result<(), ServerError> {
+        if let Some(port) = self.number("port")? {
+            config.port = u16::try_from(port).map_err(|_| ServerError::BadRequest("Invalid port".to_string()))?;
+        }
+        if let Some(max_players) = self.number("max_players")? {
+            config.max_players = max_players;
+        }
+        if let Some(memory_mb) = self.number("memory_mb")? {
+            config.memory_mb = memory_mb;
+        }
+        Ok(())
+    }
+}
+
+pub async fn read_upload(mut multipart: Multipart) -> Result<Upload, ServerError> {
+    let mut upload = Upload {
+        data: Vec::new(),
+        file_name: None,
+        fields: std::collections::HashMap::new(),
+    };
+
+    while let Some(field) = multipart
+        .next_field()
         .await
-        .map_err(|e| ServerError::Internal(e.to_string()))?;
+        .map_err(|e| ServerError::BadRequest(format!("Multipart error: {}", e)))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            upload.file_name = field.file_name().map(|name| name.to_string());
+            upload.data = field
+                .bytes()
+                .await
+                .map_err(|e| ServerError::BadRequest(e.to_string()))?
+                .to_vec();
+        } else {
+            let value = field
+                .text()
+                .await
+                .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+            upload.fields.insert(name, value);
+        }
+    }
+
+    Ok(upload)
+}
+
+/// Where a new server's software comes from
+enum ServerSource<'a> {
+    /// The upstream build, or the latest one
+    Download { build: Option<&'a str> },
+    /// The pack's loader from upstream, then the pack's files
+    Modpack(&'a Modpack),
+    Jar(&'a UploadedJar),
+}
+
+async fn install_server(
+    state: &AppState,
+    config: ServerConfig,
+    source: ServerSource<'_>,
+    progress: &Progress,
+) -> anyhow::Result<ServerResponse> {
+    let server_dir = config.server_dir(&state.servers_dir);
+    fs::create_dir_all(&server_dir).await?;
+
+    let installed = async {
+        let modpack = match source {
+            ServerSource::Modpack(modpack) => Some(modpack),
+            _ => None,
+        };
+
+        if let ServerSource::Jar(jar) = source {
+            install_server_jar(&server_dir, &jar.data).await?;
+            let jars = ServerJars {
+                current: Some(jar.record(&config.minecraft_version)),
+                previous: None,
+            };
+            db::set_server_jars(&state.db, config.id, &jars).await?;
+        } else {
+            // Download server JAR
+            progress.log(format!(
+                "Downloading {} {}",
+                config.server_type.as_str(), config.minecraft_version
+            ));
+            let build = match source {
+                ServerSource::Download { build } => build,
+                _ => modpack.and_then(|m| m.loader_version.as_deref()),
+            };
+            let jar_path = server_dir.join(SERVER_JAR);
+            let build = download_server_jar(
+                config.server_type,
+                &config.minecraft_version,
+                build,
+                &jar_path,
+                &state.upstreams,
+                &state.cache,
+                progress,
+            )
+            .await?;
+            builds::installed(state, config.id, Some(build)).await?;
+        }
+
+        if let Some(modpack) = modpack {
+            install_modpack(&state.upstreams, &state.cache, &server_dir, modpack, progress).await?;
+        }
+
+        // Proxies have no server.properties; they listen per their own config
+        let properties_path = server_dir.join("server.properties");
+        if config.server_type.is_proxy() {
+            configure_proxy(&server_dir, config.server_type, config.port, &[], None).await?;
+        } else if config.server_type.is_bedrock() {
+            initialize_bedrock_properties(&server_dir, config.port, config.max_players).await?;
+        } else if modpack.is_some() && properties_path.exists() {
+            // Keep the pack's own settings, but on this server's port
+            let mut properties = read_server_properties(&properties_path).await?;
+            properties.insert("server-port".to_string(), config.port.to_string());
+            write_server_properties(&properties_path, &properties).await?;
+        } else {
+            initialize_server_properties(&server_dir, config.port, config.max_players).await?;
+        }
+
+        // Save to database
+        db::create_server(&state.db, &config).await
+    }
+    .await;
+
+    if let Err(e) = installed {
+        let _ = fs::remove_dir_all(&server_dir).await;
+        return Err(e);
+    }
 
     // Create instance
     let instance = ServerInstance::new(config.clone());
     state.servers.write().await.insert(config.id, instance.clone());
+    let tracking = db::get_server_build(&state.db, config.id).await?;
 
-    Ok(Json(ServerResponse {
+    Ok(ServerResponse {
         id: instance.config.id,
         name: instance.config.name,
         server_type: instance.config.server_type,
@@ -112,7 +445,9 @@ pub async fn create_server(
         port: instance.config.port,
         state: instance.state,
         players_online: instance.players_online,
-    }))
+        build: tracking.build,
+        update: tracking.update,
+    })
 }
 
 pub async fn list_servers(
@@ -121,12 +456,16 @@ pub async fn list_servers(
     let configs = db::list_servers(&state.db)
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?;
+    let mut builds = db::list_server_builds(&state.db)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
 
     let servers = state.servers.read().await;
     let mut response = Vec::new();
 
     for config in configs {
         let instance = servers.get(&config.id);
+        let tracking = builds.remove(&config.id).unwrap_or_default();
         
         response.push(ServerResponse {
             id: config.id,
@@ -136,6 +475,8 @@ pub async fn list_servers(
             port: config.port,
             state: instance.map(|i| i.state).unwrap_or(ServerState::Stopped),
             players_online: instance.map(|i| i.players_online).unwrap_or(0),
+            build: tracking.build,
+            update: tracking.update,
         });
     }
 
@@ -150,6 +491,9 @@ pub async fn get_server(
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?
         .ok_or(ServerError::NotFound)?;
+    let tracking = db::get_server_build(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
 
     let servers = state.servers.read().await;
     let instance = servers.get(&id);
@@ -162,6 +506,8 @@ pub async fn get_server(
         port: config.port,
         state: instance.map(|i| i.state).unwrap_or(ServerState::Stopped),
         players_online: instance.map(|i| i.players_online).unwrap_or(0),
+        build: tracking.build,
+        update: tracking.update,
     }))
 }
 
@@ -177,6 +523,7 @@ pub async fn delete_server(
         }
     }
     drop(processes);
+    countdown::abandon(&state, id).await;
 
     // Get config for directory path
     let config = db::get_server(&state.db, id)
@@ -184,10 +531,44 @@ pub async fn delete_server(
         .map_err(|e| ServerError::Internal(e.to_string()))?
         .ok_or(ServerError::NotFound)?;
 
+    // The proxy's server list would point at nothing
+    let network = db::find_server_network(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    if network.is_some() {
+        return Err(ServerError::BadRequest(
+            "Server is part of a network; remove it from the network first".to_string(),
+        ));
+    }
+
     // Delete from database
     db::delete_server(&state.db, id)
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?;
+    db::delete_server_schedules(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    db::delete_watchdog_policy(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    db::delete_server_crashes(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    db::delete_server_triggers(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    db::delete_server_history(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    db::delete_server_jars(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    db::delete_server_upgrade(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    db::delete_server_build(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
 
     // Remove from memory
     state.servers.write().await.remove(&id);
@@ -205,6 +586,8 @@ pub async fn delete_server(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Start a server. One that opted in to updates and has a newer build
+/// waiting is updated and started by a job instead, answering 202.
 pub async fn start_server(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
@@ -214,6 +597,51 @@ pub async fn start_server(
         .map_err(|e| ServerError::Internal(e.to_string()))?
         .ok_or(ServerError::NotFound)?;
 
+    let process = state.processes.read().await.get(&id).cloned();
+    if let Some(process) = process {
+        if process.is_running().await {
+            return Err(ServerError::AlreadyRunning);
+        }
+    }
+    let update = builds::pending_update(&state, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    let Some(update) = update else {
+        return launch(state, id, false).await;
+    };
+
+    // A start already waiting on its update
+    let active = db::list_jobs(&state.db, Some(id), None, 20)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    if active.iter().any(|j| j.kind == JobKind::UpdateBuild && !j.status.is_finished()) {
+        return Ok(StatusCode::ACCEPTED);
+    }
+
+    let task_state = state.clone();
+    jobs::submit(&state, JobKind::UpdateBuild, Some(id), move |progress| async move {
+        if let Err(e) = builds::apply_update(&task_state, &config, &update, &progress).await {
+            progress.log(format!("Update failed, starting the current build: {:#}", e));
+        }
+        progress.check_cancelled()?;
+        launch(task_state, id, false)
+            .await
+            .map_err(|e| anyhow::anyhow!("Start failed: {:?}", e))?;
+        Ok(serde_json::json!({ "build": update.build }))
+    })
+    .await
+    .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Start a server, with `--forceUpgrade` for its first start on a new version
+pub async fn launch(state: Arc<AppState>, id: Uuid, force_upgrade: bool) -> Result<StatusCode, ServerError> {
+    let config = db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
     let mut processes = state.processes.write().await;
     let mut servers = state.servers.write().await;
     let mut monitors = state.monitors.write().await;
@@ -227,18 +655,21 @@ pub async fn start_server(
 
     // Create process
     let mut process = ServerProcess::new(config.clone(), state.servers_dir.clone());
+    process.set_force_upgrade(force_upgrade);
     let pid = process
         .start()
         .await
         .map_err(|e| ServerError::Internal(e.to_string()))?;
 
-    // Update instance
+    // Update instance; presence marks it running once the console says so
+    let server_type = config.server_type;
     if let Some(instance) = servers.get_mut(&id) {
-        instance.state = ServerState::Running;
+        instance.state = ServerState::Starting;
         instance.pid = Some(pid);
+        instance.players_online = 0;
     } else {
         let mut instance = ServerInstance::new(config);
-        instance.state = ServerState::Running;
+        instance.state = ServerState::Starting;
         instance.pid = Some(pid);
         servers.insert(id, instance);
     }
@@ -249,31 +680,44 @@ pub async fn start_server(
     monitors.insert(id, monitor);
 
     // Spawn supervisor task
+    let process = Arc::new(process);
     let state_clone = state.clone();
     let id_clone = id;
     let child_arc = process.get_child();
+    let process_clone = process.clone();
     
     tokio::spawn(async move {
         let mut child_guard = child_arc.write().await;
         if let Some(mut child) = child_guard.take() {
             drop(child_guard); // Release lock while waiting
-            let _ = child.wait().await;
+            let status = child.wait().await;
             tracing::info!("Server {} process exited", id_clone);
+            let clean = status.map(|s| s.success()).unwrap_or(false);
+            crashes::on_exit(state_clone.clone(), id_clone, clean);
             
             // Update state to Stopped
             let mut servers = state_clone.servers.write().await;
             if let Some(instance) = servers.get_mut(&id_clone) {
                 instance.state = ServerState::Stopped;
                 instance.pid = None;
+                instance.players_online = 0;
             }
             
-            // Cleanup process and monitor
-            state_clone.processes.write().await.remove(&id_clone);
-            state_clone.monitors.write().await.remove(&id_clone);
+            // Cleanup process and monitor, unless a newer process has
+            // already replaced this one
+            let mut processes = state_clone.processes.write().await;
+            if processes.get(&id_clone).is_some_and(|p| Arc::ptr_eq(p, &process_clone)) {
+                processes.remove(&id_clone);
+                state_clone.monitors.write().await.remove(&id_clone);
+            }
         }
     });
 
-    processes.insert(id, Arc::new(process));
+    processes.insert(id, process.clone());
+    history::spawn(state.clone(), id, process.clone());
+    presence::spawn(state.clone(), id, server_type, process.clone());
+    triggers::spawn(state.clone(), id, process.clone());
+    watchdog::spawn(state.clone(), id, process);
 
     Ok(StatusCode::OK)
 }
@@ -282,6 +726,7 @@ pub async fn stop_server(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, ServerError> {
+    countdown::abandon(&state, id).await;
     let processes = state.processes.read().await;
     let process = processes.get(&id).ok_or(ServerError::NotRunning)?;
 
@@ -305,6 +750,7 @@ pub async fn force_stop_server(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, ServerError> {
+    countdown::abandon(&state, id).await;
     let processes = state.processes.read().await;
     let process = processes.get(&id).ok_or(ServerError::NotRunning)?;
 
@@ -325,35 +771,12 @@ pub async fn force_stop_server(
     Ok(StatusCode::OK)
 }
 
-pub async fn install_plugin(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<Uuid>,
-    Json(payload): Json<InstallPluginRequest>,
-) -> Result<StatusCode, ServerError> {
-    let config = db::get_server(&state.db, id)
-        .await
-        .map_err(|e| ServerError::Internal(e.to_string()))?
-        .ok_or(ServerError::NotFound)?;
-
-    let server_dir = config.server_dir(&state.servers_dir);
-    
-    // Pass config.server_type to install_plugin
-    server_manager::install_plugin(
-        &server_dir,
-        &payload.plugin_name,
-        &config.minecraft_version,
-        config.server_type,
-    )
-    .await
-    .map_err(|e| ServerError::Internal(e.to_string()))?;
-
-    Ok(StatusCode::OK)
-}
-
 pub async fn restart_server(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, ServerError> {
+    countdown::abandon(&state, id).await;
+
     // 1. Get the process
     let processes = state.processes.read().await;
     let process = if let Some(p) = processes.get(&id) {
@@ -366,16 +789,17 @@ pub async fn restart_server(
     };
     drop(processes);
 
-    // 2. Stop it gracefully
-    process.stop().await.map_err(|e| ServerError::Internal(e.to_string()))?;
+    // 2. Stop it gracefully (recovered processes have no stdin, so kill those)
+    if process.stop().await.is_err() {
+        process.force_stop().await.map_err(|e| ServerError::Internal(e.to_string()))?;
+    }
 
-    // 3. Wait for it to actually exit
+    // 3. Wait for the supervisor to notice the exit
     // Set a timeout so we don't wait forever if it hangs
-    let wait_result = tokio::time::timeout(tokio::time::Duration::from_secs(30), process.wait()).await;
-    
-    if let Err(_) = wait_result {
+    if !wait_for_exit(&state, id, Duration::from_secs(30)).await {
         tracing::warn!("Server {} stop timed out, force stopping", id);
         let _ = process.force_stop().await;
+        wait_for_exit(&state, id, Duration::from_secs(10)).await;
     }
 
     // 4. Update instance state locally
@@ -393,6 +817,60 @@ pub async fn restart_server(
     Ok(StatusCode::OK)
 }
 
+/// Wait until the supervisor has removed the server's process, returning
+/// false if it is still there after `timeout`
+pub async fn wait_for_exit(state: &AppState, id: Uuid, timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    while tokio::time::Instant::now() < deadline {
+        if !state.processes.read().await.contains_key(&id) {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    !state.processes.read().await.contains_key(&id)
+}
+
+pub async fn start_restart_countdown(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<RestartCountdown>>,
+) -> Result<Json<CountdownStatus>, ServerError> {
+    let processes = state.processes.read().await;
+    if !processes.contains_key(&id) {
+        return Err(ServerError::NotRunning);
+    }
+    drop(processes);
+
+    let options = payload.map(|Json(p)| p).unwrap_or_default();
+    let status = countdown::begin(&state, id, options)
+        .await
+        .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+
+    Ok(Json(status))
+}
+
+pub async fn get_restart_countdown(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CountdownStatus>, ServerError> {
+    let pending = state.pending_restarts.read().await;
+    let entry = pending.get(&id).ok_or(ServerError::NotFound)?;
+
+    Ok(Json(countdown::status(entry)))
+}
+
+pub async fn cancel_restart_countdown(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ServerError> {
+    countdown::cancel(&state, id)
+        .await
+        .map_err(|_| ServerError::NotFound)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
 
 #[derive(Debug)]
 pub enum ServerError {
@@ -401,6 +879,7 @@ pub enum ServerError {
     NotRunning,
     ServerRunning,
     InvalidServerType,
+    BadRequest(String),
     Internal(String),
 }
 
@@ -412,6 +891,7 @@ impl IntoResponse for ServerError {
             ServerError::NotRunning => (StatusCode::CONFLICT, "Server not running"),
             ServerError::ServerRunning => (StatusCode::CONFLICT, "Cannot delete running server"),
             ServerError::InvalidServerType => (StatusCode::BAD_REQUEST, "Invalid server type"),
+            ServerError::BadRequest(msg) => return (StatusCode::BAD_REQUEST, msg).into_response(),
             ServerError::Internal(msg) => {
                 tracing::error!("Internal error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
@@ -0,0 +1,284 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        Path, Query, State, WebSocketUpgrade,
+    },
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    routes::servers::ServerError,
+    state::AppState,
+    triggers::{self, Trigger, TriggerAction},
+};
+
+/// Cooldown for triggers created without one
+const DEFAULT_COOLDOWN_SECS: u64 = 60;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTriggerRequest {
+    pub name: String,
+    pub pattern: String,
+    pub threshold: Option<u32>,
+    pub window_secs: Option<u64>,
+    pub cooldown_secs: Option<u64>,
+    pub action: TriggerAction,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTriggerRequest {
+    pub name: Option<String>,
+    pub pattern: Option<String>,
+    pub threshold: Option<u32>,
+    pub window_secs: Option<u64>,
+    pub cooldown_secs: Option<u64>,
+    pub action: Option<TriggerAction>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TriggersResponse {
+    pub triggers: Vec<Trigger>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FireTriggerRequest {
+    /// Line to report as the match; defaults to a placeholder
+    pub line: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    pub server_id: Option<Uuid>,
+}
+
+fn validate_trigger(trigger: &Trigger) -> Result<(), ServerError> {
+    triggers::compile_pattern(&trigger.pattern)
+        .map_err(|e| ServerError::BadRequest(format!("{:#}", e)))?;
+
+    if trigger.threshold == 0 {
+        return Err(ServerError::BadRequest("Threshold must be at least 1".to_string()));
+    }
+
+    // A command or restart can print a line its own pattern matches, which
+    // would fire the trigger again on every line without a cooldown
+    if trigger.cooldown_secs == 0
+        && matches!(trigger.action, TriggerAction::Command { .. } | TriggerAction::Restart { .. })
+    {
+        return Err(ServerError::BadRequest(
+            "Command and restart triggers need a cooldown of at least 1 second".to_string(),
+        ));
+    }
+
+    let text = match &trigger.action {
+        TriggerAction::Command { command } => command,
+        TriggerAction::Event { name } => name,
+        TriggerAction::Webhook { url } => {
+            let parsed = reqwest::Url::parse(url)
+                .map_err(|e| ServerError::BadRequest(format!("Invalid webhook URL: {}", e)))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(ServerError::BadRequest(
+                    "Webhook URL must use http or https".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+        _ => return Ok(()),
+    };
+
+    if text.trim().is_empty() || text.chars().any(|c| c.is_control()) {
+        return Err(ServerError::BadRequest(
+            "Action argument must be a single non-empty line".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn get_server_trigger(
+    state: &AppState,
+    id: Uuid,
+    trigger_id: Uuid,
+) -> Result<Trigger, ServerError> {
+    db::get_trigger(&state.db, trigger_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .filter(|t| t.server_id == id)
+        .ok_or(ServerError::NotFound)
+}
+
+pub async fn list_triggers(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TriggersResponse>, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let triggers = db::list_triggers(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(TriggersResponse { triggers }))
+}
+
+pub async fn create_trigger(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateTriggerRequest>,
+) -> Result<Json<Trigger>, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let trigger = Trigger {
+        id: Uuid::new_v4(),
+        server_id: id,
+        name: payload.name,
+        pattern: payload.pattern,
+        threshold: payload.threshold.unwrap_or(1),
+        window_secs: payload.window_secs.unwrap_or(60),
+        cooldown_secs: payload.cooldown_secs.unwrap_or(DEFAULT_COOLDOWN_SECS),
+        action: payload.action,
+        enabled: payload.enabled.unwrap_or(true),
+        last_fired_at: None,
+        last_status: None,
+        last_error: None,
+    };
+    validate_trigger(&trigger)?;
+
+    db::create_trigger(&state.db, &trigger)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    state.trigger_reload.send_replace(());
+
+    Ok(Json(trigger))
+}
+
+pub async fn get_trigger(
+    State(state): State<Arc<AppState>>,
+    Path((id, trigger_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Trigger>, ServerError> {
+    let trigger = get_server_trigger(&state, id, trigger_id).await?;
+    Ok(Json(trigger))
+}
+
+pub async fn update_trigger(
+    State(state): State<Arc<AppState>>,
+    Path((id, trigger_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateTriggerRequest>,
+) -> Result<Json<Trigger>, ServerError> {
+    let mut trigger = get_server_trigger(&state, id, trigger_id).await?;
+
+    if let Some(name) = payload.name {
+        trigger.name = name;
+    }
+    if let Some(pattern) = payload.pattern {
+        trigger.pattern = pattern;
+    }
+    if let Some(threshold) = payload.threshold {
+        trigger.threshold = threshold;
+    }
+    if let Some(window_secs) = payload.window_secs {
+        trigger.window_secs = window_secs;
+    }
+    if let Some(cooldown_secs) = payload.cooldown_secs {
+        trigger.cooldown_secs = cooldown_secs;
+    }
+    if let Some(action) = payload.action {
+        trigger.action = action;
+    }
+    if let Some(enabled) = payload.enabled {
+        trigger.enabled = enabled;
+    }
+    validate_trigger(&trigger)?;
+
+    db::update_trigger(&state.db, &trigger)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    state.trigger_reload.send_replace(());
+
+    Ok(Json(trigger))
+}
+
+pub async fn delete_trigger(
+    State(state): State<Arc<AppState>>,
+    Path((id, trigger_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ServerError> {
+    get_server_trigger(&state, id, trigger_id).await?;
+
+    db::delete_trigger(&state.db, trigger_id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    state.trigger_reload.send_replace(());
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Run a trigger's action now, for testing webhooks and commands
+pub async fn fire_trigger(
+    State(state): State<Arc<AppState>>,
+    Path((id, trigger_id)): Path<(Uuid, Uuid)>,
+    payload: Option<Json<FireTriggerRequest>>,
+) -> Result<Json<Trigger>, ServerError> {
+    let trigger = get_server_trigger(&state, id, trigger_id).await?;
+
+    let line = payload
+        .and_then(|Json(p)| p.line)
+        .unwrap_or_else(|| format!("Manual test of trigger '{}'", trigger.name));
+    triggers::fire(&state, &trigger, &line).await;
+
+    let trigger = get_server_trigger(&state, id, trigger_id).await?;
+    Ok(Json(trigger))
+}
+
+/// Stream events published by trigger `event` actions as JSON
+pub async fn events_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<EventsQuery>,
+) -> Response {
+    ws.on_upgrade(move |socket| handle_events_socket(socket, state, query.server_id))
+}
+
+async fn handle_events_socket(socket: WebSocket, state: Arc<AppState>, server_id: Option<Uuid>) {
+    let (mut sender, mut receiver) = socket.split();
+    let mut events = state.events.subscribe();
+
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                };
+                if server_id.is_some_and(|id| id != event.server_id) {
+                    continue;
+                }
+                let text = serde_json::to_string(&event).unwrap_or_default();
+                if sender.send(Message::Text(text)).await.is_err() {
+                    return;
+                }
+            }
+            msg = receiver.next() => {
+                if !matches!(msg, Some(Ok(_))) {
+                    return;
+                }
+            }
+        }
+    }
+}
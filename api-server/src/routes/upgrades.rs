@@ -0,0 +1,145 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use server_manager::{check_addon_compatibility, runs_server_jar, AddonCompatibility, ServerConfig};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    jobs::{self, Job, JobKind},
+    routes::servers::ServerError,
+    state::AppState,
+    upgrades::{self, UpgradeRecord, UpgradeRequest, UpgradeStatus},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct UpgradeCheckQuery {
+    pub version: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpgradeCheck {
+    pub from_version: String,
+    pub to_version: String,
+    pub addons: Vec<AddonCompatibility>,
+}
+
+async fn get_config(state: &AppState, id: Uuid) -> Result<ServerConfig, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)
+}
+
+/// Worlds and jars can't be swapped under a running server
+async fn ensure_stopped(state: &AppState, id: Uuid) -> Result<(), ServerError> {
+    let process = state.processes.read().await.get(&id).cloned();
+    if let Some(process) = process {
+        if process.is_running().await {
+            return Err(ServerError::BadRequest(
+                "Stop the server before changing its version".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Which installed add-ons have a release for another Minecraft version
+pub async fn check_upgrade(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<UpgradeCheckQuery>,
+) -> Result<Json<UpgradeCheck>, ServerError> {
+    let config = get_config(&state, id).await?;
+
+    let addons = check_addon_compatibility(
+        &state.upstreams,
+        &config.server_dir(&state.servers_dir),
+        config.server_type,
+        &query.version,
+    )
+    .await
+    .map_err(|e| ServerError::Internal(format!("{:#}", e)))?;
+
+    Ok(Json(UpgradeCheck {
+        from_version: config.minecraft_version,
+        to_version: query.version,
+        addons,
+    }))
+}
+
+/// The server's most recent upgrade, if any
+pub async fn get_upgrade(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Option<UpgradeRecord>>, ServerError> {
+    get_config(&state, id).await?;
+
+    let upgrade = db::get_server_upgrade(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(upgrade))
+}
+
+pub async fn upgrade_server(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpgradeRequest>,
+) -> Result<(StatusCode, Json<Job>), ServerError> {
+    let config = get_config(&state, id).await?;
+    if !runs_server_jar(config.server_type, &config.server_dir(&state.servers_dir)) {
+        return Err(ServerError::BadRequest(format!(
+            "{} servers here can't be upgraded in place",
+            config.server_type.as_str()
+        )));
+    }
+    if request.version.trim().is_empty() || request.version == config.minecraft_version {
+        return Err(ServerError::BadRequest(format!(
+            "Server is already on {}",
+            config.minecraft_version
+        )));
+    }
+    ensure_stopped(&state, id).await?;
+
+    let task_state = state.clone();
+    let job = jobs::submit(&state, JobKind::UpgradeServer, Some(id), move |progress| async move {
+        let upgrade = upgrades::upgrade(&task_state, config, &request, &progress).await?;
+        Ok(serde_json::to_value(upgrade)?)
+    })
+    .await
+    .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Restore the jar and worlds from before the server's last upgrade
+pub async fn rollback_upgrade(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<(StatusCode, Json<Job>), ServerError> {
+    get_config(&state, id).await?;
+    let upgrade = db::get_server_upgrade(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+    if upgrade.is_none_or(|u| u.status == UpgradeStatus::RolledBack) {
+        return Err(ServerError::BadRequest(
+            "There is no upgrade to roll back".to_string(),
+        ));
+    }
+    ensure_stopped(&state, id).await?;
+
+    let task_state = state.clone();
+    let job = jobs::submit(&state, JobKind::RollbackUpgrade, Some(id), move |progress| async move {
+        let upgrade = upgrades::rollback(&task_state, id, &progress).await?;
+        Ok(serde_json::to_value(upgrade)?)
+    })
+    .await
+    .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
@@ -0,0 +1,49 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{db, routes::servers::ServerError, state::AppState, watchdog::WatchdogPolicy};
+
+pub async fn get_policy(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WatchdogPolicy>, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    let policy = db::get_watchdog_policy(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .unwrap_or_default();
+
+    Ok(Json(policy))
+}
+
+/// Takes effect the next time the server starts
+pub async fn update_policy(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<WatchdogPolicy>,
+) -> Result<Json<WatchdogPolicy>, ServerError> {
+    db::get_server(&state.db, id)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?
+        .ok_or(ServerError::NotFound)?;
+
+    if payload.hang_timeout_secs < 30 {
+        return Err(ServerError::BadRequest(
+            "hang_timeout_secs must be at least 30".to_string(),
+        ));
+    }
+
+    db::set_watchdog_policy(&state.db, id, &payload)
+        .await
+        .map_err(|e| ServerError::Internal(e.to_string()))?;
+
+    Ok(Json(payload))
+}
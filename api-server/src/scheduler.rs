@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, State};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+use crate::{countdown::{self, RestartCountdown}, db, routes, state::AppState};
+
+/// Longest the scheduler sleeps before re-reading schedules from the database
+const MAX_SLEEP_SECS: i64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduledTask {
+    Restart {
+        #[serde(default)]
+        countdown: Option<RestartCountdown>,
+    },
+    Stop,
+    Start,
+    Backup { world_name: String },
+    Command { command: String },
+    Broadcast { message: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Schedule {
+    pub id: Uuid,
+    pub server_id: Uuid,
+    pub name: String,
+    pub cron: String,
+    pub timezone: String,
+    pub task: ScheduledTask,
+    pub enabled: bool,
+    pub last_run_at: Option<i64>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub next_run_at: Option<i64>,
+}
+
+/// Parse a cron expression, accepting the classic 5-field form as well as
+/// the 6/7-field form with seconds (and optional year)
+pub fn parse_cron(expr: &str) -> Result<cron::Schedule> {
+    let fields = expr.split_whitespace().count();
+    let normalized = if fields == 5 {
+        format!("0 {}", expr.trim())
+    } else {
+        expr.trim().to_string()
+    };
+
+    cron::Schedule::from_str(&normalized)
+        .map_err(|e| anyhow::anyhow!("Invalid cron expression '{}': {}", expr, e))
+}
+
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    Tz::from_str(name).map_err(|_| anyhow::anyhow!("Unknown timezone '{}'", name))
+}
+
+/// Next time a schedule fires strictly after `after`
+pub fn next_run_after(expr: &str, timezone: &str, after: DateTime<Utc>) -> Result<Option<i64>> {
+    let schedule = parse_cron(expr)?;
+    let tz = parse_timezone(timezone)?;
+
+    Ok(schedule
+        .after(&after.with_timezone(&tz))
+        .next()
+        .map(|t| t.with_timezone(&Utc).timestamp()))
+}
+
+/// Background loop that runs due schedules.
+///
+/// Wakes up when the earliest schedule is due, at least once a minute, or
+/// immediately when schedules are changed through the API.
+pub async fn run(state: Arc<AppState>) {
+    loop {
+        let sleep_secs = match tick(&state).await {
+            Ok(secs) => secs,
+            Err(e) => {
+                tracing::error!("Scheduler tick failed: {}", e);
+                MAX_SLEEP_SECS
+            }
+        };
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(sleep_secs as u64)) => {}
+            _ = state.schedule_notify.notified() => {}
+        }
+    }
+}
+
+async fn tick(state: &Arc<AppState>) -> Result<i64> {
+    let now = Utc::now();
+    let schedules = db::list_enabled_schedules(&state.db).await?;
+    let mut sleep_secs = MAX_SLEEP_SECS;
+
+    for schedule in schedules {
+        let Some(next_run_at) = schedule.next_run_at else {
+            continue;
+        };
+
+        if next_run_at > now.timestamp() {
+            sleep_secs = sleep_secs.min(next_run_at - now.timestamp());
+            continue;
+        }
+
+        // Advance next_run_at before running so a slow task can't fire twice
+        let next = next_run_after(&schedule.cron, &schedule.timezone, now).unwrap_or(None);
+        db::set_schedule_next_run(&state.db, schedule.id, next).await?;
+        if let Some(next) = next {
+            sleep_secs = sleep_secs.min(next - now.timestamp());
+        }
+
+        let state = state.clone();
+        tokio::spawn(async move {
+            execute(&state, &schedule).await;
+        });
+    }
+
+    Ok(sleep_secs.max(1))
+}
+
+/// Run a schedule's task now and record the outcome
+pub async fn execute(state: &Arc<AppState>, schedule: &Schedule) {
+    tracing::info!(
+        "Running schedule '{}' for server {}",
+        schedule.name,
+        schedule.server_id
+    );
+
+    let result = run_task(state, schedule.server_id, &schedule.task).await;
+
+    let (status, error) = match &result {
+        Ok(()) => ("success", None),
+        Err(e) => {
+            tracing::warn!("Schedule '{}' failed: {}", schedule.name, e);
+            ("failed", Some(e.to_string()))
+        }
+    };
+
+    if let Err(e) = db::record_schedule_run(
+        &state.db,
+        schedule.id,
+        Utc::now().timestamp(),
+        status,
+        error.as_deref(),
+    )
+    .await
+    {
+        tracing::error!("Failed to record schedule run: {}", e);
+    }
+}
+
+async fn run_task(state: &Arc<AppState>, server_id: Uuid, task: &ScheduledTask) -> Result<()> {
+    match task {
+        ScheduledTask::Restart { countdown: Some(options) } => {
+            countdown::begin(state, server_id, options.clone()).await?;
+        }
+        ScheduledTask::Restart { countdown: None } => {
+            routes::servers::restart_server(State(state.clone()), Path(server_id))
+                .await
+                .map_err(|e| anyhow::anyhow!("Restart failed: {:?}", e))?;
+        }
+        ScheduledTask::Stop => {
+            routes::servers::stop_server(State(state.clone()), Path(server_id))
+                .await
+                .map_err(|e| anyhow::anyhow!("Stop failed: {:?}", e))?;
+        }
+        ScheduledTask::Start => {
+            routes::servers::start_server(State(state.clone()), Path(server_id))
+                .await
+                .map_err(|e| anyhow::anyhow!("Start failed: {:?}", e))?;
+        }
+        ScheduledTask::Backup { world_name } => {
+            let config = db::get_server(&state.db, server_id)
+                .await?
+                .context("Server not found")?;
+            let server_dir = config.server_dir(&state.servers_dir);
+            server_manager::backup_world(&server_dir, world_name, &server_manager::Progress::none()).await?;
+        }
+        ScheduledTask::Command { command } => {
+            send_console(state, server_id, command.clone()).await?;
+        }
+        ScheduledTask::Broadcast { message } => {
+            let message = server_manager::sanitize_console_arg(message);
+            send_console(state, server_id, format!("say {}", message)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn send_console(state: &Arc<AppState>, server_id: Uuid, command: String) -> Result<()> {
+    let process = state
+        .processes
+        .read()
+        .await
+        .get(&server_id)
+        .cloned()
+        .context("Server is not running")?;
+
+    process.send_command(command).await
+}
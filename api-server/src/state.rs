@@ -1,36 +1,64 @@
-use server_manager::{ServerInstance, ServerProcess, ServerMonitor};
+use server_manager::{ArtifactCache, ServerInstance, ServerProcess, ServerMonitor, Upstreams};
 use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, watch, Notify, RwLock, Semaphore};
 use uuid::Uuid;
 
+use crate::countdown::PendingRestart;
+use crate::jobs::RunningJob;
+use crate::triggers::TriggerEvent;
+
 pub struct AppState {
     pub db: SqlitePool,
     pub servers_dir: PathBuf,
+    /// Server jars and plugins shared by all servers
+    pub cache: ArtifactCache,
+    /// Server download and Modrinth endpoints and the shared HTTP client
+    pub upstreams: Upstreams,
     pub admin_password: String,
     pub jwt_secret: String,
     pub servers: Arc<RwLock<HashMap<Uuid, ServerInstance>>>,
     pub processes: Arc<RwLock<HashMap<Uuid, Arc<ServerProcess>>>>,
     pub monitors: Arc<RwLock<HashMap<Uuid, ServerMonitor>>>,
+    pub schedule_notify: Notify,
+    pub pending_restarts: Arc<RwLock<HashMap<Uuid, PendingRestart>>>,
+    /// Bumped whenever triggers change so running evaluators reload them
+    pub trigger_reload: watch::Sender<()>,
+    pub events: broadcast::Sender<TriggerEvent>,
+    /// Queued and running jobs
+    pub jobs: Arc<RwLock<HashMap<Uuid, RunningJob>>>,
+    /// One permit per job worker
+    pub job_slots: Arc<Semaphore>,
 }
 
 impl AppState {
     pub fn new(
         db: SqlitePool,
         servers_dir: PathBuf,
+        cache: ArtifactCache,
+        upstreams: Upstreams,
         admin_password: String,
         jwt_secret: String,
+        job_workers: usize,
     ) -> Self {
         Self {
             db,
             servers_dir,
+            cache,
+            upstreams,
             admin_password,
             jwt_secret,
             servers: Arc::new(RwLock::new(HashMap::new())),
             processes: Arc::new(RwLock::new(HashMap::new())),
             monitors: Arc::new(RwLock::new(HashMap::new())),
+            schedule_notify: Notify::new(),
+            pending_restarts: Arc::new(RwLock::new(HashMap::new())),
+            trigger_reload: watch::channel(()).0,
+            events: broadcast::channel(100).0,
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+            job_slots: Arc::new(Semaphore::new(job_workers.max(1))),
         }
     }
 
@@ -57,12 +85,13 @@ impl AppState {
                             instance.pid = Some(pid);
                             
                             self.servers.write().await.insert(config.id, instance);
-                            self.processes.write().await.insert(config.id, process);
+                            self.processes.write().await.insert(config.id, process.clone());
                             
                             // Re-start monitoring
                             let mut monitor = ServerMonitor::new();
                             monitor.reset_uptime();
                             self.monitors.write().await.insert(config.id, monitor);
+                            crate::watchdog::spawn(self.clone(), config.id, process);
                             
                             // Spawn a supervisor to handle cleanup if it exits
                             let state_clone = self.clone();
@@ -78,9 +107,12 @@ impl AppState {
                                 }
                                 
                                 tracing::info!("Recovered server {} exited", config_id);
+                                // Exit status of a process we didn't spawn is unknown
+                                crate::crashes::on_exit(state_clone.clone(), config_id, false);
                                 if let Some(instance) = state_clone.servers.write().await.get_mut(&config_id) {
                                     instance.state = ServerState::Stopped;
                                     instance.pid = None;
+                                    instance.players_online = 0;
                                 }
                                 state_clone.processes.write().await.remove(&config_id);
                                 state_clone.monitors.write().await.remove(&config_id);
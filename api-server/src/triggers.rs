@@ -0,0 +1,265 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, State};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use server_manager::{LogLine, MatchRate, RateLimit, ServerProcess};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use crate::{countdown::{self, RestartCountdown}, db, routes, scheduler, state::AppState};
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriggerAction {
+    Command { command: String },
+    Restart {
+        #[serde(default)]
+        countdown: Option<RestartCountdown>,
+    },
+    Stop,
+    /// POST a JSON description of the match to a URL
+    Webhook { url: String },
+    /// Publish a named event to `/api/events` subscribers
+    Event { name: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Trigger {
+    pub id: Uuid,
+    pub server_id: Uuid,
+    pub name: String,
+    /// Regex matched against each console line
+    pub pattern: String,
+    /// Matches needed within `window_secs` to fire
+    pub threshold: u32,
+    pub window_secs: u64,
+    /// Minimum time between two firings
+    pub cooldown_secs: u64,
+    pub action: TriggerAction,
+    pub enabled: bool,
+    pub last_fired_at: Option<i64>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// Published on `AppState::events` by `Event` actions
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggerEvent {
+    pub server_id: Uuid,
+    pub trigger_id: Uuid,
+    pub trigger: String,
+    pub event: String,
+    pub line: String,
+    pub timestamp: i64,
+}
+
+/// A trigger with its compiled pattern and recent match times
+struct ActiveTrigger {
+    trigger: Trigger,
+    pattern: Regex,
+    rate: MatchRate,
+}
+
+impl ActiveTrigger {
+    /// Record a match and report whether the trigger should fire
+    fn record_match(&mut self, now: Instant) -> bool {
+        let limit = RateLimit {
+            threshold: self.trigger.threshold,
+            window: Duration::from_secs(self.trigger.window_secs),
+            cooldown: Duration::from_secs(self.trigger.cooldown_secs),
+        };
+        self.rate.record(&limit, now)
+    }
+}
+
+pub fn compile_pattern(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).with_context(|| format!("Invalid trigger pattern '{}'", pattern))
+}
+
+/// Start evaluating triggers against a freshly started server's output
+pub fn spawn(state: Arc<AppState>, server_id: Uuid, process: Arc<ServerProcess>) {
+    let output = process.subscribe();
+    // Holding the process would keep its output channel open after it exits
+    drop(process);
+
+    tokio::spawn(async move {
+        watch(state, server_id, output).await;
+    });
+}
+
+async fn load(state: &AppState, server_id: Uuid) -> Vec<ActiveTrigger> {
+    let triggers = match db::list_triggers(&state.db, server_id).await {
+        Ok(triggers) => triggers,
+        Err(e) => {
+            tracing::error!("Failed to load triggers for {}: {}", server_id, e);
+            return Vec::new();
+        }
+    };
+
+    triggers
+        .into_iter()
+        .filter(|t| t.enabled)
+        .filter_map(|trigger| match compile_pattern(&trigger.pattern) {
+            Ok(pattern) => Some(ActiveTrigger {
+                trigger,
+                pattern,
+                rate: MatchRate::default(),
+            }),
+            Err(e) => {
+                tracing::warn!("Skipping trigger '{}': {:#}", trigger.name, e);
+                None
+            }
+        })
+        .collect()
+}
+
+async fn watch(state: Arc<AppState>, server_id: Uuid, mut output: broadcast::Receiver<LogLine>) {
+    let mut reload = state.trigger_reload.subscribe();
+    let mut triggers = load(&state, server_id).await;
+
+    loop {
+        tokio::select! {
+            line = output.recv() => {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                };
+
+                let now = Instant::now();
+                for active in triggers.iter_mut() {
+                    if active.pattern.is_match(&line.raw) && active.record_match(now) {
+                        let state = state.clone();
+                        let trigger = active.trigger.clone();
+                        let line = line.raw.clone();
+                        tokio::spawn(async move {
+                            fire(&state, &trigger, &line).await;
+                        });
+                    }
+                }
+            }
+            changed = reload.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                // Keep rate and cooldown state for triggers that still exist
+                let mut fresh = load(&state, server_id).await;
+                for active in fresh.iter_mut() {
+                    if let Some(old) = triggers.iter().find(|t| t.trigger.id == active.trigger.id) {
+                        active.rate = old.rate.clone();
+                        if old.trigger.pattern != active.trigger.pattern {
+                            active.rate.forget_matches();
+                        }
+                    }
+                }
+                triggers = fresh;
+            }
+        }
+    }
+}
+
+/// Run a trigger's action and record the outcome
+pub async fn fire(state: &Arc<AppState>, trigger: &Trigger, line: &str) {
+    tracing::info!(
+        "Trigger '{}' fired for server {}",
+        trigger.name,
+        trigger.server_id
+    );
+
+    let result = run_action(state, trigger, line).await;
+
+    let (status, error) = match &result {
+        Ok(()) => ("success", None),
+        Err(e) => {
+            tracing::warn!("Trigger '{}' failed: {:#}", trigger.name, e);
+            ("failed", Some(format!("{:#}", e)))
+        }
+    };
+
+    if let Err(e) = db::record_trigger_fired(
+        &state.db,
+        trigger.id,
+        chrono::Utc::now().timestamp(),
+        status,
+        error.as_deref(),
+    )
+    .await
+    {
+        tracing::error!("Failed to record trigger run: {}", e);
+    }
+}
+
+async fn run_action(state: &Arc<AppState>, trigger: &Trigger, line: &str) -> Result<()> {
+    let server_id = trigger.server_id;
+
+    match &trigger.action {
+        TriggerAction::Command { command } => {
+            scheduler::send_console(state, server_id, command.clone()).await?;
+        }
+        TriggerAction::Restart { countdown: Some(options) } => {
+            countdown::begin(state, server_id, options.clone()).await?;
+        }
+        TriggerAction::Restart { countdown: None } => {
+            routes::servers::restart_server(State(state.clone()), Path(server_id))
+                .await
+                .map_err(|e| anyhow::anyhow!("Restart failed: {:?}", e))?;
+        }
+        TriggerAction::Stop => {
+            routes::servers::stop_server(State(state.clone()), Path(server_id))
+                .await
+                .map_err(|e| anyhow::anyhow!("Stop failed: {:?}", e))?;
+        }
+        TriggerAction::Webhook { url } => {
+            send_webhook(state, trigger, url, line).await?;
+        }
+        TriggerAction::Event { name } => {
+            // No subscribers is not an error
+            let _ = state.events.send(TriggerEvent {
+                server_id,
+                trigger_id: trigger.id,
+                trigger: trigger.name.clone(),
+                event: name.clone(),
+                line: line.to_string(),
+                timestamp: chrono::Utc::now().timestamp(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+async fn send_webhook(state: &AppState, trigger: &Trigger, url: &str, line: &str) -> Result<()> {
+    let server_name = db::get_server(&state.db, trigger.server_id)
+        .await?
+        .map(|c| c.name)
+        .unwrap_or_default();
+
+    // `content` makes the payload usable as a Discord webhook as-is
+    let payload = serde_json::json!({
+        "content": format!("Trigger '{}' fired on {}: {}", trigger.name, server_name, line),
+        "server_id": trigger.server_id,
+        "server_name": server_name,
+        "trigger_id": trigger.id,
+        "trigger": trigger.name,
+        "line": line,
+        "timestamp": chrono::Utc::now().timestamp(),
+    });
+
+    state
+        .upstreams
+        .client()
+        .post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(&payload)
+        .send()
+        .await
+        .context("Webhook request failed")?
+        .error_for_status()
+        .context("Webhook returned an error")?;
+
+    Ok(())
+}
@@ -0,0 +1,288 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, State};
+use serde::{Deserialize, Serialize};
+use server_manager::{
+    backup_world, check_addon_compatibility, list_worlds, parse_console_event, restore_world, server_jar_sha256,
+    Compatibility, ConsoleEvent, Progress, ServerConfig, ServerState, ServerType,
+};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+use crate::{countdown, db, jars, routes, state::AppState};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpgradeRequest {
+    pub version: String,
+    /// Upstream build to install instead of the latest
+    pub build: Option<String>,
+    /// Start the server on the new version, rolling back if it doesn't come up
+    #[serde(default = "default_first_start")]
+    pub first_start: bool,
+    /// Convert every chunk on that first start with `--forceUpgrade`
+    #[serde(default)]
+    pub force_upgrade: bool,
+    /// Upgrade even if add-ons have no release for the new version
+    #[serde(default)]
+    pub ignore_incompatible: bool,
+    /// Seconds the first start gets to report it is ready before the upgrade
+    /// is rolled back
+    pub verify_timeout_secs: Option<u64>,
+}
+
+impl UpgradeRequest {
+    fn verify_timeout(&self) -> Duration {
+        // Converting every chunk of a big world takes a good while
+        let default = if self.force_upgrade { 3600 } else { 600 };
+        Duration::from_secs(self.verify_timeout_secs.unwrap_or(default))
+    }
+}
+
+fn default_first_start() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeStatus {
+    /// The new jar is in place but hasn't been started
+    Installed,
+    /// The server came up on the new version
+    Verified,
+    RolledBack,
+}
+
+impl UpgradeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpgradeStatus::Installed => "installed",
+            UpgradeStatus::Verified => "verified",
+            UpgradeStatus::RolledBack => "rolled_back",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "installed" => Some(UpgradeStatus::Installed),
+            "verified" => Some(UpgradeStatus::Verified),
+            "rolled_back" => Some(UpgradeStatus::RolledBack),
+            _ => None,
+        }
+    }
+}
+
+/// A world as it was before the upgrade, in the server's `backups`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldBackup {
+    pub world: String,
+    pub backup: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpgradeRecord {
+    pub from_version: String,
+    pub to_version: String,
+    /// The jar the upgrade installed; rollback won't undo a later jar change
+    pub jar_sha256: String,
+    pub backups: Vec<WorldBackup>,
+    pub status: UpgradeStatus,
+    pub created_at: i64,
+}
+
+/// Move a stopped server to another Minecraft version: check its add-ons,
+/// back up its worlds, then install the new jar with the old one kept
+pub async fn upgrade(
+    state: &Arc<AppState>,
+    config: ServerConfig,
+    request: &UpgradeRequest,
+    progress: &Progress,
+) -> Result<UpgradeRecord> {
+    let server_dir = config.server_dir(&state.servers_dir);
+    // The upgrade starts the server itself; a countdown must not restart it
+    // under the first start
+    countdown::abandon(state, config.id).await;
+
+    progress.log(format!("Checking add-ons against {}", request.version));
+    match check_addon_compatibility(&state.upstreams, &server_dir, config.server_type, &request.version).await {
+        Ok(addons) => {
+            for addon in addons.iter().filter(|a| a.compatibility == Compatibility::Unknown) {
+                progress.log(format!("{} isn't on Modrinth; check it by hand", addon.file_name));
+            }
+            let incompatible: Vec<&str> = addons
+                .iter()
+                .filter(|a| a.compatibility == Compatibility::Incompatible)
+                .map(|a| a.file_name.as_str())
+                .collect();
+            if !incompatible.is_empty() {
+                if !request.ignore_incompatible {
+                    anyhow::bail!("No release for {} of: {}", request.version, incompatible.join(", "));
+                }
+                progress.log(format!("Upgrading despite incompatible add-ons: {}", incompatible.join(", ")));
+            }
+        }
+        Err(e) if request.ignore_incompatible => {
+            progress.log(format!("Couldn't check add-ons: {:#}", e));
+        }
+        Err(e) => return Err(e.context("Couldn't check add-on compatibility")),
+    }
+    progress.check_cancelled()?;
+
+    let mut backups = Vec::new();
+    for world in list_worlds(&server_dir).await? {
+        let backup = backup_world(&server_dir, &world.name, progress).await?;
+        backups.push(WorldBackup {
+            world: world.name,
+            backup,
+        });
+    }
+
+    progress.log(format!(
+        "Downloading {} {}",
+        config.server_type.as_str(),
+        request.version
+    ));
+    // Spigot servers are rebuilt as the variant they already run
+    let build = match (&request.build, config.server_type) {
+        (None, ServerType::Spigot) => db::get_server_build(&state.db, config.id).await?.build,
+        (build, _) => build.clone(),
+    };
+    let jar = jars::download(state, &config, &request.version, build.as_deref(), progress).await?;
+    jars::set_version(state, &config, &request.version).await?;
+
+    let mut record = UpgradeRecord {
+        from_version: config.minecraft_version.clone(),
+        to_version: request.version.clone(),
+        jar_sha256: jar.sha256,
+        backups,
+        status: UpgradeStatus::Installed,
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    db::set_server_upgrade(&state.db, config.id, &record).await?;
+
+    if !request.first_start {
+        return Ok(record);
+    }
+
+    progress.log(format!("Starting on {}", request.version));
+    let started = routes::servers::launch(state.clone(), config.id, request.force_upgrade)
+        .await
+        .map_err(|e| anyhow::anyhow!("Start failed: {:?}", e));
+    let came_up = match started {
+        Ok(_) => wait_until_up(state, &config, request.verify_timeout(), progress).await?,
+        Err(e) => {
+            progress.log(format!("{:#}", e));
+            false
+        }
+    };
+
+    if !came_up {
+        progress.log(format!(
+            "Server didn't come up on {}; rolling back to {}",
+            record.to_version, record.from_version
+        ));
+        rollback(state, config.id, progress).await?;
+        anyhow::bail!(
+            "Server failed to start on {}; rolled back to {}",
+            record.to_version,
+            record.from_version
+        );
+    }
+
+    record.status = UpgradeStatus::Verified;
+    db::set_server_upgrade(&state.db, config.id, &record).await?;
+    progress.log(format!("Running on {}", record.to_version));
+    Ok(record)
+}
+
+/// Whether a freshly started server prints its ready line within `timeout`.
+/// Only the line itself counts: presence also marks servers running once
+/// they have been quiet long enough, which a hung server would pass. One
+/// that times out is killed so it can be rolled back.
+async fn wait_until_up(
+    state: &Arc<AppState>,
+    config: &ServerConfig,
+    timeout: Duration,
+    progress: &Progress,
+) -> Result<bool> {
+    let process = state
+        .processes
+        .read()
+        .await
+        .get(&config.id)
+        .cloned()
+        .context("Server didn't start")?;
+    let mut output = process.subscribe();
+
+    // The ready line may have come before we subscribed. Presence only
+    // falls back to running after minutes, so this early it was the line.
+    let server_state = state.servers.read().await.get(&config.id).map(|i| i.state);
+    if server_state == Some(ServerState::Running) {
+        return Ok(true);
+    }
+
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+    let mut exited = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            line = output.recv() => match line {
+                Ok(line) => {
+                    if parse_console_event(config.server_type, &line.message) == Some(ConsoleEvent::Ready) {
+                        return Ok(true);
+                    }
+                }
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return Ok(false),
+            },
+            _ = exited.tick() => {
+                if !process.is_running().await {
+                    return Ok(false);
+                }
+            }
+            _ = &mut deadline => {
+                progress.log(format!("No ready line after {}s; stopping the server", timeout.as_secs()));
+                routes::servers::force_stop_server(State(state.clone()), Path(config.id))
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to stop the server: {:?}", e))?;
+                return Ok(false);
+            }
+        }
+    }
+}
+
+/// Undo a stopped server's last upgrade: put the old jar back, restore the
+/// world backups and record the old version again
+pub async fn rollback(state: &AppState, server_id: Uuid, progress: &Progress) -> Result<UpgradeRecord> {
+    let config = db::get_server(&state.db, server_id)
+        .await?
+        .context("Server not found")?;
+    let mut record = db::get_server_upgrade(&state.db, server_id)
+        .await?
+        .filter(|r| r.status != UpgradeStatus::RolledBack)
+        .context("There is no upgrade to roll back")?;
+
+    let server_dir = config.server_dir(&state.servers_dir);
+    if server_jar_sha256(&server_dir).await? != record.jar_sha256 {
+        anyhow::bail!("The server jar has been replaced since the upgrade; roll it back from the jar instead");
+    }
+
+    progress.log(format!("Restoring {} jar", record.from_version));
+    jars::rollback(state, &config).await?;
+
+    for backup in &record.backups {
+        progress.log(format!("Restoring {} from {}", backup.world, backup.backup));
+        let server_dir = server_dir.clone();
+        let backup = backup.clone();
+        let task_progress = progress.clone();
+        tokio::task::spawn_blocking(move || restore_world(&server_dir, &backup.world, &backup.backup, &task_progress))
+            .await??;
+    }
+
+    jars::set_version(state, &config, &record.from_version).await?;
+    record.status = UpgradeStatus::RolledBack;
+    db::set_server_upgrade(&state.db, server_id, &record).await?;
+
+    progress.log(format!("Rolled back to {}", record.from_version));
+    Ok(record)
+}
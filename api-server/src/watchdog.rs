@@ -0,0 +1,193 @@
+use axum::extract::{Path, State};
+use serde::{Deserialize, Serialize};
+use server_manager::{ServerProcess, ServerType};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::{db, routes, state::AppState};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HangAction {
+    /// Capture a thread dump, kill the server and start it again
+    Restart,
+    /// Capture a thread dump and kill the server
+    Stop,
+    /// Only capture a thread dump
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogPolicy {
+    /// Off until an admin opts the server in; one with `enable-status=false`
+    /// or a slow modded start would otherwise be killed for looking hung
+    pub enabled: bool,
+    /// How long the server may be unresponsive before it counts as hung
+    pub hang_timeout_secs: u64,
+    /// Time after start before hang detection kicks in
+    pub startup_grace_secs: u64,
+    pub action: HangAction,
+}
+
+impl Default for WatchdogPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hang_timeout_secs: 90,
+            startup_grace_secs: 180,
+            action: HangAction::Restart,
+        }
+    }
+}
+
+/// Start watching a freshly started or recovered server
+pub fn spawn(state: Arc<AppState>, server_id: Uuid, process: Arc<ServerProcess>) {
+    tokio::spawn(async move {
+        watch(state, server_id, process).await;
+    });
+}
+
+async fn watch(state: Arc<AppState>, server_id: Uuid, process: Arc<ServerProcess>) {
+    let policy = match db::get_watchdog_policy(&state.db, server_id).await {
+        Ok(policy) => policy.unwrap_or_default(),
+        Err(e) => {
+            tracing::error!("Failed to load watchdog policy for {}: {}", server_id, e);
+            return;
+        }
+    };
+    if !policy.enabled {
+        return;
+    }
+
+    let Some((port, status_enabled, server_type)) = status_port(&state, server_id).await else {
+        return;
+    };
+
+    let hang_timeout = Duration::from_secs(policy.hang_timeout_secs);
+    let started = Instant::now();
+    // Recovered processes never produce output, so only pings count for them
+    let mut output = process.subscribe();
+    let mut last_activity = Instant::now();
+    let mut last_hang: Option<Instant> = None;
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        let hung = tokio::select! {
+            line = output.recv() => match line {
+                Ok(line) => {
+                    last_activity = Instant::now();
+                    server_manager::is_hang_message(&line.raw)
+                }
+                Err(RecvError::Lagged(_)) => false,
+                Err(RecvError::Closed) => return,
+            },
+            _ = interval.tick() => {
+                if !is_current(&state, server_id, &process).await {
+                    return;
+                }
+                if started.elapsed() < Duration::from_secs(policy.startup_grace_secs) {
+                    continue;
+                }
+
+                // Responding to a status ping means the network thread and
+                // main thread are both alive
+                let responsive = status_enabled && ping(server_type, port).await;
+                if responsive {
+                    last_activity = Instant::now();
+                }
+
+                status_enabled && last_activity.elapsed() >= hang_timeout
+            }
+        };
+
+        // Paper prints several marker lines per report; handle each hang once
+        let cooling_down = last_hang.is_some_and(|t| t.elapsed() < hang_timeout);
+        if hung && !cooling_down {
+            handle_hang(&state, server_id, &process, policy.action).await;
+            if policy.action != HangAction::None {
+                return;
+            }
+            last_hang = Some(Instant::now());
+            last_activity = Instant::now();
+        }
+    }
+}
+
+async fn ping(server_type: ServerType, port: u16) -> bool {
+    if server_type.is_bedrock() {
+        server_manager::ping_bedrock_server(port, PING_TIMEOUT).await.is_ok()
+    } else {
+        server_manager::ping_server(port, PING_TIMEOUT).await.is_ok()
+    }
+}
+
+/// Port and whether the status protocol is enabled, from server.properties.
+/// Bedrock always answers pings.
+async fn status_port(state: &AppState, server_id: Uuid) -> Option<(u16, bool, ServerType)> {
+    let config = db::get_server(&state.db, server_id).await.ok()??;
+    let server_dir = config.server_dir(&state.servers_dir);
+    let properties = server_manager::read_server_properties(&server_dir.join("server.properties"))
+        .await
+        .unwrap_or_default();
+
+    let port = properties
+        .get("server-port")
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(config.port);
+    let status_enabled = properties
+        .get("enable-status")
+        .map(|v| v != "false")
+        .unwrap_or(true);
+
+    Some((port, status_enabled, config.server_type))
+}
+
+/// Whether `process` is still the live process for this server
+async fn is_current(state: &AppState, server_id: Uuid, process: &Arc<ServerProcess>) -> bool {
+    match state.processes.read().await.get(&server_id) {
+        Some(current) => Arc::ptr_eq(current, process),
+        None => false,
+    }
+}
+
+async fn handle_hang(
+    state: &Arc<AppState>,
+    server_id: Uuid,
+    process: &Arc<ServerProcess>,
+    action: HangAction,
+) {
+    tracing::warn!("Server {} appears to be hung", server_id);
+
+    let pid = state.servers.read().await.get(&server_id).and_then(|i| i.pid);
+    let (server_dir, server_type) = match db::get_server(&state.db, server_id).await {
+        Ok(Some(config)) => (config.server_dir(&state.servers_dir), config.server_type),
+        _ => return,
+    };
+
+    // Thread dumps are a JVM feature; SIGQUIT would just kill Bedrock
+    if let Some(pid) = pid.filter(|_| !server_type.is_bedrock()) {
+        match server_manager::capture_thread_dump(&server_dir, pid, Some(process.subscribe())).await {
+            Ok(path) => tracing::info!("Saved thread dump for {} to {:?}", server_id, path),
+            Err(e) => tracing::warn!("Failed to capture thread dump for {}: {}", server_id, e),
+        }
+    }
+
+    match action {
+        HangAction::None => {}
+        HangAction::Stop => {
+            let _ = routes::servers::force_stop_server(State(state.clone()), Path(server_id)).await;
+        }
+        HangAction::Restart => {
+            let _ = routes::servers::force_stop_server(State(state.clone()), Path(server_id)).await;
+            routes::servers::wait_for_exit(state, server_id, Duration::from_secs(30)).await;
+            if let Err(e) = routes::servers::start_server(State(state.clone()), Path(server_id)).await {
+                tracing::error!("Watchdog failed to restart {}: {:?}", server_id, e);
+            }
+        }
+    }
+}
@@ -13,6 +13,9 @@ const state = {
     serverData: null,
 };
 
+// Starting servers already have a process and console
+const isUp = (server) => server.state === 'running' || server.state === 'starting';
+
 // API Client
 const api = {
     async request(endpoint, options = {}) {
@@ -38,6 +41,47 @@ const api = {
     getServers: () => api.request('/servers'),
     getServer: (id) => api.request(`/servers/${id}`),
     createServer: (data) => api.request('/servers', { method: 'POST', body: JSON.stringify(data) }),
+    importModpack: (formData) => fetch(`${API_BASE}/servers/import`, {
+        method: 'POST',
+        body: formData,
+        headers: { 'Authorization': `Bearer ${state.token}` }
+    }).then(async r => {
+        if (!r.ok) throw new Error(await r.text() || 'Import failed');
+        return r.json();
+    }),
+    uploadBedrockServer: (formData) => fetch(`${API_BASE}/versions/bedrock/upload`, {
+        method: 'POST',
+        body: formData,
+        headers: { 'Authorization': `Bearer ${state.token}` }
+    }).then(async r => {
+        if (!r.ok) throw new Error(await r.text() || 'Upload failed');
+        return r.json();
+    }),
+    createServerFromJar: (formData) => fetch(`${API_BASE}/servers/upload`, {
+        method: 'POST',
+        body: formData,
+        headers: { 'Authorization': `Bearer ${state.token}` }
+    }).then(async r => {
+        if (!r.ok) throw new Error(await r.text() || 'Upload failed');
+        return r.json();
+    }),
+    getServerJars: (id) => api.request(`/servers/${id}/jar`),
+    replaceServerJar: (id, formData) => fetch(`${API_BASE}/servers/${id}/jar`, {
+        method: 'PUT',
+        body: formData,
+        headers: { 'Authorization': `Bearer ${state.token}` }
+    }).then(async r => {
+        if (!r.ok) throw new Error(await r.text() || 'Upload failed');
+        return r.json();
+    }),
+    rollbackServerJar: (id) => api.request(`/servers/${id}/jar/rollback`, { method: 'POST' }),
+    getBuild: (id) => api.request(`/servers/${id}/build`),
+    checkBuild: (id) => api.request(`/servers/${id}/build/check`, { method: 'POST' }),
+    updateBuild: (id, data) => api.request(`/servers/${id}/build`, { method: 'PUT', body: JSON.stringify(data) }),
+    getUpgrade: (id) => api.request(`/servers/${id}/upgrade`),
+    checkUpgrade: (id, version) => api.request(`/servers/${id}/upgrade/check?version=${encodeURIComponent(version)}`),
+    upgradeServer: (id, data) => api.request(`/servers/${id}/upgrade`, { method: 'POST', body: JSON.stringify(data) }),
+    rollbackUpgrade: (id) => api.request(`/servers/${id}/upgrade/rollback`, { method: 'POST' }),
     deleteServer: (id) => api.request(`/servers/${id}`, { method: 'DELETE' }),
     startServer: (id) => api.request(`/servers/${id}/start`, { method: 'POST' }),
     stopServer: (id) => api.request(`/servers/${id}/stop`, { method: 'POST' }),
@@ -58,7 +102,7 @@ const api = {
         headers: { 'Authorization': `Bearer ${state.token}` }
     }).then(r => {
         if (!r.ok) return Promise.reject('Upload failed');
-        return r.status === 204 || r.status === 201 ? null : r.json().catch(() => null);
+        return r.json();
     }),
     setDefaultWorld: (id, name) => api.request(`/servers/${id}/worlds/${name}/default`, { method: 'POST' }),
 
@@ -70,10 +114,46 @@ const api = {
     // Plugins
     searchPlugins: (query) => api.request(`/plugins/search?q=${encodeURIComponent(query)}`),
     getInstalledPlugins: (id) => api.request(`/servers/${id}/plugins`),
-    installPlugin: (id, name) => api.request(`/servers/${id}/plugins`, { method: 'POST', body: JSON.stringify({ plugin_name: name }) }),
-    removePlugin: (id, name) => api.request(`/servers/${id}/plugins/${name}`, { method: 'DELETE' }),
+    installPlugin: (id, project, versionId) => api.request(`/servers/${id}/plugins`, { method: 'POST', body: JSON.stringify({ project, version_id: versionId }) }),
+    removePlugin: (id, name) => api.request(`/servers/${id}/plugins/${encodeURIComponent(name)}`, { method: 'DELETE' }),
+
+    // Mods
+    searchMods: (id, query) => api.request(`/servers/${id}/mods/search?q=${encodeURIComponent(query)}`),
+    getInstalledMods: (id) => api.request(`/servers/${id}/mods`),
+    installMod: (id, project, versionId) => api.request(`/servers/${id}/mods`, { method: 'POST', body: JSON.stringify({ project, version_id: versionId }) }),
+    // kind is 'plugins' or 'mods'
+    getAddonVersions: (id, kind, project) => api.request(`/servers/${id}/${kind}/versions?project=${encodeURIComponent(project)}`),
+    planAddonInstall: (id, kind, project, versionId) => api.request(`/servers/${id}/${kind}/plan`, { method: 'POST', body: JSON.stringify({ project, version_id: versionId }) }),
+    removeMod: (id, name) => api.request(`/servers/${id}/mods/${encodeURIComponent(name)}`, { method: 'DELETE' }),
+    checkAddonUpdates: (id, kind) => api.request(`/servers/${id}/${kind}/updates`),
+    // Every add-on with an update when fileName is null
+    updateAddons: (id, kind, fileName) => api.request(`/servers/${id}/${kind}/updates`, { method: 'POST', body: JSON.stringify({ file_name: fileName }) }),
+    rollbackAddonUpdate: (id, kind, fileName) => api.request(`/servers/${id}/${kind}/updates/rollback`, { method: 'POST', body: JSON.stringify({ file_name: fileName }) }),
+
+    // Networks
+    getNetworks: () => api.request('/networks'),
+    createNetwork: (name, proxyId) => api.request('/networks', { method: 'POST', body: JSON.stringify({ name, proxy_id: proxyId }) }),
+    deleteNetwork: (id) => api.request(`/networks/${id}`, { method: 'DELETE' }),
+    addBackend: (id, serverId, name) => api.request(`/networks/${id}/servers`, { method: 'POST', body: JSON.stringify({ server_id: serverId, name: name || null }) }),
+    removeBackend: (id, serverId) => api.request(`/networks/${id}/servers/${serverId}`, { method: 'DELETE' }),
+
+    // Jobs
+    getJob: (id) => api.request(`/jobs/${id}`),
+    cancelJob: (id) => api.request(`/jobs/${id}/cancel`, { method: 'POST' }),
 };
 
+// Poll a background job until it finishes; onProgress receives the job on every poll
+async function waitForJob(job, onProgress = () => {}) {
+    while (!['succeeded', 'failed', 'cancelled'].includes(job.status)) {
+        onProgress(job);
+        await new Promise(r => setTimeout(r, 1000));
+        job = await api.getJob(job.id);
+    }
+    if (job.status === 'failed') throw new Error(job.error || 'Job failed');
+    if (job.status === 'cancelled') throw new Error('Job was cancelled');
+    return job.result;
+}
+
 // Global Routing
 window.navigateTo = (view, serverId = null) => {
     state.currentView = view;
@@ -135,7 +215,9 @@ function renderSidebar() {
                     <div class="nav-item ${state.currentTab === 'console' ? 'active' : ''}" onclick="setTab('console')">Console</div>
                     <div class="nav-item ${state.currentTab === 'files' ? 'active' : ''}" onclick="setTab('files')">Files</div>
                     <div class="nav-item ${state.currentTab === 'plugins' ? 'active' : ''}" onclick="setTab('plugins')">Plugins</div>
+                    <div class="nav-item ${state.currentTab === 'mods' ? 'active' : ''}" onclick="setTab('mods')">Mods</div>
                     <div class="nav-item ${state.currentTab === 'worlds' ? 'active' : ''}" onclick="setTab('worlds')">Worlds</div>
+                    <div class="nav-item ${state.currentTab === 'network' ? 'active' : ''}" onclick="setTab('network')">Network</div>
                     <div class="nav-item ${state.currentTab === 'settings' ? 'active' : ''}" onclick="setTab('settings')">Settings</div>
                 </div>
             ` : ''}
@@ -151,7 +233,11 @@ function renderDashboard() {
     return `
         <div class="flex justify-between items-center mb-2">
             <h1>Dashboard</h1>
-            <button class="btn btn-primary btn-sm" onclick="showCreateModal()">+ Create Server</button>
+            <div class="flex gap-1">
+                <button class="btn btn-secondary btn-sm" onclick="showImportModal()">Import Modpack</button>
+                <button class="btn btn-secondary btn-sm" onclick="showUploadJarModal()">Upload Jar</button>
+                <button class="btn btn-primary btn-sm" onclick="showCreateModal()">+ Create Server</button>
+            </div>
         </div>
         
         <div class="flex gap-2 mb-2">
@@ -196,13 +282,13 @@ async function loadServerData(fullRender = false) {
             const infoText = header.querySelector('.text-muted');
 
             if (statusBadge && buttonGroup && infoText) {
-                statusBadge.className = `badge ${server.state === 'running' ? 'badge-success' : 'badge-error'}`;
+                statusBadge.className = `badge ${isUp(server) ? 'badge-success' : 'badge-error'}`;
                 statusBadge.textContent = server.state;
 
                 infoText.textContent = `${server.server_type} • ${server.minecraft_version} • Port ${server.port}`;
 
                 buttonGroup.innerHTML = `
-                    ${server.state === 'running' ?
+                    ${isUp(server) ?
                         `<button class="btn btn-danger btn-sm" onclick="handleStop()">Stop</button>` :
                         `<button class="btn btn-success btn-sm" onclick="handleStart()">Start</button>`
                     }
@@ -214,11 +300,11 @@ async function loadServerData(fullRender = false) {
                 header.innerHTML = `
                     <div class="flex justify-between items-center mb-2">
                         <div>
-                            <h1>${server.name} <span class="badge ${server.state === 'running' ? 'badge-success' : 'badge-error'}">${server.state}</span></h1>
+                            <h1>${server.name} <span class="badge ${isUp(server) ? 'badge-success' : 'badge-error'}">${server.state}</span></h1>
                             <div class="text-muted">${server.server_type} • ${server.minecraft_version} • Port ${server.port}</div>
                         </div>
                         <div class="flex gap-1">
-                            ${server.state === 'running' ?
+                            ${isUp(server) ?
                         `<button class="btn btn-danger btn-sm" onclick="handleStop()">Stop</button>` :
                         `<button class="btn btn-success btn-sm" onclick="handleStart()">Start</button>`
                     }
@@ -231,7 +317,7 @@ async function loadServerData(fullRender = false) {
         }
 
         // Auto-connect console if server became running and we are on console tab
-        if (!fullRender && state.currentTab === 'console' && server.state === 'running') {
+        if (!fullRender && state.currentTab === 'console' && isUp(server)) {
              if (!ws || ws.readyState !== WebSocket.OPEN) {
                  setupConsole(server.id);
              }
@@ -251,9 +337,18 @@ async function loadServerData(fullRender = false) {
             } else if (state.currentTab === 'plugins') {
                 content.innerHTML = renderPlugins();
                 loadPlugins();
+            } else if (state.currentTab === 'mods') {
+                content.innerHTML = renderMods();
+                loadMods();
+            } else if (state.currentTab === 'network') {
+                content.innerHTML = renderNetwork();
+                loadNetwork();
             } else if (state.currentTab === 'settings') {
                 content.innerHTML = renderSettings();
                 loadSettings();
+                loadJars();
+                loadBuild();
+                loadUpgrade();
             }
         }
     } catch (e) {
@@ -487,7 +582,8 @@ window.handleWorldUpload = async (e) => {
     formData.append('file', fileInput.files[0]);
 
     try {
-        await api.uploadWorld(state.currentServer, formData);
+        const job = await api.uploadWorld(state.currentServer, formData);
+        await waitForJob(job);
         closeModal();
         loadWorlds();
     } catch (e) {
@@ -497,7 +593,14 @@ window.handleWorldUpload = async (e) => {
     }
 };
 
-window.backupWorld = async (name) => { try { await api.backupWorld(state.currentServer, name); alert('Backup started!'); } catch (e) { alert(e.message); } };
+window.backupWorld = async (name) => {
+    try {
+        const job = await api.backupWorld(state.currentServer, name);
+        alert('Backup started!');
+        const result = await waitForJob(job);
+        alert(`Backup saved as ${result.backup_name}`);
+    } catch (e) { alert(e.message); }
+};
 window.deleteWorld = async (name) => { if (confirm(`Delete world "${name}"?`)) try { await api.deleteWorld(state.currentServer, name); loadWorlds(); } catch (e) { alert(e.message); } };
 
 // Plugins
@@ -509,6 +612,11 @@ function renderPlugins() {
                 <input type="text" id="plugin-search" class="input" placeholder="Search Modrinth plugins...">
                 <button class="btn btn-primary btn-sm" onclick="runPluginSearch()">Search</button>
             </div>
+            <div class="flex justify-between items-center">
+                <div id="addon-updates-summary-plugins" class="text-muted" style="font-size:12px"></div>
+                <button class="btn btn-secondary btn-sm" onclick="checkAddonUpdates('plugins')">Check for updates</button>
+            </div>
+            <div id="addon-updates-plugins" class="flex flex-column gap-1 mb-2"></div>
             <div id="installed-plugins-list" class="flex flex-column gap-1 mb-2">
                 <div class="text-muted">Loading installed plugins...</div>
             </div>
@@ -524,8 +632,18 @@ window.loadPlugins = async () => {
         const data = await api.getInstalledPlugins(state.currentServer);
         list.innerHTML = `<h3>Installed</h3>` + (data.plugins.map(p => `
             <div class="file-item">
-                <span>🔌 ${p.name} <span class="text-muted">v${p.version}</span></span>
-                <button class="btn btn-danger btn-sm" onclick="removePlugin('${p.name}')">Remove</button>
+                <div>
+                    <div>🔌 ${p.name} ${p.version ? `<span class="text-muted">v${p.version}</span>` : ''}
+                        ${p.api_version ? `<span class="text-muted" style="font-size:11px">API ${p.api_version}</span>` : ''}</div>
+                    ${p.description ? `<div class="text-muted" style="font-size:12px">${p.description}</div>` : ''}
+                    <div class="text-muted" style="font-size:11px">
+                        ${p.file_name}${p.authors.length ? ` • by ${p.authors.join(', ')}` : ''}
+                        ${p.depends.length ? ` • needs ${p.depends.join(', ')}` : ''}
+                        ${p.soft_depends.length ? ` • uses ${p.soft_depends.join(', ')}` : ''}
+                        ${p.commands.length ? ` • /${p.commands.join(', /')}` : ''}
+                    </div>
+                </div>
+                <button class="btn btn-danger btn-sm" onclick="removePlugin('${p.file_name}')">Remove</button>
             </div>
         `).join('') || '<div class="text-muted">No plugins found.</div>');
     } catch (e) { list.innerHTML = 'Error.'; }
@@ -537,21 +655,245 @@ window.runPluginSearch = async () => {
     results.innerHTML = '<div class="text-muted">Searching...</div>';
     try {
         const data = await api.searchPlugins(query);
-        results.innerHTML = `<h3>Results</h3>` + (data.plugins.map(p => `
-            <div class="card flex justify-between items-center">
+        results.innerHTML = `<h3>Results</h3>` + (data.plugins.map(p => renderAddonResult('plugins', p)).join('') || 'No results.');
+    } catch (e) { results.innerHTML = 'Search failed.'; }
+};
+
+// Search results for plugins and mods: pick a release, then install it with
+// whatever it requires
+function renderAddonResult(kind, addon) {
+    return `
+        <div class="card">
+            <div class="flex justify-between items-center">
                 <div>
-                    <div style="font-weight:700">${p.name}</div>
-                    <div class="text-muted" style="font-size:11px">${p.description || 'No description.'}</div>
+                    <div style="font-weight:700">${addon.name}</div>
+                    <div class="text-muted" style="font-size:11px">${addon.description || 'No description.'}</div>
                 </div>
-                <button class="btn btn-success btn-sm" onclick="installPlugin('${p.name}')">Install</button>
+                <button class="btn btn-success btn-sm" onclick="showAddonVersions('${kind}', '${addon.project_id}')">Install</button>
             </div>
-        `).join('') || 'No results.');
-    } catch (e) { results.innerHTML = 'Search failed.'; }
+            <div id="addon-versions-${addon.project_id}"></div>
+        </div>
+    `;
+}
+
+window.showAddonVersions = async (kind, project) => {
+    const area = document.getElementById(`addon-versions-${project}`);
+    area.innerHTML = '<div class="text-muted mt-1">Loading releases...</div>';
+    try {
+        const { versions } = await api.getAddonVersions(state.currentServer, kind, project);
+        if (!versions.length) {
+            area.innerHTML = '<div class="text-muted mt-1">No release for this server\'s version and loader.</div>';
+            return;
+        }
+        area.innerHTML = `
+            <div class="flex gap-1 mt-1">
+                <select id="addon-version-${project}" class="select" style="flex:1">
+                    ${versions.map(v => `<option value="${v.id}">${v.version_number}${v.version_type && v.version_type !== 'release' ? ` (${v.version_type})` : ''}</option>`).join('')}
+                </select>
+                <button class="btn btn-primary btn-sm" onclick="installAddon('${kind}', '${project}')">Install</button>
+            </div>
+        `;
+    } catch (e) { area.innerHTML = `<div class="mt-1" style="color:var(--error)">${e.message}</div>`; }
+};
+
+window.installAddon = async (kind, project) => {
+    const versionId = document.getElementById(`addon-version-${project}`).value;
+    try {
+        const plan = await api.planAddonInstall(state.currentServer, kind, project, versionId);
+        if (plan.conflicts.length) {
+            alert(`Can't install:\n${plan.conflicts.join('\n')}`);
+            return;
+        }
+        if (!plan.install.length) {
+            alert('Already installed.');
+            return;
+        }
+        const files = plan.install.map(a => `${a.file_name}${a.dependency ? ' (required)' : ''}`).join('\n');
+        const satisfied = plan.satisfied.length ? `\n\nAlready installed: ${plan.satisfied.join(', ')}` : '';
+        if (!confirm(`Install:\n${files}${satisfied}`)) return;
+
+        if (kind === 'mods') {
+            await api.installMod(state.currentServer, project, versionId);
+            loadMods();
+        } else {
+            await api.installPlugin(state.currentServer, project, versionId);
+            loadPlugins();
+        }
+        document.getElementById(`addon-versions-${project}`).innerHTML = '<div class="mt-1" style="color:var(--success)">Installed.</div>';
+    } catch (e) { alert(e.message); }
 };
 
-window.installPlugin = async (name) => { try { await api.installPlugin(state.currentServer, name); alert('Plugin installed!'); loadPlugins(); } catch (e) { alert(e.message); } };
 window.removePlugin = async (name) => { if (confirm('Remove plugin?')) try { await api.removePlugin(state.currentServer, name); loadPlugins(); } catch (e) { alert(e.message); } };
 
+// Mods (Fabric, Forge and NeoForge servers)
+function renderMods() {
+    return `
+        <div class="card">
+            <h2 class="mb-1">Mods</h2>
+            <div class="flex gap-1 mb-2">
+                <input type="text" id="mod-search" class="input" placeholder="Search Modrinth mods for this loader and version...">
+                <button class="btn btn-primary btn-sm" onclick="runModSearch()">Search</button>
+            </div>
+            <div class="flex justify-between items-center">
+                <div id="addon-updates-summary-mods" class="text-muted" style="font-size:12px"></div>
+                <button class="btn btn-secondary btn-sm" onclick="checkAddonUpdates('mods')">Check for updates</button>
+            </div>
+            <div id="addon-updates-mods" class="flex flex-column gap-1 mb-2"></div>
+            <div id="installed-mods-list" class="flex flex-column gap-1 mb-2">
+                <div class="text-muted">Loading installed mods...</div>
+            </div>
+            <div id="mod-results-list" class="flex flex-column gap-1"></div>
+        </div>
+    `;
+}
+
+window.loadMods = async () => {
+    const list = document.getElementById('installed-mods-list');
+    if (!list) return;
+    try {
+        const data = await api.getInstalledMods(state.currentServer);
+        list.innerHTML = `<h3>Installed</h3>` + (data.mods.map(m => `
+            <div class="file-item">
+                <span>🧩 ${m.name} <span class="text-muted">${m.file_name}</span></span>
+                <button class="btn btn-danger btn-sm" onclick="removeMod('${m.file_name}')">Remove</button>
+            </div>
+        `).join('') || '<div class="text-muted">No mods found.</div>');
+    } catch (e) { list.innerHTML = 'Error.'; }
+};
+
+window.runModSearch = async () => {
+    const query = document.getElementById('mod-search').value;
+    const results = document.getElementById('mod-results-list');
+    results.innerHTML = '<div class="text-muted">Searching...</div>';
+    try {
+        const data = await api.searchMods(state.currentServer, query);
+        results.innerHTML = `<h3>Results</h3>` + (data.mods.map(m => renderAddonResult('mods', m)).join('') || 'No results.');
+    } catch (e) { results.innerHTML = e.message; }
+};
+
+// Update checks, shared by plugins and mods
+window.checkAddonUpdates = async (kind) => {
+    const summary = document.getElementById(`addon-updates-summary-${kind}`);
+    const list = document.getElementById(`addon-updates-${kind}`);
+    summary.textContent = 'Checking Modrinth...';
+    list.innerHTML = '';
+    try {
+        const { updates } = await api.checkAddonUpdates(state.currentServer, kind);
+        const outdated = updates.filter(u => u.update_available);
+        const unknown = updates.filter(u => !u.project_id).length;
+        summary.textContent = `${outdated.length} of ${updates.length} outdated` + (unknown ? `, ${unknown} not on Modrinth` : '');
+        list.innerHTML = updates.filter(u => u.update_available || u.can_rollback).map(u => `
+            <div class="file-item">
+                <span>${u.file_name} <span class="text-muted">${u.installed_version || '?'}${u.update_available ? ` → ${u.latest_version}` : ''}</span></span>
+                <div class="flex gap-1">
+                    ${u.update_available ? `<button class="btn btn-success btn-sm" onclick="updateAddons('${kind}', '${u.file_name}')">Update</button>` : ''}
+                    ${u.can_rollback ? `<button class="btn btn-secondary btn-sm" onclick="rollbackAddonUpdate('${kind}', '${u.file_name}')">Roll back</button>` : ''}
+                </div>
+            </div>
+        `).join('') + (outdated.length > 1 ? `<button class="btn btn-primary btn-sm" onclick="updateAddons('${kind}', null)">Update all</button>` : '');
+    } catch (e) { summary.textContent = e.message; }
+};
+
+window.updateAddons = async (kind, fileName) => {
+    try {
+        const { updated } = await api.updateAddons(state.currentServer, kind, fileName);
+        alert(updated.length
+            ? `Updated:\n${updated.map(u => `${u.previous_file} → ${u.file_name}`).join('\n')}\n\nRestart the server to load them.`
+            : 'Everything is up to date.');
+    } catch (e) { alert(e.message); }
+    kind === 'mods' ? loadMods() : loadPlugins();
+    checkAddonUpdates(kind);
+};
+
+window.rollbackAddonUpdate = async (kind, fileName) => {
+    if (!confirm(`Put back the jar ${fileName} replaced?`)) return;
+    try { await api.rollbackAddonUpdate(state.currentServer, kind, fileName); } catch (e) { alert(e.message); }
+    kind === 'mods' ? loadMods() : loadPlugins();
+    checkAddonUpdates(kind);
+};
+
+window.removeMod = async (name) => { if (confirm('Remove mod?')) try { await api.removeMod(state.currentServer, name); loadMods(); } catch (e) { alert(e.message); } };
+
+// Network (proxy and its backends)
+const PROXY_TYPES = ['velocity', 'waterfall', 'bungeecord'];
+
+function renderNetwork() {
+    return `
+        <div class="card">
+            <h2 class="mb-1">Network</h2>
+            <div id="network-content" class="flex flex-column gap-1">
+                <div class="text-muted">Loading network...</div>
+            </div>
+        </div>
+    `;
+}
+
+window.loadNetwork = async () => {
+    const content = document.getElementById('network-content');
+    if (!content) return;
+    try {
+        const [networks, servers] = await Promise.all([api.getNetworks(), api.getServers()]);
+        const server = servers.find(s => s.id === state.currentServer);
+        const network = networks.find(n => n.proxy_id === server.id || n.backends.some(b => b.server_id === server.id));
+        const nameOf = (id) => (servers.find(s => s.id === id) || {}).name || id;
+
+        if (!network) {
+            content.innerHTML = PROXY_TYPES.includes(server.server_type) ? `
+                <div class="text-muted">This proxy has no network yet.</div>
+                <div class="flex gap-1">
+                    <input type="text" id="network-name" class="input" placeholder="Network name">
+                    <button class="btn btn-primary btn-sm" onclick="createNetwork()">Create Network</button>
+                </div>
+            ` : '<div class="text-muted">Not part of a network. Add it from the proxy\'s Network tab.</div>';
+            return;
+        }
+
+        if (network.proxy_id !== server.id) {
+            const member = network.backends.find(b => b.server_id === server.id);
+            content.innerHTML = `
+                <div>Backend <b>${member.name}</b> of network <b>${network.name}</b>, behind ${nameOf(network.proxy_id)}.</div>
+                <div class="text-muted">Only reachable through the proxy.</div>
+            `;
+            return;
+        }
+
+        const taken = new Set([network.proxy_id, ...networks.flatMap(n => [n.proxy_id, ...n.backends.map(b => b.server_id)])]);
+        const candidates = servers.filter(s => !taken.has(s.id) && !PROXY_TYPES.includes(s.server_type));
+        content.innerHTML = `
+            <div class="flex justify-between items-center">
+                <div><b>${network.name}</b> <span class="text-muted">• joins try backends top to bottom</span></div>
+                <button class="btn btn-danger btn-sm" onclick="deleteNetwork('${network.id}')">Delete Network</button>
+            </div>
+            <h3>Backends</h3>
+            ${network.backends.map(b => `
+                <div class="file-item">
+                    <span>${b.name} <span class="text-muted">${nameOf(b.server_id)}</span></span>
+                    <button class="btn btn-danger btn-sm" onclick="removeBackend('${network.id}', '${b.server_id}')">Remove</button>
+                </div>
+            `).join('') || '<div class="text-muted">No backends yet.</div>'}
+            <div class="flex gap-1">
+                <select id="backend-server" class="select">
+                    ${candidates.map(s => `<option value="${s.id}">${s.name} (${s.server_type}, port ${s.port})</option>`).join('')}
+                </select>
+                <input type="text" id="backend-name" class="input" placeholder="Name on proxy (optional)">
+                <button class="btn btn-success btn-sm" onclick="addBackend('${network.id}')" ${candidates.length ? '' : 'disabled'}>Add</button>
+            </div>
+        `;
+    } catch (e) { content.innerHTML = e.message; }
+};
+
+window.createNetwork = async () => {
+    const name = document.getElementById('network-name').value || 'network';
+    try { await api.createNetwork(name, state.currentServer); loadNetwork(); } catch (e) { alert(e.message); }
+};
+window.deleteNetwork = async (id) => { if (confirm('Delete network? Backends go back to online mode on their own ports.')) try { await api.deleteNetwork(id); loadNetwork(); } catch (e) { alert(e.message); } };
+window.addBackend = async (id) => {
+    const serverId = document.getElementById('backend-server').value;
+    const name = document.getElementById('backend-name').value;
+    try { await api.addBackend(id, serverId, name); loadNetwork(); } catch (e) { alert(e.message); }
+};
+window.removeBackend = async (id, serverId) => { if (confirm('Remove backend?')) try { await api.removeBackend(id, serverId); loadNetwork(); } catch (e) { alert(e.message); } };
+
 // Settings
 function renderSettings() {
     return `
@@ -564,9 +906,232 @@ function renderSettings() {
                 <button class="btn btn-primary" onclick="saveSettings()">Save Configuration</button>
             </div>
         </div>
+        <div class="card mt-2">
+            <h2 class="mb-1">Server Jar</h2>
+            <div id="jar-area">
+                <div class="text-muted">Loading...</div>
+            </div>
+            <div class="flex justify-end gap-1 mt-2">
+                <input type="file" id="jar-file" accept=".jar" style="display:none" onchange="replaceJar(this)">
+                <button class="btn btn-secondary" id="jar-rollback-btn" onclick="rollbackJar()" disabled>Roll Back</button>
+                <button class="btn btn-primary" onclick="document.getElementById('jar-file').click()">Replace Jar</button>
+            </div>
+        </div>
+        <div class="card mt-2">
+            <h2 class="mb-1">Server Build</h2>
+            <div id="build-area">
+                <div class="text-muted">Loading...</div>
+            </div>
+            <div class="flex items-center justify-end gap-1 mt-2">
+                <label class="text-muted" style="font-size:12px"><input type="checkbox" id="build-pinned" onchange="setBuildOptions()"> Pin this build</label>
+                <label class="text-muted" style="font-size:12px"><input type="checkbox" id="build-auto" onchange="setBuildOptions()"> Update on next start</label>
+                <button class="btn btn-secondary" id="build-check-btn" onclick="checkBuild()">Check Now</button>
+                <button class="btn btn-primary" id="build-install-btn" onclick="installBuild()" disabled>Install Update</button>
+            </div>
+        </div>
+        <div class="card mt-2">
+            <h2 class="mb-1">Minecraft Version</h2>
+            <div id="upgrade-area">
+                <div class="text-muted">Loading...</div>
+            </div>
+            <div class="flex items-center gap-1 mt-2">
+                <select id="upgrade-version" class="select" style="flex:1"></select>
+                <label class="text-muted" style="font-size:12px"><input type="checkbox" id="upgrade-force"> Force upgrade chunks</label>
+                <label class="text-muted" style="font-size:12px"><input type="checkbox" id="upgrade-ignore"> Ignore incompatible add-ons</label>
+            </div>
+            <div id="upgrade-addons" class="mt-1"></div>
+            <div class="flex justify-end gap-1 mt-2">
+                <button class="btn btn-secondary" id="upgrade-rollback-btn" onclick="rollbackUpgrade()" disabled>Roll Back Upgrade</button>
+                <button class="btn btn-secondary" onclick="checkUpgrade()">Check Add-ons</button>
+                <button class="btn btn-primary" id="upgrade-btn" onclick="upgradeServer()">Upgrade</button>
+            </div>
+        </div>
+    `;
+}
+
+function renderBuild(tracking) {
+    document.getElementById('build-pinned').checked = tracking.pinned;
+    document.getElementById('build-auto').checked = tracking.auto_update;
+    document.getElementById('build-install-btn').disabled = !tracking.update;
+    if (!tracking.build) {
+        return '<div class="text-muted">Build unknown; updates are only tracked for downloaded jars.</div>';
+    }
+    const checked = tracking.checked_at ? `Checked ${new Date(tracking.checked_at * 1000).toLocaleString()}` : 'Not checked yet';
+    const update = tracking.update;
+    return `
+        <div>Build <strong>${tracking.build}</strong>${tracking.channel ? ` on ${tracking.channel}` : ''}${tracking.previous_build ? ` <span class="text-muted">(previously ${tracking.previous_build})</span>` : ''}</div>
+        <div class="text-muted" style="font-size:12px">${checked}</div>
+        ${update ? `
+            <div class="mt-1" style="color:var(--success)">Build ${update.build}${update.channel ? ` (${update.channel})` : ''} available, ${update.builds_behind} behind</div>
+            ${update.changes.map(c => `
+                <div style="font-size:12px"><span class="text-muted" style="font-family:monospace">${c.commit.slice(0, 7)}</span> ${c.summary}</div>
+            `).join('')}
+        ` : ''}
     `;
 }
 
+window.loadBuild = async () => {
+    const area = document.getElementById('build-area');
+    if (!area) return;
+    try {
+        area.innerHTML = renderBuild(await api.getBuild(state.currentServer));
+    } catch (e) { area.innerHTML = 'Failed to load build details.'; }
+};
+
+window.checkBuild = async () => {
+    const btn = document.getElementById('build-check-btn');
+    btn.disabled = true;
+    try {
+        document.getElementById('build-area').innerHTML = renderBuild(await api.checkBuild(state.currentServer));
+    } catch (e) { alert(e.message); }
+    btn.disabled = false;
+};
+
+window.setBuildOptions = async () => {
+    try {
+        const tracking = await api.updateBuild(state.currentServer, {
+            pinned: document.getElementById('build-pinned').checked,
+            auto_update: document.getElementById('build-auto').checked,
+        });
+        document.getElementById('build-area').innerHTML = renderBuild(tracking);
+    } catch (e) { alert(e.message); loadBuild(); }
+};
+
+window.installBuild = async () => {
+    const tracking = await api.getBuild(state.currentServer);
+    if (!tracking.update || !confirm(`Install build ${tracking.update.build}? The current jar is kept for rollback.`)) return;
+    const btn = document.getElementById('build-install-btn');
+    btn.disabled = true;
+    try {
+        document.getElementById('build-area').innerHTML = renderBuild(
+            await api.updateBuild(state.currentServer, { build: tracking.update.build, pinned: tracking.pinned })
+        );
+    } catch (e) { alert(e.message); btn.disabled = false; }
+    loadJars();
+};
+
+window.loadUpgrade = async () => {
+    const area = document.getElementById('upgrade-area');
+    if (!area) return;
+    try {
+        const server = state.serverData || await api.getServer(state.currentServer);
+        const [upgrade, versions] = await Promise.all([
+            api.getUpgrade(state.currentServer),
+            api.getVersions(server.server_type).catch(() => ({ versions: [] })),
+        ]);
+        area.innerHTML = `
+            <div>Currently on <strong>${server.minecraft_version}</strong></div>
+            ${upgrade ? `<div class="text-muted" style="font-size:12px">Last upgrade: ${upgrade.from_version} → ${upgrade.to_version}, ${upgrade.status.replace('_', ' ')} (${new Date(upgrade.created_at * 1000).toLocaleString()})</div>` : ''}
+        `;
+        document.getElementById('upgrade-version').innerHTML = versions.versions.slice().reverse()
+            .filter(v => v !== server.minecraft_version)
+            .map(v => `<option value="${v}">${v}</option>`).join('');
+        document.getElementById('upgrade-rollback-btn').disabled = !upgrade || upgrade.status === 'rolled_back';
+    } catch (e) { area.innerHTML = 'Failed to load version details.'; }
+};
+
+window.checkUpgrade = async () => {
+    const version = document.getElementById('upgrade-version').value;
+    const list = document.getElementById('upgrade-addons');
+    if (!version) return;
+    list.innerHTML = '<div class="text-muted">Checking...</div>';
+    try {
+        const check = await api.checkUpgrade(state.currentServer, version);
+        const colors = { compatible: 'var(--success)', incompatible: 'var(--error)', unknown: 'var(--text-muted)' };
+        list.innerHTML = check.addons.map(a => `
+            <div class="flex justify-between" style="font-size:12px">
+                <span style="font-family:monospace">${a.file_name}</span>
+                <span style="color:${colors[a.compatibility]}">${a.compatibility}${a.compatible_version ? ` (${a.compatible_version})` : ''}</span>
+            </div>
+        `).join('') || '<div class="text-muted">No add-ons installed.</div>';
+    } catch (e) { list.innerHTML = `<div style="color:var(--error)">${e.message}</div>`; }
+};
+
+window.upgradeServer = async () => {
+    const version = document.getElementById('upgrade-version').value;
+    if (!version || !confirm(`Back up the worlds and upgrade to ${version}?`)) return;
+    const btn = document.getElementById('upgrade-btn');
+    btn.disabled = true;
+    try {
+        const job = await api.upgradeServer(state.currentServer, {
+            version,
+            force_upgrade: document.getElementById('upgrade-force').checked,
+            ignore_incompatible: document.getElementById('upgrade-ignore').checked,
+        });
+        await waitForJob(job, (j) => { btn.textContent = `Upgrading... ${Math.floor(j.progress)}%`; });
+    } catch (e) { alert(e.message); }
+    btn.disabled = false;
+    btn.textContent = 'Upgrade';
+    state.serverData = null;
+    loadServerData();
+    loadUpgrade();
+    loadJars();
+};
+
+window.rollbackUpgrade = async () => {
+    if (!confirm('Restore the jar and worlds from before the last upgrade?')) return;
+    const btn = document.getElementById('upgrade-rollback-btn');
+    btn.disabled = true;
+    try {
+        const job = await api.rollbackUpgrade(state.currentServer);
+        await waitForJob(job);
+    } catch (e) { alert(e.message); }
+    state.serverData = null;
+    loadServerData();
+    loadUpgrade();
+    loadJars();
+};
+
+function renderJarRecord(label, jar) {
+    if (!jar) return '';
+    const from = jar.source === 'upload'
+        ? `Uploaded${jar.file_name ? ` as ${jar.file_name}` : ''}${jar.uploaded_by ? ` by ${jar.uploaded_by}` : ''}`
+        : 'Downloaded';
+    return `
+        <div class="mb-1">
+            <div><strong>${label}:</strong> ${jar.version} <span class="text-muted">${from}, ${new Date(jar.installed_at * 1000).toLocaleString()}</span></div>
+            <div class="text-muted" style="font-size:11px; font-family:monospace">sha256 ${jar.sha256}</div>
+        </div>
+    `;
+}
+
+window.loadJars = async () => {
+    const area = document.getElementById('jar-area');
+    if (!area) return;
+    try {
+        const jars = await api.getServerJars(state.currentServer);
+        area.innerHTML = (renderJarRecord('Current', jars.current) + renderJarRecord('Previous', jars.previous))
+            || '<div class="text-muted">Installed when the server was created.</div>';
+        document.getElementById('jar-rollback-btn').disabled = !jars.previous;
+    } catch (e) { area.innerHTML = 'Failed to load jar details.'; }
+};
+
+window.replaceJar = async (input) => {
+    const file = input.files[0];
+    input.value = '';
+    if (!file) return;
+    const version = prompt('Minecraft version of this jar (leave empty to detect it)', '');
+    if (version === null) return;
+
+    const formData = new FormData();
+    formData.append('file', file);
+    if (version.trim()) formData.append('version', version.trim());
+    try {
+        await api.replaceServerJar(state.currentServer, formData);
+        loadJars();
+        loadBuild();
+    } catch (e) { alert(e.message); }
+};
+
+window.rollbackJar = async () => {
+    if (!confirm('Swap back to the previous jar?')) return;
+    try {
+        await api.rollbackServerJar(state.currentServer);
+        loadJars();
+        loadBuild();
+    } catch (e) { alert(e.message); }
+};
+
 window.loadSettings = async () => {
     const area = document.getElementById('settings-area');
     if (!area) return;
@@ -606,9 +1171,10 @@ async function loadDashboardData() {
             <div class="card flex justify-between items-center" onclick="navigateTo('server', '${s.id}')" style="cursor:pointer">
                 <div>
                     <div style="font-weight:700">${s.name}</div>
-                    <div class="text-muted" style="font-size:11px">${s.server_type} • ${s.minecraft_version} • Port ${s.port}</div>
+                    <div class="text-muted" style="font-size:11px">${s.server_type} • ${s.minecraft_version}${s.build ? ` #${s.build}` : ''} • Port ${s.port}</div>
                 </div>
-                <span class="badge ${s.state === 'running' ? 'badge-success' : 'badge-error'}">${s.state}</span>
+                ${s.update ? `<span class="badge badge-success" title="Build ${s.update.build}">update</span>` : ''}
+                <span class="badge ${isUp(s) ? 'badge-success' : 'badge-error'}">${s.state}</span>
             </div>
         `).join('') || '<div class="card text-muted">No servers found.</div>';
     } catch (e) { }
@@ -630,15 +1196,36 @@ window.showCreateModal = () => {
                         <label class="text-muted" style="font-size:10px">TYPE</label>
                         <select id="new-type" class="select" onchange="updateVersionSelector()">
                             <option value="paper">Paper</option>
+                            <option value="purpur">Purpur</option>
+                            <option value="folia">Folia</option>
                             <option value="spigot">Spigot</option>
+                            <option value="vanilla">Vanilla</option>
+                            <option value="fabric">Fabric</option>
+                            <option value="forge">Forge</option>
+                            <option value="neoforge">NeoForge</option>
+                            <option value="velocity">Velocity (proxy)</option>
+                            <option value="waterfall">Waterfall (proxy)</option>
+                            <option value="bungeecord">BungeeCord (proxy)</option>
+                            <option value="bedrock">Bedrock</option>
                         </select>
                     </div>
+                    <div class="mb-1" id="bedrock-upload" style="display:none">
+                        <label class="text-muted" style="font-size:10px">OR UPLOAD SERVER (bedrock-server-*.zip)</label>
+                        <input type="file" id="bedrock-file" class="input" accept=".zip" onchange="handleBedrockUpload()">
+                    </div>
                     <div class="mb-1">
                         <label class="text-muted" style="font-size:10px">VERSION</label>
                         <select id="new-version" class="select">
                             <option value="">Loading versions...</option>
                         </select>
                     </div>
+                    <div class="mb-1" id="spigot-variant" style="display:none">
+                        <label class="text-muted" style="font-size:10px">BUILD WITH BUILDTOOLS</label>
+                        <select id="new-variant" class="select">
+                            <option value="spigot">Spigot</option>
+                            <option value="craftbukkit">CraftBukkit</option>
+                        </select>
+                    </div>
                     <div class="flex justify-end gap-1">
                         <button type="button" class="btn btn-secondary btn-sm" onclick="closeModal()">Cancel</button>
                         <button type="submit" id="create-btn" class="btn btn-primary btn-sm" disabled>Create</button>
@@ -650,6 +1237,118 @@ window.showCreateModal = () => {
     updateVersionSelector();
 };
 
+window.showImportModal = () => {
+    const modal = document.getElementById('modal-container');
+    modal.innerHTML = `
+        <div class="modal-overlay" onclick="closeModal()">
+            <div class="modal" onclick="event.stopPropagation()">
+                <h2 class="mb-1">Import Modpack</h2>
+                <form onsubmit="handleImport(event)">
+                    <div class="mb-1">
+                        <label class="text-muted" style="font-size:10px">MODRINTH PACK (.mrpack)</label>
+                        <input type="file" id="import-file" class="input" accept=".mrpack" required>
+                    </div>
+                    <div class="mb-1">
+                        <label class="text-muted" style="font-size:10px">NAME</label>
+                        <input type="text" id="import-name" class="input" placeholder="Defaults to the pack name">
+                    </div>
+                    <div class="flex justify-end gap-1">
+                        <button type="button" class="btn btn-secondary btn-sm" onclick="closeModal()">Cancel</button>
+                        <button type="submit" id="import-btn" class="btn btn-primary btn-sm">Import</button>
+                    </div>
+                </form>
+            </div>
+        </div>
+    `;
+};
+
+window.handleImport = async (e) => {
+    e.preventDefault();
+    const btn = document.getElementById('import-btn');
+    btn.disabled = true;
+    btn.textContent = 'Uploading...';
+
+    const formData = new FormData();
+    formData.append('file', document.getElementById('import-file').files[0]);
+    formData.append('name', document.getElementById('import-name').value);
+    try {
+        const job = await api.importModpack(formData);
+        await waitForJob(job, (j) => { btn.textContent = `Importing... ${Math.floor(j.progress)}%`; });
+        closeModal();
+        loadDashboardData();
+    } catch (e) {
+        alert(e.message);
+        btn.disabled = false;
+        btn.textContent = 'Import';
+    }
+};
+
+window.showUploadJarModal = () => {
+    const modal = document.getElementById('modal-container');
+    modal.innerHTML = `
+        <div class="modal-overlay" onclick="closeModal()">
+            <div class="modal" onclick="event.stopPropagation()">
+                <h2 class="mb-1">Upload Server Jar</h2>
+                <form onsubmit="handleUploadJar(event)">
+                    <div class="mb-1">
+                        <label class="text-muted" style="font-size:10px">SERVER JAR</label>
+                        <input type="file" id="jar-upload-file" class="input" accept=".jar" required>
+                    </div>
+                    <div class="mb-1">
+                        <label class="text-muted" style="font-size:10px">TYPE</label>
+                        <select id="jar-upload-type" class="select">
+                            <option value="paper">Paper</option>
+                            <option value="purpur">Purpur</option>
+                            <option value="folia">Folia</option>
+                            <option value="spigot">Spigot</option>
+                            <option value="vanilla">Vanilla</option>
+                            <option value="fabric">Fabric</option>
+                            <option value="velocity">Velocity (proxy)</option>
+                            <option value="waterfall">Waterfall (proxy)</option>
+                            <option value="bungeecord">BungeeCord (proxy)</option>
+                        </select>
+                    </div>
+                    <div class="mb-1">
+                        <label class="text-muted" style="font-size:10px">VERSION</label>
+                        <input type="text" id="jar-upload-version" class="input" placeholder="Detected from the jar when empty">
+                    </div>
+                    <div class="mb-1">
+                        <label class="text-muted" style="font-size:10px">NAME</label>
+                        <input type="text" id="jar-upload-name" class="input" placeholder="Defaults to the jar name">
+                    </div>
+                    <div class="flex justify-end gap-1">
+                        <button type="button" class="btn btn-secondary btn-sm" onclick="closeModal()">Cancel</button>
+                        <button type="submit" id="jar-upload-btn" class="btn btn-primary btn-sm">Create</button>
+                    </div>
+                </form>
+            </div>
+        </div>
+    `;
+};
+
+window.handleUploadJar = async (e) => {
+    e.preventDefault();
+    const btn = document.getElementById('jar-upload-btn');
+    btn.disabled = true;
+    btn.textContent = 'Uploading...';
+
+    const formData = new FormData();
+    formData.append('file', document.getElementById('jar-upload-file').files[0]);
+    formData.append('server_type', document.getElementById('jar-upload-type').value);
+    formData.append('version', document.getElementById('jar-upload-version').value);
+    formData.append('name', document.getElementById('jar-upload-name').value);
+    try {
+        const job = await api.createServerFromJar(formData);
+        await waitForJob(job, (j) => { btn.textContent = `Creating... ${Math.floor(j.progress)}%`; });
+        closeModal();
+        loadDashboardData();
+    } catch (e) {
+        alert(e.message);
+        btn.disabled = false;
+        btn.textContent = 'Create';
+    }
+};
+
 window.updateVersionSelector = async () => {
     const type = document.getElementById('new-type').value;
     const selector = document.getElementById('new-version');
@@ -658,6 +1357,8 @@ window.updateVersionSelector = async () => {
 
     selector.innerHTML = '<option value="">Loading...</option>';
     btn.disabled = true;
+    document.getElementById('bedrock-upload').style.display = type === 'bedrock' ? '' : 'none';
+    document.getElementById('spigot-variant').style.display = type === 'spigot' ? '' : 'none';
 
     try {
         const data = await api.getVersions(type);
@@ -670,6 +1371,20 @@ window.updateVersionSelector = async () => {
     }
 };
 
+window.handleBedrockUpload = async () => {
+    const input = document.getElementById('bedrock-file');
+    if (!input.files.length) return;
+
+    const formData = new FormData();
+    formData.append('file', input.files[0]);
+    try {
+        const { version } = await api.uploadBedrockServer(formData);
+        await updateVersionSelector();
+        document.getElementById('new-version').value = version;
+    } catch (e) { alert(e.message); }
+    input.value = '';
+};
+
 window.handleCreate = async (e) => {
     e.preventDefault();
     const btn = document.getElementById('create-btn');
@@ -678,11 +1393,13 @@ window.handleCreate = async (e) => {
 
     const data = {
         name: document.getElementById('new-name').value,
-        server_type: document.getElementById('new-type').value, // Now sends lowercase 'paper' or 'spigot'
+        server_type: document.getElementById('new-type').value,
         minecraft_version: document.getElementById('new-version').value,
     };
+    if (data.server_type === 'spigot') data.build = document.getElementById('new-variant').value;
     try {
-        await api.createServer(data);
+        const job = await api.createServer(data);
+        await waitForJob(job, (j) => { btn.textContent = `Creating... ${Math.floor(j.progress)}%`; });
         closeModal();
         loadDashboardData();
     } catch (e) {
@@ -0,0 +1,282 @@
+//! Updating installed plugins and mods to the newest release for the server,
+//! found by looking their jars up on Modrinth by hash. The jar an update
+//! replaces is kept next to it as `<file>.previous`, which no loader picks up,
+//! so the update can be rolled back.
+
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::plugins::{
+    cache_addon_file, check_addon_file_name, hash_addon_jars, latest_versions_by_hash, versions_by_hash, AddonVersion,
+    ModrinthFileVersion,
+};
+use crate::progress::Progress;
+use crate::providers::provider;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::fs;
+
+/// Which jar each update replaced, by the jar it installed, kept in the
+/// add-on directory
+const UPDATES_FILE: &str = ".updates.json";
+
+/// An installed plugin or mod against the newest release for the server
+#[derive(Debug, Clone, Serialize)]
+pub struct AddonUpdate {
+    pub file_name: String,
+    /// `None` for a jar Modrinth doesn't know, which can't be checked
+    pub project_id: Option<String>,
+    pub installed_version: Option<String>,
+    /// Newest release for the server's loaders and Minecraft version
+    pub latest_version: Option<String>,
+    pub latest_version_id: Option<String>,
+    pub update_available: bool,
+    /// An earlier update's jar is kept to roll back to
+    pub can_rollback: bool,
+}
+
+/// An update that was applied
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedAddonUpdate {
+    pub project_id: String,
+    /// The jar that was replaced, now kept as `<previous_file>.previous`
+    pub previous_file: String,
+    pub previous_version: String,
+    pub file_name: String,
+    pub version: String,
+}
+
+struct PendingUpdate {
+    file_name: String,
+    installed_version: String,
+    latest: AddonVersion,
+}
+
+/// Check every installed jar for a newer release that runs on the server's
+/// loaders and `minecraft_version`
+pub async fn check_addon_updates(
+    upstreams: &Upstreams,
+    server_dir: &Path,
+    server_type: ServerType,
+    minecraft_version: &str,
+) -> Result<Vec<AddonUpdate>> {
+    let addons_dir = server_dir.join(provider(server_type).addon_kind().dir());
+    let files = hash_addon_jars(&addons_dir).await?;
+    let rollbacks = read_updates(&addons_dir).await?;
+    let (installed, latest) = lookup(upstreams, &files, server_type, minecraft_version).await?;
+
+    Ok(files
+        .into_iter()
+        .map(|(file_name, hash)| {
+            let installed = installed.get(&hash);
+            let latest = latest.get(&hash);
+            let can_rollback = rollbacks
+                .get(&file_name)
+                .is_some_and(|previous| addons_dir.join(previous_name(previous)).exists());
+            AddonUpdate {
+                project_id: installed.map(|v| v.project_id.clone()),
+                installed_version: installed.map(|v| v.version_number.clone()),
+                latest_version: latest.map(|v| v.version_number.clone()),
+                latest_version_id: latest.map(|v| v.id.clone()),
+                update_available: matches!((installed, latest), (Some(i), Some(l)) if i.id != l.id),
+                can_rollback,
+                file_name,
+            }
+        })
+        .collect())
+}
+
+/// Update one installed jar, by file name, or every jar with an update when
+/// `file_name` is `None`. All new jars are downloaded before any is swapped
+/// in.
+pub async fn update_addons(
+    upstreams: &Upstreams,
+    cache: &ArtifactCache,
+    server_dir: &Path,
+    server_type: ServerType,
+    minecraft_version: &str,
+    file_name: Option<&str>,
+    progress: &Progress,
+) -> Result<Vec<AppliedAddonUpdate>> {
+    let kind = provider(server_type).addon_kind();
+    let addons_dir = server_dir.join(kind.dir());
+    let mut files = hash_addon_jars(&addons_dir).await?;
+    if let Some(file_name) = file_name {
+        files.retain(|(f, _)| f == file_name);
+        if files.is_empty() {
+            anyhow::bail!("{} is not installed", file_name);
+        }
+    }
+    let (installed, latest) = lookup(upstreams, &files, server_type, minecraft_version).await?;
+
+    let mut pending = Vec::new();
+    for (file, hash) in &files {
+        let (Some(installed), Some(latest)) = (installed.get(hash), latest.get(hash)) else {
+            if file_name.is_some() {
+                anyhow::bail!("{} isn't a Modrinth release for {} {}", file, server_type.as_str(), minecraft_version);
+            }
+            continue;
+        };
+        if installed.id == latest.id {
+            continue;
+        }
+        pending.push(PendingUpdate {
+            file_name: file.clone(),
+            installed_version: installed.version_number.clone(),
+            latest: latest.clone(),
+        });
+    }
+
+    // A new jar may only take the name of the jar it replaces
+    for update in &pending {
+        let new_name = primary_file_name(&update.latest)?;
+        if new_name != update.file_name && addons_dir.join(new_name).exists() {
+            anyhow::bail!("Can't update {}: {} already exists", update.file_name, new_name);
+        }
+    }
+
+    let mut artifacts = Vec::new();
+    for (i, update) in pending.iter().enumerate() {
+        progress.check_cancelled()?;
+        progress.fraction(i as u64, pending.len() as u64);
+        progress.log(format!(
+            "Downloading {} {} {}",
+            kind.project_type(),
+            update.latest.project_id,
+            update.latest.version_number
+        ));
+        let file = update.latest.primary_file().context("Release has no files")?;
+        artifacts.push(cache_addon_file(upstreams, cache, &update.latest.id, file).await?);
+    }
+    progress.fraction(pending.len() as u64, pending.len() as u64);
+
+    // Jars swapped in before a failure still need their rollback entries
+    let mut rollbacks = read_updates(&addons_dir).await?;
+    let applied = swap_in(cache, &addons_dir, pending, artifacts, &mut rollbacks, progress).await;
+    write_updates(&addons_dir, &rollbacks).await?;
+
+    applied
+}
+
+/// Replace each jar with its downloaded update, recording the jar it replaced
+async fn swap_in(
+    cache: &ArtifactCache,
+    addons_dir: &Path,
+    pending: Vec<PendingUpdate>,
+    artifacts: Vec<CachedArtifact>,
+    rollbacks: &mut HashMap<String, String>,
+    progress: &Progress,
+) -> Result<Vec<AppliedAddonUpdate>> {
+    let mut applied = Vec::new();
+    for (update, artifact) in pending.into_iter().zip(artifacts) {
+        let new_name = primary_file_name(&update.latest)?.to_string();
+        let current = addons_dir.join(&update.file_name);
+        let previous = addons_dir.join(previous_name(&update.file_name));
+
+        // Only the jar directly before an update is kept
+        if let Some(older) = rollbacks.remove(&update.file_name) {
+            let _ = fs::remove_file(addons_dir.join(previous_name(&older))).await;
+        }
+        fs::rename(&current, &previous)
+            .await
+            .with_context(|| format!("Failed to keep {}", update.file_name))?;
+        if let Err(e) = cache.copy_into(&artifact, &addons_dir.join(&new_name)).await {
+            let _ = fs::rename(&previous, &current).await;
+            return Err(e);
+        }
+        rollbacks.insert(new_name.clone(), update.file_name.clone());
+        progress.log(format!("Updated {} to {}", update.file_name, new_name));
+
+        applied.push(AppliedAddonUpdate {
+            project_id: update.latest.project_id.clone(),
+            previous_file: update.file_name,
+            previous_version: update.installed_version,
+            file_name: new_name,
+            version: update.latest.version_number.clone(),
+        });
+    }
+    Ok(applied)
+}
+
+/// Put back the jar an update to `file_name` replaced
+pub async fn rollback_addon_update(server_dir: &Path, server_type: ServerType, file_name: &str) -> Result<()> {
+    let addons_dir = server_dir.join(provider(server_type).addon_kind().dir());
+    let mut rollbacks = read_updates(&addons_dir).await?;
+    let previous_file = rollbacks
+        .get(file_name)
+        .with_context(|| format!("{} wasn't updated, so there is nothing to roll back to", file_name))?
+        .clone();
+    let previous = addons_dir.join(previous_name(&previous_file));
+    if !previous.exists() {
+        anyhow::bail!("The jar {} replaced is gone", file_name);
+    }
+
+    let current = addons_dir.join(file_name);
+    if current.exists() {
+        fs::remove_file(&current)
+            .await
+            .with_context(|| format!("Failed to remove {}", file_name))?;
+    }
+    fs::rename(&previous, addons_dir.join(&previous_file))
+        .await
+        .with_context(|| format!("Failed to restore {}", previous_file))?;
+
+    rollbacks.remove(file_name);
+    write_updates(&addons_dir, &rollbacks).await
+}
+
+type Lookup = (HashMap<String, ModrinthFileVersion>, HashMap<String, AddonVersion>);
+
+/// The installed release and the newest release for the server of each
+/// hashed jar
+async fn lookup(
+    upstreams: &Upstreams,
+    files: &[(String, String)],
+    server_type: ServerType,
+    minecraft_version: &str,
+) -> Result<Lookup> {
+    let loaders = provider(server_type).plugin_loaders();
+    if loaders.is_empty() {
+        anyhow::bail!("{} servers don't support plugins or mods", server_type.as_str());
+    }
+
+    let installed = versions_by_hash(upstreams, files).await?;
+    let mut latest: HashMap<String, AddonVersion> =
+        latest_versions_by_hash(upstreams, files, loaders, minecraft_version).await?;
+    // Proxy plugins follow the proxy's API rather than Minecraft versions
+    if !server_type.is_proxy() {
+        latest.retain(|_, v| v.game_versions.iter().any(|gv| gv == minecraft_version));
+    }
+    Ok((installed, latest))
+}
+
+/// The name the new jar is installed under, checked since it comes from
+/// upstream
+fn primary_file_name(version: &AddonVersion) -> Result<&str> {
+    let name = version
+        .primary_file()
+        .map(|f| f.filename.as_str())
+        .with_context(|| format!("No files available for {} {}", version.project_id, version.version_number))?;
+    check_addon_file_name(name)?;
+    Ok(name)
+}
+
+fn previous_name(file_name: &str) -> String {
+    format!("{}.previous", file_name)
+}
+
+async fn read_updates(addons_dir: &Path) -> Result<HashMap<String, String>> {
+    match fs::read(addons_dir.join(UPDATES_FILE)).await {
+        Ok(data) => serde_json::from_slice(&data).context("Failed to parse add-on update history"),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(e).context("Failed to read add-on update history"),
+    }
+}
+
+async fn write_updates(addons_dir: &Path, rollbacks: &HashMap<String, String>) -> Result<()> {
+    fs::write(addons_dir.join(UPDATES_FILE), serde_json::to_vec_pretty(rollbacks)?)
+        .await
+        .context("Failed to save add-on update history")
+}
@@ -0,0 +1,310 @@
+use crate::downloader::sha256_file;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::sync::{Mutex, MutexGuard};
+
+const INDEX_FILE: &str = "index.json";
+
+fn modified_millis(metadata: &std::fs::Metadata) -> Option<i64> {
+    let modified = metadata.modified().ok()?;
+    let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(since_epoch.as_millis() as i64)
+}
+
+/// A file in the artifact cache and the name it was stored under
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedArtifact {
+    /// Lookup key, e.g. `paper/1.21.1/130` or `modrinth/<sha512>`
+    pub key: String,
+    pub sha256: String,
+    pub size: u64,
+    /// Original file name, e.g. `paper-1.21.1-130.jar`
+    pub file_name: String,
+    pub created_at: i64,
+    pub last_used_at: i64,
+    /// Modification time of the object when it was stored, in milliseconds
+    #[serde(default)]
+    pub modified_at: Option<i64>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct CacheGcReport {
+    pub removed: Vec<CachedArtifact>,
+    pub freed_bytes: u64,
+}
+
+/// Content-addressed store for server jars, BuildTools output and plugins,
+/// shared by all servers. Objects live under `objects/<sha256>` and are
+/// copied into server directories, reflinked where the filesystem supports
+/// it, so nothing a server does to its files can reach the shared object.
+pub struct ArtifactCache {
+    root: PathBuf,
+    /// Serializes index reads and writes
+    index: Mutex<()>,
+    /// BuildTools runs share a work directory, so only one runs at a time
+    build: Mutex<()>,
+}
+
+impl ArtifactCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            index: Mutex::new(()),
+            build: Mutex::new(()),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn object_path(&self, sha256: &str) -> PathBuf {
+        self.root.join("objects").join(sha256)
+    }
+
+    /// A fresh path to download into before calling [`ArtifactCache::insert`]
+    pub(crate) async fn temp_path(&self, file_name: &str) -> Result<PathBuf> {
+        let dir = self.root.join("tmp");
+        fs::create_dir_all(&dir).await.context("Failed to create cache directory")?;
+        Ok(dir.join(format!("{}-{}", uuid::Uuid::new_v4(), file_name)))
+    }
+
+    /// Work directory for BuildTools, held for as long as the guard lives
+    pub(crate) async fn build_dir(&self) -> Result<(PathBuf, MutexGuard<'_, ()>)> {
+        let guard = self.build.lock().await;
+        let dir = self.root.join("build");
+        fs::create_dir_all(&dir).await.context("Failed to create build directory")?;
+        Ok((dir, guard))
+    }
+
+    async fn load_index(&self) -> Result<HashMap<String, CachedArtifact>> {
+        match fs::read(self.root.join(INDEX_FILE)).await {
+            Ok(data) => serde_json::from_slice(&data).context("Failed to parse cache index"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e).context("Failed to read cache index"),
+        }
+    }
+
+    async fn save_index(&self, index: &HashMap<String, CachedArtifact>) -> Result<()> {
+        fs::create_dir_all(&self.root).await?;
+        let tmp = self.root.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&tmp, serde_json::to_vec_pretty(index)?)
+            .await
+            .context("Failed to write cache index")?;
+        fs::rename(&tmp, self.root.join(INDEX_FILE))
+            .await
+            .context("Failed to write cache index")?;
+        Ok(())
+    }
+
+    /// Look up an artifact by key, forgetting it if its object has gone or
+    /// no longer has the content it was stored with
+    pub async fn get(&self, key: &str) -> Result<Option<CachedArtifact>> {
+        let _lock = self.index.lock().await;
+        let mut index = self.load_index().await?;
+
+        let Some(artifact) = index.get(key).cloned() else {
+            return Ok(None);
+        };
+        self.verified(&mut index, artifact).await
+    }
+
+    /// Look up any artifact with the given content
+    pub async fn get_by_hash(&self, sha256: &str) -> Result<Option<CachedArtifact>> {
+        let _lock = self.index.lock().await;
+        let mut index = self.load_index().await?;
+
+        let Some(artifact) = index.values().find(|a| a.sha256.eq_ignore_ascii_case(sha256)).cloned() else {
+            return Ok(None);
+        };
+        self.verified(&mut index, artifact).await
+    }
+
+    /// The most recently added artifact whose key starts with `prefix`
+    pub async fn latest(&self, prefix: &str) -> Result<Option<CachedArtifact>> {
+        let _lock = self.index.lock().await;
+        let mut index = self.load_index().await?;
+
+        let Some(artifact) = index
+            .values()
+            .filter(|a| a.key.starts_with(prefix))
+            .filter(|a| self.object_path(&a.sha256).exists())
+            .max_by_key(|a| a.created_at)
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        self.verified(&mut index, artifact).await
+    }
+
+    /// `artifact` if its object is still intact, judged by its size and
+    /// modification time since it was hashed when stored. A missing or
+    /// changed object is dropped with every key pointing at it, so callers
+    /// fetch it afresh rather than hand out bad bytes. Called with the index
+    /// lock held.
+    async fn verified(
+        &self,
+        index: &mut HashMap<String, CachedArtifact>,
+        artifact: CachedArtifact,
+    ) -> Result<Option<CachedArtifact>> {
+        let object = self.object_path(&artifact.sha256);
+        let intact = match fs::metadata(&object).await {
+            Ok(metadata) => {
+                let modified = modified_millis(&metadata);
+                metadata.len() == artifact.size
+                    && artifact.modified_at.is_none_or(|m| modified == Some(m))
+            }
+            Err(_) => false,
+        };
+        if intact {
+            return Ok(Some(artifact));
+        }
+
+        if object.exists() {
+            tracing::warn!("Cached {} changed since it was stored, discarding it", artifact.key);
+            let _ = fs::remove_file(&object).await;
+        }
+        index.retain(|_, a| a.sha256 != artifact.sha256);
+        self.save_index(index).await?;
+        Ok(None)
+    }
+
+    pub async fn list(&self) -> Result<Vec<CachedArtifact>> {
+        let _lock = self.index.lock().await;
+        let mut artifacts: Vec<_> = self.load_index().await?.into_values().collect();
+        artifacts.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(artifacts)
+    }
+
+    /// Move a downloaded or built file into the cache under `key`
+    pub(crate) async fn insert(&self, key: &str, path: &Path, file_name: &str) -> Result<CachedArtifact> {
+        let sha256 = sha256_file(path).await?;
+        let size = fs::metadata(path).await?.len();
+
+        // Held from placing the object so garbage collection can't see it unindexed
+        let _lock = self.index.lock().await;
+
+        let object = self.object_path(&sha256);
+        fs::create_dir_all(object.parent().unwrap()).await?;
+        if object.exists() {
+            let _ = fs::remove_file(path).await;
+        } else if fs::rename(path, &object).await.is_err() {
+            // The file may live on another filesystem, e.g. a BuildTools output
+            fs::copy(path, &object).await.context("Failed to store artifact")?;
+            let _ = fs::remove_file(path).await;
+        }
+        let modified_at = fs::metadata(&object).await.ok().and_then(|m| modified_millis(&m));
+
+        let now = chrono::Utc::now().timestamp();
+        let artifact = CachedArtifact {
+            key: key.to_string(),
+            sha256,
+            size,
+            file_name: file_name.to_string(),
+            created_at: now,
+            last_used_at: now,
+            modified_at,
+        };
+
+        let mut index = self.load_index().await?;
+        index.insert(key.to_string(), artifact.clone());
+        self.save_index(&index).await?;
+
+        tracing::info!("Cached {} ({})", key, artifact.sha256);
+        Ok(artifact)
+    }
+
+    /// Place a copy of an artifact at `destination`. The copy shares the
+    /// object's blocks on filesystems that reflink, e.g. Btrfs and XFS, but
+    /// never its inode: servers write their files in place, and plugin
+    /// loaders copy updates over installed jars.
+    pub async fn copy_into(&self, artifact: &CachedArtifact, destination: &Path) -> Result<()> {
+        // Held throughout so garbage collection can't remove the object first
+        let _lock = self.index.lock().await;
+        let object = self.object_path(&artifact.sha256);
+
+        // The file may be an older install's hardlink to the object, which
+        // writing through would corrupt
+        match fs::remove_file(destination).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).context("Failed to replace existing file"),
+        }
+        // `copy_file_range` under the hood, which reflinks where it can
+        fs::copy(&object, destination)
+            .await
+            .context("Failed to copy artifact from cache")?;
+
+        let mut index = self.load_index().await?;
+        if let Some(entry) = index.get_mut(&artifact.key) {
+            entry.last_used_at = chrono::Utc::now().timestamp();
+            self.save_index(&index).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove artifacts that haven't been used since `unused_since`, plus
+    /// objects nothing refers to. Servers hold their own copies, so none
+    /// depends on an object staying.
+    pub async fn gc(&self, unused_since: i64) -> Result<CacheGcReport> {
+        let _lock = self.index.lock().await;
+        let mut index = self.load_index().await?;
+        let mut report = CacheGcReport::default();
+
+        let stale: Vec<String> = index
+            .values()
+            .filter(|a| a.last_used_at < unused_since)
+            .map(|a| a.key.clone())
+            .collect();
+        for key in stale {
+            if let Some(artifact) = index.remove(&key) {
+                report.removed.push(artifact);
+            }
+        }
+        self.save_index(&index).await?;
+
+        // Delete objects no remaining key points at
+        let objects_dir = self.root.join("objects");
+        if objects_dir.exists() {
+            let mut entries = fs::read_dir(&objects_dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if index.values().any(|a| a.sha256 == name) {
+                    continue;
+                }
+                let size = entry.metadata().await.map(|m| m.len()).unwrap_or(0);
+                if fs::remove_file(entry.path()).await.is_ok() {
+                    report.freed_bytes += size;
+                }
+            }
+        }
+
+        // Leftovers of interrupted downloads; younger ones may still be running
+        let tmp_cutoff = unused_since.min(chrono::Utc::now().timestamp() - 86400);
+        let tmp_dir = self.root.join("tmp");
+        if tmp_dir.exists() {
+            let mut entries = fs::read_dir(&tmp_dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let modified = entry
+                    .metadata()
+                    .await
+                    .ok()
+                    .and_then(|m| m.modified().ok())
+                    .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp())
+                    .unwrap_or(0);
+                if modified < tmp_cutoff {
+                    let size = entry.metadata().await.map(|m| m.len()).unwrap_or(0);
+                    if fs::remove_file(entry.path()).await.is_ok() {
+                        report.freed_bytes += size;
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
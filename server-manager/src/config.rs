@@ -34,7 +34,7 @@ pub async fn write_server_properties(
     let mut lines = Vec::new();
     
     lines.push("#Minecraft server properties".to_string());
-    lines.push(format!("#Generated by Minecraft Server Manager"));
+    lines.push("#Generated by Minecraft Server Manager".to_string());
     lines.push(String::new());
 
     // Sort keys for consistent output
@@ -112,3 +112,24 @@ pub async fn initialize_server_properties(
     let properties = get_default_properties(port, max_players);
     write_server_properties(&properties_path, &properties).await
 }
+
+/// Bedrock ships its own server.properties with different keys, so keep it
+/// and only set the ports and player limit. Bedrock also listens on IPv6,
+/// on the next port up.
+pub async fn initialize_bedrock_properties(
+    server_dir: &Path,
+    port: u16,
+    max_players: u32,
+) -> Result<()> {
+    let properties_path = server_dir.join("server.properties");
+    let mut properties = if properties_path.exists() {
+        read_server_properties(&properties_path).await?
+    } else {
+        HashMap::new()
+    };
+
+    properties.insert("server-port".to_string(), port.to_string());
+    properties.insert("server-portv6".to_string(), port.wrapping_add(1).to_string());
+    properties.insert("max-players".to_string(), max_players.to_string());
+    write_server_properties(&properties_path, &properties).await
+}
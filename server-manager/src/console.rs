@@ -0,0 +1,367 @@
+use crate::types::ServerType;
+use anyhow::Result;
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+            LogLevel::Fatal => "fatal",
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "TRACE" | "FINEST" | "FINER" => Ok(LogLevel::Trace),
+            "DEBUG" | "FINE" | "CONFIG" => Ok(LogLevel::Debug),
+            "INFO" => Ok(LogLevel::Info),
+            "WARN" | "WARNING" => Ok(LogLevel::Warn),
+            "ERROR" | "SEVERE" => Ok(LogLevel::Error),
+            "FATAL" => Ok(LogLevel::Fatal),
+            _ => anyhow::bail!("Unknown log level '{}'", s),
+        }
+    }
+}
+
+/// One line of server output, parsed into its log4j fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    /// Unix time the line was received
+    pub timestamp: i64,
+    pub level: LogLevel,
+    pub thread: Option<String>,
+    /// Plugin, mod or logger name, e.g. "Essentials"
+    pub source: Option<String>,
+    pub message: String,
+    /// The line as printed, without colour codes
+    pub raw: String,
+    pub stderr: bool,
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.stderr {
+            write!(f, "[ERROR] {}", self.raw)
+        } else {
+            f.write_str(&self.raw)
+        }
+    }
+}
+
+/// A command written to the server's stdin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsoleCommand {
+    pub timestamp: i64,
+    pub command: String,
+    /// Who sent it; `None` for commands issued by the manager itself
+    pub user: Option<String>,
+}
+
+/// Fields taken from the `[time level]` header of a line
+#[derive(Debug, Clone)]
+pub struct ParsedLine {
+    pub time: Option<NaiveTime>,
+    pub level: LogLevel,
+    pub thread: Option<String>,
+    pub source: Option<String>,
+    pub message: String,
+}
+
+/// Parses consecutive lines of one output stream. Lines without a header,
+/// such as stack traces, inherit the level, thread and source of the line
+/// before them.
+pub struct LogParser {
+    fallback: LogLevel,
+    last: Option<(LogLevel, Option<String>, Option<String>)>,
+}
+
+impl LogParser {
+    /// `fallback` is the level of headerless lines at the start of the stream
+    pub fn new(fallback: LogLevel) -> Self {
+        Self {
+            fallback,
+            last: None,
+        }
+    }
+
+    pub fn parse(&mut self, line: &str) -> ParsedLine {
+        if let Some(parsed) = parse_log_header(line) {
+            self.last = Some((parsed.level, parsed.thread.clone(), parsed.source.clone()));
+            return parsed;
+        }
+
+        let (level, thread, source) = self
+            .last
+            .clone()
+            .unwrap_or((self.fallback, None, None));
+
+        ParsedLine {
+            time: None,
+            level,
+            thread,
+            source,
+            message: line.to_string(),
+        }
+    }
+
+    /// Parse a line read from the live console
+    pub fn parse_console(&mut self, raw: &str, stderr: bool) -> LogLine {
+        let raw = strip_ansi(raw);
+        let parsed = self.parse(&raw);
+
+        LogLine {
+            timestamp: chrono::Utc::now().timestamp(),
+            level: parsed.level,
+            thread: parsed.thread,
+            source: parsed.source,
+            message: parsed.message,
+            raw,
+            stderr,
+        }
+    }
+}
+
+/// Remove ANSI colour sequences some server software prints even without a TTY
+pub fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            // Parameters and intermediates, then one final byte in @..~
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+
+    out
+}
+
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(text, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(text, "%H:%M:%S%.f"))
+        .ok()
+}
+
+/// Split "[inner] rest" into its parts
+fn bracketed(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix('[')?;
+    let end = rest.find(']')?;
+    Some((&rest[..end], &rest[end + 1..]))
+}
+
+/// Parse the log4j header of a line. Handles the console format used by
+/// Paper and Spigot, `[12:34:56 INFO]: message`, and the file format used by
+/// vanilla, Fabric and Forge, `[12:34:56] [Server thread/WARN] [source]: message`.
+/// Bedrock's `[2024-05-01 12:34:56:789 INFO] message` is handled too.
+pub fn parse_log_header(line: &str) -> Option<ParsedLine> {
+    let (first, mut rest) = bracketed(line)?;
+
+    if let Some(parsed) = parse_bedrock_header(first, rest) {
+        return Some(parsed);
+    }
+
+    let (time, level, thread) = match first.split_once(' ') {
+        Some((time, level)) => (parse_time(time)?, level.parse().ok()?, None),
+        None => {
+            let time = parse_time(first)?;
+            let (thread_level, after) = bracketed(rest.strip_prefix(' ')?)?;
+            rest = after;
+            let (thread, level) = thread_level.rsplit_once('/')?;
+            (time, level.parse().ok()?, Some(thread.to_string()))
+        }
+    };
+
+    // Forge and some proxies name the logger in the header
+    let mut source = None;
+    if let Some((logger, after)) = rest.strip_prefix(' ').and_then(bracketed) {
+        source = Some(logger.to_string());
+        rest = after;
+    }
+
+    let mut message = rest.strip_prefix(':')?.trim_start().to_string();
+
+    // Bukkit plugin loggers prefix messages with "[PluginName] "
+    if source.is_none() {
+        if let Some((name, after)) = bracketed(&message) {
+            if is_source_name(name) && after.starts_with(' ') {
+                source = Some(name.to_string());
+                message = after[1..].to_string();
+            }
+        }
+    }
+
+    Some(ParsedLine {
+        time: Some(time),
+        level,
+        thread,
+        source,
+        message,
+    })
+}
+
+fn parse_bedrock_header(first: &str, rest: &str) -> Option<ParsedLine> {
+    let mut parts = first.split(' ');
+    let (_date, time, level) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(ParsedLine {
+        time: Some(NaiveTime::parse_from_str(time, "%H:%M:%S:%3f").ok()?),
+        level: level.parse().ok()?,
+        thread: None,
+        source: None,
+        message: rest.trim_start().to_string(),
+    })
+}
+
+fn is_source_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Something a server announces on its console that the manager tracks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleEvent {
+    /// Startup has finished and players can join
+    Ready,
+    PlayerJoined(String),
+    PlayerLeft(String),
+}
+
+/// Recognise readiness and players joining or leaving in a parsed console
+/// message. Proxies only report readiness.
+pub fn parse_console_event(server_type: ServerType, message: &str) -> Option<ConsoleEvent> {
+    if server_type.is_bedrock() {
+        if message == "Server started." {
+            return Some(ConsoleEvent::Ready);
+        }
+        // "Player connected: Some Name, xuid: 2535..."
+        let player = |prefix: &str| {
+            let rest = message.strip_prefix(prefix)?;
+            Some(rest.split_once(", xuid:").map_or(rest, |(name, _)| name).to_string())
+        };
+        if let Some(name) = player("Player connected: ") {
+            return Some(ConsoleEvent::PlayerJoined(name));
+        }
+        if let Some(name) = player("Player disconnected: ") {
+            return Some(ConsoleEvent::PlayerLeft(name));
+        }
+        return None;
+    }
+
+    // "Done (12.345s)! For help, type "help"", or Velocity's "Done (1.23s)!"
+    if message.starts_with("Done (") && message.contains(")!") {
+        return Some(ConsoleEvent::Ready);
+    }
+    if server_type.is_proxy() {
+        return message.starts_with("Listening on /").then_some(ConsoleEvent::Ready);
+    }
+
+    // Chat shows up as "<Name> ...", so a bare name is the server speaking
+    let player = |suffix: &str| {
+        let name = message.strip_suffix(suffix)?;
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        valid.then(|| name.to_string())
+    };
+    if let Some(name) = player(" joined the game") {
+        return Some(ConsoleEvent::PlayerJoined(name));
+    }
+    player(" left the game").map(ConsoleEvent::PlayerLeft)
+}
+
+/// Level, thread and source criteria shared by the console and log search
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Only these levels; empty means any
+    pub levels: Vec<LogLevel>,
+    pub min_level: Option<LogLevel>,
+    /// Source name, compared case-insensitively
+    pub source: Option<String>,
+    /// Substring of the thread name, compared case-insensitively
+    pub thread: Option<String>,
+}
+
+impl LogFilter {
+    /// Build a filter from a comma-separated level list and the other criteria
+    pub fn parse(
+        levels: Option<&str>,
+        min_level: Option<&str>,
+        source: Option<String>,
+        thread: Option<String>,
+    ) -> Result<Self> {
+        let levels = levels
+            .map(|l| {
+                l.split(',')
+                    .filter(|s| !s.trim().is_empty())
+                    .map(str::parse)
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let min_level = min_level.map(str::parse).transpose()?;
+
+        Ok(Self {
+            levels,
+            min_level,
+            source,
+            thread,
+        })
+    }
+
+    pub fn matches(&self, level: LogLevel, thread: Option<&str>, source: Option<&str>) -> bool {
+        if !self.levels.is_empty() && !self.levels.contains(&level) {
+            return false;
+        }
+        if self.min_level.is_some_and(|min| level < min) {
+            return false;
+        }
+        if let Some(wanted) = &self.source {
+            if !source.is_some_and(|s| s.eq_ignore_ascii_case(wanted)) {
+                return false;
+            }
+        }
+        if let Some(wanted) = &self.thread {
+            let wanted = wanted.to_lowercase();
+            if !thread.is_some_and(|t| t.to_lowercase().contains(&wanted)) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn matches_line(&self, line: &LogLine) -> bool {
+        self.matches(line.level, line.thread.as_deref(), line.source.as_deref())
+    }
+}
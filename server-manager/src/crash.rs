@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Package prefixes belonging to the JVM or the server itself, skipped when
+/// looking for the plugin or mod that caused a crash
+const PLATFORM_PACKAGES: &[&str] = &[
+    "java.",
+    "javax.",
+    "jdk.",
+    "sun.",
+    "com.sun.",
+    "net.minecraft.",
+    "com.mojang.",
+    "org.bukkit.",
+    "org.spigotmc.",
+    "io.papermc.",
+    "com.destroystokyo.",
+    "co.aikar.",
+    "net.md_5.",
+    "it.unimi.",
+    "io.netty.",
+    "com.google.",
+    "org.apache.",
+    "org.slf4j.",
+    "net.fabricmc.",
+    "net.minecraftforge.",
+    "net.neoforged.",
+    "cpw.mods.",
+    "org.spongepowered.",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    /// `crash-reports/crash-*.txt` written by the server
+    CrashReport,
+    /// `hs_err_pid*.log` written by the JVM on a fatal error
+    JvmFatal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashSummary {
+    /// Path of the report relative to the server directory
+    pub file: String,
+    pub kind: CrashKind,
+    pub description: Option<String>,
+    pub exception: Option<String>,
+    pub culprit: Option<String>,
+    pub occurred_at: i64,
+}
+
+/// Find crash reports and JVM fatal error logs in a server directory
+pub async fn find_crash_files(server_dir: &Path) -> Result<Vec<(PathBuf, CrashKind)>> {
+    let mut files = Vec::new();
+
+    let reports_dir = server_dir.join("crash-reports");
+    if reports_dir.is_dir() {
+        let mut entries = fs::read_dir(&reports_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("txt") {
+                files.push((path, CrashKind::CrashReport));
+            }
+        }
+    }
+
+    let mut entries = fs::read_dir(server_dir)
+        .await
+        .context("Failed to read server directory")?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("hs_err_pid") && name.ends_with(".log") {
+            files.push((entry.path(), CrashKind::JvmFatal));
+        }
+    }
+
+    Ok(files)
+}
+
+pub async fn parse_crash_file(server_dir: &Path, path: &Path, kind: CrashKind) -> Result<CrashSummary> {
+    let bytes = fs::read(path)
+        .await
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    let content = String::from_utf8_lossy(&bytes);
+
+    let modified = fs::metadata(path)
+        .await?
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let file = path
+        .strip_prefix(server_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string();
+
+    let mut summary = match kind {
+        CrashKind::CrashReport => parse_crash_report(&content),
+        CrashKind::JvmFatal => parse_hs_err(&content),
+    };
+    summary.file = file;
+    if summary.occurred_at == 0 {
+        summary.occurred_at = modified;
+    }
+
+    Ok(summary)
+}
+
+fn parse_crash_report(content: &str) -> CrashSummary {
+    let mut description = None;
+    let mut exception = None;
+    let mut culprit = None;
+    let mut occurred_at = 0;
+
+    let mut lines = content.lines().peekable();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+
+        if let Some(time) = trimmed.strip_prefix("Time: ") {
+            occurred_at = parse_report_time(time).unwrap_or(0);
+        } else if let Some(desc) = trimmed.strip_prefix("Description: ") {
+            description = Some(desc.to_string());
+
+            // The exception follows the description after a blank line
+            while lines.peek().is_some_and(|l| l.trim().is_empty()) {
+                lines.next();
+            }
+            exception = lines.next().map(|l| l.trim().to_string());
+        } else if let Some(mods) = trimmed
+            .strip_prefix("Suspected Mods: ")
+            .or_else(|| trimmed.strip_prefix("Suspected Mod: "))
+        {
+            // Forge lists suspects inline or on the following lines
+            if culprit.is_none() && mods != "NONE" && !mods.is_empty() {
+                culprit = Some(mods.to_string());
+            } else if culprit.is_none() && mods.is_empty() {
+                culprit = lines.next().map(|l| l.trim().to_string());
+            }
+        } else if culprit.is_none() && exception.is_some() {
+            if let Some(frame) = trimmed.strip_prefix("at ") {
+                if !PLATFORM_PACKAGES.iter().any(|p| frame.starts_with(p)) {
+                    culprit = Some(frame_package(frame));
+                }
+            }
+        }
+    }
+
+    CrashSummary {
+        file: String::new(),
+        kind: CrashKind::CrashReport,
+        description,
+        exception,
+        culprit,
+        occurred_at,
+    }
+}
+
+fn parse_hs_err(content: &str) -> CrashSummary {
+    let mut exception = None;
+    let mut culprit = None;
+    let mut description = None;
+
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let body = line.trim_start_matches('#').trim();
+
+        if description.is_none() && body.starts_with("A fatal error has been detected") {
+            description = Some(body.trim_end_matches(':').to_string());
+        } else if exception.is_none()
+            && (body.starts_with("SIG")
+                || body.starts_with("EXCEPTION_")
+                || body.starts_with("Internal Error")
+                || body.starts_with("There is insufficient memory")
+                || body.starts_with("Out of Memory Error"))
+        {
+            exception = Some(body.to_string());
+        } else if body == "Problematic frame:" {
+            culprit = lines
+                .next()
+                .map(|l| l.trim_start_matches('#').trim().to_string());
+        }
+    }
+
+    CrashSummary {
+        file: String::new(),
+        kind: CrashKind::JvmFatal,
+        description,
+        exception,
+        culprit,
+        occurred_at: 0,
+    }
+}
+
+/// "com.example.plugin.Foo.bar(Foo.java:10)" -> "com.example.plugin"
+fn frame_package(frame: &str) -> String {
+    let method = frame.split('(').next().unwrap_or(frame);
+    let parts: Vec<&str> = method.split('.').collect();
+
+    // Drop the class and method names
+    if parts.len() > 2 {
+        parts[..parts.len() - 2].join(".")
+    } else {
+        method.to_string()
+    }
+}
+
+/// Crash reports use the server's local time, e.g. "2024-06-01 12:34:56"
+fn parse_report_time(time: &str) -> Option<i64> {
+    let naive = chrono::NaiveDateTime::parse_from_str(time.trim(), "%Y-%m-%d %H:%M:%S").ok()?;
+    naive
+        .and_local_timezone(chrono::Local)
+        .single()
+        .map(|t| t.timestamp())
+}
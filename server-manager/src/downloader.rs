@@ -1,192 +1,311 @@
+use crate::cache::ArtifactCache;
+use crate::progress::Progress;
+use crate::providers::{provider, ServerBuild};
+use crate::upstream::Upstreams;
 use crate::types::ServerType;
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use futures::StreamExt;
+use reqwest::StatusCode;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
 use std::path::Path;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::Duration;
 
-const PAPER_API_BASE: &str = "https://api.papermc.io/v2";
-const SPIGOT_BUILDTOOLS_URL: &str = "https://hub.spigotmc.org/jenkins/job/BuildTools/lastSuccessfulBuild/artifact/target/BuildTools.jar";
+/// Tries per download, each resuming where the last stopped
+const DOWNLOAD_ATTEMPTS: u32 = 3;
 
-#[derive(Debug, Deserialize)]
-struct PaperVersions {
-    versions: Vec<String>,
+pub async fn get_available_versions(upstreams: &Upstreams, server_type: ServerType) -> Result<Vec<String>> {
+    provider(server_type).list_versions(upstreams).await
 }
 
-#[derive(Debug, Deserialize)]
-struct PaperBuilds {
-    builds: Vec<u32>,
+pub async fn get_available_builds(
+    upstreams: &Upstreams,
+    server_type: ServerType,
+    version: &str,
+) -> Result<Vec<ServerBuild>> {
+    provider(server_type).list_builds(upstreams, version).await
+}
+
+/// Builds released after the one a server has installed
+#[derive(Debug, Clone, Default)]
+pub struct NewerBuilds {
+    /// Release channel of the installed build, if upstream still lists it
+    pub channel: Option<String>,
+    /// Oldest first
+    pub builds: Vec<ServerBuild>,
+}
+
+/// Builds of `version` released after the `installed` one. Servers on a
+/// stable channel are only offered builds from that channel. Empty for
+/// distributions whose builds can't be ordered.
+pub async fn get_newer_builds(
+    upstreams: &Upstreams,
+    server_type: ServerType,
+    version: &str,
+    installed: &str,
+) -> Result<NewerBuilds> {
+    let provider = provider(server_type);
+    if !provider.tracks_builds() {
+        return Ok(NewerBuilds::default());
+    }
+    let Ok(installed_number) = installed.parse::<u64>() else {
+        return Ok(NewerBuilds::default());
+    };
+
+    let builds = provider.list_builds(upstreams, version).await?;
+    let channel = builds.iter().find(|b| b.id == installed).and_then(|b| b.channel.clone());
+    let stable_channel = channel.clone().filter(|c| c != "experimental");
+
+    Ok(NewerBuilds {
+        channel,
+        builds: builds
+            .into_iter()
+            .filter(|b| b.id.parse::<u64>().is_ok_and(|n| n > installed_number))
+            .filter(|b| stable_channel.is_none() || b.channel == stable_channel)
+            .collect(),
+    })
+}
+
+/// Install a server jar, the latest build unless `build` pins one, and
+/// return the build installed
+pub async fn download_server_jar(
+    server_type: ServerType,
+    version: &str,
+    build: Option<&str>,
+    destination: &Path,
+    upstreams: &Upstreams,
+    cache: &ArtifactCache,
+    progress: &Progress,
+) -> Result<String> {
+    tracing::info!("Downloading {} {} to {:?}", server_type.as_str(), version, destination);
+
+    let provider = provider(server_type);
+    let artifact = match provider
+        .fetch(upstreams, cache, version, build, progress)
+        .await
+    {
+        Ok(artifact) => artifact,
+        // Offline: fall back to the newest build we already have. Bad
+        // checksums and failed builds are real errors and still surface.
+        Err(e) if build.is_none() && e.chain().any(|c| c.is::<reqwest::Error>()) => {
+            let prefix = format!("{}/{}/", server_type.as_str(), version);
+            let Some(cached) = cache.latest(&prefix).await? else {
+                return Err(e);
+            };
+            tracing::warn!("Upstream unavailable, using cached {}: {:#}", cached.key, e);
+            progress.log(format!("Upstream unavailable, using cached {}", cached.file_name));
+            cached
+        }
+        Err(e) => return Err(e),
+    };
+
+    provider.install(cache, &artifact, destination, progress).await?;
+    tracing::info!("Installed {}", artifact.file_name);
+
+    // Cached under `type/version/build`
+    let build = artifact.key.rsplit('/').next().unwrap_or_default();
+    Ok(build.to_string())
 }
 
-#[derive(Debug, Deserialize)]
-struct PaperBuildInfo {
-    downloads: PaperDownloads,
+/// Expected digest of a download, as lowercase hex
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checksum {
+    Md5(String),
+    Sha1(String),
+    Sha256(String),
+    Sha512(String),
 }
 
-#[derive(Debug, Deserialize)]
-struct PaperDownloads {
-    application: PaperApplication,
+impl Checksum {
+    fn hasher(&self) -> Hasher {
+        match self {
+            Checksum::Md5(_) => Hasher::Md5(Md5::new()),
+            Checksum::Sha1(_) => Hasher::Sha1(Sha1::new()),
+            Checksum::Sha256(_) => Hasher::Sha256(Sha256::new()),
+            Checksum::Sha512(_) => Hasher::Sha512(Sha512::new()),
+        }
+    }
+
+    fn expected(&self) -> &str {
+        match self {
+            Checksum::Md5(hex) | Checksum::Sha1(hex) | Checksum::Sha256(hex) | Checksum::Sha512(hex) => hex,
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct PaperApplication {
-    name: String,
+enum Hasher {
+    Md5(Md5),
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Sha512(Sha512),
 }
 
-pub async fn get_available_versions(server_type: ServerType) -> Result<Vec<String>> {
-    match server_type {
-        ServerType::Paper => {
-            let url = format!("{}/projects/paper", PAPER_API_BASE);
-            let response = reqwest::get(&url)
-                .await
-                .context("Failed to fetch Paper versions")?;
-            
-            let versions: PaperVersions = response
-                .json()
-                .await
-                .context("Failed to parse Paper versions")?;
-            
-            Ok(versions.versions)
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Sha1(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Sha512(h) => h.update(data),
         }
-        ServerType::Spigot => {
-            // For Spigot, we'll support common versions
-            // BuildTools can build any version, but we'll list popular ones
-            Ok(vec![
-                "1.21.1".to_string(),
-                "1.21".to_string(),
-                "1.20.6".to_string(),
-                "1.20.4".to_string(),
-                "1.20.1".to_string(),
-                "1.19.4".to_string(),
-            ])
+    }
+
+    fn finish(self) -> String {
+        match self {
+            Hasher::Md5(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha1(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha256(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha512(h) => format!("{:x}", h.finalize()),
         }
     }
 }
 
-pub async fn download_server_jar(
-    server_type: ServerType,
-    version: &str,
+/// Download `url` to `destination`, streaming into a `.part` file next to
+/// it. An interrupted transfer is resumed with a range request, and the file
+/// only replaces `destination` once it is complete and matches `checksum`.
+pub(crate) async fn download_file(
+    client: &reqwest::Client,
+    url: &str,
     destination: &Path,
+    checksum: Option<&Checksum>,
+    progress: &Progress,
 ) -> Result<()> {
-    match server_type {
-        ServerType::Paper => download_paper(version, destination).await,
-        ServerType::Spigot => download_spigot(version, destination).await,
+    let file_name = destination
+        .file_name()
+        .context("Invalid destination path")?
+        .to_string_lossy();
+    let part_path = destination.with_file_name(format!("{}.part", file_name));
+
+    let mut attempt = 1;
+    loop {
+        match fetch_to_part(client, url, &part_path, progress).await {
+            Ok(()) => break,
+            Err(e) if attempt < DOWNLOAD_ATTEMPTS && !progress.is_cancelled() && is_resumable(&e) => {
+                tracing::warn!("Download of {} interrupted, resuming: {:#}", url, e);
+                attempt += 1;
+                tokio::time::sleep(Duration::from_secs(attempt as u64)).await;
+            }
+            Err(e) => return Err(e),
+        }
     }
-}
 
-async fn download_paper(version: &str, destination: &Path) -> Result<()> {
-    tracing::info!("Downloading Paper {} to {:?}", version, destination);
+    if let Some(checksum) = checksum {
+        let actual = hash_file(&part_path, checksum.hasher()).await?;
+        if !actual.eq_ignore_ascii_case(checksum.expected()) {
+            // A resumed file that doesn't verify can't be trusted to resume again
+            let _ = fs::remove_file(&part_path).await;
+            anyhow::bail!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                url,
+                checksum.expected(),
+                actual
+            );
+        }
+    }
 
-    // Get latest build for version
-    let builds_url = format!("{}/projects/paper/versions/{}", PAPER_API_BASE, version);
-    let response = reqwest::get(&builds_url)
-        .await
-        .context("Failed to fetch Paper builds")?;
-    
-    let builds: PaperBuilds = response
-        .json()
-        .await
-        .context("Failed to parse Paper builds")?;
-    
-    let latest_build = builds.builds.last()
-        .context("No builds available for this version")?;
-
-    // Get build info
-    let build_url = format!(
-        "{}/projects/paper/versions/{}/builds/{}",
-        PAPER_API_BASE, version, latest_build
-    );
-    let response = reqwest::get(&build_url)
-        .await
-        .context("Failed to fetch build info")?;
-    
-    let build_info: PaperBuildInfo = response
-        .json()
+    fs::rename(&part_path, destination)
         .await
-        .context("Failed to parse build info")?;
+        .context("Failed to move download into place")?;
 
-    // Download JAR
-    let jar_url = format!(
-        "{}/projects/paper/versions/{}/builds/{}/downloads/{}",
-        PAPER_API_BASE, version, latest_build, build_info.downloads.application.name
-    );
-
-    download_file(&jar_url, destination).await?;
-    
-    tracing::info!("Successfully downloaded Paper {}", version);
     Ok(())
 }
 
-async fn download_spigot(version: &str, destination: &Path) -> Result<()> {
-    tracing::info!("Downloading Spigot {} to {:?}", version, destination);
-    
-    // For Spigot, we need to download BuildTools and run it
-    // This is a simplified version - in production, you'd want to cache BuildTools
-    let build_dir = destination.parent()
-        .context("Invalid destination path")?
-        .join("build");
-    
-    fs::create_dir_all(&build_dir).await?;
-    
-    let buildtools_path = build_dir.join("BuildTools.jar");
-    
-    // Download BuildTools if not exists
-    if !buildtools_path.exists() {
-        tracing::info!("Downloading Spigot BuildTools...");
-        download_file(SPIGOT_BUILDTOOLS_URL, &buildtools_path).await?;
+/// Fetch the rest of `url` into `part_path`, continuing after whatever an
+/// earlier attempt left there
+async fn fetch_to_part(client: &reqwest::Client, url: &str, part_path: &Path, progress: &Progress) -> Result<()> {
+    let existing = fs::metadata(part_path).await.map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
     }
+    let response = request.send().await?;
 
-    // Run BuildTools
-    tracing::info!("Building Spigot {} (this may take a while)...", version);
-    let output = tokio::process::Command::new("java")
-        .arg("-jar")
-        .arg(&buildtools_path)
-        .arg("--rev")
-        .arg(version)
-        .current_dir(&build_dir)
-        .output()
-        .await
-        .context("Failed to run BuildTools")?;
+    let resumed = response.status() == StatusCode::PARTIAL_CONTENT;
+    if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file is stale or already complete; start over
+        fs::remove_file(part_path).await.context("Failed to remove partial download")?;
+        return Box::pin(fetch_to_part(client, url, part_path, progress)).await;
+    }
+    if !response.status().is_success() {
+        anyhow::bail!("Download failed with status: {}", response.status());
+    }
+
+    let mut done = if resumed { existing } else { 0 };
+    let total = response.content_length().map(|len| len + done).unwrap_or(0);
 
-    if !output.status.success() {
-        anyhow::bail!(
-            "BuildTools failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        );
+    let mut file = if resumed {
+        tracing::info!("Resuming download of {} at {} bytes", url, existing);
+        fs::OpenOptions::new().append(true).open(part_path).await
+    } else {
+        fs::File::create(part_path).await
     }
+    .context("Failed to create destination file")?;
+
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        progress.check_cancelled()?;
+        let chunk = chunk?;
+        file.write_all(&chunk)
+            .await
+            .context("Failed to write file")?;
+
+        done += chunk.len() as u64;
+        progress.fraction(done, total);
+    }
+
+    file.flush().await.context("Failed to write file")?;
 
-    // Find the built JAR and move it to destination
-    let spigot_jar = build_dir.join(format!("spigot-{}.jar", version));
-    if !spigot_jar.exists() {
-        anyhow::bail!("Built JAR not found at {:?}", spigot_jar);
+    if total > 0 && done < total {
+        return Err(EndedEarly { done, total }.into());
     }
 
-    fs::copy(&spigot_jar, destination).await?;
-    
-    tracing::info!("Successfully built Spigot {}", version);
     Ok(())
 }
 
-async fn download_file(url: &str, destination: &Path) -> Result<()> {
-    let response = reqwest::get(url)
-        .await
-        .context("Failed to download file")?;
+/// A body shorter than its announced length; what did arrive stays in the
+/// part file to resume from
+#[derive(Debug)]
+struct EndedEarly {
+    done: u64,
+    total: u64,
+}
 
-    if !response.status().is_success() {
-        anyhow::bail!("Download failed with status: {}", response.status());
+impl std::fmt::Display for EndedEarly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Download ended early: got {} of {} bytes", self.done, self.total)
     }
+}
 
-    let bytes = response.bytes()
-        .await
-        .context("Failed to read response bytes")?;
+impl std::error::Error for EndedEarly {}
 
-    let mut file = fs::File::create(destination)
-        .await
-        .context("Failed to create destination file")?;
+/// Whether a failed fetch can pick up where it stopped
+fn is_resumable(e: &anyhow::Error) -> bool {
+    e.is::<reqwest::Error>() || e.is::<EndedEarly>()
+}
 
-    file.write_all(&bytes)
-        .await
-        .context("Failed to write file")?;
+pub(crate) async fn sha256_file(path: &Path) -> Result<String> {
+    hash_file(path, Hasher::Sha256(Sha256::new())).await
+}
 
-    Ok(())
+pub(crate) async fn sha512_file(path: &Path) -> Result<String> {
+    hash_file(path, Hasher::Sha512(Sha512::new())).await
+}
+
+async fn hash_file(path: &Path, mut hasher: Hasher) -> Result<String> {
+    let mut file = fs::File::open(path).await.context("Failed to open download")?;
+    let mut buf = vec![0u8; 64 * 1024];
+
+    loop {
+        let n = file.read(&mut buf).await.context("Failed to read download")?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(hasher.finish())
 }
@@ -0,0 +1,25 @@
+use anyhow::{Context, Result};
+
+/// Parse a duration like "10m", "30s", "1h" or a bare number of seconds
+pub fn parse_duration(text: &str) -> Result<u64> {
+    let text = text.trim();
+    let (number, unit) = match text.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => text.split_at(idx),
+        None => (text, "s"),
+    };
+
+    let value: u64 = number
+        .parse()
+        .with_context(|| format!("Invalid duration '{}'", text))?;
+
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => anyhow::bail!("Invalid duration unit in '{}'", text),
+    };
+
+    value
+        .checked_mul(multiplier)
+        .with_context(|| format!("Duration '{}' is too long", text))
+}
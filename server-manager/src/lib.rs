@@ -4,7 +4,23 @@ pub mod process;
 pub mod config;
 pub mod world;
 pub mod plugins;
+pub mod plugin_meta;
+pub mod addon_updates;
 pub mod monitor;
+pub mod players;
+pub mod watchdog;
+pub mod crash;
+pub mod logs;
+pub mod console;
+pub mod duration;
+pub mod rate;
+pub mod progress;
+pub mod cache;
+pub mod upstream;
+pub mod providers;
+pub mod network;
+pub mod modpack;
+pub mod server_jar;
 
 pub use types::*;
 pub use downloader::*;
@@ -12,4 +28,20 @@ pub use process::*;
 pub use config::*;
 pub use world::*;
 pub use plugins::*;
+pub use plugin_meta::*;
+pub use addon_updates::*;
 pub use monitor::*;
+pub use players::*;
+pub use watchdog::*;
+pub use crash::*;
+pub use logs::*;
+pub use console::*;
+pub use duration::*;
+pub use rate::*;
+pub use progress::*;
+pub use cache::*;
+pub use upstream::*;
+pub use providers::*;
+pub use network::*;
+pub use modpack::*;
+pub use server_jar::*;
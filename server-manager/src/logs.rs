@@ -0,0 +1,430 @@
+use crate::console::{parse_log_header, LogFilter, LogLevel, LogParser};
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
+use flate2::read::GzDecoder;
+use regex::Regex;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogFileInfo {
+    pub name: String,
+    pub size: u64,
+    pub last_modified: u64,
+    pub compressed: bool,
+    /// Day the log covers, from the archive name or the file's modification time
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogPage {
+    pub lines: Vec<String>,
+    pub offset: usize,
+    pub next_offset: usize,
+    pub eof: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogMatch {
+    pub file: String,
+    pub line_number: usize,
+    pub line: String,
+    pub timestamp: Option<i64>,
+    pub level: LogLevel,
+    pub thread: Option<String>,
+    pub source: Option<String>,
+}
+
+pub struct LogSearch {
+    pub pattern: Option<Regex>,
+    pub filter: LogFilter,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub limit: usize,
+}
+
+impl LogSearch {
+    pub fn new(
+        pattern: Option<&str>,
+        filter: LogFilter,
+        from: Option<i64>,
+        to: Option<i64>,
+        limit: usize,
+    ) -> Result<Self> {
+        let pattern = pattern
+            .map(|p| Regex::new(p).context("Invalid search pattern"))
+            .transpose()?;
+        Ok(Self {
+            pattern,
+            filter,
+            from,
+            to,
+            limit,
+        })
+    }
+}
+
+/// Follows a growing log file, coping with truncation and rotation
+pub struct LogTail {
+    path: PathBuf,
+    position: u64,
+    partial: Vec<u8>,
+}
+
+fn is_log_name(name: &str) -> bool {
+    name.ends_with(".log") || name.ends_with(".log.gz")
+}
+
+/// Date encoded in a rotated log name such as `2024-06-01-3.log.gz`
+fn archive_date(name: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(name.get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn modified_secs(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn file_date(name: &str, metadata: &std::fs::Metadata) -> Option<NaiveDate> {
+    archive_date(name).or_else(|| {
+        Local
+            .timestamp_opt(modified_secs(metadata) as i64, 0)
+            .single()
+            .map(|t| t.date_naive())
+    })
+}
+
+/// List `latest.log` and rotated archives, oldest first
+pub async fn list_log_files(server_dir: &Path) -> Result<Vec<LogFileInfo>> {
+    let logs_dir = server_dir.join("logs");
+    if !logs_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    let mut entries = fs::read_dir(&logs_dir).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !is_log_name(&name) {
+            continue;
+        }
+
+        let metadata = entry.metadata().await?;
+        if !metadata.is_file() {
+            continue;
+        }
+
+        files.push(LogFileInfo {
+            compressed: name.ends_with(".gz"),
+            date: file_date(&name, &metadata).map(|d| d.to_string()),
+            size: metadata.len(),
+            last_modified: modified_secs(&metadata),
+            name,
+        });
+    }
+
+    // latest.log sorts after every dated archive
+    files.sort_by(|a, b| {
+        let key = |f: &LogFileInfo| (f.name == "latest.log", f.date.clone(), f.name.clone());
+        key(a).cmp(&key(b))
+    });
+
+    Ok(files)
+}
+
+/// Resolve a log file name inside `logs/`, rejecting anything else
+pub fn resolve_log_file(server_dir: &Path, name: &str) -> Result<PathBuf> {
+    if name.contains('/') || name.contains('\\') || name.contains("..") || !is_log_name(name) {
+        anyhow::bail!("Invalid log file name '{}'", name);
+    }
+
+    let path = server_dir.join("logs").join(name);
+    if !path.is_file() {
+        anyhow::bail!("Log file '{}' not found", name);
+    }
+    Ok(path)
+}
+
+/// Open a log for reading, transparently decompressing `.gz` archives
+pub fn open_log_reader(path: &Path) -> Result<Box<dyn BufRead + Send>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+
+    if is_compressed(path) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+fn is_compressed(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("gz")
+}
+
+/// Length of a log as served, after decompression (blocking)
+pub fn log_length(path: &Path) -> Result<u64> {
+    if is_compressed(path) {
+        let mut reader = open_log_reader(path)?;
+        std::io::copy(&mut reader, &mut std::io::sink())
+            .with_context(|| format!("Failed to read {:?}", path))
+    } else {
+        Ok(std::fs::metadata(path)?.len())
+    }
+}
+
+/// Open a log for reading from byte `start` of its decompressed text
+/// (blocking)
+pub fn open_log_reader_at(path: &Path, start: u64) -> Result<Box<dyn BufRead + Send>> {
+    if is_compressed(path) {
+        let mut reader = open_log_reader(path)?;
+        std::io::copy(&mut reader.by_ref().take(start), &mut std::io::sink())
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        Ok(reader)
+    } else {
+        let mut file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        file.seek(SeekFrom::Start(start))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+fn read_line_lossy(reader: &mut dyn BufRead, buf: &mut Vec<u8>) -> Result<Option<String>> {
+    buf.clear();
+    if reader.read_until(b'\n', buf)? == 0 {
+        return Ok(None);
+    }
+    let line = String::from_utf8_lossy(buf);
+    Ok(Some(line.trim_end_matches(['\n', '\r']).to_string()))
+}
+
+/// Read `limit` lines starting at line `offset` (blocking)
+pub fn read_log_page(path: &Path, offset: usize, limit: usize) -> Result<LogPage> {
+    let mut reader = open_log_reader(path)?;
+    let mut buf = Vec::new();
+    let mut lines = Vec::new();
+    let mut index = 0;
+
+    while let Some(line) = read_line_lossy(reader.as_mut(), &mut buf)? {
+        if index >= offset {
+            if lines.len() == limit {
+                return Ok(LogPage {
+                    next_offset: offset + lines.len(),
+                    lines,
+                    offset,
+                    eof: false,
+                });
+            }
+            lines.push(line);
+        }
+        index += 1;
+    }
+
+    Ok(LogPage {
+        next_offset: offset + lines.len(),
+        lines,
+        offset,
+        eof: true,
+    })
+}
+
+/// Dates the lines of a log, which only carry a time of day, from the day
+/// the log starts, moving to the next day whenever the time goes backwards
+struct LineDates {
+    date: NaiveDate,
+    previous: Option<NaiveTime>,
+}
+
+impl LineDates {
+    fn new(start: NaiveDate) -> Self {
+        Self { date: start, previous: None }
+    }
+
+    fn date(&mut self, time: NaiveTime) -> NaiveDate {
+        if self.previous.is_some_and(|previous| passed_midnight(previous, time)) {
+            self.date = self.date.succ_opt().unwrap_or(self.date);
+        }
+        self.previous = Some(time);
+        self.date
+    }
+}
+
+/// Whether `time` follows `previous` on a later day. Lines written by
+/// different threads can be a little out of order, which isn't a new day.
+fn passed_midnight(previous: NaiveTime, time: NaiveTime) -> bool {
+    previous - time > chrono::Duration::minutes(1)
+}
+
+/// Midnights a log passes, from where its time of day goes backwards
+/// (blocking)
+fn count_midnights(path: &Path) -> Result<u64> {
+    let mut reader = open_log_reader(path)?;
+    let mut buf = Vec::new();
+    let mut previous = None;
+    let mut count = 0;
+
+    while let Some(line) = read_line_lossy(reader.as_mut(), &mut buf)? {
+        let Some(time) = parse_log_header(&line).and_then(|parsed| parsed.time) else {
+            continue;
+        };
+        if previous.is_some_and(|previous| passed_midnight(previous, time)) {
+            count += 1;
+        }
+        previous = Some(time);
+    }
+    Ok(count)
+}
+
+/// Day a log starts on: from the archive name, or for latest.log, the day
+/// it was last written to less the midnights it passes
+fn start_date(file: &LogFileInfo, path: &Path) -> Result<Option<NaiveDate>> {
+    if let Some(date) = archive_date(&file.name) {
+        return Ok(Some(date));
+    }
+    let Some(end) = file
+        .date
+        .as_deref()
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    else {
+        return Ok(None);
+    };
+    Ok(end.checked_sub_days(chrono::Days::new(count_midnights(path)?)))
+}
+
+fn local_timestamp(date: NaiveDate, time: NaiveTime) -> Option<i64> {
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|t| t.timestamp())
+}
+
+/// Search every log of a server for lines matching a pattern and filter
+/// within an optional time range (blocking)
+pub fn search_logs(server_dir: &Path, files: &[LogFileInfo], search: &LogSearch) -> Result<Vec<LogMatch>> {
+    let mut matches = Vec::new();
+
+    let from_date = search
+        .from
+        .and_then(|t| Local.timestamp_opt(t, 0).single())
+        .map(|t| t.date_naive());
+    let to_date = search
+        .to
+        .and_then(|t| Local.timestamp_opt(t, 0).single())
+        .map(|t| t.date_naive());
+
+    for (i, file) in files.iter().enumerate() {
+        // Skip whole archives outside the requested days. A log can run past
+        // midnight, so it ends no earlier than the day it starts and no later
+        // than the day the next one starts.
+        if let Some(date) = archive_date(&file.name) {
+            let next = files.get(i + 1).and_then(|f| archive_date(&f.name));
+            if to_date.is_some_and(|to| date > to)
+                || next.zip(from_date).is_some_and(|(next, from)| next < from)
+            {
+                continue;
+            }
+        }
+
+        let path = server_dir.join("logs").join(&file.name);
+        let mut dates = start_date(file, &path)?.map(LineDates::new);
+        let mut reader = open_log_reader(&path)?;
+        let mut buf = Vec::new();
+        let mut line_number = 0;
+        // Continuation lines such as stack traces inherit the previous timestamp
+        let mut timestamp = None;
+        let mut parser = LogParser::new(LogLevel::Info);
+
+        while let Some(line) = read_line_lossy(reader.as_mut(), &mut buf)? {
+            line_number += 1;
+
+            let parsed = parser.parse(&line);
+            if let (Some(dates), Some(time)) = (dates.as_mut(), parsed.time) {
+                timestamp = local_timestamp(dates.date(time), time);
+            }
+
+            if let Some(ts) = timestamp {
+                if search.from.is_some_and(|from| ts < from) || search.to.is_some_and(|to| ts > to) {
+                    continue;
+                }
+            }
+
+            if !search
+                .filter
+                .matches(parsed.level, parsed.thread.as_deref(), parsed.source.as_deref())
+            {
+                continue;
+            }
+
+            if search.pattern.as_ref().is_none_or(|p| p.is_match(&line)) {
+                matches.push(LogMatch {
+                    file: file.name.clone(),
+                    line_number,
+                    line,
+                    timestamp,
+                    level: parsed.level,
+                    thread: parsed.thread,
+                    source: parsed.source,
+                });
+
+                if matches.len() >= search.limit {
+                    return Ok(matches);
+                }
+            }
+        }
+    }
+
+    Ok(matches)
+}
+
+impl LogTail {
+    /// Start following `path` from its current end
+    pub async fn from_end(path: PathBuf) -> Result<Self> {
+        let position = fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            position,
+            partial: Vec::new(),
+        })
+    }
+
+    /// Return complete lines appended since the last poll
+    pub async fn poll(&mut self) -> Result<Vec<String>> {
+        use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+        let len = match fs::metadata(&self.path).await {
+            Ok(metadata) => metadata.len(),
+            // Between rotation and the new latest.log being created
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        // The file was rotated or truncated: start over from the beginning
+        if len < self.position {
+            self.position = 0;
+            self.partial.clear();
+        }
+        if len == self.position {
+            return Ok(Vec::new());
+        }
+
+        let mut file = fs::File::open(&self.path).await?;
+        file.seek(std::io::SeekFrom::Start(self.position)).await?;
+        let mut chunk = Vec::new();
+        file.take(len - self.position).read_to_end(&mut chunk).await?;
+        self.position += chunk.len() as u64;
+
+        self.partial.extend_from_slice(&chunk);
+        let mut lines = Vec::new();
+        while let Some(idx) = self.partial.iter().position(|&b| b == b'\n') {
+            let raw: Vec<u8> = self.partial.drain(..=idx).collect();
+            let line = String::from_utf8_lossy(&raw);
+            lines.push(line.trim_end_matches(['\n', '\r']).to_string());
+        }
+
+        Ok(lines)
+    }
+}
@@ -0,0 +1,236 @@
+//! Modrinth modpacks (`.mrpack`): a zip with `modrinth.index.json` listing
+//! files to download, plus `overrides/` and `server-overrides/` to copy over
+//! the server directory.
+
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::downloader::{download_file, Checksum};
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+
+const INDEX_FILE: &str = "modrinth.index.json";
+
+/// Copied in this order, so server-specific files win
+const OVERRIDE_DIRS: [&str; 2] = ["overrides/", "server-overrides/"];
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModpackIndex {
+    format_version: u32,
+    game: String,
+    version_id: String,
+    name: String,
+    files: Vec<ModpackFile>,
+    dependencies: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModpackFile {
+    path: String,
+    hashes: ModpackHashes,
+    env: Option<ModpackEnv>,
+    downloads: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ModpackHashes {
+    sha512: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ModpackEnv {
+    server: String,
+}
+
+/// A parsed `.mrpack`, ready to install into a new server
+#[derive(Clone)]
+pub struct Modpack {
+    pub name: String,
+    pub version_id: String,
+    pub minecraft_version: String,
+    pub server_type: ServerType,
+    /// Loader version to install as the server build; none for vanilla packs
+    pub loader_version: Option<String>,
+    /// Files the server needs, by path in the server directory
+    files: Vec<ModpackFile>,
+    archive: Arc<Vec<u8>>,
+}
+
+/// Read a `.mrpack` and work out which server it needs
+pub fn read_modpack(data: Vec<u8>) -> Result<Modpack> {
+    let mut index = String::new();
+    zip::ZipArchive::new(Cursor::new(&data))
+        .context("Not a valid .mrpack archive")?
+        .by_name(INDEX_FILE)
+        .with_context(|| format!("Modpack has no {}", INDEX_FILE))?
+        .read_to_string(&mut index)
+        .with_context(|| format!("Failed to read {}", INDEX_FILE))?;
+    let index: ModpackIndex = serde_json::from_str(&index).with_context(|| format!("Invalid {}", INDEX_FILE))?;
+
+    if index.format_version != 1 {
+        anyhow::bail!("Unsupported modpack format version {}", index.format_version);
+    }
+    if index.game != "minecraft" {
+        anyhow::bail!("Modpack is for {}, not Minecraft", index.game);
+    }
+
+    let minecraft_version = index
+        .dependencies
+        .get("minecraft")
+        .context("Modpack doesn't declare a Minecraft version")?
+        .clone();
+
+    let (server_type, loader_version) = if let Some(version) = index.dependencies.get("fabric-loader") {
+        (ServerType::Fabric, Some(version.clone()))
+    } else if let Some(version) = index.dependencies.get("forge") {
+        (ServerType::Forge, Some(version.clone()))
+    } else if let Some(version) = index.dependencies.get("neoforge") {
+        (ServerType::NeoForge, Some(version.clone()))
+    } else if index.dependencies.contains_key("quilt-loader") {
+        anyhow::bail!("Quilt modpacks are not supported");
+    } else {
+        (ServerType::Vanilla, None)
+    };
+
+    let mut files = Vec::new();
+    for file in index.files {
+        if file.env.as_ref().is_some_and(|env| env.server == "unsupported") {
+            continue;
+        }
+        if !is_safe_path(&file.path) {
+            anyhow::bail!("Modpack file {} would be written outside the server", file.path);
+        }
+        if file.downloads.is_empty() {
+            anyhow::bail!("Modpack file {} has no downloads", file.path);
+        }
+        files.push(file);
+    }
+
+    Ok(Modpack {
+        name: index.name,
+        version_id: index.version_id,
+        minecraft_version,
+        server_type,
+        loader_version,
+        files,
+        archive: Arc::new(data),
+    })
+}
+
+/// Download the pack's server-side files into `server_dir`, verified by
+/// their SHA-512 and shared with plugin installs through the cache, then
+/// copy the overrides over them
+pub async fn install_modpack(
+    upstreams: &Upstreams,
+    cache: &ArtifactCache,
+    server_dir: &Path,
+    modpack: &Modpack,
+    progress: &Progress,
+) -> Result<()> {
+    let total = modpack.files.len() as u64;
+    for (i, file) in modpack.files.iter().enumerate() {
+        progress.check_cancelled()?;
+        progress.fraction(i as u64, total);
+        progress.log(format!("Downloading {}", file.path));
+
+        let destination = server_dir.join(&file.path);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let artifact = fetch_file(upstreams, cache, file, progress)
+            .await
+            .with_context(|| format!("Failed to download {}", file.path))?;
+        cache.copy_into(&artifact, &destination).await?;
+    }
+    progress.fraction(total, total);
+
+    progress.log("Applying overrides");
+    let archive = modpack.archive.clone();
+    let server_dir = server_dir.to_path_buf();
+    let progress = progress.clone();
+    tokio::task::spawn_blocking(move || extract_overrides(&archive, &server_dir, &progress)).await?
+}
+
+async fn fetch_file(
+    upstreams: &Upstreams,
+    cache: &ArtifactCache,
+    file: &ModpackFile,
+    progress: &Progress,
+) -> Result<CachedArtifact> {
+    let sha512 = file.hashes.sha512.to_lowercase();
+    let key = format!("modrinth/{}", sha512);
+    if let Some(artifact) = cache.get(&key).await? {
+        return Ok(artifact);
+    }
+
+    let file_name = file.path.rsplit('/').next().unwrap_or(&file.path);
+    let checksum = Checksum::Sha512(sha512);
+    let temp = cache.temp_path(file_name).await?;
+
+    // Mirrors are listed in order of preference
+    let mut last_error = None;
+    for url in &file.downloads {
+        progress.check_cancelled()?;
+        match download_file(upstreams.client(), url, &temp, Some(&checksum), &Progress::none()).await {
+            Ok(()) => return cache.insert(&key, &temp, file_name).await,
+            Err(e) => {
+                tracing::warn!("Download of {} from {} failed: {:#}", file.path, url, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.context("No downloads available")?)
+}
+
+fn extract_overrides(data: &[u8], server_dir: &Path, progress: &Progress) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).context("Failed to open modpack")?;
+
+    for prefix in OVERRIDE_DIRS {
+        for i in 0..archive.len() {
+            progress.check_cancelled()?;
+
+            let mut entry = archive.by_index(i).context("Failed to read modpack entry")?;
+            let Some(relative) = entry
+                .enclosed_name()
+                .and_then(|path| path.strip_prefix(prefix).ok().map(PathBuf::from))
+            else {
+                continue;
+            };
+            if relative.as_os_str().is_empty() {
+                continue;
+            }
+
+            let outpath = server_dir.join(&relative);
+            if entry.is_dir() {
+                std::fs::create_dir_all(&outpath).context("Failed to create override directory")?;
+                continue;
+            }
+            if let Some(parent) = outpath.parent() {
+                std::fs::create_dir_all(parent).context("Failed to create override directory")?;
+            }
+
+            // Replace rather than write through a hardlink an older install
+            // may have made into the cache
+            let _ = std::fs::remove_file(&outpath);
+            let mut outfile = std::fs::File::create(&outpath).context("Failed to create override file")?;
+            std::io::copy(&mut entry, &mut outfile).context("Failed to extract override")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Relative, and never climbing out of the server directory
+fn is_safe_path(path: &str) -> bool {
+    let path = Path::new(path);
+    path.components().next().is_some() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
@@ -0,0 +1,382 @@
+//! Wiring a proxy to its backend servers: the proxy's server list and
+//! player info forwarding on both sides.
+
+use crate::config::{read_server_properties, write_server_properties};
+use crate::types::ServerType;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tokio::fs;
+use toml_edit::{value, Array, DocumentMut, Item, Table};
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+/// Backends only listen here, so players can't bypass the proxy
+pub const BACKEND_HOST: &str = "127.0.0.1";
+
+const VELOCITY_SECRET_FILE: &str = "forwarding.secret";
+
+/// Settings a backend had before joining a network, kept in its directory
+/// until it leaves
+const STANDALONE_SETTINGS_FILE: &str = ".network-standalone.json";
+
+/// `server.properties` values `join_network` overrides. `None` when the key
+/// wasn't set.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StandaloneSettings {
+    online_mode: Option<String>,
+    server_ip: Option<String>,
+}
+
+/// A backend as the proxy knows it
+#[derive(Debug, Clone)]
+pub struct NetworkBackend {
+    /// Name players `/server` to, e.g. `lobby`
+    pub name: String,
+    pub port: u16,
+}
+
+/// Names become TOML keys and YAML map keys in the proxy config
+pub fn is_valid_backend_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "try"
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Random secret for Velocity's modern forwarding
+pub fn generate_forwarding_secret() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Whether a backend can take forwarded player info from this kind of
+/// proxy. Velocity's modern forwarding needs Paper or a fork; BungeeCord
+/// forwarding works from Spigot up.
+pub fn check_backend(proxy_type: ServerType, backend_type: ServerType) -> Result<()> {
+    let supported = match proxy_type {
+        ServerType::Velocity => matches!(
+            backend_type,
+            ServerType::Paper | ServerType::Purpur | ServerType::Folia
+        ),
+        ServerType::Waterfall | ServerType::BungeeCord => matches!(
+            backend_type,
+            ServerType::Paper | ServerType::Purpur | ServerType::Folia | ServerType::Spigot
+        ),
+        _ => anyhow::bail!("{} is not a proxy", proxy_type.as_str()),
+    };
+
+    if !supported {
+        anyhow::bail!(
+            "{} servers can't receive forwarded player info from {}",
+            backend_type.as_str(),
+            proxy_type.as_str()
+        );
+    }
+    Ok(())
+}
+
+/// Write the proxy's listener and server list. Backends are tried in order
+/// when players join. `forwarding_secret` switches on player info
+/// forwarding; BungeeCord-style proxies have no secret and only use it as
+/// the switch.
+pub async fn configure_proxy(
+    proxy_dir: &Path,
+    proxy_type: ServerType,
+    port: u16,
+    backends: &[NetworkBackend],
+    forwarding_secret: Option<&str>,
+) -> Result<()> {
+    match proxy_type {
+        ServerType::Velocity => configure_velocity(proxy_dir, port, backends, forwarding_secret).await,
+        ServerType::Waterfall | ServerType::BungeeCord => {
+            configure_bungee(proxy_dir, port, backends, forwarding_secret.is_some()).await
+        }
+        _ => anyhow::bail!("{} is not a proxy", proxy_type.as_str()),
+    }
+}
+
+async fn configure_velocity(
+    proxy_dir: &Path,
+    port: u16,
+    backends: &[NetworkBackend],
+    forwarding_secret: Option<&str>,
+) -> Result<()> {
+    let path = proxy_dir.join("velocity.toml");
+    let existing = match fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).context("Failed to read velocity.toml"),
+    };
+    let mut doc: DocumentMut = existing.parse().context("Failed to parse velocity.toml")?;
+
+    // Velocity fills in anything else it needs on first start
+    if !doc.contains_key("config-version") {
+        doc["config-version"] = value("2.7");
+    }
+    doc["bind"] = value(format!("0.0.0.0:{}", port));
+
+    if let Some(secret) = forwarding_secret {
+        doc["player-info-forwarding-mode"] = value("modern");
+        doc["forwarding-secret-file"] = value(VELOCITY_SECRET_FILE);
+        fs::write(proxy_dir.join(VELOCITY_SECRET_FILE), secret)
+            .await
+            .context("Failed to write forwarding secret")?;
+    }
+
+    let mut servers = Table::new();
+    let mut try_order = Array::new();
+    for backend in backends {
+        servers[backend.name.as_str()] = value(format!("{}:{}", BACKEND_HOST, backend.port));
+        try_order.push(backend.name.as_str());
+    }
+    servers["try"] = value(try_order);
+    doc["servers"] = Item::Table(servers);
+
+    // Keep the admin's forced hosts, minus servers that aren't backends,
+    // which Velocity refuses to start with, and the example.com defaults
+    let names: HashSet<&str> = backends.iter().map(|b| b.name.as_str()).collect();
+    let mut forced_hosts = Table::new();
+    if let Some(existing) = doc.get("forced-hosts").and_then(Item::as_table_like) {
+        for (host, servers) in existing.iter() {
+            if host.ends_with(".example.com") {
+                continue;
+            }
+            let kept: Array = servers
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|s| s.as_str())
+                .filter(|s| names.contains(s))
+                .collect();
+            if !kept.is_empty() {
+                forced_hosts[host] = value(kept);
+            }
+        }
+    }
+    doc["forced-hosts"] = Item::Table(forced_hosts);
+
+    fs::write(&path, doc.to_string())
+        .await
+        .context("Failed to write velocity.toml")
+}
+
+async fn configure_bungee(
+    proxy_dir: &Path,
+    port: u16,
+    backends: &[NetworkBackend],
+    ip_forward: bool,
+) -> Result<()> {
+    let path = proxy_dir.join("config.yml");
+    let mut doc = read_yaml(&path).await?;
+
+    let mut servers = Hash::new();
+    for backend in backends {
+        let mut server = Hash::new();
+        server.insert(
+            yaml_str("address"),
+            yaml_str(&format!("{}:{}", BACKEND_HOST, backend.port)),
+        );
+        server.insert(yaml_str("motd"), yaml_str(&backend.name));
+        server.insert(yaml_str("restricted"), Yaml::Boolean(false));
+        servers.insert(yaml_str(&backend.name), Yaml::Hash(server));
+    }
+    set_yaml(&mut doc, &["servers"], Yaml::Hash(servers));
+
+    // Keep any other listener settings the admin has made
+    let mut listener = match yaml_get(&doc, &["listeners"]) {
+        Some(Yaml::Array(listeners)) => listeners.first().cloned().unwrap_or(Yaml::Hash(Hash::new())),
+        _ => Yaml::Hash(Hash::new()),
+    };
+    set_yaml(&mut listener, &["host"], yaml_str(&format!("0.0.0.0:{}", port)));
+    set_yaml(&mut listener, &["query_port"], Yaml::Integer(port as i64));
+    set_yaml(
+        &mut listener,
+        &["priorities"],
+        Yaml::Array(backends.iter().map(|b| yaml_str(&b.name)).collect()),
+    );
+    set_yaml(&mut doc, &["listeners"], Yaml::Array(vec![listener]));
+
+    set_yaml(&mut doc, &["ip_forward"], Yaml::Boolean(ip_forward));
+
+    write_yaml(&path, &doc).await
+}
+
+/// Make a server a backend of a proxy: offline mode (the proxy
+/// authenticates players), listening on loopback only, and trusting the
+/// proxy's forwarded player info
+pub async fn join_network(
+    server_dir: &Path,
+    server_type: ServerType,
+    port: u16,
+    proxy_type: ServerType,
+    forwarding_secret: &str,
+) -> Result<()> {
+    check_backend(proxy_type, server_type)?;
+
+    // Joining again, e.g. when the proxy's server list changes, must not
+    // overwrite what the server had on its own
+    let saved = server_dir.join(STANDALONE_SETTINGS_FILE);
+    if !fs::try_exists(&saved).await? {
+        let properties = read_properties(server_dir).await?;
+        let settings = StandaloneSettings {
+            online_mode: properties.get("online-mode").cloned(),
+            server_ip: properties.get("server-ip").cloned(),
+        };
+        fs::write(&saved, serde_json::to_vec_pretty(&settings)?)
+            .await
+            .context("Failed to save the server's own settings")?;
+    }
+
+    update_server_properties(server_dir, &[
+        ("online-mode", "false".to_string()),
+        ("server-ip", BACKEND_HOST.to_string()),
+        ("server-port", port.to_string()),
+    ])
+    .await?;
+
+    let modern = proxy_type == ServerType::Velocity;
+    set_bungeecord_forwarding(server_dir, !modern).await?;
+    if server_type != ServerType::Spigot {
+        set_velocity_forwarding(server_dir, modern.then_some(forwarding_secret)).await?;
+    }
+
+    Ok(())
+}
+
+/// Undo `join_network`, so the server can take players directly again with
+/// the settings it had before joining
+pub async fn leave_network(server_dir: &Path, server_type: ServerType) -> Result<()> {
+    let saved = server_dir.join(STANDALONE_SETTINGS_FILE);
+    // Servers that joined before their settings were saved get the defaults
+    let settings = match fs::read(&saved).await {
+        Ok(data) => {
+            serde_json::from_slice(&data).context("Failed to parse the server's own settings")?
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => StandaloneSettings::default(),
+        Err(e) => return Err(e).context("Failed to read the server's own settings"),
+    };
+
+    update_server_properties(server_dir, &[
+        ("online-mode", settings.online_mode.unwrap_or_else(|| "true".to_string())),
+        ("server-ip", settings.server_ip.unwrap_or_default()),
+    ])
+    .await?;
+
+    if fs::try_exists(server_dir.join("spigot.yml")).await? {
+        set_bungeecord_forwarding(server_dir, false).await?;
+    }
+    if server_type != ServerType::Spigot
+        && fs::try_exists(server_dir.join("config").join("paper-global.yml")).await?
+    {
+        set_velocity_forwarding(server_dir, None).await?;
+    }
+
+    if let Err(e) = fs::remove_file(&saved).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(e).context("Failed to remove the server's saved settings");
+        }
+    }
+    Ok(())
+}
+
+async fn read_properties(server_dir: &Path) -> Result<HashMap<String, String>> {
+    let path = server_dir.join("server.properties");
+    if path.exists() {
+        read_server_properties(&path).await
+    } else {
+        Ok(HashMap::new())
+    }
+}
+
+async fn update_server_properties(server_dir: &Path, changes: &[(&str, String)]) -> Result<()> {
+    let mut properties = read_properties(server_dir).await?;
+
+    for (key, value) in changes {
+        properties.insert(key.to_string(), value.clone());
+    }
+
+    write_server_properties(&server_dir.join("server.properties"), &properties).await
+}
+
+/// `settings.bungeecord` in spigot.yml
+async fn set_bungeecord_forwarding(server_dir: &Path, enabled: bool) -> Result<()> {
+    let path = server_dir.join("spigot.yml");
+    let mut doc = read_yaml(&path).await?;
+    set_yaml(&mut doc, &["settings", "bungeecord"], Yaml::Boolean(enabled));
+    write_yaml(&path, &doc).await
+}
+
+/// `proxies.velocity` in Paper's config/paper-global.yml
+async fn set_velocity_forwarding(server_dir: &Path, secret: Option<&str>) -> Result<()> {
+    let config_dir = server_dir.join("config");
+    fs::create_dir_all(&config_dir).await?;
+
+    let path = config_dir.join("paper-global.yml");
+    let mut doc = read_yaml(&path).await?;
+
+    set_yaml(&mut doc, &["proxies", "velocity", "enabled"], Yaml::Boolean(secret.is_some()));
+    if let Some(secret) = secret {
+        set_yaml(&mut doc, &["proxies", "velocity", "online-mode"], Yaml::Boolean(true));
+        set_yaml(&mut doc, &["proxies", "velocity", "secret"], yaml_str(secret));
+    }
+
+    write_yaml(&path, &doc).await
+}
+
+fn yaml_str(s: &str) -> Yaml {
+    Yaml::String(s.to_string())
+}
+
+/// The file's first document, or an empty map when it doesn't exist yet.
+/// Comments don't survive the round trip.
+async fn read_yaml(path: &Path) -> Result<Yaml> {
+    let content = match fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Yaml::Hash(Hash::new())),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", path)),
+    };
+
+    let docs = YamlLoader::load_from_str(&content).with_context(|| format!("Failed to parse {:?}", path))?;
+    Ok(docs.into_iter().next().unwrap_or(Yaml::Hash(Hash::new())))
+}
+
+async fn write_yaml(path: &Path, doc: &Yaml) -> Result<()> {
+    let mut out = String::new();
+    YamlEmitter::new(&mut out)
+        .dump(doc)
+        .with_context(|| format!("Failed to serialize {:?}", path))?;
+    out.push('\n');
+
+    fs::write(path, out)
+        .await
+        .with_context(|| format!("Failed to write {:?}", path))
+}
+
+fn yaml_get<'a>(doc: &'a Yaml, path: &[&str]) -> Option<&'a Yaml> {
+    path.iter().try_fold(doc, |node, key| match node {
+        Yaml::Hash(map) => map.get(&yaml_str(key)),
+        _ => None,
+    })
+}
+
+/// Set a nested key, replacing anything in the way that isn't a map
+fn set_yaml(doc: &mut Yaml, path: &[&str], new_value: Yaml) {
+    let Some((key, rest)) = path.split_first() else {
+        *doc = new_value;
+        return;
+    };
+
+    if !matches!(doc, Yaml::Hash(_)) {
+        *doc = Yaml::Hash(Hash::new());
+    }
+    if let Yaml::Hash(map) = doc {
+        let child = map.entry(yaml_str(key)).or_insert(Yaml::Null);
+        set_yaml(child, rest, new_value);
+    }
+}
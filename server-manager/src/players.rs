@@ -0,0 +1,343 @@
+use anyhow::{Context, Result};
+use md5::{Digest, Md5};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::Path;
+use tokio::fs;
+use uuid::Uuid;
+
+const MOJANG_PROFILE_API: &str = "https://api.mojang.com/users/profiles/minecraft";
+
+/// Default reason written by the vanilla server when none is given
+const DEFAULT_BAN_REASON: &str = "Banned by an operator.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpEntry {
+    pub uuid: String,
+    pub name: String,
+    pub level: u8,
+    pub bypasses_player_limit: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    pub uuid: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BannedPlayerEntry {
+    pub uuid: String,
+    pub name: String,
+    pub created: String,
+    pub source: String,
+    pub expires: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BannedIpEntry {
+    pub ip: String,
+    pub created: String,
+    pub source: String,
+    pub expires: String,
+    pub reason: String,
+}
+
+/// Entry in a Bedrock server's `allowlist.json`. The server fills in the
+/// XUID when the player first joins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowlistEntry {
+    pub ignores_player_limit: bool,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xuid: Option<String>,
+}
+
+/// Entry in a Bedrock server's `permissions.json`, keyed by XUID only
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionEntry {
+    pub permission: String,
+    pub xuid: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MojangProfile {
+    id: String,
+    name: String,
+}
+
+/// A player name together with the UUID the server will know them by
+#[derive(Debug, Clone)]
+pub struct ResolvedPlayer {
+    pub uuid: Uuid,
+    pub name: String,
+}
+
+pub fn validate_player_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 16
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !valid {
+        anyhow::bail!("Invalid player name '{}'", name);
+    }
+    Ok(())
+}
+
+/// Xbox gamertags, as used by Bedrock servers, may contain spaces
+pub fn validate_gamertag(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 16
+        && !name.starts_with(' ')
+        && !name.ends_with(' ')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == ' ');
+
+    if !valid {
+        anyhow::bail!("Invalid gamertag '{}'", name);
+    }
+    Ok(())
+}
+
+pub fn validate_ip(ip: &str) -> Result<IpAddr> {
+    ip.parse::<IpAddr>()
+        .with_context(|| format!("Invalid IP address '{}'", ip))
+}
+
+/// Strip anything that would let an argument break out of a single console command
+pub fn sanitize_console_arg(arg: &str) -> String {
+    arg
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// UUID the server assigns to a player when `online-mode=false`.
+///
+/// Matches Java's `UUID.nameUUIDFromBytes("OfflinePlayer:" + name)`.
+pub fn offline_uuid(name: &str) -> Uuid {
+    let digest = Md5::digest(format!("OfflinePlayer:{}", name).as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest);
+    uuid::Builder::from_md5_bytes(bytes).into_uuid()
+}
+
+/// Resolve a player name to the UUID used in the server's JSON lists
+pub async fn resolve_player(name: &str, online_mode: bool) -> Result<ResolvedPlayer> {
+    validate_player_name(name)?;
+
+    if !online_mode {
+        return Ok(ResolvedPlayer {
+            uuid: offline_uuid(name),
+            name: name.to_string(),
+        });
+    }
+
+    let url = format!("{}/{}", MOJANG_PROFILE_API, name);
+    let response = reqwest::get(&url)
+        .await
+        .context("Failed to look up player profile")?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND
+        || response.status() == reqwest::StatusCode::NO_CONTENT
+    {
+        anyhow::bail!("Player '{}' does not exist", name);
+    }
+    if !response.status().is_success() {
+        anyhow::bail!("Profile lookup failed with status: {}", response.status());
+    }
+
+    let profile: MojangProfile = response
+        .json()
+        .await
+        .context("Failed to parse player profile")?;
+
+    let uuid = Uuid::parse_str(&profile.id).context("Invalid UUID in player profile")?;
+
+    Ok(ResolvedPlayer {
+        uuid,
+        name: profile.name,
+    })
+}
+
+async fn read_list<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read {:?}", path))?;
+
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+}
+
+async fn write_list<T: Serialize>(path: &Path, entries: &[T]) -> Result<()> {
+    let content = serde_json::to_string_pretty(entries)?;
+    fs::write(path, content)
+        .await
+        .with_context(|| format!("Failed to write {:?}", path))
+}
+
+fn ban_timestamp() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S %z").to_string()
+}
+
+pub async fn list_ops(server_dir: &Path) -> Result<Vec<OpEntry>> {
+    read_list(&server_dir.join("ops.json")).await
+}
+
+pub async fn list_whitelist(server_dir: &Path) -> Result<Vec<WhitelistEntry>> {
+    read_list(&server_dir.join("whitelist.json")).await
+}
+
+pub async fn list_banned_players(server_dir: &Path) -> Result<Vec<BannedPlayerEntry>> {
+    read_list(&server_dir.join("banned-players.json")).await
+}
+
+pub async fn list_banned_ips(server_dir: &Path) -> Result<Vec<BannedIpEntry>> {
+    read_list(&server_dir.join("banned-ips.json")).await
+}
+
+pub async fn add_op(server_dir: &Path, player: &ResolvedPlayer, level: u8) -> Result<()> {
+    let path = server_dir.join("ops.json");
+    let mut ops: Vec<OpEntry> = read_list(&path).await?;
+    let uuid = player.uuid.to_string();
+
+    ops.retain(|e| e.uuid != uuid);
+    ops.push(OpEntry {
+        uuid,
+        name: player.name.clone(),
+        level,
+        bypasses_player_limit: false,
+    });
+
+    write_list(&path, &ops).await
+}
+
+pub async fn remove_op(server_dir: &Path, name: &str) -> Result<()> {
+    let path = server_dir.join("ops.json");
+    let mut ops: Vec<OpEntry> = read_list(&path).await?;
+    ops.retain(|e| !e.name.eq_ignore_ascii_case(name));
+    write_list(&path, &ops).await
+}
+
+pub async fn add_to_whitelist(server_dir: &Path, player: &ResolvedPlayer) -> Result<()> {
+    let path = server_dir.join("whitelist.json");
+    let mut entries: Vec<WhitelistEntry> = read_list(&path).await?;
+    let uuid = player.uuid.to_string();
+
+    if entries.iter().any(|e| e.uuid == uuid) {
+        return Ok(());
+    }
+    entries.push(WhitelistEntry {
+        uuid,
+        name: player.name.clone(),
+    });
+
+    write_list(&path, &entries).await
+}
+
+pub async fn remove_from_whitelist(server_dir: &Path, name: &str) -> Result<()> {
+    let path = server_dir.join("whitelist.json");
+    let mut entries: Vec<WhitelistEntry> = read_list(&path).await?;
+    entries.retain(|e| !e.name.eq_ignore_ascii_case(name));
+    write_list(&path, &entries).await
+}
+
+pub async fn ban_player(
+    server_dir: &Path,
+    player: &ResolvedPlayer,
+    reason: Option<&str>,
+) -> Result<()> {
+    let path = server_dir.join("banned-players.json");
+    let mut entries: Vec<BannedPlayerEntry> = read_list(&path).await?;
+    let uuid = player.uuid.to_string();
+
+    entries.retain(|e| e.uuid != uuid);
+    entries.push(BannedPlayerEntry {
+        uuid,
+        name: player.name.clone(),
+        created: ban_timestamp(),
+        source: "Server".to_string(),
+        expires: "forever".to_string(),
+        reason: reason.unwrap_or(DEFAULT_BAN_REASON).to_string(),
+    });
+
+    write_list(&path, &entries).await
+}
+
+pub async fn pardon_player(server_dir: &Path, name: &str) -> Result<()> {
+    let path = server_dir.join("banned-players.json");
+    let mut entries: Vec<BannedPlayerEntry> = read_list(&path).await?;
+    entries.retain(|e| !e.name.eq_ignore_ascii_case(name));
+    write_list(&path, &entries).await
+}
+
+pub async fn ban_ip(server_dir: &Path, ip: IpAddr, reason: Option<&str>) -> Result<()> {
+    let path = server_dir.join("banned-ips.json");
+    let mut entries: Vec<BannedIpEntry> = read_list(&path).await?;
+    let ip = ip.to_string();
+
+    entries.retain(|e| e.ip != ip);
+    entries.push(BannedIpEntry {
+        ip,
+        created: ban_timestamp(),
+        source: "Server".to_string(),
+        expires: "forever".to_string(),
+        reason: reason.unwrap_or(DEFAULT_BAN_REASON).to_string(),
+    });
+
+    write_list(&path, &entries).await
+}
+
+pub async fn pardon_ip(server_dir: &Path, ip: IpAddr) -> Result<()> {
+    let path = server_dir.join("banned-ips.json");
+    let mut entries: Vec<BannedIpEntry> = read_list(&path).await?;
+    let ip = ip.to_string();
+    entries.retain(|e| e.ip != ip);
+    write_list(&path, &entries).await
+}
+
+pub async fn list_allowlist(server_dir: &Path) -> Result<Vec<AllowlistEntry>> {
+    read_list(&server_dir.join("allowlist.json")).await
+}
+
+/// Bedrock operators, i.e. `permissions.json` entries with the operator level
+pub async fn list_bedrock_operators(server_dir: &Path) -> Result<Vec<PermissionEntry>> {
+    let entries: Vec<PermissionEntry> = read_list(&server_dir.join("permissions.json")).await?;
+    Ok(entries.into_iter().filter(|e| e.permission == "operator").collect())
+}
+
+pub async fn add_to_allowlist(server_dir: &Path, name: &str) -> Result<()> {
+    validate_gamertag(name)?;
+
+    let path = server_dir.join("allowlist.json");
+    let mut entries: Vec<AllowlistEntry> = read_list(&path).await?;
+    if entries.iter().any(|e| e.name.eq_ignore_ascii_case(name)) {
+        return Ok(());
+    }
+    entries.push(AllowlistEntry {
+        ignores_player_limit: false,
+        name: name.to_string(),
+        xuid: None,
+    });
+
+    write_list(&path, &entries).await
+}
+
+pub async fn remove_from_allowlist(server_dir: &Path, name: &str) -> Result<()> {
+    let path = server_dir.join("allowlist.json");
+    let mut entries: Vec<AllowlistEntry> = read_list(&path).await?;
+    entries.retain(|e| !e.name.eq_ignore_ascii_case(name));
+    write_list(&path, &entries).await
+}
@@ -0,0 +1,198 @@
+//! What a plugin jar declares about itself in the descriptor its platform
+//! loads it by: `plugin.yml`, `paper-plugin.yml`, `bungee.yml` or
+//! `velocity-plugin.json`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+use yaml_rust2::{Yaml, YamlLoader};
+
+/// The descriptor a plugin's metadata came from
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginPlatform {
+    /// `plugin.yml`, read by Spigot and everything built on it
+    Bukkit,
+    /// `paper-plugin.yml`
+    Paper,
+    /// `bungee.yml`, read by BungeeCord and Waterfall
+    BungeeCord,
+    /// `velocity-plugin.json`
+    Velocity,
+}
+
+impl PluginPlatform {
+    /// Descriptors in the order they're looked for. Paper prefers its own
+    /// over `plugin.yml` when a jar ships both.
+    const ALL: [PluginPlatform; 4] = [
+        PluginPlatform::Paper,
+        PluginPlatform::Bukkit,
+        PluginPlatform::BungeeCord,
+        PluginPlatform::Velocity,
+    ];
+
+    pub fn descriptor(&self) -> &'static str {
+        match self {
+            PluginPlatform::Bukkit => "plugin.yml",
+            PluginPlatform::Paper => "paper-plugin.yml",
+            PluginPlatform::BungeeCord => "bungee.yml",
+            PluginPlatform::Velocity => "velocity-plugin.json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct PluginMetadata {
+    /// Where the metadata came from; `None` for a jar without a descriptor,
+    /// whose name is then its file stem
+    pub platform: Option<PluginPlatform>,
+    pub name: String,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    /// Oldest server API the plugin was written against, e.g. `1.20`
+    pub api_version: Option<String>,
+    /// Plugins it won't load without
+    pub depends: Vec<String>,
+    /// Plugins it uses when they're present
+    pub soft_depends: Vec<String>,
+    /// Commands declared up front; Paper and Velocity plugins register
+    /// theirs in code, so these are Bukkit and BungeeCord only
+    pub commands: Vec<String>,
+}
+
+/// Read the metadata of a plugin jar, `None` if it has no descriptor
+pub fn read_plugin_metadata(jar: &Path) -> Result<Option<PluginMetadata>> {
+    let file = std::fs::File::open(jar).with_context(|| format!("Failed to open {}", jar.display()))?;
+    let mut archive = zip::ZipArchive::new(file).with_context(|| format!("{} is not a valid jar", jar.display()))?;
+
+    for platform in PluginPlatform::ALL {
+        let Ok(mut entry) = archive.by_name(platform.descriptor()) else {
+            continue;
+        };
+        let mut text = String::new();
+        entry
+            .read_to_string(&mut text)
+            .with_context(|| format!("Failed to read {}", platform.descriptor()))?;
+
+        let metadata = match platform {
+            PluginPlatform::Velocity => parse_velocity(&text),
+            _ => parse_yaml(platform, &text),
+        }
+        .with_context(|| format!("Invalid {} in {}", platform.descriptor(), jar.display()))?;
+        return Ok(Some(metadata));
+    }
+
+    Ok(None)
+}
+
+fn parse_yaml(platform: PluginPlatform, text: &str) -> Result<PluginMetadata> {
+    let docs = YamlLoader::load_from_str(text)?;
+    let doc = docs.first().context("Descriptor is empty")?;
+
+    let mut authors = strings(&doc["authors"]);
+    if let Some(author) = scalar(&doc["author"]) {
+        authors.insert(0, author);
+    }
+
+    let (depends, soft_depends) = match platform {
+        // Paper lists dependencies per load phase, each marked required or not
+        PluginPlatform::Paper => {
+            let mut depends = Vec::new();
+            let mut soft_depends = Vec::new();
+            for phase in ["bootstrap", "server"] {
+                let Some(dependencies) = doc["dependencies"][phase].as_hash() else {
+                    continue;
+                };
+                for (name, options) in dependencies {
+                    let Some(name) = scalar(name) else {
+                        continue;
+                    };
+                    let list = if options["required"].as_bool().unwrap_or(true) {
+                        &mut depends
+                    } else {
+                        &mut soft_depends
+                    };
+                    if !list.contains(&name) {
+                        list.push(name);
+                    }
+                }
+            }
+            (depends, soft_depends)
+        }
+        PluginPlatform::BungeeCord => (strings(&doc["depends"]), strings(&doc["softDepends"])),
+        _ => (strings(&doc["depend"]), strings(&doc["softdepend"])),
+    };
+
+    let commands = doc["commands"]
+        .as_hash()
+        .map(|commands| commands.keys().filter_map(scalar).collect())
+        .unwrap_or_default();
+
+    Ok(PluginMetadata {
+        platform: Some(platform),
+        name: scalar(&doc["name"]).context("Descriptor has no name")?,
+        version: scalar(&doc["version"]),
+        description: scalar(&doc["description"]),
+        authors,
+        api_version: scalar(&doc["api-version"]),
+        depends,
+        soft_depends,
+        commands,
+    })
+}
+
+/// YAML scalars as written; `version: 1.0` is a float to the parser
+fn scalar(yaml: &Yaml) -> Option<String> {
+    match yaml {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// A list of names, or a single one written without brackets
+fn strings(yaml: &Yaml) -> Vec<String> {
+    match yaml {
+        Yaml::Array(items) => items.iter().filter_map(scalar).collect(),
+        other => scalar(other).into_iter().collect(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VelocityDescriptor {
+    id: String,
+    name: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    authors: Vec<String>,
+    #[serde(default)]
+    dependencies: Vec<VelocityDependency>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VelocityDependency {
+    id: String,
+    #[serde(default)]
+    optional: bool,
+}
+
+fn parse_velocity(text: &str) -> Result<PluginMetadata> {
+    let descriptor: VelocityDescriptor = serde_json::from_str(text)?;
+    let (soft, hard): (Vec<_>, Vec<_>) = descriptor.dependencies.into_iter().partition(|d| d.optional);
+
+    Ok(PluginMetadata {
+        platform: Some(PluginPlatform::Velocity),
+        name: descriptor.name.unwrap_or(descriptor.id),
+        version: descriptor.version,
+        description: descriptor.description,
+        authors: descriptor.authors,
+        api_version: None,
+        depends: hard.into_iter().map(|d| d.id).collect(),
+        soft_depends: soft.into_iter().map(|d| d.id).collect(),
+        commands: Vec::new(),
+    })
+}
@@ -1,11 +1,17 @@
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::downloader::{download_file, sha512_file, Checksum};
+use crate::plugin_meta::{read_plugin_metadata, PluginMetadata};
+use crate::progress::Progress;
+use crate::providers::{provider, AddonKind};
 use crate::types::{PluginInfo, ServerType};
+use crate::upstream::Upstreams;
 use anyhow::{Context, Result};
-use serde::Deserialize;
-use std::path::Path;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path};
 use tokio::fs;
 
-const MODRINTH_API_BASE: &str = "https://api.modrinth.com/v2";
-
 #[derive(Debug, Deserialize)]
 struct ModrinthSearchResponse {
     hits: Vec<ModrinthProject>,
@@ -19,46 +25,136 @@ struct ModrinthProject {
     project_id: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct ModrinthVersion {
-    #[serde(rename = "version_number")]
-    _version_number: String,
-    game_versions: Vec<String>,
-    loaders: Vec<String>,
-    files: Vec<ModrinthFile>,
+/// A release of a Modrinth project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddonVersion {
+    pub id: String,
+    pub project_id: String,
+    pub version_number: String,
+    /// `release`, `beta` or `alpha`
+    #[serde(default)]
+    pub version_type: String,
+    pub game_versions: Vec<String>,
+    pub loaders: Vec<String>,
+    #[serde(default)]
+    pub dependencies: Vec<AddonDependency>,
+    pub files: Vec<AddonFile>,
+}
+
+impl AddonVersion {
+    /// The file Modrinth marks primary, else the first
+    pub(crate) fn primary_file(&self) -> Option<&AddonFile> {
+        self.files.iter().find(|f| f.primary).or_else(|| self.files.first())
+    }
+
+    fn runs_on(&self, loaders: &[&str]) -> bool {
+        self.loaders.iter().any(|l| loaders.contains(&l.to_lowercase().as_str()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddonDependency {
+    pub project_id: Option<String>,
+    /// Set when the dependency needs one particular release
+    pub version_id: Option<String>,
+    pub dependency_type: DependencyType,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyType {
+    Required,
+    Optional,
+    Incompatible,
+    /// Shaded into the jar already
+    Embedded,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddonFile {
+    pub url: String,
+    pub filename: String,
+    pub hashes: AddonHashes,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddonHashes {
+    pub sha512: Option<String>,
+}
+
+/// A version looked up by one of its files' hashes
 #[derive(Debug, Deserialize)]
-struct ModrinthFile {
-    url: String,
-    filename: String,
+pub(crate) struct ModrinthFileVersion {
+    #[serde(default)]
+    pub(crate) id: String,
+    pub(crate) project_id: String,
+    pub(crate) version_number: String,
+    pub(crate) game_versions: Vec<String>,
+    #[serde(default)]
+    dependencies: Vec<AddonDependency>,
 }
 
-fn get_client() -> reqwest::Client {
-    reqwest::Client::builder()
-        .user_agent("mineserv-manager (https://github.com/vpastila/mineserv)")
-        .build()
-        .unwrap_or_default()
+#[derive(Debug, Deserialize)]
+struct ModrinthProjectInfo {
+    id: String,
+    title: String,
 }
 
-pub async fn search_plugins(query: &str, server_type: &str) -> Result<Vec<PluginInfo>> {
-    let facets = match server_type.to_lowercase().as_str() {
-        "paper" => r#"[["categories:paper"]]"#,
-        "spigot" => r#"[["categories:spigot"]]"#,
-        _ => r#"[["project_type:plugin"]]"#,
+pub async fn search_plugins(upstreams: &Upstreams, query: &str, server_type: &str) -> Result<Vec<PluginInfo>> {
+    // Any of the loaders the server runs; facets in one inner list are ORed
+    let loaders = server_type
+        .parse::<ServerType>()
+        .map(|t| provider(t).plugin_loaders())
+        .unwrap_or_default();
+    let facets = if loaders.is_empty() {
+        serde_json::json!([["project_type:plugin"]])
+    } else {
+        serde_json::json!([loader_facet(loaders)])
     };
 
+    search_addons(upstreams, query, &facets).await
+}
+
+/// Search Modrinth for mods that run on this loader and Minecraft version
+pub async fn search_mods(
+    upstreams: &Upstreams,
+    query: &str,
+    server_type: ServerType,
+    minecraft_version: &str,
+) -> Result<Vec<PluginInfo>> {
+    let provider = provider(server_type);
+    if provider.addon_kind() != AddonKind::Mod {
+        anyhow::bail!("{} servers don't support mods", server_type.as_str());
+    }
+
+    let facets = serde_json::json!([
+        loader_facet(provider.plugin_loaders()),
+        [format!("versions:{}", minecraft_version)],
+        ["project_type:mod"],
+    ]);
+
+    search_addons(upstreams, query, &facets).await
+}
+
+fn loader_facet(loaders: &[&str]) -> Vec<String> {
+    loaders.iter().map(|l| format!("categories:{}", l)).collect()
+}
+
+async fn search_addons(upstreams: &Upstreams, query: &str, facets: &serde_json::Value) -> Result<Vec<PluginInfo>> {
     let url = format!(
         "{}/search?query={}&facets={}&limit=20",
-        MODRINTH_API_BASE,
+        upstreams.modrinth_api(),
         urlencoding::encode(query),
-        urlencoding::encode(facets)
+        urlencoding::encode(&facets.to_string())
     );
 
-    let client = get_client();
+    let client = upstreams.client();
     let response = client.get(&url)
         .send()
         .await
+        .and_then(|r| r.error_for_status())
         .context("Failed to search plugins")?;
 
     let search_result: ModrinthSearchResponse = response
@@ -74,6 +170,7 @@ pub async fn search_plugins(query: &str, server_type: &str) -> Result<Vec<Plugin
             version: String::new(), // Will be filled when installing
             description: Some(project.description),
             author: Some(project.author),
+            project_id: Some(project.project_id),
             installed: false,
         })
         .collect();
@@ -81,105 +178,487 @@ pub async fn search_plugins(query: &str, server_type: &str) -> Result<Vec<Plugin
     Ok(plugins)
 }
 
+/// An add-on file an install will put in place
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedAddon {
+    pub project_id: String,
+    pub version_id: String,
+    pub version_number: String,
+    pub file_name: String,
+    /// Pulled in as a required dependency rather than asked for
+    pub dependency: bool,
+    #[serde(skip)]
+    file: AddonFile,
+}
+
+/// What installing an add-on with its required dependencies comes to
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallPlan {
+    /// The add-on asked for first, then its dependencies
+    pub install: Vec<PlannedAddon>,
+    /// Installed files that already satisfy part of the request
+    pub satisfied: Vec<String>,
+    /// Why the set can't be installed; empty when it can
+    pub conflicts: Vec<String>,
+}
+
+/// Releases of a Modrinth project, by id or slug, that run on the server's
+/// loaders and Minecraft version, newest first
+pub async fn list_addon_versions(
+    upstreams: &Upstreams,
+    project: &str,
+    server_type: ServerType,
+    minecraft_version: &str,
+) -> Result<Vec<AddonVersion>> {
+    let loaders = addon_loaders(server_type, None)?;
+    let project = get_project(upstreams, project, provider(server_type).addon_kind()).await?;
+    project_versions(upstreams, &project.id, loaders, minecraft_version).await
+}
+
 pub async fn install_plugin(
+    upstreams: &Upstreams,
     server_dir: &Path,
-    plugin_name: &str,
+    project: &str,
+    version_id: Option<&str>,
     minecraft_version: &str,
     server_type: ServerType,
-) -> Result<()> {
-    tracing::info!("Installing plugin: {}", plugin_name);
+    cache: &ArtifactCache,
+) -> Result<InstallPlan> {
+    addon_loaders(server_type, Some(AddonKind::Plugin))?;
+    let plan = plan_addon_install(upstreams, server_dir, project, version_id, minecraft_version, server_type).await?;
+    install_addons(upstreams, cache, server_dir, server_type, &plan).await?;
+    Ok(plan)
+}
 
-    // Search for the plugin
-    let url = format!(
-        "{}/search?query={}&limit=1",
-        MODRINTH_API_BASE,
-        urlencoding::encode(plugin_name)
-    );
+pub async fn install_mod(
+    upstreams: &Upstreams,
+    server_dir: &Path,
+    project: &str,
+    version_id: Option<&str>,
+    minecraft_version: &str,
+    server_type: ServerType,
+    cache: &ArtifactCache,
+) -> Result<InstallPlan> {
+    addon_loaders(server_type, Some(AddonKind::Mod))?;
+    let plan = plan_addon_install(upstreams, server_dir, project, version_id, minecraft_version, server_type).await?;
+    install_addons(upstreams, cache, server_dir, server_type, &plan).await?;
+    Ok(plan)
+}
 
-    let client = get_client();
-    let response = client.get(&url)
+/// The loaders add-ons must be built for, refusing servers that can't load
+/// them (or not this `kind` of them)
+fn addon_loaders(server_type: ServerType, kind: Option<AddonKind>) -> Result<&'static [&'static str]> {
+    let provider = provider(server_type);
+    let kind = kind.unwrap_or(provider.addon_kind());
+    let loaders = provider.plugin_loaders();
+    if loaders.is_empty() || provider.addon_kind() != kind {
+        anyhow::bail!("{} servers don't support {}s", server_type.as_str(), kind.project_type());
+    }
+    Ok(loaders)
+}
+
+async fn get_project(upstreams: &Upstreams, project: &str, kind: AddonKind) -> Result<ModrinthProjectInfo> {
+    let response = upstreams
+        .client()
+        .get(format!("{}/project/{}", upstreams.modrinth_api(), urlencoding::encode(project)))
         .send()
         .await
-        .context("Failed to search for plugin")?;
+        .with_context(|| format!("Failed to look up {}", project))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        anyhow::bail!("{} '{}' not found", capitalized(kind), project);
+    }
 
-    let search_result: ModrinthSearchResponse = response
+    response
+        .error_for_status()
+        .with_context(|| format!("Failed to look up {}", project))?
         .json()
         .await
-        .context("Failed to parse search results")?;
+        .context("Failed to parse project")
+}
 
-    let project = search_result
-        .hits
-        .first()
-        .context("Plugin not found")?;
-
-    // Get versions for this project
-    let versions_url = format!(
-        "{}/project/{}/version",
-        MODRINTH_API_BASE,
-        project.project_id
+async fn get_version(upstreams: &Upstreams, version_id: &str) -> Result<AddonVersion> {
+    upstreams
+        .client()
+        .get(format!("{}/version/{}", upstreams.modrinth_api(), urlencoding::encode(version_id)))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .with_context(|| format!("Failed to fetch version {}", version_id))?
+        .json()
+        .await
+        .context("Failed to parse version")
+}
+
+async fn project_versions(
+    upstreams: &Upstreams,
+    project: &str,
+    loaders: &[&str],
+    minecraft_version: &str,
+) -> Result<Vec<AddonVersion>> {
+    let url = format!(
+        "{}/project/{}/version?loaders={}&game_versions={}",
+        upstreams.modrinth_api(),
+        urlencoding::encode(project),
+        urlencoding::encode(&serde_json::json!(loaders).to_string()),
+        urlencoding::encode(&serde_json::json!([minecraft_version]).to_string())
     );
 
-    let client = get_client();
-    let response = client.get(&versions_url)
+    let versions: Vec<AddonVersion> = upstreams
+        .client()
+        .get(&url)
         .send()
         .await
-        .context("Failed to fetch plugin versions")?;
-
-    let all_versions: Vec<ModrinthVersion> = response
+        .and_then(|r| r.error_for_status())
+        .context("Failed to fetch plugin versions")?
         .json()
         .await
         .context("Failed to parse versions")?;
 
-    // Find a version that matches the game version and loader
-    let loader = match server_type {
-        crate::types::ServerType::Paper => "paper",
-        crate::types::ServerType::Spigot => "spigot",
+    // Modrinth filters too, but a mirror may not
+    Ok(versions
+        .into_iter()
+        .filter(|v| v.runs_on(loaders) && v.game_versions.iter().any(|gv| gv == minecraft_version))
+        .collect())
+}
+
+/// Work out which files installing `project` (a particular release of it
+/// when `version_id` is set) takes, following required dependencies and
+/// checking them against what is already installed
+pub async fn plan_addon_install(
+    upstreams: &Upstreams,
+    server_dir: &Path,
+    project: &str,
+    version_id: Option<&str>,
+    minecraft_version: &str,
+    server_type: ServerType,
+) -> Result<InstallPlan> {
+    let kind = provider(server_type).addon_kind();
+    let loaders = addon_loaders(server_type, None)?;
+    let addons_dir = server_dir.join(kind.dir());
+
+    let requested = get_project(upstreams, project, kind).await?;
+    let root = match version_id {
+        Some(version_id) => {
+            let version = get_version(upstreams, version_id).await?;
+            if version.project_id != requested.id {
+                anyhow::bail!("Version {} isn't a release of {}", version_id, requested.title);
+            }
+            if !version.runs_on(loaders) {
+                anyhow::bail!("{} {} doesn't run on {}", requested.title, version.version_number, server_type.as_str());
+            }
+            version
+        }
+        None => project_versions(upstreams, &requested.id, loaders, minecraft_version)
+            .await?
+            .into_iter()
+            .next()
+            .with_context(|| format!("{} has no release for {} on {}", requested.title, minecraft_version, server_type.as_str()))?,
     };
 
-    let version = all_versions.iter().find(|v| {
-        let mc_match = v.game_versions.iter().any(|gv| gv == minecraft_version);
-        let loader_match = v.loaders.iter().any(|l| l.to_lowercase() == loader);
-        mc_match && loader_match
-    }).or_else(|| {
-        // Fallback: Latest version that supports the loader
-        all_versions.iter().find(|v| {
-            v.loaders.iter().any(|l| l.to_lowercase() == loader)
-        })
-    }).context("No compatible version found for this plugin and server type")?;
+    let installed = identify_installed(upstreams, &addons_dir).await?;
+    let mut plan = InstallPlan {
+        install: Vec::new(),
+        satisfied: Vec::new(),
+        conflicts: Vec::new(),
+    };
+
+    // Each project is resolved once, to the first release asked for
+    let mut resolved: HashMap<String, (String, String)> = HashMap::new();
+    let mut queue = std::collections::VecDeque::from([(root, version_id.is_some(), false)]);
+    while let Some((version, pinned, dependency)) = queue.pop_front() {
+        if let Some((id, number)) = resolved.get(&version.project_id) {
+            if pinned && *id != version.id {
+                plan.conflicts.push(format!(
+                    "{} is needed as both {} and {}",
+                    version.project_id, number, version.version_number
+                ));
+            }
+            continue;
+        }
+        resolved.insert(version.project_id.clone(), (version.id.clone(), version.version_number.clone()));
+
+        let mut conflicting = false;
+        if let Some((file_name, existing)) = installed
+            .iter()
+            .find_map(|(f, v)| v.as_ref().filter(|v| v.project_id == version.project_id).map(|v| (f, v)))
+        {
+            // A dependency is met by whatever release is there unless it
+            // needs a particular one
+            if existing.id == version.id || (dependency && !pinned) {
+                plan.satisfied.push(file_name.clone());
+                continue;
+            }
+            // Its dependencies are still worked out, so the plan shows
+            // everything that stands in the way
+            plan.conflicts.push(format!(
+                "{} is {} of {}, not {}; remove it first",
+                file_name, existing.version_number, version.project_id, version.version_number
+            ));
+            conflicting = true;
+        }
+
+        let file = version
+            .primary_file()
+            .with_context(|| format!("No files available for {} {}", version.project_id, version.version_number))?
+            .clone();
+        check_addon_file_name(&file.filename)?;
+        if !conflicting && installed.iter().any(|(f, _)| *f == file.filename) {
+            plan.conflicts.push(format!("{} already exists and isn't {}", file.filename, version.project_id));
+        }
+
+        for dep in &version.dependencies {
+            match dep.dependency_type {
+                DependencyType::Required => {
+                    let resolved = match (&dep.version_id, &dep.project_id) {
+                        (Some(id), _) => Some((get_version(upstreams, id).await?, true)),
+                        (None, Some(project_id)) => project_versions(upstreams, project_id, loaders, minecraft_version)
+                            .await?
+                            .into_iter()
+                            .next()
+                            .map(|v| (v, false)),
+                        (None, None) => continue,
+                    };
+                    match resolved {
+                        Some((dep_version, dep_pinned)) => queue.push_back((dep_version, dep_pinned, true)),
+                        None => plan.conflicts.push(format!(
+                            "{} needs {}, which has no release for {} on {}",
+                            version.project_id,
+                            dep.project_id.as_deref().unwrap_or_default(),
+                            minecraft_version,
+                            server_type.as_str()
+                        )),
+                    }
+                }
+                DependencyType::Incompatible => {
+                    let Some(project_id) = &dep.project_id else {
+                        continue;
+                    };
+                    for (file_name, _) in installed
+                        .iter()
+                        .filter(|(_, v)| v.as_ref().is_some_and(|v| v.project_id == *project_id))
+                    {
+                        plan.conflicts.push(format!("{} is incompatible with {}", version.project_id, file_name));
+                    }
+                }
+                DependencyType::Optional | DependencyType::Embedded => {}
+            }
+        }
+
+        // Installed add-ons can rule out the new one too
+        for (file_name, existing) in &installed {
+            let Some(existing) = existing else {
+                continue;
+            };
+            let excludes = existing.dependencies.iter().any(|d| {
+                d.dependency_type == DependencyType::Incompatible && d.project_id.as_deref() == Some(&version.project_id)
+            });
+            if excludes {
+                plan.conflicts.push(format!("{} is incompatible with {}", file_name, version.project_id));
+            }
+        }
+
+        if conflicting {
+            continue;
+        }
+        plan.install.push(PlannedAddon {
+            project_id: version.project_id.clone(),
+            version_id: version.id.clone(),
+            version_number: version.version_number.clone(),
+            file_name: file.filename.clone(),
+            dependency,
+            file,
+        });
+    }
+
+    Ok(plan)
+}
 
-    let file = version
-        .files
-        .first()
-        .context("No files available for this version")?;
+/// Refuse a file name from Modrinth, or the mirror standing in for it, that
+/// isn't a plain jar name and so could land outside the add-on directory
+pub(crate) fn check_addon_file_name(name: &str) -> Result<()> {
+    let mut components = Path::new(name).components();
+    let plain = matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none();
+    if !plain || !name.ends_with(".jar") {
+        anyhow::bail!("Refusing add-on file name '{}'", name);
+    }
+    Ok(())
+}
+
+/// The jars in an add-on directory with the Modrinth release each is, where
+/// Modrinth knows the file
+async fn identify_installed(
+    upstreams: &Upstreams,
+    addons_dir: &Path,
+) -> Result<Vec<(String, Option<ModrinthFileVersion>)>> {
+    let files = hash_addon_jars(addons_dir).await?;
+    let mut known = versions_by_hash(upstreams, &files).await?;
+
+    Ok(files
+        .into_iter()
+        .map(|(file_name, hash)| (file_name, known.remove(&hash)))
+        .collect())
+}
 
-    // Download the plugin
-    let plugins_dir = server_dir.join("plugins");
-    fs::create_dir_all(&plugins_dir).await?;
+/// The jars in an add-on directory with their SHA-512, by file name
+pub(crate) async fn hash_addon_jars(addons_dir: &Path) -> Result<Vec<(String, String)>> {
+    if !addons_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    let mut entries = fs::read_dir(addons_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("jar") {
+            continue;
+        }
+        let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+        files.push((file_name, sha512_file(&path).await?));
+    }
+    files.sort();
+    Ok(files)
+}
 
-    let plugin_path = plugins_dir.join(&file.filename);
+/// The Modrinth releases hashed files belong to, by hash; files Modrinth
+/// doesn't know are left out
+pub(crate) async fn versions_by_hash(
+    upstreams: &Upstreams,
+    files: &[(String, String)],
+) -> Result<HashMap<String, ModrinthFileVersion>> {
+    if files.is_empty() {
+        return Ok(HashMap::new());
+    }
 
-    let client = get_client();
-    let response = client.get(&file.url)
+    let hashes: Vec<&str> = files.iter().map(|(_, hash)| hash.as_str()).collect();
+    upstreams
+        .client()
+        .post(format!("{}/version_files", upstreams.modrinth_api()))
+        .json(&serde_json::json!({ "hashes": hashes, "algorithm": "sha512" }))
         .send()
         .await
-        .context("Failed to download plugin")?;
+        .and_then(|r| r.error_for_status())
+        .context("Failed to look up installed add-ons")?
+        .json()
+        .await
+        .context("Failed to parse installed add-ons")
+}
 
-    let bytes = response
-        .bytes()
+/// The newest release of each hashed file's project for these loaders and
+/// Minecraft version, by hash. Modrinth answers with the newest release for
+/// any version when none matches, so callers check `game_versions`.
+pub(crate) async fn latest_versions_by_hash<T: DeserializeOwned>(
+    upstreams: &Upstreams,
+    files: &[(String, String)],
+    loaders: &[&str],
+    minecraft_version: &str,
+) -> Result<HashMap<String, T>> {
+    if files.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let hashes: Vec<&str> = files.iter().map(|(_, hash)| hash.as_str()).collect();
+    upstreams
+        .client()
+        .post(format!("{}/version_files/update", upstreams.modrinth_api()))
+        .json(&serde_json::json!({
+            "hashes": hashes,
+            "algorithm": "sha512",
+            "loaders": loaders,
+            "game_versions": [minecraft_version],
+        }))
+        .send()
         .await
-        .context("Failed to read plugin bytes")?;
+        .and_then(|r| r.error_for_status())
+        .context("Failed to look up compatible add-on versions")?
+        .json()
+        .await
+        .context("Failed to parse compatible add-on versions")
+}
 
-    fs::write(&plugin_path, &bytes)
+/// Fetch a release's file into the cache, keyed by its hash when Modrinth
+/// publishes one
+pub(crate) async fn cache_addon_file(
+    upstreams: &Upstreams,
+    cache: &ArtifactCache,
+    version_id: &str,
+    file: &AddonFile,
+) -> Result<CachedArtifact> {
+    // Without a published hash the file can't be matched up by content,
+    // but a Modrinth release never changes
+    let (key, checksum) = match &file.hashes.sha512 {
+        Some(sha512) => (format!("modrinth/{}", sha512), Some(Checksum::Sha512(sha512.clone()))),
+        None => (format!("modrinth/{}/{}", version_id, file.filename), None),
+    };
+    if let Some(artifact) = cache.get(&key).await? {
+        return Ok(artifact);
+    }
+
+    let temp = cache.temp_path(&file.filename).await?;
+    download_file(upstreams.client(), &file.url, &temp, checksum.as_ref(), &Progress::none())
         .await
-        .context("Failed to write plugin file")?;
+        .with_context(|| format!("Failed to download {}", file.filename))?;
+    cache.insert(&key, &temp, &file.filename).await
+}
+
+/// Install every file of a plan or none of them: all are downloaded and
+/// verified before the first is put in place
+pub async fn install_addons(
+    upstreams: &Upstreams,
+    cache: &ArtifactCache,
+    server_dir: &Path,
+    server_type: ServerType,
+    plan: &InstallPlan,
+) -> Result<()> {
+    if !plan.conflicts.is_empty() {
+        anyhow::bail!("Can't install: {}", plan.conflicts.join("; "));
+    }
+    let kind = provider(server_type).addon_kind();
+
+    let mut artifacts = Vec::new();
+    for addon in &plan.install {
+        tracing::info!("Downloading {} {} {}", kind.project_type(), addon.project_id, addon.version_number);
+        let artifact = cache_addon_file(upstreams, cache, &addon.version_id, &addon.file).await?;
+        artifacts.push((addon, artifact));
+    }
+
+    let addons_dir = server_dir.join(kind.dir());
+    fs::create_dir_all(&addons_dir).await?;
+
+    let mut linked = Vec::new();
+    for (addon, artifact) in artifacts {
+        let path = addons_dir.join(&addon.file_name);
+        if let Err(e) = cache.copy_into(&artifact, &path).await {
+            for path in linked {
+                let _ = fs::remove_file(path).await;
+            }
+            return Err(e);
+        }
+        linked.push(path);
+        tracing::info!("Successfully installed {}: {}", kind.project_type(), addon.file_name);
+    }
 
-    tracing::info!("Successfully installed plugin: {}", plugin_name);
     Ok(())
 }
 
+/// A jar in a server's plugins or mods directory
+#[derive(Debug, Clone, Serialize)]
+pub struct InstalledAddon {
+    /// Name of the jar, which identifies the add-on for removal
+    pub file_name: String,
+    #[serde(flatten)]
+    pub metadata: PluginMetadata,
+}
 
-pub async fn list_installed_plugins(server_dir: &Path) -> Result<Vec<PluginInfo>> {
-    let plugins_dir = server_dir.join("plugins");
+pub async fn list_installed_plugins(server_dir: &Path) -> Result<Vec<InstalledAddon>> {
+    list_installed_addons(server_dir, AddonKind::Plugin).await
+}
+
+pub async fn list_installed_mods(server_dir: &Path) -> Result<Vec<InstalledAddon>> {
+    list_installed_addons(server_dir, AddonKind::Mod).await
+}
+
+async fn list_installed_addons(server_dir: &Path, kind: AddonKind) -> Result<Vec<InstalledAddon>> {
+    let plugins_dir = server_dir.join(kind.dir());
     
     if !plugins_dir.exists() {
         return Ok(Vec::new());
@@ -195,35 +674,151 @@ pub async fn list_installed_plugins(server_dir: &Path) -> Result<Vec<PluginInfo>
             continue;
         }
 
-        let name = path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        plugins.push(PluginInfo {
-            name,
-            version: String::from("unknown"),
-            description: None,
-            author: None,
-            installed: true,
+        let Some(file_name) = path.file_name().and_then(|s| s.to_str()).map(String::from) else {
+            continue;
+        };
+        let metadata = match kind {
+            AddonKind::Plugin => read_metadata(path.clone()).await,
+            AddonKind::Mod => None,
+        };
+        let metadata = metadata.unwrap_or_else(|| PluginMetadata {
+            name: path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string(),
+            ..Default::default()
         });
+
+        plugins.push(InstalledAddon { file_name, metadata });
     }
 
+    plugins.sort_by(|a, b| a.file_name.cmp(&b.file_name));
     Ok(plugins)
 }
 
-pub async fn remove_plugin(server_dir: &Path, plugin_name: &str) -> Result<()> {
-    let plugins_dir = server_dir.join("plugins");
-    let plugin_path = plugins_dir.join(format!("{}.jar", plugin_name));
+/// A broken jar is listed by its file name rather than failing the listing
+async fn read_metadata(path: std::path::PathBuf) -> Option<PluginMetadata> {
+    let result = tokio::task::spawn_blocking(move || read_plugin_metadata(&path)).await;
+    match result {
+        Ok(Ok(metadata)) => metadata,
+        Ok(Err(e)) => {
+            tracing::warn!("{:#}", e);
+            None
+        }
+        Err(e) => {
+            tracing::warn!("Failed to read plugin metadata: {}", e);
+            None
+        }
+    }
+}
+
+/// Remove a plugin by its jar's file name, or by the name it declares
+pub async fn remove_plugin(server_dir: &Path, plugin: &str) -> Result<()> {
+    remove_addon(server_dir, plugin, AddonKind::Plugin).await
+}
+
+pub async fn remove_mod(server_dir: &Path, mod_file: &str) -> Result<()> {
+    remove_addon(server_dir, mod_file, AddonKind::Mod).await
+}
+
+async fn remove_addon(server_dir: &Path, plugin: &str, kind: AddonKind) -> Result<()> {
+    if plugin.is_empty() || plugin.contains(['/', '\\']) || plugin.starts_with('.') {
+        anyhow::bail!("Invalid {} name '{}'", kind.project_type(), plugin);
+    }
+
+    let file_name = if plugin.ends_with(".jar") {
+        plugin.to_string()
+    } else {
+        format!("{}.jar", plugin)
+    };
+    let mut plugin_path = server_dir.join(kind.dir()).join(file_name);
 
     if !plugin_path.exists() {
-        anyhow::bail!("Plugin '{}' not found", plugin_name);
+        let declared = list_installed_addons(server_dir, kind)
+            .await?
+            .into_iter()
+            .find(|a| a.metadata.platform.is_some() && a.metadata.name.eq_ignore_ascii_case(plugin));
+        match declared {
+            Some(addon) => plugin_path = server_dir.join(kind.dir()).join(addon.file_name),
+            None => anyhow::bail!("{} '{}' not found", capitalized(kind), plugin),
+        }
     }
 
     fs::remove_file(&plugin_path)
         .await
-        .context("Failed to remove plugin")?;
+        .with_context(|| format!("Failed to remove {}", kind.project_type()))?;
 
     Ok(())
 }
+
+fn capitalized(kind: AddonKind) -> &'static str {
+    match kind {
+        AddonKind::Plugin => "Plugin",
+        AddonKind::Mod => "Mod",
+    }
+}
+
+/// Whether an installed add-on has a release for another Minecraft version
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compatibility {
+    Compatible,
+    Incompatible,
+    /// Not published on Modrinth, so it can't be checked
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AddonCompatibility {
+    pub file_name: String,
+    pub project_id: Option<String>,
+    /// Modrinth version number of the installed file
+    pub installed_version: Option<String>,
+    /// Newest release that runs on the target version
+    pub compatible_version: Option<String>,
+    pub compatibility: Compatibility,
+}
+
+/// Check each installed plugin or mod against Modrinth for a release that
+/// runs on `minecraft_version`, matching the installed files by hash
+pub async fn check_addon_compatibility(
+    upstreams: &Upstreams,
+    server_dir: &Path,
+    server_type: ServerType,
+    minecraft_version: &str,
+) -> Result<Vec<AddonCompatibility>> {
+    let provider = provider(server_type);
+    let addons_dir = server_dir.join(provider.addon_kind().dir());
+    // Proxy plugins follow the proxy's API rather than Minecraft versions
+    if server_type.is_proxy() || provider.plugin_loaders().is_empty() || !addons_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let files = hash_addon_jars(&addons_dir).await?;
+    let installed = versions_by_hash(upstreams, &files).await?;
+    let updates: HashMap<String, ModrinthFileVersion> =
+        latest_versions_by_hash(upstreams, &files, provider.plugin_loaders(), minecraft_version).await?;
+
+    Ok(files
+        .into_iter()
+        .map(|(file_name, hash)| {
+            let installed = installed.get(&hash);
+            let update = updates
+                .get(&hash)
+                .filter(|v| v.game_versions.iter().any(|gv| gv == minecraft_version));
+            let compatibility = match (installed, update) {
+                (None, _) => Compatibility::Unknown,
+                (Some(_), Some(_)) => Compatibility::Compatible,
+                (Some(_), None) => Compatibility::Incompatible,
+            };
+            AddonCompatibility {
+                file_name,
+                project_id: installed.map(|v| v.project_id.clone()),
+                installed_version: installed.map(|v| v.version_number.clone()),
+                compatible_version: update.map(|v| v.version_number.clone()),
+                compatibility,
+            }
+        })
+        .collect())
+}
@@ -1,6 +1,8 @@
-use crate::types::ServerConfig;
+use crate::console::{ConsoleCommand, LogLevel, LogLine, LogParser};
+use crate::providers::BEDROCK_BINARY;
+use crate::types::{ServerConfig, ServerType};
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -14,24 +16,30 @@ pub struct ServerProcess {
     base_dir: PathBuf,
     child: Arc<RwLock<Option<Child>>>,
     stdin_tx: Option<mpsc::UnboundedSender<String>>,
-    output_tx: broadcast::Sender<String>,
+    output_tx: broadcast::Sender<LogLine>,
+    command_tx: broadcast::Sender<ConsoleCommand>,
+    force_upgrade: bool,
 }
 
 impl ServerProcess {
     pub fn new(config: ServerConfig, base_dir: PathBuf) -> Self {
         let (output_tx, _) = broadcast::channel(1000);
+        let (command_tx, _) = broadcast::channel(100);
         Self {
             config,
             base_dir,
             child: Arc::new(RwLock::new(None)),
             stdin_tx: None,
             output_tx,
+            command_tx,
+            force_upgrade: false,
         }
     }
 
     /// Create a ServerProcess from an existing PID (recovery scenario)
     pub fn from_pid(config: ServerConfig, base_dir: PathBuf, _pid: u32) -> Self {
         let (output_tx, _) = broadcast::channel(1000);
+        let (command_tx, _) = broadcast::channel(100);
         
         // Note: For recovered processes, we currenty don't have access to stdin/stdout
         // as they were owned by the previous parent process.
@@ -43,56 +51,28 @@ impl ServerProcess {
             child: Arc::new(RwLock::new(None)), // We don't have the Child object for recovered processes
             stdin_tx: None,
             output_tx,
+            command_tx,
+            force_upgrade: false,
         }
     }
 
+    /// Have the next start convert every chunk to the current version with
+    /// `--forceUpgrade`, rather than as they are loaded
+    pub fn set_force_upgrade(&mut self, force_upgrade: bool) {
+        self.force_upgrade = force_upgrade;
+    }
+
     pub async fn start(
         &mut self,
     ) -> Result<u32> {
         let server_dir = self.config.server_dir(&self.base_dir);
-        let jar_path = server_dir.join("server.jar");
-
-        if !jar_path.exists() {
-            anyhow::bail!("Server JAR not found at {:?}", jar_path);
-        }
-
-        // Accept EULA
-        let eula_path = server_dir.join("eula.txt");
-        tokio::fs::write(&eula_path, "eula=true\n")
-            .await
-            .context("Failed to write eula.txt")?;
-
-        // Build JVM arguments
-        let memory_arg = format!("-Xmx{}M", self.config.memory_mb);
-        let min_memory_arg = format!("-Xms{}M", self.config.memory_mb / 2);
+        let mut command = if self.config.server_type.is_bedrock() {
+            bedrock_command(&server_dir)?
+        } else {
+            self.java_command(&server_dir).await?
+        };
 
-        let mut child = Command::new("java")
-            .arg(&min_memory_arg)
-            .arg(&memory_arg)
-            .arg("-XX:+UseG1GC")
-            .arg("-XX:+ParallelRefProcEnabled")
-            .arg("-XX:MaxGCPauseMillis=200")
-            .arg("-XX:+UnlockExperimentalVMOptions")
-            .arg("-XX:+DisableExplicitGC")
-            .arg("-XX:+AlwaysPreTouch")
-            .arg("-XX:G1NewSizePercent=30")
-            .arg("-XX:G1MaxNewSizePercent=40")
-            .arg("-XX:G1HeapRegionSize=8M")
-            .arg("-XX:G1ReservePercent=20")
-            .arg("-XX:G1HeapWastePercent=5")
-            .arg("-XX:G1MixedGCCountTarget=4")
-            .arg("-XX:InitiatingHeapOccupancyPercent=15")
-            .arg("-XX:G1MixedGCLiveThresholdPercent=90")
-            .arg("-XX:G1RSetUpdatingPauseTimePercent=5")
-            .arg("-XX:SurvivorRatio=32")
-            .arg("-XX:+PerfDisableSharedMem")
-            .arg("-XX:MaxTenuringThreshold=1")
-            .arg("-XX:+ExitOnOutOfMemoryError")
-            .arg("-Dusing.aikars.flags=https://mcflags.emc.gs")
-            .arg("-Daikars.new.flags=true")
-            .arg("-jar")
-            .arg("server.jar")
-            .arg("--nogui")
+        let mut child = command
             .current_dir(&server_dir)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -135,16 +115,18 @@ impl ServerProcess {
         tokio::spawn(async move {
             let reader = BufReader::new(stdout);
             let mut lines = LinesStream::new(reader.lines());
+            let mut parser = LogParser::new(LogLevel::Info);
             while let Some(Ok(line)) = lines.next().await {
-                let _ = output_tx.send(line);
+                let _ = output_tx.send(parser.parse_console(&line, false));
             }
         });
 
         tokio::spawn(async move {
             let reader = BufReader::new(stderr);
             let mut lines = LinesStream::new(reader.lines());
+            let mut parser = LogParser::new(LogLevel::Error);
             while let Some(Ok(line)) = lines.next().await {
-                let _ = output_tx_err.send(format!("[ERROR] {}", line));
+                let _ = output_tx_err.send(parser.parse_console(&line, true));
             }
         });
 
@@ -154,10 +136,72 @@ impl ServerProcess {
         Ok(pid)
     }
 
+    async fn java_command(&self, server_dir: &Path) -> Result<Command> {
+        let launch_args = launch_args(self.config.server_type, server_dir).await?;
+
+        // Accept EULA
+        let eula_path = server_dir.join("eula.txt");
+        tokio::fs::write(&eula_path, "eula=true\n")
+            .await
+            .context("Failed to write eula.txt")?;
+
+        // Build JVM arguments
+        let memory_arg = format!("-Xmx{}M", self.config.memory_mb);
+        let min_memory_arg = format!("-Xms{}M", self.config.memory_mb / 2);
+
+        // Proxies have no GUI and reject the flag
+        let gui_args: &[&str] = if self.config.server_type.is_proxy() { &[] } else { &["--nogui"] };
+        let upgrade_args: &[&str] = if self.force_upgrade && !self.config.server_type.is_proxy() {
+            &["--forceUpgrade"]
+        } else {
+            &[]
+        };
+
+        let mut command = Command::new("java");
+        command
+            .arg(&min_memory_arg)
+            .arg(&memory_arg)
+            .arg("-XX:+UseG1GC")
+            .arg("-XX:+ParallelRefProcEnabled")
+            .arg("-XX:MaxGCPauseMillis=200")
+            .arg("-XX:+UnlockExperimentalVMOptions")
+            .arg("-XX:+DisableExplicitGC")
+            .arg("-XX:+AlwaysPreTouch")
+            .arg("-XX:G1NewSizePercent=30")
+            .arg("-XX:G1MaxNewSizePercent=40")
+            .arg("-XX:G1HeapRegionSize=8M")
+            .arg("-XX:G1ReservePercent=20")
+            .arg("-XX:G1HeapWastePercent=5")
+            .arg("-XX:G1MixedGCCountTarget=4")
+            .arg("-XX:InitiatingHeapOccupancyPercent=15")
+            .arg("-XX:G1MixedGCLiveThresholdPercent=90")
+            .arg("-XX:G1RSetUpdatingPauseTimePercent=5")
+            .arg("-XX:SurvivorRatio=32")
+            .arg("-XX:+PerfDisableSharedMem")
+            .arg("-XX:MaxTenuringThreshold=1")
+            .arg("-XX:+ExitOnOutOfMemoryError")
+            .arg("-Dusing.aikars.flags=https://mcflags.emc.gs")
+            .arg("-Daikars.new.flags=true")
+            .args(&launch_args)
+            .args(gui_args)
+            .args(upgrade_args);
+        Ok(command)
+    }
+
     pub async fn send_command(&self, command: String) -> Result<()> {
+        self.send_command_as(command, None).await
+    }
+
+    /// Send a command on behalf of `user`, announcing it to command subscribers
+    pub async fn send_command_as(&self, command: String, user: Option<String>) -> Result<()> {
         if let Some(tx) = &self.stdin_tx {
-            tx.send(command)
+            tx.send(command.clone())
                 .context("Failed to send command to server")?;
+            let _ = self.command_tx.send(ConsoleCommand {
+                timestamp: chrono::Utc::now().timestamp(),
+                command,
+                user,
+            });
             Ok(())
         } else {
             // For recovered processes, we can't send commands via stdin easily
@@ -211,10 +255,40 @@ impl ServerProcess {
         Ok(())
     }
 
-    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+    pub fn subscribe(&self) -> broadcast::Receiver<LogLine> {
         self.output_tx.subscribe()
     }
 
+    pub fn subscribe_commands(&self) -> broadcast::Receiver<ConsoleCommand> {
+        self.command_tx.subscribe()
+    }
+
+    /// Ask the server how many players are online using the `list` command
+    pub async fn query_player_count(&self) -> Result<u32> {
+        let mut rx = self.subscribe();
+        self.send_command("list".to_string()).await?;
+
+        let wait = async {
+            loop {
+                match rx.recv().await {
+                    Ok(line) => {
+                        if let Some(count) = parse_player_count(&line.message) {
+                            return Ok(count);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => {
+                        anyhow::bail!("Server output closed")
+                    }
+                }
+            }
+        };
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), wait)
+            .await
+            .context("Timed out waiting for player list")?
+    }
+
     pub fn get_child(&self) -> Arc<RwLock<Option<Child>>> {
         self.child.clone()
     }
@@ -241,3 +315,49 @@ impl ServerProcess {
     }
 }
 
+/// What to run after the JVM flags. Forge and NeoForge installs launch
+/// through the `@libraries/.../unix_args.txt` argument file their `run.sh`
+/// names; their `user_jvm_args.txt` is skipped in favour of our own memory
+/// flags. Everything else runs `server.jar`, even with a `run.sh` of the
+/// admin's own next to it.
+async fn launch_args(server_type: ServerType, server_dir: &Path) -> Result<Vec<String>> {
+    let run_script = server_dir.join("run.sh");
+    let script = match server_type {
+        ServerType::Forge | ServerType::NeoForge => tokio::fs::read_to_string(&run_script).await.ok(),
+        _ => None,
+    };
+    if let Some(script) = script {
+        let args_file = script
+            .split_whitespace()
+            .find(|token| token.starts_with("@libraries/") && token.ends_with("unix_args.txt"))
+            .context("run.sh doesn't name a libraries argument file")?;
+        return Ok(vec![args_file.to_string()]);
+    }
+
+    let jar_path = server_dir.join("server.jar");
+    if !jar_path.exists() {
+        anyhow::bail!("Server JAR not found at {:?}", jar_path);
+    }
+    Ok(vec!["-jar".to_string(), "server.jar".to_string()])
+}
+
+/// Bedrock Dedicated Server runs natively and loads its bundled libraries
+/// from the server directory
+fn bedrock_command(server_dir: &Path) -> Result<Command> {
+    let binary = server_dir.join(BEDROCK_BINARY);
+    if !binary.exists() {
+        anyhow::bail!("Bedrock server not found at {:?}", binary);
+    }
+
+    let mut command = Command::new(binary);
+    command.env("LD_LIBRARY_PATH", ".");
+    Ok(command)
+}
+
+/// Parse the reply to `list`, e.g. "There are 3 of a max of 20 players online: ..."
+/// or the older "There are 3/20 players online:". Bedrock uses the latter.
+fn parse_player_count(line: &str) -> Option<u32> {
+    let rest = &line[line.find("There are ")? + "There are ".len()..];
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
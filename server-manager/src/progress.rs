@@ -0,0 +1,73 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone)]
+pub enum ProgressEvent {
+    /// Completion from 0 to 100
+    Percent(f32),
+    Log(String),
+}
+
+/// Handle passed into long-running operations to report progress and
+/// notice cancellation. `Progress::none()` discards everything.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    tx: Option<mpsc::UnboundedSender<ProgressEvent>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Progress {
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<ProgressEvent>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let progress = Self {
+            tx: Some(tx),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
+        (progress, rx)
+    }
+
+    pub fn none() -> Self {
+        Self {
+            tx: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn percent(&self, percent: f32) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(ProgressEvent::Percent(percent.clamp(0.0, 100.0)));
+        }
+    }
+
+    pub fn log(&self, line: impl Into<String>) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(ProgressEvent::Log(line.into()));
+        }
+    }
+
+    /// Report `done` out of `total` units of work
+    pub fn fraction(&self, done: u64, total: u64) {
+        if total > 0 {
+            self.percent(done as f32 * 100.0 / total as f32);
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Fail with an error if the operation has been cancelled. Blocking code
+    /// can't be aborted, so it calls this between units of work.
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            anyhow::bail!("Cancelled");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+use super::{fetch_into_cache, get_json, jar_key, ServerBuild, ServerProvider};
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::io::Cursor;
+use std::path::Path;
+use tokio::fs;
+
+/// The server binary inside the BDS zip
+pub const BEDROCK_BINARY: &str = "bedrock_server";
+
+/// Shipped with the zip, but ours to keep once the server has been set up
+const PRESERVED_FILES: [&str; 3] = ["server.properties", "allowlist.json", "permissions.json"];
+
+#[derive(Debug, Deserialize)]
+struct DownloadLinks {
+    result: DownloadLinksResult,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadLinksResult {
+    links: Vec<DownloadLink>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadLink {
+    download_type: String,
+    download_url: String,
+}
+
+/// Bedrock Dedicated Server for Linux. Mojang only advertises the current
+/// release, so older versions come from the cache, e.g. uploaded archives.
+/// Each version is its own single build.
+pub struct BedrockProvider;
+
+#[async_trait]
+impl ServerProvider for BedrockProvider {
+    fn server_type(&self) -> ServerType {
+        ServerType::Bedrock
+    }
+
+    fn plugin_loaders(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    async fn list_versions(&self, upstreams: &Upstreams) -> Result<Vec<String>> {
+        let links: DownloadLinks = get_json(upstreams, upstreams.bedrock_versions(), "Bedrock versions").await?;
+        let link = links
+            .result
+            .links
+            .iter()
+            .find(|l| l.download_type == "serverBedrockLinux")
+            .context("No Bedrock Dedicated Server for Linux is listed")?;
+
+        let version = version_from_url(&link.download_url)
+            .with_context(|| format!("Unrecognised Bedrock download {}", link.download_url))?;
+        Ok(vec![version])
+    }
+
+    async fn list_builds(&self, _upstreams: &Upstreams, version: &str) -> Result<Vec<ServerBuild>> {
+        Ok(vec![ServerBuild {
+            id: version.to_string(),
+            channel: None,
+            changes: Vec::new(),
+        }])
+    }
+
+    async fn fetch(
+        &self,
+        upstreams: &Upstreams,
+        cache: &ArtifactCache,
+        version: &str,
+        build: Option<&str>,
+        progress: &Progress,
+    ) -> Result<CachedArtifact> {
+        if build.is_some_and(|b| b != version) {
+            anyhow::bail!("Bedrock {} has no build {}", version, build.unwrap_or_default());
+        }
+        if !is_valid_version(version) {
+            anyhow::bail!("Invalid Bedrock version {}", version);
+        }
+
+        fetch_into_cache(
+            upstreams,
+            cache,
+            &jar_key(ServerType::Bedrock, version, version),
+            &upstreams.bedrock_download_url(version),
+            &format!("bedrock-server-{}.zip", version),
+            None,
+            progress,
+        )
+        .await
+    }
+
+    /// Unpack the zip over the server directory, keeping its config files
+    async fn install(
+        &self,
+        cache: &ArtifactCache,
+        artifact: &CachedArtifact,
+        destination: &Path,
+        progress: &Progress,
+    ) -> Result<()> {
+        let server_dir = destination.parent().context("Server jar has no parent directory")?;
+        let archive = server_dir.join("bedrock-server.zip");
+        cache.copy_into(artifact, &archive).await?;
+
+        progress.log(format!("Unpacking {}", artifact.file_name));
+        let data = fs::read(&archive).await.context("Failed to read Bedrock archive")?;
+        let _ = fs::remove_file(&archive).await;
+
+        let server_dir = server_dir.to_path_buf();
+        let progress = progress.clone();
+        tokio::task::spawn_blocking(move || extract_server(&data, &server_dir, &progress)).await?
+    }
+}
+
+/// Add a Bedrock Dedicated Server zip to the cache as `version`, for hosts
+/// that can't download it themselves
+pub async fn import_bedrock_server(cache: &ArtifactCache, version: &str, data: Vec<u8>) -> Result<CachedArtifact> {
+    if !is_valid_version(version) {
+        anyhow::bail!("Invalid Bedrock version {}", version);
+    }
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(&data)).context("Not a valid zip archive")?;
+    if archive.by_name(BEDROCK_BINARY).is_err() {
+        anyhow::bail!("Archive has no {}; is it the Linux server?", BEDROCK_BINARY);
+    }
+
+    let file_name = format!("bedrock-server-{}.zip", version);
+    let temp = cache.temp_path(&file_name).await?;
+    fs::write(&temp, &data).await.context("Failed to store Bedrock archive")?;
+
+    cache
+        .insert(&jar_key(ServerType::Bedrock, version, version), &temp, &file_name)
+        .await
+}
+
+/// `bedrock-server-1.21.51.02.zip` names version `1.21.51.02`
+fn version_from_url(url: &str) -> Option<String> {
+    let name = url.rsplit('/').next()?;
+    let version = name.strip_prefix("bedrock-server-")?.strip_suffix(".zip")?;
+    is_valid_version(version).then(|| version.to_string())
+}
+
+fn is_valid_version(version: &str) -> bool {
+    !version.is_empty()
+        && version.split('.').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn extract_server(data: &[u8], server_dir: &Path, progress: &Progress) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).context("Failed to open Bedrock archive")?;
+    let total = archive.len() as u64;
+
+    for i in 0..archive.len() {
+        progress.check_cancelled()?;
+        progress.fraction(i as u64, total);
+
+        let mut entry = archive.by_index(i).context("Failed to read Bedrock archive")?;
+        let Some(relative) = entry.enclosed_name() else {
+            continue;
+        };
+        let outpath = server_dir.join(&relative);
+
+        if entry.is_dir() {
+            std::fs::create_dir_all(&outpath).context("Failed to create server directory")?;
+            continue;
+        }
+        if PRESERVED_FILES.iter().any(|f| relative == Path::new(f)) && outpath.exists() {
+            continue;
+        }
+        if let Some(parent) = outpath.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create server directory")?;
+        }
+
+        let mut outfile = std::fs::File::create(&outpath).context("Failed to create server file")?;
+        std::io::copy(&mut entry, &mut outfile).context("Failed to extract server file")?;
+
+        if let Some(mode) = entry.unix_mode() {
+            std::fs::set_permissions(&outpath, std::fs::Permissions::from_mode(mode & 0o777))?;
+        }
+    }
+
+    // Not every zip tool records the executable bit
+    let binary = server_dir.join(BEDROCK_BINARY);
+    if !binary.exists() {
+        anyhow::bail!("Archive has no {}; is it the Linux server?", BEDROCK_BINARY);
+    }
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755))?;
+
+    Ok(())
+}
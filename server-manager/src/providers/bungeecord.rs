@@ -0,0 +1,103 @@
+use super::{fetch_into_cache, get_json, jar_key, ServerBuild, ServerProvider};
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+/// BungeeCord isn't versioned per Minecraft release; one rolling line
+/// supports them all
+const VERSION: &str = "latest";
+
+#[derive(Debug, Deserialize)]
+struct JenkinsJob {
+    builds: Vec<JenkinsBuild>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JenkinsBuild {
+    number: u32,
+    result: Option<String>,
+}
+
+/// BungeeCord from md_5's Jenkins. Builds are Jenkins build numbers.
+pub struct BungeeCordProvider;
+
+#[async_trait]
+impl ServerProvider for BungeeCordProvider {
+    fn server_type(&self) -> ServerType {
+        ServerType::BungeeCord
+    }
+
+    fn plugin_loaders(&self) -> &'static [&'static str] {
+        &["bungeecord"]
+    }
+
+    async fn list_versions(&self, _upstreams: &Upstreams) -> Result<Vec<String>> {
+        Ok(vec![VERSION.to_string()])
+    }
+
+    async fn list_builds(&self, upstreams: &Upstreams, version: &str) -> Result<Vec<ServerBuild>> {
+        if version != VERSION {
+            anyhow::bail!("BungeeCord has no version {}", version);
+        }
+
+        // Newest first, including failed and running builds
+        let url = format!("{}/api/json?tree=builds[number,result]", upstreams.bungeecord_ci());
+        let job: JenkinsJob = get_json(upstreams, &url, "BungeeCord builds").await?;
+        Ok(job
+            .builds
+            .into_iter()
+            .rev()
+            .filter(|b| b.result.as_deref() == Some("SUCCESS"))
+            .map(|b| ServerBuild {
+                id: b.number.to_string(),
+                channel: None,
+                changes: Vec::new(),
+            })
+            .collect())
+    }
+
+    async fn fetch(
+        &self,
+        upstreams: &Upstreams,
+        cache: &ArtifactCache,
+        version: &str,
+        build: Option<&str>,
+        progress: &Progress,
+    ) -> Result<CachedArtifact> {
+        if version != VERSION {
+            anyhow::bail!("BungeeCord has no version {}", version);
+        }
+
+        let build = match build {
+            Some(build) => build.to_string(),
+            None => {
+                let url = format!("{}/lastSuccessfulBuild/api/json?tree=number,result", upstreams.bungeecord_ci());
+                let latest: JenkinsBuild = get_json(upstreams, &url, "BungeeCord build").await?;
+                latest.number.to_string()
+            }
+        };
+
+        let url = format!(
+            "{}/{}/artifact/bootstrap/target/BungeeCord.jar",
+            upstreams.bungeecord_ci(),
+            build
+        );
+        let key = jar_key(ServerType::BungeeCord, VERSION, &build);
+
+        // Jenkins publishes no checksums for artifacts
+        fetch_into_cache(
+            upstreams,
+            cache,
+            &key,
+            &url,
+            &format!("BungeeCord-{}.jar", build),
+            None,
+            progress,
+        )
+        .await
+    }
+}
@@ -0,0 +1,128 @@
+use super::{fetch_into_cache, get_json, jar_key, AddonKind, ServerBuild, ServerProvider};
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct FabricGameVersion {
+    version: String,
+    stable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct FabricLoaderEntry {
+    loader: FabricComponent,
+}
+
+#[derive(Debug, Deserialize)]
+struct FabricComponent {
+    version: String,
+    stable: bool,
+}
+
+/// Fabric's meta API serves a launcher jar that fetches the vanilla server
+/// and loader libraries on first start. Builds are loader versions.
+pub struct FabricProvider;
+
+impl FabricProvider {
+    /// Loader versions for a game version, newest first
+    async fn loaders(&self, upstreams: &Upstreams, version: &str) -> Result<Vec<FabricComponent>> {
+        let url = format!("{}/versions/loader/{}", upstreams.fabric_meta(), version);
+        let entries: Vec<FabricLoaderEntry> = get_json(upstreams, &url, "Fabric loaders").await?;
+        Ok(entries.into_iter().map(|e| e.loader).collect())
+    }
+}
+
+/// Newest stable version, or the newest at all when nothing is marked stable
+fn newest_stable(components: &[FabricComponent]) -> Option<String> {
+    components
+        .iter()
+        .find(|c| c.stable)
+        .or(components.first())
+        .map(|c| c.version.clone())
+}
+
+#[async_trait]
+impl ServerProvider for FabricProvider {
+    fn server_type(&self) -> ServerType {
+        ServerType::Fabric
+    }
+
+    fn plugin_loaders(&self) -> &'static [&'static str] {
+        &["fabric"]
+    }
+
+    fn addon_kind(&self) -> AddonKind {
+        AddonKind::Mod
+    }
+
+    async fn list_versions(&self, upstreams: &Upstreams) -> Result<Vec<String>> {
+        // Newest first and includes snapshots
+        let url = format!("{}/versions/game", upstreams.fabric_meta());
+        let versions: Vec<FabricGameVersion> = get_json(upstreams, &url, "Fabric versions").await?;
+        Ok(versions
+            .into_iter()
+            .rev()
+            .filter(|v| v.stable)
+            .map(|v| v.version)
+            .collect())
+    }
+
+    async fn list_builds(&self, upstreams: &Upstreams, version: &str) -> Result<Vec<ServerBuild>> {
+        Ok(self
+            .loaders(upstreams, version)
+            .await?
+            .into_iter()
+            .rev()
+            .map(|l| ServerBuild {
+                id: l.version,
+                channel: Some(if l.stable { "stable" } else { "beta" }.to_string()),
+                changes: Vec::new(),
+            })
+            .collect())
+    }
+
+    async fn fetch(
+        &self,
+        upstreams: &Upstreams,
+        cache: &ArtifactCache,
+        version: &str,
+        build: Option<&str>,
+        progress: &Progress,
+    ) -> Result<CachedArtifact> {
+        let loader = match build {
+            Some(build) => build.to_string(),
+            None => newest_stable(&self.loaders(upstreams, version).await?)
+                .with_context(|| format!("Fabric doesn't support Minecraft {}", version))?,
+        };
+
+        let key = jar_key(ServerType::Fabric, version, &loader);
+        if let Some(cached) = cache.get(&key).await? {
+            progress.log(format!("Using cached {}", cached.file_name));
+            return Ok(cached);
+        }
+
+        let url = format!("{}/versions/installer", upstreams.fabric_meta());
+        let installers: Vec<FabricComponent> = get_json(upstreams, &url, "Fabric installers").await?;
+        let installer = newest_stable(&installers).context("No Fabric installer available")?;
+
+        let url = format!(
+            "{}/versions/loader/{}/{}/{}/server/jar",
+            upstreams.fabric_meta(),
+            version,
+            loader,
+            installer
+        );
+        let file_name = format!(
+            "fabric-server-mc.{}-loader.{}-launcher.{}.jar",
+            version, loader, installer
+        );
+
+        // The meta API publishes no checksums for launcher jars
+        fetch_into_cache(upstreams, cache, &key, &url, &file_name, None, progress).await
+    }
+}
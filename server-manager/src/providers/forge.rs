@@ -0,0 +1,216 @@
+use super::{
+    compare_versions, fetch_into_cache, get_json, jar_key, maven_sha1, AddonKind, ServerBuild,
+    ServerProvider,
+};
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::fs;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+#[derive(Debug, Deserialize)]
+struct ForgePromotions {
+    promos: HashMap<String, String>,
+}
+
+impl ForgePromotions {
+    /// Recommended and latest build of a Minecraft version, oldest first
+    fn builds(&self, version: &str) -> Vec<ServerBuild> {
+        let recommended = self.promos.get(&format!("{}-recommended", version));
+        let latest = self.promos.get(&format!("{}-latest", version));
+
+        let mut builds: Vec<ServerBuild> = Vec::new();
+        for (build, channel) in [(recommended, "recommended"), (latest, "latest")] {
+            let Some(build) = build else { continue };
+            if builds.iter().any(|b| &b.id == build) {
+                continue;
+            }
+            builds.push(ServerBuild {
+                id: build.clone(),
+                channel: Some(channel.to_string()),
+                changes: Vec::new(),
+            });
+        }
+        builds.sort_by(|a, b| compare_versions(&a.id, &b.id));
+        builds
+    }
+}
+
+/// Minecraft Forge. Builds are Forge versions, installed by running Forge's
+/// installer in the server directory.
+pub struct ForgeProvider;
+
+impl ForgeProvider {
+    async fn promotions(&self, upstreams: &Upstreams) -> Result<ForgePromotions> {
+        get_json(upstreams, upstreams.forge_promotions(), "Forge promotions").await
+    }
+}
+
+#[async_trait]
+impl ServerProvider for ForgeProvider {
+    fn server_type(&self) -> ServerType {
+        ServerType::Forge
+    }
+
+    fn plugin_loaders(&self) -> &'static [&'static str] {
+        &["forge"]
+    }
+
+    fn addon_kind(&self) -> AddonKind {
+        AddonKind::Mod
+    }
+
+    async fn list_versions(&self, upstreams: &Upstreams) -> Result<Vec<String>> {
+        let promotions = self.promotions(upstreams).await?;
+
+        // Keys look like `1.21.1-latest`; skip pre-release oddities
+        let mut versions: Vec<String> = promotions
+            .promos
+            .keys()
+            .filter_map(|key| key.rsplit_once('-').map(|(version, _)| version))
+            .filter(|v| v.chars().all(|c| c.is_ascii_digit() || c == '.'))
+            .map(str::to_string)
+            .collect();
+        versions.sort_by(|a, b| compare_versions(a, b));
+        versions.dedup();
+        Ok(versions)
+    }
+
+    async fn list_builds(&self, upstreams: &Upstreams, version: &str) -> Result<Vec<ServerBuild>> {
+        Ok(self.promotions(upstreams).await?.builds(version))
+    }
+
+    async fn fetch(
+        &self,
+        upstreams: &Upstreams,
+        cache: &ArtifactCache,
+        version: &str,
+        build: Option<&str>,
+        progress: &Progress,
+    ) -> Result<CachedArtifact> {
+        let build = match build {
+            Some(build) => build.to_string(),
+            None => {
+                let builds = self.promotions(upstreams).await?.builds(version);
+                builds
+                    .iter()
+                    .find(|b| b.channel.as_deref() == Some("recommended"))
+                    .or(builds.last())
+                    .with_context(|| format!("Forge doesn't support Minecraft {}", version))?
+                    .id
+                    .clone()
+            }
+        };
+
+        let key = jar_key(ServerType::Forge, version, &build);
+        if let Some(cached) = cache.get(&key).await? {
+            progress.log(format!("Using cached {}", cached.file_name));
+            return Ok(cached);
+        }
+
+        let file_name = format!("forge-{}-{}-installer.jar", version, build);
+        let url = format!(
+            "{}/net/minecraftforge/forge/{}-{}/{}",
+            upstreams.forge_maven(),
+            version,
+            build,
+            file_name
+        );
+        let checksum = maven_sha1(upstreams, &url).await;
+
+        fetch_into_cache(upstreams, cache, &key, &url, &file_name, checksum, progress).await
+    }
+
+    async fn install(
+        &self,
+        cache: &ArtifactCache,
+        artifact: &CachedArtifact,
+        destination: &Path,
+        progress: &Progress,
+    ) -> Result<()> {
+        run_installer(cache, artifact, destination, progress).await
+    }
+}
+
+/// Run a Forge-style installer headlessly next to `destination`.
+///
+/// Installers since Minecraft 1.17 leave a `run.sh` that launches through
+/// `@libraries/...` argument files, which the process picks up on start.
+/// Older ones produce a runnable jar, which becomes `destination`.
+pub(super) async fn run_installer(
+    cache: &ArtifactCache,
+    artifact: &CachedArtifact,
+    destination: &Path,
+    progress: &Progress,
+) -> Result<()> {
+    let server_dir = destination.parent().context("Server jar has no parent directory")?;
+    let installer = server_dir.join("installer.jar");
+    cache.copy_into(artifact, &installer).await?;
+
+    tracing::info!("Running {} (this may take a while)...", artifact.file_name);
+    progress.log(format!("Running {}", artifact.file_name));
+    let mut child = tokio::process::Command::new("java")
+        .arg("-jar")
+        .arg("installer.jar")
+        .arg("--installServer")
+        .current_dir(server_dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // Cancelling the job drops this future; don't leave the installer running
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to run installer")?;
+
+    let stdout = child.stdout.take().context("Failed to get installer stdout")?;
+    let stderr = child.stderr.take().context("Failed to get installer stderr")?;
+
+    let stderr_task = tokio::spawn(async move {
+        let mut lines = BufReader::new(stderr).lines();
+        let mut collected = Vec::new();
+        while let Ok(Some(line)) = lines.next_line().await {
+            collected.push(line);
+        }
+        collected
+    });
+
+    let mut lines = BufReader::new(stdout).lines();
+    while let Some(line) = lines.next_line().await? {
+        progress.check_cancelled()?;
+        progress.log(line);
+    }
+
+    let status = child.wait().await.context("Failed to wait for installer")?;
+    let stderr_lines = stderr_task.await.unwrap_or_default();
+
+    let _ = fs::remove_file(&installer).await;
+    let _ = fs::remove_file(server_dir.join("installer.jar.log")).await;
+
+    if !status.success() {
+        anyhow::bail!("Installer failed: {}", stderr_lines.join("\n"));
+    }
+
+    if fs::try_exists(server_dir.join("run.sh")).await? {
+        return Ok(());
+    }
+
+    // Legacy layout: a forge-<version>.jar (or -universal.jar) to run directly
+    let mut entries = fs::read_dir(server_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.ends_with(".jar") && name.contains("forge-") && !name.contains("installer") {
+            fs::rename(entry.path(), destination)
+                .await
+                .context("Failed to move server jar into place")?;
+            return Ok(());
+        }
+    }
+
+    anyhow::bail!("Installer produced neither run.sh nor a server jar")
+}
@@ -0,0 +1,213 @@
+//! Server distributions: where their versions, builds and jars come from
+
+mod bedrock;
+mod bungeecord;
+mod fabric;
+mod forge;
+mod neoforge;
+mod papermc;
+mod purpur;
+mod spigot;
+mod vanilla;
+
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::downloader::{download_file, Checksum};
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::path::Path;
+
+pub use bedrock::{import_bedrock_server, BedrockProvider, BEDROCK_BINARY};
+pub use bungeecord::BungeeCordProvider;
+pub use fabric::FabricProvider;
+pub use forge::ForgeProvider;
+pub use neoforge::NeoForgeProvider;
+pub use papermc::PaperMcProvider;
+pub use purpur::PurpurProvider;
+pub use spigot::SpigotProvider;
+pub use vanilla::VanillaProvider;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerBuild {
+    /// Build identifier as the upstream names it, e.g. `130`
+    pub id: String,
+    /// Release channel when the upstream has one, e.g. `experimental`
+    pub channel: Option<String>,
+    /// Commits that went into the build, when the upstream lists them
+    pub changes: Vec<BuildChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildChange {
+    pub commit: String,
+    pub summary: String,
+}
+
+/// How a distribution is extended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddonKind {
+    Plugin,
+    Mod,
+}
+
+impl AddonKind {
+    /// Directory in the server where add-on jars go
+    pub fn dir(&self) -> &'static str {
+        match self {
+            AddonKind::Plugin => "plugins",
+            AddonKind::Mod => "mods",
+        }
+    }
+
+    /// Modrinth `project_type` facet value
+    pub fn project_type(&self) -> &'static str {
+        match self {
+            AddonKind::Plugin => "plugin",
+            AddonKind::Mod => "mod",
+        }
+    }
+}
+
+#[async_trait]
+pub trait ServerProvider: Send + Sync {
+    fn server_type(&self) -> ServerType;
+
+    /// Modrinth loaders whose add-ons run on this distribution, best match
+    /// first. Empty when it can't load any.
+    fn plugin_loaders(&self) -> &'static [&'static str];
+
+    fn addon_kind(&self) -> AddonKind {
+        AddonKind::Plugin
+    }
+
+    /// Whether builds of a version are numbered in release order, so a
+    /// server can be told about builds newer than its own
+    fn tracks_builds(&self) -> bool {
+        false
+    }
+
+    /// Minecraft versions, oldest first
+    async fn list_versions(&self, upstreams: &Upstreams) -> Result<Vec<String>>;
+
+    /// Builds of a Minecraft version, oldest first
+    async fn list_builds(&self, upstreams: &Upstreams, version: &str) -> Result<Vec<ServerBuild>>;
+
+    /// Put the server jar for a build into the cache; the latest build when
+    /// `build` is `None`
+    async fn fetch(
+        &self,
+        upstreams: &Upstreams,
+        cache: &ArtifactCache,
+        version: &str,
+        build: Option<&str>,
+        progress: &Progress,
+    ) -> Result<CachedArtifact>;
+
+    /// Turn a fetched artifact into a runnable server at `destination`, the
+    /// `server.jar` in the server directory. Most distributions run the jar
+    /// as-is.
+    async fn install(
+        &self,
+        cache: &ArtifactCache,
+        artifact: &CachedArtifact,
+        destination: &Path,
+        _progress: &Progress,
+    ) -> Result<()> {
+        cache.copy_into(artifact, destination).await
+    }
+}
+
+pub fn provider(server_type: ServerType) -> &'static dyn ServerProvider {
+    match server_type {
+        ServerType::Paper => &PaperMcProvider::PAPER,
+        ServerType::Folia => &PaperMcProvider::FOLIA,
+        ServerType::Purpur => &PurpurProvider,
+        ServerType::Spigot => &SpigotProvider,
+        ServerType::Vanilla => &VanillaProvider,
+        ServerType::Fabric => &FabricProvider,
+        ServerType::Forge => &ForgeProvider,
+        ServerType::NeoForge => &NeoForgeProvider,
+        ServerType::Velocity => &PaperMcProvider::VELOCITY,
+        ServerType::Waterfall => &PaperMcProvider::WATERFALL,
+        ServerType::BungeeCord => &BungeeCordProvider,
+        ServerType::Bedrock => &BedrockProvider,
+    }
+}
+
+/// Cache key of a server jar. All builds of a version share the
+/// `type/version/` prefix, which the offline fallback relies on.
+pub(crate) fn jar_key(server_type: ServerType, version: &str, build: &str) -> String {
+    format!("{}/{}/{}", server_type.as_str(), version, build)
+}
+
+pub(crate) async fn get_json<T: DeserializeOwned>(upstreams: &Upstreams, url: &str, what: &str) -> Result<T> {
+    upstreams
+        .client()
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .with_context(|| format!("Failed to fetch {}", what))?
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse {}", what))
+}
+
+/// Download a jar into the cache under `key` unless it is already there
+pub(crate) async fn fetch_into_cache(
+    upstreams: &Upstreams,
+    cache: &ArtifactCache,
+    key: &str,
+    url: &str,
+    file_name: &str,
+    checksum: Option<Checksum>,
+    progress: &Progress,
+) -> Result<CachedArtifact> {
+    if let Some(cached) = cache.get(key).await? {
+        progress.log(format!("Using cached {}", cached.file_name));
+        return Ok(cached);
+    }
+
+    progress.log(format!("Downloading {}", file_name));
+    let temp = cache.temp_path(file_name).await?;
+    download_file(upstreams.client(), url, &temp, checksum.as_ref(), progress).await?;
+
+    cache.insert(key, &temp, file_name).await
+}
+
+/// The `.sha1` Maven publishes next to an artifact, if the repository has one
+pub(crate) async fn maven_sha1(upstreams: &Upstreams, url: &str) -> Option<Checksum> {
+    let response = upstreams
+        .client()
+        .get(format!("{}.sha1", url))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status());
+
+    match response {
+        Ok(response) => {
+            let text = response.text().await.ok()?;
+            // Some repositories append the file name after the digest
+            let hex = text.split_whitespace().next()?.to_lowercase();
+            Some(Checksum::Sha1(hex))
+        }
+        Err(e) => {
+            tracing::warn!("No checksum for {}: {}", url, e);
+            None
+        }
+    }
+}
+
+/// Order dotted version numbers numerically, so `1.9` sorts before `1.10`
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |v: &str| -> Vec<u64> {
+        v.split(|c: char| !c.is_ascii_digit())
+            .filter_map(|p| p.parse().ok())
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
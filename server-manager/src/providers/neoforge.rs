@@ -0,0 +1,143 @@
+use super::forge::run_installer;
+use super::{
+    compare_versions, fetch_into_cache, get_json, jar_key, maven_sha1, AddonKind, ServerBuild,
+    ServerProvider,
+};
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+struct MavenVersions {
+    versions: Vec<String>,
+}
+
+/// Minecraft version a NeoForge version targets: `21.1.77` is for `1.21.1`,
+/// `21.0.167` for `1.21`
+fn minecraft_version(neoforge: &str) -> Option<String> {
+    let mut parts = neoforge.split('.');
+    let major: u32 = parts.next()?.parse().ok()?;
+    let minor: u32 = parts.next()?.parse().ok()?;
+    match minor {
+        0 => Some(format!("1.{}", major)),
+        _ => Some(format!("1.{}.{}", major, minor)),
+    }
+}
+
+/// NeoForge, installed like Forge. Builds are NeoForge versions.
+pub struct NeoForgeProvider;
+
+impl NeoForgeProvider {
+    /// All NeoForge versions, oldest first
+    async fn all_builds(&self, upstreams: &Upstreams) -> Result<Vec<String>> {
+        let url = format!(
+            "{}/api/maven/versions/releases/net/neoforged/neoforge",
+            upstreams.neoforge_maven()
+        );
+        let mut versions = get_json::<MavenVersions>(upstreams, &url, "NeoForge versions")
+            .await?
+            .versions;
+        versions.sort_by(|a, b| compare_versions(a, b));
+        Ok(versions)
+    }
+}
+
+#[async_trait]
+impl ServerProvider for NeoForgeProvider {
+    fn server_type(&self) -> ServerType {
+        ServerType::NeoForge
+    }
+
+    fn plugin_loaders(&self) -> &'static [&'static str] {
+        &["neoforge"]
+    }
+
+    fn addon_kind(&self) -> AddonKind {
+        AddonKind::Mod
+    }
+
+    async fn list_versions(&self, upstreams: &Upstreams) -> Result<Vec<String>> {
+        let mut versions: Vec<String> = self
+            .all_builds(upstreams)
+            .await?
+            .iter()
+            .filter_map(|v| minecraft_version(v))
+            .collect();
+        versions.sort_by(|a, b| compare_versions(a, b));
+        versions.dedup();
+        Ok(versions)
+    }
+
+    async fn list_builds(&self, upstreams: &Upstreams, version: &str) -> Result<Vec<ServerBuild>> {
+        Ok(self
+            .all_builds(upstreams)
+            .await?
+            .into_iter()
+            .filter(|b| minecraft_version(b).as_deref() == Some(version))
+            .map(|id| ServerBuild {
+                channel: Some(if id.contains("-beta") { "beta" } else { "release" }.to_string()),
+                id,
+                changes: Vec::new(),
+            })
+            .collect())
+    }
+
+    async fn fetch(
+        &self,
+        upstreams: &Upstreams,
+        cache: &ArtifactCache,
+        version: &str,
+        build: Option<&str>,
+        progress: &Progress,
+    ) -> Result<CachedArtifact> {
+        let build = match build {
+            Some(build) if minecraft_version(build).as_deref() != Some(version) => {
+                anyhow::bail!("NeoForge {} is not for Minecraft {}", build, version)
+            }
+            Some(build) => build.to_string(),
+            None => {
+                let builds = self.list_builds(upstreams, version).await?;
+                builds
+                    .iter()
+                    .rev()
+                    .find(|b| b.channel.as_deref() == Some("release"))
+                    .or(builds.last())
+                    .with_context(|| format!("NeoForge doesn't support Minecraft {}", version))?
+                    .id
+                    .clone()
+            }
+        };
+
+        let key = jar_key(ServerType::NeoForge, version, &build);
+        if let Some(cached) = cache.get(&key).await? {
+            progress.log(format!("Using cached {}", cached.file_name));
+            return Ok(cached);
+        }
+
+        let file_name = format!("neoforge-{}-installer.jar", build);
+        let url = format!(
+            "{}/releases/net/neoforged/neoforge/{}/{}",
+            upstreams.neoforge_maven(),
+            build,
+            file_name
+        );
+        let checksum = maven_sha1(upstreams, &url).await;
+
+        fetch_into_cache(upstreams, cache, &key, &url, &file_name, checksum, progress).await
+    }
+
+    async fn install(
+        &self,
+        cache: &ArtifactCache,
+        artifact: &CachedArtifact,
+        destination: &Path,
+        progress: &Progress,
+    ) -> Result<()> {
+        run_installer(cache, artifact, destination, progress).await
+    }
+}
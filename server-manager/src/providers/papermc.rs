@@ -0,0 +1,174 @@
+use super::{fetch_into_cache, get_json, jar_key, BuildChange, ServerBuild, ServerProvider};
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::downloader::Checksum;
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct PaperVersions {
+    versions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PaperBuilds {
+    builds: Vec<PaperBuild>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PaperBuild {
+    build: u32,
+    channel: String,
+    #[serde(default)]
+    changes: Vec<BuildChange>,
+    downloads: PaperDownloads,
+}
+
+#[derive(Debug, Deserialize)]
+struct PaperDownloads {
+    application: PaperApplication,
+}
+
+#[derive(Debug, Deserialize)]
+struct PaperApplication {
+    name: String,
+    sha256: String,
+}
+
+/// Projects published through the PaperMC downloads API
+pub struct PaperMcProvider {
+    server_type: ServerType,
+    project: &'static str,
+    name: &'static str,
+    loaders: &'static [&'static str],
+}
+
+impl PaperMcProvider {
+    pub const PAPER: Self = Self {
+        server_type: ServerType::Paper,
+        project: "paper",
+        name: "Paper",
+        loaders: &["paper", "spigot", "bukkit"],
+    };
+
+    /// Folia's threading breaks most Bukkit plugins, so only Folia-aware ones
+    pub const FOLIA: Self = Self {
+        server_type: ServerType::Folia,
+        project: "folia",
+        name: "Folia",
+        loaders: &["folia"],
+    };
+
+    pub const VELOCITY: Self = Self {
+        server_type: ServerType::Velocity,
+        project: "velocity",
+        name: "Velocity",
+        loaders: &["velocity"],
+    };
+
+    pub const WATERFALL: Self = Self {
+        server_type: ServerType::Waterfall,
+        project: "waterfall",
+        name: "Waterfall",
+        loaders: &["waterfall", "bungeecord"],
+    };
+
+    async fn builds(&self, upstreams: &Upstreams, version: &str) -> Result<Vec<PaperBuild>> {
+        let url = format!(
+            "{}/projects/{}/versions/{}/builds",
+            upstreams.paper_api(),
+            self.project,
+            version
+        );
+        let builds: PaperBuilds = get_json(upstreams, &url, &format!("{} builds", self.name)).await?;
+        Ok(builds.builds)
+    }
+}
+
+#[async_trait]
+impl ServerProvider for PaperMcProvider {
+    fn server_type(&self) -> ServerType {
+        self.server_type
+    }
+
+    fn plugin_loaders(&self) -> &'static [&'static str] {
+        self.loaders
+    }
+
+    fn tracks_builds(&self) -> bool {
+        true
+    }
+
+    async fn list_versions(&self, upstreams: &Upstreams) -> Result<Vec<String>> {
+        let url = format!("{}/projects/{}", upstreams.paper_api(), self.project);
+        let versions: PaperVersions = get_json(upstreams, &url, &format!("{} versions", self.name)).await?;
+        Ok(versions.versions)
+    }
+
+    async fn list_builds(&self, upstreams: &Upstreams, version: &str) -> Result<Vec<ServerBuild>> {
+        Ok(self
+            .builds(upstreams, version)
+            .await?
+            .into_iter()
+            .map(|b| ServerBuild {
+                id: b.build.to_string(),
+                channel: Some(b.channel.to_lowercase()),
+                changes: b.changes,
+            })
+            .collect())
+    }
+
+    async fn fetch(
+        &self,
+        upstreams: &Upstreams,
+        cache: &ArtifactCache,
+        version: &str,
+        build: Option<&str>,
+        progress: &Progress,
+    ) -> Result<CachedArtifact> {
+        // A pinned build that is already cached needs no API round trip
+        if let Some(build) = build {
+            if let Some(cached) = cache.get(&jar_key(self.server_type, version, build)).await? {
+                progress.log(format!("Using cached {}", cached.file_name));
+                return Ok(cached);
+            }
+        }
+
+        let builds = self.builds(upstreams, version).await?;
+        let chosen = match build {
+            Some(id) => builds
+                .into_iter()
+                .find(|b| b.build.to_string() == id)
+                .with_context(|| format!("{} {} has no build {}", self.name, version, id))?,
+            None => builds
+                .into_iter()
+                .last()
+                .context("No builds available for this version")?,
+        };
+
+        let application = chosen.downloads.application;
+        let url = format!(
+            "{}/projects/{}/versions/{}/builds/{}/downloads/{}",
+            upstreams.paper_api(),
+            self.project,
+            version,
+            chosen.build,
+            application.name
+        );
+        let key = jar_key(self.server_type, version, &chosen.build.to_string());
+
+        fetch_into_cache(
+            upstreams,
+            cache,
+            &key,
+            &url,
+            &application.name,
+            Some(Checksum::Sha256(application.sha256)),
+            progress,
+        )
+        .await
+    }
+}
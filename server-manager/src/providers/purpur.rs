@@ -0,0 +1,102 @@
+use super::{fetch_into_cache, get_json, jar_key, ServerBuild, ServerProvider};
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::downloader::Checksum;
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct PurpurVersions {
+    versions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PurpurVersion {
+    builds: PurpurBuilds,
+}
+
+#[derive(Debug, Deserialize)]
+struct PurpurBuilds {
+    latest: String,
+    all: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PurpurBuildInfo {
+    md5: String,
+}
+
+pub struct PurpurProvider;
+
+#[async_trait]
+impl ServerProvider for PurpurProvider {
+    fn server_type(&self) -> ServerType {
+        ServerType::Purpur
+    }
+
+    fn plugin_loaders(&self) -> &'static [&'static str] {
+        &["purpur", "paper", "spigot", "bukkit"]
+    }
+
+    async fn list_versions(&self, upstreams: &Upstreams) -> Result<Vec<String>> {
+        let versions: PurpurVersions = get_json(upstreams, upstreams.purpur_api(), "Purpur versions").await?;
+        Ok(versions.versions)
+    }
+
+    async fn list_builds(&self, upstreams: &Upstreams, version: &str) -> Result<Vec<ServerBuild>> {
+        let url = format!("{}/{}", upstreams.purpur_api(), version);
+        let version: PurpurVersion = get_json(upstreams, &url, "Purpur builds").await?;
+
+        Ok(version
+            .builds
+            .all
+            .into_iter()
+            .map(|id| ServerBuild {
+                id,
+                channel: None,
+                changes: Vec::new(),
+            })
+            .collect())
+    }
+
+    async fn fetch(
+        &self,
+        upstreams: &Upstreams,
+        cache: &ArtifactCache,
+        version: &str,
+        build: Option<&str>,
+        progress: &Progress,
+    ) -> Result<CachedArtifact> {
+        let build = match build {
+            Some(build) => build.to_string(),
+            None => {
+                let url = format!("{}/{}", upstreams.purpur_api(), version);
+                let info: PurpurVersion = get_json(upstreams, &url, "Purpur builds").await?;
+                info.builds.latest
+            }
+        };
+
+        let key = jar_key(ServerType::Purpur, version, &build);
+        if let Some(cached) = cache.get(&key).await? {
+            progress.log(format!("Using cached {}", cached.file_name));
+            return Ok(cached);
+        }
+
+        let build_url = format!("{}/{}/{}", upstreams.purpur_api(), version, build);
+        let info: PurpurBuildInfo = get_json(upstreams, &build_url, "Purpur build info").await?;
+
+        fetch_into_cache(
+            upstreams,
+            cache,
+            &key,
+            &format!("{}/download", build_url),
+            &format!("purpur-{}-{}.jar", version, build),
+            Some(Checksum::Md5(info.md5)),
+            progress,
+        )
+        .await
+    }
+}
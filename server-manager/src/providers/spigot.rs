@@ -0,0 +1,189 @@
+use super::{compare_versions, jar_key, ServerBuild, ServerProvider};
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::downloader::download_file;
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+/// Spigot has no downloads; each version is compiled locally with BuildTools.
+/// Its builds are what BuildTools can `--compile`, Spigot last so that it is
+/// the default.
+const BUILDS: &[&str] = &["craftbukkit", "spigot"];
+
+/// What servers created before CraftBukkit could be chosen recorded
+const LEGACY_BUILD: &str = "buildtools";
+
+/// Lines of BuildTools' error output kept for the failure message
+const STDERR_TAIL: usize = 20;
+
+pub struct SpigotProvider;
+
+#[async_trait]
+impl ServerProvider for SpigotProvider {
+    fn server_type(&self) -> ServerType {
+        ServerType::Spigot
+    }
+
+    fn plugin_loaders(&self) -> &'static [&'static str] {
+        &["spigot", "bukkit"]
+    }
+
+    async fn list_versions(&self, upstreams: &Upstreams) -> Result<Vec<String>> {
+        // The index is a directory listing of `<version>.json` files, next
+        // to numbered ones for individual builds and pre-releases
+        let listing = upstreams
+            .client()
+            .get(format!("{}/", upstreams.spigot_versions()))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("Failed to fetch Spigot versions")?
+            .text()
+            .await
+            .context("Failed to read Spigot versions")?;
+
+        let pattern = Regex::new(r#"href="(\d+\.\d+(?:\.\d+)?)\.json""#)?;
+        let mut versions: Vec<String> = pattern
+            .captures_iter(&listing)
+            .map(|c| c[1].to_string())
+            .collect();
+        versions.sort_by(|a, b| compare_versions(a, b));
+        versions.dedup();
+
+        if versions.is_empty() {
+            anyhow::bail!("No versions in the Spigot versions index");
+        }
+        Ok(versions)
+    }
+
+    async fn list_builds(&self, _upstreams: &Upstreams, _version: &str) -> Result<Vec<ServerBuild>> {
+        Ok(BUILDS
+            .iter()
+            .map(|b| ServerBuild {
+                id: b.to_string(),
+                channel: None,
+                changes: Vec::new(),
+            })
+            .collect())
+    }
+
+    async fn fetch(
+        &self,
+        upstreams: &Upstreams,
+        cache: &ArtifactCache,
+        version: &str,
+        build: Option<&str>,
+        progress: &Progress,
+    ) -> Result<CachedArtifact> {
+        let target = match build {
+            None | Some(LEGACY_BUILD) => "spigot",
+            Some(build) if BUILDS.contains(&build) => build,
+            Some(build) => anyhow::bail!("Spigot {} has no build {}", version, build),
+        };
+        let name = if target == "spigot" { "Spigot" } else { "CraftBukkit" };
+
+        let key = jar_key(ServerType::Spigot, version, target);
+        if let Some(cached) = cache.get(&key).await? {
+            progress.log(format!("Using cached {} {}", name, version));
+            return Ok(cached);
+        }
+
+        // BuildTools keeps its checkouts in a work directory shared by all
+        // builds, with the Maven repository inside it so dependencies are
+        // only downloaded once
+        let (build_dir, _build_lock) = cache.build_dir().await?;
+
+        // Another job may have built it while we waited for the work directory
+        if let Some(cached) = cache.get(&key).await? {
+            progress.log(format!("Using cached {} {}", name, version));
+            return Ok(cached);
+        }
+
+        let buildtools_path = build_dir.join("BuildTools.jar");
+
+        // Download BuildTools if not exists
+        if !buildtools_path.exists() {
+            tracing::info!("Downloading Spigot BuildTools...");
+            progress.log("Downloading Spigot BuildTools");
+            download_file(
+                upstreams.client(),
+                upstreams.spigot_buildtools_url(),
+                &buildtools_path,
+                None,
+                &Progress::none(),
+            )
+            .await?;
+        }
+
+        // BuildTools runs Maven with MAVEN_OPTS, adding a heap size when unset
+        let maven_opts = format!(
+            "{} -Dmaven.repo.local={}",
+            std::env::var("MAVEN_OPTS").unwrap_or_else(|_| "-Xmx1024M".to_string()),
+            build_dir.join("maven").display()
+        );
+
+        tracing::info!("Building {} {} (this may take a while)...", name, version);
+        progress.log(format!("Building {} {} with BuildTools", name, version));
+        let mut child = tokio::process::Command::new("java")
+            .arg("-jar")
+            .arg(&buildtools_path)
+            .arg("--rev")
+            .arg(version)
+            .arg("--compile")
+            .arg(target)
+            .env("MAVEN_OPTS", maven_opts)
+            .current_dir(&build_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // Cancelling the job drops this future; don't leave the build running
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to run BuildTools")?;
+
+        let stdout = child.stdout.take().context("Failed to get BuildTools stdout")?;
+        let stderr = child.stderr.take().context("Failed to get BuildTools stderr")?;
+
+        let stderr_progress = progress.clone();
+        let stderr_task = tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            let mut tail = std::collections::VecDeque::new();
+            while let Ok(Some(line)) = lines.next_line().await {
+                stderr_progress.log(line.clone());
+                if tail.len() == STDERR_TAIL {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            }
+            tail
+        });
+
+        let mut lines = BufReader::new(stdout).lines();
+        while let Some(line) = lines.next_line().await? {
+            progress.check_cancelled()?;
+            progress.log(line);
+        }
+
+        let status = child.wait().await.context("Failed to wait for BuildTools")?;
+        let stderr_lines = stderr_task.await.unwrap_or_default();
+
+        if !status.success() {
+            anyhow::bail!("BuildTools failed: {}", Vec::from(stderr_lines).join("\n"));
+        }
+
+        // Find the built JAR and move it into the cache
+        let file_name = format!("{}-{}.jar", target, version);
+        let built_jar = build_dir.join(&file_name);
+        if !built_jar.exists() {
+            anyhow::bail!("Built JAR not found at {:?}", built_jar);
+        }
+
+        let artifact = cache.insert(&key, &built_jar, &file_name).await?;
+        tracing::info!("Successfully built {} {}", name, version);
+        Ok(artifact)
+    }
+}
@@ -0,0 +1,128 @@
+use super::{fetch_into_cache, get_json, jar_key, ServerBuild, ServerProvider};
+use crate::cache::{ArtifactCache, CachedArtifact};
+use crate::downloader::Checksum;
+use crate::progress::Progress;
+use crate::types::ServerType;
+use crate::upstream::Upstreams;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct VersionManifest {
+    versions: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestEntry {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionInfo {
+    downloads: VersionDownloads,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionDownloads {
+    server: Option<VersionDownload>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionDownload {
+    sha1: String,
+    url: String,
+}
+
+/// Mojang's own server. There is exactly one jar per version, so its only
+/// build is named after the version.
+pub struct VanillaProvider;
+
+impl VanillaProvider {
+    async fn manifest(&self, upstreams: &Upstreams) -> Result<VersionManifest> {
+        get_json(upstreams, upstreams.mojang_version_manifest(), "Minecraft version manifest").await
+    }
+}
+
+#[async_trait]
+impl ServerProvider for VanillaProvider {
+    fn server_type(&self) -> ServerType {
+        ServerType::Vanilla
+    }
+
+    fn plugin_loaders(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    async fn list_versions(&self, upstreams: &Upstreams) -> Result<Vec<String>> {
+        // The manifest is newest first and includes snapshots
+        let manifest = self.manifest(upstreams).await?;
+        Ok(manifest
+            .versions
+            .into_iter()
+            .rev()
+            .filter(|v| v.kind == "release")
+            .map(|v| v.id)
+            .collect())
+    }
+
+    async fn list_builds(&self, upstreams: &Upstreams, version: &str) -> Result<Vec<ServerBuild>> {
+        let manifest = self.manifest(upstreams).await?;
+        Ok(manifest
+            .versions
+            .into_iter()
+            .filter(|v| v.id == version)
+            .map(|v| ServerBuild {
+                id: v.id,
+                channel: Some(v.kind),
+                changes: Vec::new(),
+            })
+            .collect())
+    }
+
+    async fn fetch(
+        &self,
+        upstreams: &Upstreams,
+        cache: &ArtifactCache,
+        version: &str,
+        build: Option<&str>,
+        progress: &Progress,
+    ) -> Result<CachedArtifact> {
+        if let Some(build) = build.filter(|b| *b != version) {
+            anyhow::bail!("Minecraft {} has no build {}", version, build);
+        }
+
+        let key = jar_key(ServerType::Vanilla, version, version);
+        if let Some(cached) = cache.get(&key).await? {
+            progress.log(format!("Using cached {}", cached.file_name));
+            return Ok(cached);
+        }
+
+        let manifest = self.manifest(upstreams).await?;
+        let entry = manifest
+            .versions
+            .into_iter()
+            .find(|v| v.id == version)
+            .with_context(|| format!("Unknown Minecraft version {}", version))?;
+
+        let info: VersionInfo = get_json(upstreams, &entry.url, "Minecraft version info").await?;
+        let server = info
+            .downloads
+            .server
+            .with_context(|| format!("Minecraft {} has no server download", version))?;
+
+        fetch_into_cache(
+            upstreams,
+            cache,
+            &key,
+            &server.url,
+            &format!("minecraft_server.{}.jar", version),
+            Some(Checksum::Sha1(server.sha1)),
+            progress,
+        )
+        .await
+    }
+}
@@ -0,0 +1,54 @@
+//! Deciding when console-line triggers fire: a number of matches within a
+//! window, and no more often than a cooldown allows.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How often matches must occur for a trigger to fire, and how long it then
+/// waits before it can fire again
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    /// Matches needed within `window`
+    pub threshold: u32,
+    pub window: Duration,
+    pub cooldown: Duration,
+}
+
+/// Recent match times of one trigger
+#[derive(Debug, Clone, Default)]
+pub struct MatchRate {
+    matches: VecDeque<Instant>,
+    last_fired: Option<Instant>,
+}
+
+impl MatchRate {
+    /// Record a match and report whether the trigger should fire
+    pub fn record(&mut self, limit: &RateLimit, now: Instant) -> bool {
+        self.matches.push_back(now);
+        while self
+            .matches
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > limit.window)
+        {
+            self.matches.pop_front();
+        }
+
+        if self.matches.len() < limit.threshold.max(1) as usize {
+            return false;
+        }
+
+        if self.last_fired.is_some_and(|t| now.duration_since(t) < limit.cooldown) {
+            return false;
+        }
+
+        self.matches.clear();
+        self.last_fired = Some(now);
+        true
+    }
+
+    /// Drop the matches counted so far but stay in cooldown, for a trigger
+    /// whose pattern changed
+    pub fn forget_matches(&mut self) {
+        self.matches.clear();
+    }
+}
@@ -0,0 +1,142 @@
+//! Server jars supplied by the user rather than downloaded: patched forks,
+//! internal builds and the like. The jar they replace is kept next to it so a
+//! bad build can be rolled back.
+
+use crate::downloader::sha256_file;
+use crate::types::ServerType;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::io::{Cursor, Read};
+use std::path::Path;
+use tokio::fs;
+
+pub const SERVER_JAR: &str = "server.jar";
+/// The jar `server.jar` replaced, for rollback
+pub const PREVIOUS_SERVER_JAR: &str = "server.jar.previous";
+
+/// What an uploaded jar says about itself
+#[derive(Debug, Clone)]
+pub struct JarInfo {
+    /// `Main-Class` from the manifest
+    pub main_class: String,
+    pub sha256: String,
+    /// Minecraft version from the `version.json` Mojang, Paper and most
+    /// forks bundle, if present
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionJson {
+    id: String,
+}
+
+/// Check that `data` is a runnable jar, i.e. its manifest names a
+/// `Main-Class` that the jar contains
+pub fn inspect_jar(data: &[u8]) -> Result<JarInfo> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).context("Not a valid jar")?;
+
+    let mut manifest = String::new();
+    archive
+        .by_name("META-INF/MANIFEST.MF")
+        .context("Jar has no manifest")?
+        .read_to_string(&mut manifest)
+        .context("Failed to read jar manifest")?;
+    let main_class = manifest_attribute(&manifest, "Main-Class")
+        .context("Jar manifest has no Main-Class; it isn't runnable")?;
+
+    let class_file = format!("{}.class", main_class.replace('.', "/"));
+    if archive.by_name(&class_file).is_err() {
+        anyhow::bail!("Jar doesn't contain its Main-Class {}", main_class);
+    }
+
+    let version = archive.by_name("version.json").ok().and_then(|mut entry| {
+        let mut json = String::new();
+        entry.read_to_string(&mut json).ok()?;
+        serde_json::from_str::<VersionJson>(&json).ok().map(|v| v.id)
+    });
+
+    Ok(JarInfo {
+        main_class,
+        sha256: format!("{:x}", Sha256::digest(data)),
+        version,
+    })
+}
+
+/// Manifest values wrap at 72 bytes onto lines starting with a space
+fn manifest_attribute(manifest: &str, name: &str) -> Option<String> {
+    let mut lines = manifest.lines().peekable();
+    while let Some(line) = lines.next() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        if !key.trim().eq_ignore_ascii_case(name) {
+            continue;
+        }
+
+        let mut value = value.trim_start().to_string();
+        while let Some(continuation) = lines.peek().and_then(|l| l.strip_prefix(' ')) {
+            value.push_str(continuation);
+            lines.next();
+        }
+        let value = value.trim().to_string();
+        return (!value.is_empty()).then_some(value);
+    }
+    None
+}
+
+/// Whether the server launches from `server.jar`. Bedrock runs natively and
+/// modern Forge and NeoForge installs launch through `run.sh`'s argument file.
+pub fn runs_server_jar(server_type: ServerType, server_dir: &Path) -> bool {
+    match server_type {
+        ServerType::Forge | ServerType::NeoForge => !server_dir.join("run.sh").exists(),
+        _ => !server_type.is_bedrock(),
+    }
+}
+
+/// Write `data` as the server jar, keeping the current one as
+/// [`PREVIOUS_SERVER_JAR`]
+pub async fn install_server_jar(server_dir: &Path, data: &[u8]) -> Result<()> {
+    let jar = server_dir.join(SERVER_JAR);
+    let temp = server_dir.join(format!("{}.uploading", SERVER_JAR));
+    fs::write(&temp, data).await.context("Failed to write server jar")?;
+
+    keep_previous_jar(server_dir).await?;
+    fs::rename(&temp, &jar).await.context("Failed to install server jar")
+}
+
+/// Move the current server jar aside as [`PREVIOUS_SERVER_JAR`], replacing
+/// any older one
+pub async fn keep_previous_jar(server_dir: &Path) -> Result<()> {
+    let jar = server_dir.join(SERVER_JAR);
+    if !jar.exists() {
+        return Ok(());
+    }
+    fs::rename(&jar, server_dir.join(PREVIOUS_SERVER_JAR))
+        .await
+        .context("Failed to keep previous server jar")
+}
+
+/// Swap the server jar with the one it replaced
+pub async fn rollback_server_jar(server_dir: &Path) -> Result<()> {
+    let jar = server_dir.join(SERVER_JAR);
+    let previous = server_dir.join(PREVIOUS_SERVER_JAR);
+    if !previous.exists() {
+        anyhow::bail!("There is no previous server jar to roll back to");
+    }
+
+    let swap = server_dir.join(format!("{}.swap", SERVER_JAR));
+    if jar.exists() {
+        fs::rename(&jar, &swap).await.context("Failed to move server jar aside")?;
+    }
+    fs::rename(&previous, &jar).await.context("Failed to restore previous server jar")?;
+    if swap.exists() {
+        fs::rename(&swap, &previous).await.context("Failed to keep replaced server jar")?;
+    }
+    Ok(())
+}
+
+/// SHA-256 of an installed jar, for jars that weren't uploaded
+pub async fn server_jar_sha256(server_dir: &Path) -> Result<String> {
+    sha256_file(&server_dir.join(SERVER_JAR)).await
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -8,6 +8,80 @@ use uuid::Uuid;
 pub enum ServerType {
     Paper,
     Spigot,
+    Purpur,
+    Folia,
+    Vanilla,
+    Fabric,
+    Forge,
+    NeoForge,
+    Velocity,
+    Waterfall,
+    BungeeCord,
+    Bedrock,
+}
+
+impl ServerType {
+    pub const ALL: [ServerType; 12] = [
+        ServerType::Paper,
+        ServerType::Spigot,
+        ServerType::Purpur,
+        ServerType::Folia,
+        ServerType::Vanilla,
+        ServerType::Fabric,
+        ServerType::Forge,
+        ServerType::NeoForge,
+        ServerType::Velocity,
+        ServerType::Waterfall,
+        ServerType::BungeeCord,
+        ServerType::Bedrock,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServerType::Paper => "paper",
+            ServerType::Spigot => "spigot",
+            ServerType::Purpur => "purpur",
+            ServerType::Folia => "folia",
+            ServerType::Vanilla => "vanilla",
+            ServerType::Fabric => "fabric",
+            ServerType::Forge => "forge",
+            ServerType::NeoForge => "neoforge",
+            ServerType::Velocity => "velocity",
+            ServerType::Waterfall => "waterfall",
+            ServerType::BungeeCord => "bungeecord",
+            ServerType::Bedrock => "bedrock",
+        }
+    }
+
+    /// Proxies front a network of backend servers rather than host a world
+    pub fn is_proxy(&self) -> bool {
+        matches!(self, ServerType::Velocity | ServerType::Waterfall | ServerType::BungeeCord)
+    }
+
+    /// Bedrock Dedicated Server: a native binary speaking RakNet over UDP,
+    /// with its own config files and console format
+    pub fn is_bedrock(&self) -> bool {
+        matches!(self, ServerType::Bedrock)
+    }
+
+    pub fn default_port(&self) -> u16 {
+        if self.is_bedrock() {
+            19132
+        } else {
+            25565
+        }
+    }
+}
+
+impl std::str::FromStr for ServerType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ServerType::ALL
+            .into_iter()
+            .find(|t| t.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| anyhow::anyhow!("Unknown server type '{}'", s))
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -39,7 +113,7 @@ impl ServerConfig {
             name,
             server_type,
             minecraft_version,
-            port: 25565,
+            port: server_type.default_port(),
             max_players: 20,
             memory_mb: 2048,
             auto_start: false,
@@ -47,7 +121,7 @@ impl ServerConfig {
         }
     }
 
-    pub fn server_dir(&self, base_dir: &PathBuf) -> PathBuf {
+    pub fn server_dir(&self, base_dir: &Path) -> PathBuf {
         base_dir.join(self.id.to_string())
     }
 }
@@ -85,6 +159,9 @@ pub struct PluginInfo {
     pub version: String,
     pub description: Option<String>,
     pub author: Option<String>,
+    /// Modrinth project to install it by
+    #[serde(default)]
+    pub project_id: Option<String>,
     pub installed: bool,
 }
 
@@ -0,0 +1,221 @@
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+const USER_AGENT: &str = concat!(
+    "mineserv-manager/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/vpastila/mineserv)"
+);
+
+/// Where server jars and plugins come from and how to reach them
+#[derive(Debug, Clone)]
+pub struct UpstreamConfig {
+    pub paper_api: String,
+    pub purpur_api: String,
+    pub spigot_buildtools_url: String,
+    /// SpigotMC's index of the versions BuildTools can build
+    pub spigot_versions: String,
+    /// Mojang's version manifest, for vanilla servers
+    pub mojang_version_manifest: String,
+    pub fabric_meta: String,
+    pub forge_maven: String,
+    /// Forge's latest and recommended build of each Minecraft version
+    pub forge_promotions: String,
+    pub neoforge_maven: String,
+    /// BungeeCord's Jenkins job
+    pub bungeecord_ci: String,
+    /// Bedrock Dedicated Server zip for Linux, with `{version}` in place of
+    /// the version
+    pub bedrock_download_url: String,
+    /// Minecraft's download links API, which names the current BDS release
+    pub bedrock_versions: String,
+    pub modrinth_api: String,
+    /// Proxy for all upstream requests; otherwise the usual `HTTPS_PROXY`
+    /// style variables apply
+    pub proxy: Option<String>,
+    pub connect_timeout: Duration,
+    /// Maximum silence on a connection, so slow downloads aren't cut off
+    pub read_timeout: Duration,
+}
+
+impl Default for UpstreamConfig {
+    fn default() -> Self {
+        Self {
+            paper_api: "https://api.papermc.io/v2".to_string(),
+            purpur_api: "https://api.purpurmc.org/v2/purpur".to_string(),
+            spigot_buildtools_url: "https://hub.spigotmc.org/jenkins/job/BuildTools/lastSuccessfulBuild/artifact/target/BuildTools.jar".to_string(),
+            spigot_versions: "https://hub.spigotmc.org/versions".to_string(),
+            mojang_version_manifest: "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json".to_string(),
+            fabric_meta: "https://meta.fabricmc.net/v2".to_string(),
+            forge_maven: "https://maven.minecraftforge.net".to_string(),
+            forge_promotions: "https://files.minecraftforge.net/net/minecraftforge/forge/promotions_slim.json".to_string(),
+            neoforge_maven: "https://maven.neoforged.net".to_string(),
+            bungeecord_ci: "https://ci.md-5.net/job/BungeeCord".to_string(),
+            bedrock_download_url: "https://www.minecraft.net/bedrockdedicatedserver/bin-linux/bedrock-server-{version}.zip".to_string(),
+            bedrock_versions: "https://net-secondary.web.minecraft-services.net/api/v1.0/download/links".to_string(),
+            modrinth_api: "https://api.modrinth.com/v2".to_string(),
+            proxy: None,
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl UpstreamConfig {
+    /// Override the defaults with `PAPER_API_BASE`, `PURPUR_API_BASE`,
+    /// `SPIGOT_BUILDTOOLS_URL`, `SPIGOT_VERSIONS_BASE`, `MOJANG_VERSION_MANIFEST`, `FABRIC_META_BASE`,
+    /// `FORGE_MAVEN_BASE`, `FORGE_PROMOTIONS_URL`, `NEOFORGE_MAVEN_BASE`,
+    /// `BUNGEECORD_CI_URL`, `BEDROCK_DOWNLOAD_URL`, `BEDROCK_VERSIONS_URL`,
+    /// `MODRINTH_API_BASE`, `UPSTREAM_PROXY`,
+    /// `UPSTREAM_CONNECT_TIMEOUT` and `UPSTREAM_READ_TIMEOUT` (seconds)
+    pub fn from_env() -> Result<Self> {
+        let mut config = Self::default();
+
+        if let Ok(url) = std::env::var("PAPER_API_BASE") {
+            config.paper_api = url;
+        }
+        if let Ok(url) = std::env::var("PURPUR_API_BASE") {
+            config.purpur_api = url;
+        }
+        if let Ok(url) = std::env::var("SPIGOT_BUILDTOOLS_URL") {
+            config.spigot_buildtools_url = url;
+        }
+        if let Ok(url) = std::env::var("SPIGOT_VERSIONS_BASE") {
+            config.spigot_versions = url;
+        }
+        if let Ok(url) = std::env::var("MOJANG_VERSION_MANIFEST") {
+            config.mojang_version_manifest = url;
+        }
+        if let Ok(url) = std::env::var("FABRIC_META_BASE") {
+            config.fabric_meta = url;
+        }
+        if let Ok(url) = std::env::var("FORGE_MAVEN_BASE") {
+            config.forge_maven = url;
+        }
+        if let Ok(url) = std::env::var("FORGE_PROMOTIONS_URL") {
+            config.forge_promotions = url;
+        }
+        if let Ok(url) = std::env::var("NEOFORGE_MAVEN_BASE") {
+            config.neoforge_maven = url;
+        }
+        if let Ok(url) = std::env::var("BUNGEECORD_CI_URL") {
+            config.bungeecord_ci = url;
+        }
+        if let Ok(url) = std::env::var("BEDROCK_DOWNLOAD_URL") {
+            config.bedrock_download_url = url;
+        }
+        if let Ok(url) = std::env::var("BEDROCK_VERSIONS_URL") {
+            config.bedrock_versions = url;
+        }
+        if let Ok(url) = std::env::var("MODRINTH_API_BASE") {
+            config.modrinth_api = url;
+        }
+        if let Ok(proxy) = std::env::var("UPSTREAM_PROXY") {
+            config.proxy = Some(proxy);
+        }
+        if let Ok(secs) = std::env::var("UPSTREAM_CONNECT_TIMEOUT") {
+            let secs = secs.parse().context("Invalid UPSTREAM_CONNECT_TIMEOUT")?;
+            config.connect_timeout = Duration::from_secs(secs);
+        }
+        if let Ok(secs) = std::env::var("UPSTREAM_READ_TIMEOUT") {
+            let secs = secs.parse().context("Invalid UPSTREAM_READ_TIMEOUT")?;
+            config.read_timeout = Duration::from_secs(secs);
+        }
+
+        Ok(config)
+    }
+}
+
+/// Upstream endpoints together with the one HTTP client used to reach them
+#[derive(Debug, Clone)]
+pub struct Upstreams {
+    config: UpstreamConfig,
+    client: reqwest::Client,
+}
+
+impl Upstreams {
+    pub fn new(mut config: UpstreamConfig) -> Result<Self> {
+        for url in [
+            &mut config.paper_api,
+            &mut config.purpur_api,
+            &mut config.spigot_versions,
+            &mut config.fabric_meta,
+            &mut config.forge_maven,
+            &mut config.neoforge_maven,
+            &mut config.bungeecord_ci,
+            &mut config.modrinth_api,
+        ] {
+            while url.ends_with('/') {
+                url.pop();
+            }
+        }
+
+        let mut builder = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(config.connect_timeout)
+            .read_timeout(config.read_timeout);
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy).context("Invalid upstream proxy")?);
+        }
+        let client = builder.build().context("Failed to build HTTP client")?;
+
+        Ok(Self { config, client })
+    }
+
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn paper_api(&self) -> &str {
+        &self.config.paper_api
+    }
+
+    pub fn purpur_api(&self) -> &str {
+        &self.config.purpur_api
+    }
+
+    pub fn mojang_version_manifest(&self) -> &str {
+        &self.config.mojang_version_manifest
+    }
+
+    pub fn fabric_meta(&self) -> &str {
+        &self.config.fabric_meta
+    }
+
+    pub fn forge_maven(&self) -> &str {
+        &self.config.forge_maven
+    }
+
+    pub fn forge_promotions(&self) -> &str {
+        &self.config.forge_promotions
+    }
+
+    pub fn neoforge_maven(&self) -> &str {
+        &self.config.neoforge_maven
+    }
+
+    pub fn bungeecord_ci(&self) -> &str {
+        &self.config.bungeecord_ci
+    }
+
+    /// Download URL of a Bedrock Dedicated Server version
+    pub fn bedrock_download_url(&self, version: &str) -> String {
+        self.config.bedrock_download_url.replace("{version}", version)
+    }
+
+    pub fn bedrock_versions(&self) -> &str {
+        &self.config.bedrock_versions
+    }
+
+    pub fn spigot_buildtools_url(&self) -> &str {
+        &self.config.spigot_buildtools_url
+    }
+
+    pub fn spigot_versions(&self) -> &str {
+        &self.config.spigot_versions
+    }
+
+    pub fn modrinth_api(&self) -> &str {
+        &self.config.modrinth_api
+    }
+}
@@ -0,0 +1,182 @@
+use crate::console::LogLine;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::broadcast;
+
+/// Console lines Paper and Spigot print when their own watchdog detects a stalled tick loop
+const HANG_MARKERS: &[&str] = &[
+    "The server has stopped responding!",
+    "DO NOT REPORT THIS TO PAPER - THIS IS NOT A BUG OR A CRASH",
+];
+
+pub fn is_hang_message(line: &str) -> bool {
+    HANG_MARKERS.iter().any(|marker| line.contains(marker))
+}
+
+fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7F | 0x80) as u8);
+        value >>= 7;
+    }
+}
+
+async fn read_varint(stream: &mut TcpStream) -> Result<i32> {
+    let mut result = 0i32;
+    for i in 0..5 {
+        let byte = stream.read_u8().await?;
+        result |= ((byte & 0x7F) as i32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    anyhow::bail!("VarInt too long")
+}
+
+fn packet(id: i32, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    write_varint(&mut body, id);
+    body.extend_from_slice(payload);
+
+    let mut framed = Vec::new();
+    write_varint(&mut framed, body.len() as i32);
+    framed.extend_from_slice(&body);
+    framed
+}
+
+/// Perform a Server List Ping against a local server and return the status JSON
+pub async fn ping_server(port: u16, timeout: Duration) -> Result<String> {
+    tokio::time::timeout(timeout, async {
+        let mut stream = TcpStream::connect(("127.0.0.1", port))
+            .await
+            .context("Failed to connect for status ping")?;
+
+        let host = b"127.0.0.1";
+        let mut handshake = Vec::new();
+        write_varint(&mut handshake, -1); // protocol version: unknown
+        write_varint(&mut handshake, host.len() as i32);
+        handshake.extend_from_slice(host);
+        handshake.extend_from_slice(&port.to_be_bytes());
+        write_varint(&mut handshake, 1); // next state: status
+
+        stream.write_all(&packet(0x00, &handshake)).await?;
+        stream.write_all(&packet(0x00, &[])).await?;
+
+        let _length = read_varint(&mut stream).await?;
+        let packet_id = read_varint(&mut stream).await?;
+        if packet_id != 0x00 {
+            anyhow::bail!("Unexpected status packet id {}", packet_id);
+        }
+
+        let json_len = read_varint(&mut stream).await?;
+        let mut json = vec![0u8; json_len.max(0) as usize];
+        stream.read_exact(&mut json).await?;
+
+        Ok(String::from_utf8_lossy(&json).to_string())
+    })
+    .await
+    .context("Status ping timed out")?
+}
+
+/// Marks RakNet offline messages
+const RAKNET_MAGIC: [u8; 16] = [
+    0x00, 0xff, 0xff, 0x00, 0xfe, 0xfe, 0xfe, 0xfe, 0xfd, 0xfd, 0xfd, 0xfd, 0x12, 0x34, 0x56, 0x78,
+];
+const RAKNET_UNCONNECTED_PING: u8 = 0x01;
+const RAKNET_UNCONNECTED_PONG: u8 = 0x1c;
+
+/// Send a RakNet unconnected ping to a local Bedrock server and return its
+/// MOTD string, e.g. `MCPE;Dedicated Server;712;1.21.51;0;10;...`
+pub async fn ping_bedrock_server(port: u16, timeout: Duration) -> Result<String> {
+    tokio::time::timeout(timeout, async {
+        let socket = UdpSocket::bind(("127.0.0.1", 0))
+            .await
+            .context("Failed to open socket for status ping")?;
+        socket
+            .connect(("127.0.0.1", port))
+            .await
+            .context("Failed to connect for status ping")?;
+
+        let mut ping = vec![RAKNET_UNCONNECTED_PING];
+        ping.extend_from_slice(&(chrono::Utc::now().timestamp_millis() as u64).to_be_bytes());
+        ping.extend_from_slice(&RAKNET_MAGIC);
+        ping.extend_from_slice(&uuid::Uuid::new_v4().as_u64_pair().0.to_be_bytes()); // client GUID
+        socket.send(&ping).await?;
+
+        let mut buf = [0u8; 1500];
+        let len = socket.recv(&mut buf).await?;
+        let pong = &buf[..len];
+
+        // id, time, server GUID, magic, string length, string
+        if pong.first() != Some(&RAKNET_UNCONNECTED_PONG) || len < 35 {
+            anyhow::bail!("Unexpected status reply");
+        }
+        let motd_len = u16::from_be_bytes([pong[33], pong[34]]) as usize;
+        let motd = pong.get(35..35 + motd_len).context("Truncated status reply")?;
+
+        Ok(String::from_utf8_lossy(motd).to_string())
+    })
+    .await
+    .context("Status ping timed out")?
+}
+
+/// Save a thread dump of the server JVM into `logs/`.
+///
+/// Uses `jstack` when available. Otherwise sends SIGQUIT, which makes the JVM
+/// print the dump to stdout, and collects it from `output` for a few seconds.
+pub async fn capture_thread_dump(
+    server_dir: &Path,
+    pid: u32,
+    output: Option<broadcast::Receiver<LogLine>>,
+) -> Result<PathBuf> {
+    let logs_dir = server_dir.join("logs");
+    fs::create_dir_all(&logs_dir).await?;
+
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+    let dump_path = logs_dir.join(format!("thread-dump-{}.txt", timestamp));
+
+    let jstack = tokio::process::Command::new("jstack")
+        .arg("-l")
+        .arg(pid.to_string())
+        .output()
+        .await;
+
+    if let Ok(out) = jstack {
+        if out.status.success() && !out.stdout.is_empty() {
+            fs::write(&dump_path, &out.stdout)
+                .await
+                .context("Failed to write thread dump")?;
+            return Ok(dump_path);
+        }
+    }
+
+    let mut rx = output.context("jstack unavailable and server output is not attached")?;
+
+    unsafe {
+        libc::kill(pid as i32, libc::SIGQUIT);
+    }
+
+    let mut lines = Vec::new();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(3);
+    while let Ok(Ok(line)) = tokio::time::timeout_at(deadline, rx.recv()).await {
+        lines.push(line.raw);
+    }
+
+    if lines.is_empty() {
+        anyhow::bail!("No thread dump output received after SIGQUIT");
+    }
+
+    fs::write(&dump_path, lines.join("\n"))
+        .await
+        .context("Failed to write thread dump")?;
+
+    Ok(dump_path)
+}
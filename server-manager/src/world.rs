@@ -1,7 +1,11 @@
+use crate::progress::Progress;
 use crate::types::WorldInfo;
 use anyhow::{Context, Result};
 use std::path::Path;
 use tokio::fs;
+use tokio::io::AsyncReadExt;
+
+const ZIP_CHUNK_SIZE: usize = 1024 * 1024;
 
 pub async fn list_worlds(server_dir: &Path) -> Result<Vec<WorldInfo>> {
     let mut worlds = Vec::new();
@@ -43,7 +47,7 @@ pub async fn list_worlds(server_dir: &Path) -> Result<Vec<WorldInfo>> {
     Ok(worlds)
 }
 
-pub async fn backup_world(server_dir: &Path, world_name: &str) -> Result<String> {
+pub async fn backup_world(server_dir: &Path, world_name: &str, progress: &Progress) -> Result<String> {
     let world_path = server_dir.join(world_name);
     
     if !world_path.exists() {
@@ -64,10 +68,18 @@ pub async fn backup_world(server_dir: &Path, world_name: &str) -> Result<String>
     let options = zip::write::FileOptions::<()>::default()
         .compression_method(zip::CompressionMethod::Deflated);
 
-    add_dir_to_zip(&mut zip, &world_path, world_name, options).await?;
-    
-    zip.finish().context("Failed to finalize zip")?;
+    let total = calculate_dir_size(&world_path).await?;
+    let mut done = 0;
+    progress.log(format!("Backing up {} ({} MB)", world_name, total / 1024 / 1024));
 
+    let result = add_dir_to_zip(&mut zip, &world_path, world_name, options, progress, &mut done, total).await;
+    if let Err(e) = result.and_then(|_| zip.finish().context("Failed to finalize zip").map(|_| ())) {
+        // Don't leave a truncated archive that looks like a usable backup
+        let _ = fs::remove_file(&backup_path).await;
+        return Err(e);
+    }
+
+    progress.log(format!("Saved backups/{}", backup_name));
     Ok(backup_name)
 }
 
@@ -85,7 +97,7 @@ pub async fn delete_world(server_dir: &Path, world_name: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn upload_world(server_dir: &Path, world_name: &str, zip_data: Vec<u8>) -> Result<()> {
+pub fn upload_world(server_dir: &Path, world_name: &str, zip_data: Vec<u8>, progress: &Progress) -> Result<()> {
     let world_path = server_dir.join(world_name);
     if world_path.exists() {
         anyhow::bail!("World '{}' already exists", world_name);
@@ -93,10 +105,48 @@ pub fn upload_world(server_dir: &Path, world_name: &str, zip_data: Vec<u8>) -> R
 
     std::fs::create_dir_all(&world_path).context("Failed to create world directory")?;
 
-    let cursor = std::io::Cursor::new(zip_data);
-    let mut archive = zip::ZipArchive::new(cursor).context("Failed to open zip archive")?;
+    let result = extract_world(&world_path, std::io::Cursor::new(zip_data), progress);
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(&world_path);
+    }
+    result
+}
+
+/// Replace a world with one of its backups from [`backup_world`]. The backup
+/// is unpacked beside the world first, so a bad archive leaves it untouched.
+pub fn restore_world(server_dir: &Path, world_name: &str, backup_name: &str, progress: &Progress) -> Result<()> {
+    let backup_path = server_dir.join("backups").join(backup_name);
+    // Backups of large worlds run to gigabytes, so they're read from disk as needed
+    let backup = std::fs::File::open(&backup_path)
+        .with_context(|| format!("Failed to open backup {}", backup_name))?;
+
+    // Backups hold the world under its own name
+    let staging = server_dir.join(format!(".restore-{}", world_name));
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::create_dir_all(&staging).context("Failed to create restore directory")?;
+
+    let restored = extract_world(&staging, backup, progress).and_then(|_| {
+        let world_path = server_dir.join(world_name);
+        if !staging.join(world_name).join("level.dat").exists() {
+            anyhow::bail!("Backup {} doesn't contain world '{}'", backup_name, world_name);
+        }
+        if world_path.exists() {
+            std::fs::remove_dir_all(&world_path).context("Failed to remove world")?;
+        }
+        std::fs::rename(staging.join(world_name), &world_path).context("Failed to restore world")
+    });
+    let _ = std::fs::remove_dir_all(&staging);
+    restored
+}
+
+fn extract_world<R: std::io::Read + std::io::Seek>(world_path: &Path, zip: R, progress: &Progress) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(zip).context("Failed to open zip archive")?;
+    let total = archive.len() as u64;
 
     for i in 0..archive.len() {
+        progress.check_cancelled()?;
+        progress.fraction(i as u64, total);
+
         let mut file = archive.by_index(i).context("Failed to access file in zip")?;
         let outpath = match file.enclosed_name() {
             Some(path) => world_path.join(path),
@@ -108,7 +158,7 @@ pub fn upload_world(server_dir: &Path, world_name: &str, zip_data: Vec<u8>) -> R
         } else {
             if let Some(p) = outpath.parent() {
                 if !p.exists() {
-                    std::fs::create_dir_all(&p).context("Failed to create parent directory in zip")?;
+                    std::fs::create_dir_all(p).context("Failed to create parent directory in zip")?;
                 }
             }
             let mut outfile = std::fs::File::create(&outpath).context("Failed to create output file")?;
@@ -146,10 +196,15 @@ async fn add_dir_to_zip(
     dir: &Path,
     prefix: &str,
     options: zip::write::FileOptions<'_, ()>,
+    progress: &Progress,
+    done: &mut u64,
+    total: u64,
 ) -> Result<()> {
     let mut entries = fs::read_dir(dir).await?;
     
     while let Some(entry) = entries.next_entry().await? {
+        progress.check_cancelled()?;
+
         let path = entry.path();
         let name = path.file_name()
             .and_then(|n| n.to_str())
@@ -158,18 +213,32 @@ async fn add_dir_to_zip(
         let zip_path = format!("{}/{}", prefix, name);
 
         if path.is_dir() {
-            zip.add_directory(&zip_path, options.clone())
+            zip.add_directory(&zip_path, options)
                 .context("Failed to add directory to zip")?;
-            Box::pin(add_dir_to_zip(zip, &path, &zip_path, options.clone())).await?;
+            Box::pin(add_dir_to_zip(zip, &path, &zip_path, options, progress, done, total)).await?;
         } else {
-            zip.start_file(&zip_path, options.clone())
+            zip.start_file(&zip_path, options)
                 .context("Failed to start file in zip")?;
             
-            let content = std::fs::read(&path)
+            // Copy in chunks so large region files report progress and
+            // can be cancelled part way
+            let mut file = fs::File::open(&path)
+                .await
                 .context("Failed to read file")?;
-            
-            std::io::Write::write_all(zip, &content)
-                .context("Failed to write file to zip")?;
+            let mut buf = vec![0u8; ZIP_CHUNK_SIZE];
+            loop {
+                progress.check_cancelled()?;
+
+                let n = file.read(&mut buf).await.context("Failed to read file")?;
+                if n == 0 {
+                    break;
+                }
+                std::io::Write::write_all(zip, &buf[..n])
+                    .context("Failed to write file to zip")?;
+
+                *done += n as u64;
+                progress.fraction(*done, total);
+            }
         }
     }
 
@@ -0,0 +1,147 @@
+//! Parsing console and log lines, and the events the manager tracks from them.
+
+use server_manager::{
+    parse_console_event, parse_duration, parse_log_header, ConsoleEvent, LogFilter, LogLevel, LogParser, ServerType,
+};
+
+#[test]
+fn parses_bedrock_log_header() {
+    let line = parse_log_header("[2024-05-01 12:34:56:789 INFO] Player connected: Steve, xuid: 2535412345").unwrap();
+    assert_eq!(line.level, LogLevel::Info);
+    assert_eq!(line.time.unwrap().to_string(), "12:34:56.789");
+    assert_eq!(line.message, "Player connected: Steve, xuid: 2535412345");
+
+    let line = parse_log_header("[12:34:56 WARN]: [Essentials] Hello").unwrap();
+    assert_eq!(line.level, LogLevel::Warn);
+    assert_eq!(line.source.as_deref(), Some("Essentials"));
+}
+
+#[test]
+fn recognises_bedrock_events() {
+    let event = |message| parse_console_event(ServerType::Bedrock, message);
+    assert_eq!(event("Server started."), Some(ConsoleEvent::Ready));
+    assert_eq!(
+        event("Player connected: Some Name, xuid: 2535412345"),
+        Some(ConsoleEvent::PlayerJoined("Some Name".to_string()))
+    );
+    assert_eq!(
+        event("Player disconnected: Some Name, xuid: 2535412345, pfid: abc"),
+        Some(ConsoleEvent::PlayerLeft("Some Name".to_string()))
+    );
+    assert_eq!(event("Steve joined the game"), None);
+}
+
+#[test]
+fn recognises_java_events() {
+    let event = |message| parse_console_event(ServerType::Paper, message);
+    assert_eq!(event("Done (3.217s)! For help, type \"help\""), Some(ConsoleEvent::Ready));
+    assert_eq!(event("Steve joined the game"), Some(ConsoleEvent::PlayerJoined("Steve".to_string())));
+    assert_eq!(event("Steve left the game"), Some(ConsoleEvent::PlayerLeft("Steve".to_string())));
+    assert_eq!(event("<Steve> I left the game"), None);
+
+    assert_eq!(
+        parse_console_event(ServerType::Velocity, "Listening on /[0:0:0:0:0:0:0:0%0]:25565"),
+        Some(ConsoleEvent::Ready)
+    );
+}
+
+#[test]
+fn parses_java_log_headers() {
+    let line = parse_log_header("[12:34:56] [Server thread/INFO]: Steve joined the game").unwrap();
+    assert_eq!(line.time.unwrap().to_string(), "12:34:56");
+    assert_eq!(line.level, LogLevel::Info);
+    assert_eq!(line.thread.as_deref(), Some("Server thread"));
+    assert_eq!(line.source, None);
+    assert_eq!(line.message, "Steve joined the game");
+
+    // Plugin loggers prefix the message with their name
+    let line = parse_log_header("[12:34:56] [Server thread/WARN]: [Essentials] Hello").unwrap();
+    assert_eq!(line.level, LogLevel::Warn);
+    assert_eq!(line.source.as_deref(), Some("Essentials"));
+    assert_eq!(line.message, "Hello");
+
+    // Forge names the logger in the header
+    let line = parse_log_header("[12:34:56] [main/ERROR] [net.minecraftforge.fml.loading/]: Failed").unwrap();
+    assert_eq!(line.level, LogLevel::Error);
+    assert_eq!(line.thread.as_deref(), Some("main"));
+    assert_eq!(line.source.as_deref(), Some("net.minecraftforge.fml.loading/"));
+    assert_eq!(line.message, "Failed");
+
+    // Chat isn't a source, and neither is a bracket without a space after it
+    let line = parse_log_header("[12:34:56] [Server thread/INFO]: [Not a plugin!] hi").unwrap();
+    assert_eq!(line.source, None);
+    assert_eq!(line.message, "[Not a plugin!] hi");
+
+    assert!(parse_log_header("\tat java.lang.Thread.run(Thread.java:833)").is_none());
+    assert!(parse_log_header("[12:34:56] [Server thread/LOUD]: unknown level").is_none());
+    assert!(parse_log_header("[not a time] [Server thread/INFO]: hi").is_none());
+}
+
+#[test]
+fn continuation_lines_inherit_the_previous_header() {
+    let mut parser = LogParser::new(LogLevel::Warn);
+
+    // Nothing to inherit yet
+    let line = parser.parse("Starting server");
+    assert_eq!(line.level, LogLevel::Warn);
+    assert_eq!(line.thread, None);
+    assert!(line.time.is_none());
+
+    parser.parse("[12:00:00] [Server thread/ERROR]: [Essentials] Could not pass event");
+    let line = parser.parse("java.lang.NullPointerException: null");
+    assert_eq!(line.level, LogLevel::Error);
+    assert_eq!(line.thread.as_deref(), Some("Server thread"));
+    assert_eq!(line.source.as_deref(), Some("Essentials"));
+    assert!(line.time.is_none());
+    assert_eq!(line.message, "java.lang.NullPointerException: null");
+
+    let line = parser.parse("[12:00:01] [Server thread/INFO]: Back to normal");
+    assert_eq!(line.level, LogLevel::Info);
+    assert_eq!(line.source, None);
+}
+
+#[test]
+fn strips_colour_codes_from_console_lines() {
+    let mut parser = LogParser::new(LogLevel::Info);
+    let line = parser.parse_console("\x1b[33m[12:00:00 WARN]: Can't keep up!\x1b[0m", false);
+    assert_eq!(line.level, LogLevel::Warn);
+    assert_eq!(line.raw, "[12:00:00 WARN]: Can't keep up!");
+    assert_eq!(line.message, "Can't keep up!");
+}
+
+#[test]
+fn filters_by_level_source_and_thread() {
+    let filter = LogFilter::parse(Some("warn, error"), None, None, None).unwrap();
+    assert!(filter.matches(LogLevel::Warn, None, None));
+    assert!(filter.matches(LogLevel::Error, None, None));
+    assert!(!filter.matches(LogLevel::Fatal, None, None));
+
+    let filter = LogFilter::parse(None, Some("warning"), None, None).unwrap();
+    assert!(!filter.matches(LogLevel::Info, None, None));
+    assert!(filter.matches(LogLevel::Warn, None, None));
+    assert!(filter.matches(LogLevel::Fatal, None, None));
+
+    let filter =
+        LogFilter::parse(None, None, Some("essentials".to_string()), Some("server".to_string())).unwrap();
+    assert!(filter.matches(LogLevel::Info, Some("Server thread"), Some("Essentials")));
+    assert!(!filter.matches(LogLevel::Info, Some("Server thread"), Some("EssentialsChat")));
+    assert!(!filter.matches(LogLevel::Info, Some("Worker-Main-1"), Some("Essentials")));
+    assert!(!filter.matches(LogLevel::Info, None, None));
+
+    assert!(LogFilter::default().matches(LogLevel::Trace, None, None));
+    assert!(LogFilter::parse(Some("info,loud"), None, None, None).is_err());
+    assert!(LogFilter::parse(None, Some("loud"), None, None).is_err());
+}
+
+#[test]
+fn parses_durations() {
+    assert_eq!(parse_duration("45").unwrap(), 45);
+    assert_eq!(parse_duration("30s").unwrap(), 30);
+    assert_eq!(parse_duration(" 10m ").unwrap(), 600);
+    assert_eq!(parse_duration("2h").unwrap(), 7200);
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("1d").is_err());
+    assert!(parse_duration("m").is_err());
+    assert!(parse_duration("9999999999999999h").is_err());
+    assert!(parse_duration("99999999999999999999").is_err());
+}
@@ -0,0 +1,100 @@
+//! Finding and summarising crash reports and JVM fatal error logs.
+
+use chrono::{Local, NaiveDateTime};
+use server_manager::{find_crash_files, parse_crash_file, CrashKind};
+use std::fs::{self, File};
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+const CRASH_REPORT: &str = include_str!("fixtures/crash-report.txt");
+const HS_ERR: &str = include_str!("fixtures/hs_err_pid4242.log");
+
+fn server_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let reports = dir.path().join("crash-reports");
+    fs::create_dir(&reports).unwrap();
+    fs::write(reports.join("crash-2024-06-01_12.34.56-server.txt"), CRASH_REPORT).unwrap();
+    fs::write(reports.join("README.md"), "not a report").unwrap();
+    fs::write(dir.path().join("hs_err_pid4242.log"), HS_ERR).unwrap();
+    fs::write(dir.path().join("hs_err_pid4242.txt"), HS_ERR).unwrap();
+    fs::write(dir.path().join("server.log"), "").unwrap();
+    dir
+}
+
+async fn crash_files(server_dir: &Path) -> Vec<(String, CrashKind)> {
+    let mut files: Vec<_> = find_crash_files(server_dir)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(path, kind)| {
+            let name = path.strip_prefix(server_dir).unwrap().to_string_lossy().to_string();
+            (name, kind)
+        })
+        .collect();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+#[tokio::test]
+async fn finds_crash_reports_and_jvm_error_logs() {
+    let dir = server_dir();
+    assert_eq!(
+        crash_files(dir.path()).await,
+        [
+            ("crash-reports/crash-2024-06-01_12.34.56-server.txt".to_string(), CrashKind::CrashReport),
+            ("hs_err_pid4242.log".to_string(), CrashKind::JvmFatal),
+        ]
+    );
+
+    // A server that never crashed has no crash-reports directory
+    let empty = tempfile::tempdir().unwrap();
+    assert!(crash_files(empty.path()).await.is_empty());
+}
+
+#[tokio::test]
+async fn summarises_a_crash_report() {
+    let dir = server_dir();
+    let path = dir.path().join("crash-reports/crash-2024-06-01_12.34.56-server.txt");
+    let summary = parse_crash_file(dir.path(), &path, CrashKind::CrashReport).await.unwrap();
+
+    assert_eq!(summary.file, "crash-reports/crash-2024-06-01_12.34.56-server.txt");
+    assert_eq!(summary.kind, CrashKind::CrashReport);
+    assert_eq!(summary.description.as_deref(), Some("Exception in server tick loop"));
+    assert_eq!(
+        summary.exception.as_deref(),
+        Some("java.lang.NullPointerException: Cannot invoke \"org.bukkit.entity.Player.getName()\" because \"player\" is null")
+    );
+    // The first frame outside the JVM and the server
+    assert_eq!(summary.culprit.as_deref(), Some("com.example.warps"));
+
+    let time = NaiveDateTime::parse_from_str("2024-06-01 12:34:56", "%Y-%m-%d %H:%M:%S").unwrap();
+    let expected = time.and_local_timezone(Local).single().unwrap().timestamp();
+    assert_eq!(summary.occurred_at, expected);
+}
+
+#[tokio::test]
+async fn summarises_a_jvm_fatal_error_log() {
+    let dir = server_dir();
+    let path = dir.path().join("hs_err_pid4242.log");
+    let modified = UNIX_EPOCH + Duration::from_secs(1_717_245_296);
+    File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+
+    let summary = parse_crash_file(dir.path(), &path, CrashKind::JvmFatal).await.unwrap();
+
+    assert_eq!(summary.file, "hs_err_pid4242.log");
+    assert_eq!(summary.kind, CrashKind::JvmFatal);
+    assert_eq!(
+        summary.description.as_deref(),
+        Some("A fatal error has been detected by the Java Runtime Environment")
+    );
+    assert_eq!(
+        summary.exception.as_deref(),
+        Some("SIGSEGV (0xb) at pc=0x00007f3c2d1e4a10, pid=4242, tid=4260")
+    );
+    assert_eq!(
+        summary.culprit.as_deref(),
+        Some("C  [liblwjgl.so+0x4a10]  Java_org_lwjgl_system_JNI_invokePV+0x10")
+    );
+    // hs_err logs have no parseable time, so the file's is used
+    assert_eq!(summary.occurred_at, 1_717_245_296);
+}
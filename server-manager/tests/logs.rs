@@ -0,0 +1,151 @@
+//! Reading and searching a server's logs.
+
+use chrono::{Local, NaiveDate, TimeZone};
+use flate2::{write::GzEncoder, Compression};
+use server_manager::{list_log_files, read_log_page, search_logs, LogFilter, LogLevel, LogSearch};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+fn local(date: &str, time: &str) -> i64 {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+    let time = chrono::NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap();
+    Local.from_local_datetime(&date.and_time(time)).earliest().unwrap().timestamp()
+}
+
+fn write_log(path: &Path, lines: &[&str]) {
+    fs::write(path, lines.join("\n") + "\n").unwrap();
+}
+
+fn write_archive(path: &Path, lines: &[&str]) {
+    let mut encoder = GzEncoder::new(File::create(path).unwrap(), Compression::default());
+    encoder.write_all((lines.join("\n") + "\n").as_bytes()).unwrap();
+    encoder.finish().unwrap();
+}
+
+/// Make latest.log look last written at `time` on `date`
+fn touch(path: &Path, date: &str, time: &str) {
+    let secs = local(date, time) as u64;
+    let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+    File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+}
+
+#[test]
+fn pages_through_plain_and_compressed_logs() {
+    let dir = tempfile::tempdir().unwrap();
+    let lines = ["one", "two", "three", "four", "five"];
+    let plain = dir.path().join("latest.log");
+    let archive = dir.path().join("2024-06-01-1.log.gz");
+    write_log(&plain, &lines);
+    write_archive(&archive, &lines);
+
+    for path in [&plain, &archive] {
+        let page = read_log_page(path, 1, 2).unwrap();
+        assert_eq!(page.lines, ["two", "three"]);
+        assert_eq!(page.offset, 1);
+        assert_eq!(page.next_offset, 3);
+        assert!(!page.eof);
+
+        let page = read_log_page(path, 3, 10).unwrap();
+        assert_eq!(page.lines, ["four", "five"]);
+        assert_eq!(page.next_offset, 5);
+        assert!(page.eof);
+
+        let page = read_log_page(path, 9, 10).unwrap();
+        assert!(page.lines.is_empty());
+        assert!(page.eof);
+    }
+}
+
+#[tokio::test]
+async fn dates_latest_log_lines_from_before_midnight() {
+    let dir = tempfile::tempdir().unwrap();
+    let logs = dir.path().join("logs");
+    fs::create_dir(&logs).unwrap();
+    let latest = logs.join("latest.log");
+    write_log(
+        &latest,
+        &[
+            "[23:59:58] [Server thread/INFO]: Saving chunks before midnight",
+            "[00:00:02] [Server thread/WARN]: Can't keep up after midnight",
+            "java.lang.Exception: still after midnight",
+        ],
+    );
+    touch(&latest, "2024-06-02", "00:05:00");
+
+    let files = list_log_files(dir.path()).await.unwrap();
+    let search = LogSearch::new(Some("midnight"), LogFilter::default(), None, None, 10).unwrap();
+    let matches = search_logs(dir.path(), &files, &search).unwrap();
+
+    let timestamps: Vec<_> = matches.iter().map(|m| m.timestamp).collect();
+    assert_eq!(
+        timestamps,
+        [
+            Some(local("2024-06-01", "23:59:58")),
+            Some(local("2024-06-02", "00:00:02")),
+            Some(local("2024-06-02", "00:00:02")),
+        ]
+    );
+    assert_eq!(matches[2].level, LogLevel::Warn);
+}
+
+#[tokio::test]
+async fn searches_archives_by_pattern_level_and_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let logs = dir.path().join("logs");
+    fs::create_dir(&logs).unwrap();
+    write_archive(
+        &logs.join("2024-06-01-1.log.gz"),
+        &[
+            "[10:00:00] [Server thread/INFO]: Done (3.2s)!",
+            "[23:00:00] [Server thread/WARN]: [Essentials] Low on memory",
+            "[01:00:00] [Server thread/WARN]: [Essentials] Low on memory again",
+        ],
+    );
+    write_archive(
+        &logs.join("2024-06-03-1.log.gz"),
+        &["[09:00:00] [Server thread/ERROR]: [Essentials] Out of memory"],
+    );
+    let latest = logs.join("latest.log");
+    write_log(&latest, &["[08:00:00] [Server thread/WARN]: [Essentials] Low on memory today"]);
+    touch(&latest, "2024-06-05", "08:00:00");
+
+    let files = list_log_files(dir.path()).await.unwrap();
+    let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["2024-06-01-1.log.gz", "2024-06-03-1.log.gz", "latest.log"]);
+
+    let search = |pattern, filter, from, to| {
+        let search = LogSearch::new(pattern, filter, from, to, 10).unwrap();
+        search_logs(dir.path(), &files, &search).unwrap()
+    };
+    let warnings = || LogFilter::parse(None, Some("warn"), Some("essentials".to_string()), None).unwrap();
+
+    let found = search(Some("memory"), warnings(), None, None);
+    let lines: Vec<_> = found.iter().map(|m| (m.file.as_str(), m.line_number)).collect();
+    assert_eq!(
+        lines,
+        [
+            ("2024-06-01-1.log.gz", 2),
+            ("2024-06-01-1.log.gz", 3),
+            ("2024-06-03-1.log.gz", 1),
+            ("latest.log", 1),
+        ]
+    );
+    assert_eq!(found[0].source.as_deref(), Some("Essentials"));
+
+    // The first archive runs into June 2nd, so it is still searched
+    let found = search(
+        None,
+        warnings(),
+        Some(local("2024-06-02", "00:00:00")),
+        Some(local("2024-06-03", "23:59:59")),
+    );
+    let lines: Vec<_> = found.iter().map(|m| (m.file.as_str(), m.line_number)).collect();
+    assert_eq!(lines, [("2024-06-01-1.log.gz", 3), ("2024-06-03-1.log.gz", 1)]);
+    assert_eq!(found[0].timestamp, Some(local("2024-06-02", "01:00:00")));
+
+    let errors = LogFilter::parse(Some("error"), None, None, None).unwrap();
+    let found = search(Some("memory"), errors, None, None);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].level, LogLevel::Error);
+}
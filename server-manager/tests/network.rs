@@ -0,0 +1,148 @@
+//! Proxy and backend config files written for a network.
+
+use server_manager::{
+    check_backend, configure_proxy, join_network, leave_network, read_server_properties,
+    NetworkBackend, ServerType,
+};
+use std::fs;
+use tempfile::TempDir;
+
+fn backends() -> Vec<NetworkBackend> {
+    vec![
+        NetworkBackend { name: "lobby".to_string(), port: 25566 },
+        NetworkBackend { name: "survival".to_string(), port: 25567 },
+    ]
+}
+
+#[tokio::test]
+async fn writes_velocity_server_list_and_secret() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("velocity.toml"),
+        concat!(
+            "motd = \"<red>Hello\"\n\n[servers]\nfactions = \"127.0.0.1:30068\"\ntry = [\"factions\"]\n\n",
+            "[forced-hosts]\n\"lobby.example.com\" = [\"lobby\"]\n\"factions.example.com\" = [\"factions\"]\n",
+            "\"play.mynetwork.net\" = [\"lobby\", \"factions\"]\n\"pvp.mynetwork.net\" = [\"factions\"]\n",
+        ),
+    )
+    .unwrap();
+
+    configure_proxy(dir.path(), ServerType::Velocity, 25565, &backends(), Some("s3cret"))
+        .await
+        .unwrap();
+
+    let doc: toml_edit::DocumentMut = fs::read_to_string(dir.path().join("velocity.toml"))
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(doc["motd"].as_str(), Some("<red>Hello"));
+    assert_eq!(doc["bind"].as_str(), Some("0.0.0.0:25565"));
+    assert_eq!(doc["player-info-forwarding-mode"].as_str(), Some("modern"));
+    assert_eq!(doc["servers"]["lobby"].as_str(), Some("127.0.0.1:25566"));
+    assert!(doc["servers"].get("factions").is_none());
+
+    let order: Vec<_> = doc["servers"]["try"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    assert_eq!(order, ["lobby", "survival"]);
+    assert_eq!(fs::read_to_string(dir.path().join("forwarding.secret")).unwrap(), "s3cret");
+
+    // The admin's forced hosts stay, without servers that aren't backends
+    let forced: Vec<_> = doc["forced-hosts"]
+        .as_table()
+        .unwrap()
+        .iter()
+        .map(|(host, servers)| {
+            let servers = servers.as_array().unwrap();
+            (host, servers.iter().map(|v| v.as_str().unwrap()).collect::<Vec<_>>())
+        })
+        .collect();
+    assert_eq!(forced, [("play.mynetwork.net", vec!["lobby"])]);
+}
+
+#[tokio::test]
+async fn writes_bungeecord_servers_and_listener() {
+    let dir = TempDir::new().unwrap();
+
+    configure_proxy(dir.path(), ServerType::Waterfall, 25565, &backends(), Some("unused"))
+        .await
+        .unwrap();
+
+    let config = fs::read_to_string(dir.path().join("config.yml")).unwrap();
+    assert!(config.contains("ip_forward: true"));
+    assert!(config.contains("address: \"127.0.0.1:25567\"") || config.contains("address: 127.0.0.1:25567"));
+    assert!(config.contains("0.0.0.0:25565"));
+}
+
+#[tokio::test]
+async fn joins_and_leaves_velocity_network() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("server.properties"), "motd=Lobby\nonline-mode=true\n").unwrap();
+
+    join_network(dir.path(), ServerType::Paper, 25566, ServerType::Velocity, "s3cret")
+        .await
+        .unwrap();
+
+    let properties = read_server_properties(&dir.path().join("server.properties")).await.unwrap();
+    assert_eq!(properties["online-mode"], "false");
+    assert_eq!(properties["server-ip"], "127.0.0.1");
+    assert_eq!(properties["server-port"], "25566");
+    assert_eq!(properties["motd"], "Lobby");
+
+    let paper = fs::read_to_string(dir.path().join("config/paper-global.yml")).unwrap();
+    assert!(paper.contains("enabled: true"));
+    assert!(paper.contains("secret: s3cret"));
+    assert!(fs::read_to_string(dir.path().join("spigot.yml")).unwrap().contains("bungeecord: false"));
+
+    leave_network(dir.path(), ServerType::Paper).await.unwrap();
+
+    let properties = read_server_properties(&dir.path().join("server.properties")).await.unwrap();
+    assert_eq!(properties["online-mode"], "true");
+    assert_eq!(properties["server-ip"], "");
+    let paper = fs::read_to_string(dir.path().join("config/paper-global.yml")).unwrap();
+    assert!(paper.contains("enabled: false"));
+}
+
+#[tokio::test]
+async fn restores_the_settings_a_backend_had_before_joining() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("server.properties"), "online-mode=false\nserver-ip=10.0.0.5\n").unwrap();
+
+    // Joining again as the network changes keeps the first saved settings
+    for _ in 0..2 {
+        join_network(dir.path(), ServerType::Paper, 25566, ServerType::Waterfall, "unused")
+            .await
+            .unwrap();
+    }
+    let properties = read_server_properties(&dir.path().join("server.properties")).await.unwrap();
+    assert_eq!(properties["server-ip"], "127.0.0.1");
+
+    leave_network(dir.path(), ServerType::Paper).await.unwrap();
+
+    let properties = read_server_properties(&dir.path().join("server.properties")).await.unwrap();
+    assert_eq!(properties["online-mode"], "false");
+    assert_eq!(properties["server-ip"], "10.0.0.5");
+
+    // Leaving forgets them, so the next join saves the settings of the time
+    fs::write(dir.path().join("server.properties"), "online-mode=true\n").unwrap();
+    join_network(dir.path(), ServerType::Paper, 25566, ServerType::Velocity, "s3cret")
+        .await
+        .unwrap();
+    leave_network(dir.path(), ServerType::Paper).await.unwrap();
+
+    let properties = read_server_properties(&dir.path().join("server.properties")).await.unwrap();
+    assert_eq!(properties["online-mode"], "true");
+    assert_eq!(properties["server-ip"], "");
+}
+
+#[test]
+fn rejects_backends_without_forwarding_support() {
+    assert!(check_backend(ServerType::Velocity, ServerType::Paper).is_ok());
+    assert!(check_backend(ServerType::BungeeCord, ServerType::Spigot).is_ok());
+    assert!(check_backend(ServerType::Velocity, ServerType::Spigot).is_err());
+    assert!(check_backend(ServerType::Velocity, ServerType::Vanilla).is_err());
+    assert!(check_backend(ServerType::Paper, ServerType::Paper).is_err());
+}
@@ -0,0 +1,21 @@
+//! Player identities the server's JSON lists are keyed by.
+
+use server_manager::{offline_uuid, resolve_player};
+
+#[test]
+fn derives_offline_uuids_like_the_server() {
+    // UUID.nameUUIDFromBytes("OfflinePlayer:Notch".getBytes())
+    assert_eq!(offline_uuid("Notch").to_string(), "b50ad385-829d-3141-a216-7e7d7539ba7f");
+    assert_eq!(offline_uuid("jeb_").to_string(), "a762f560-4fce-3236-812a-b80efff0b62b");
+    assert_eq!(offline_uuid("Notch").get_version_num(), 3);
+    assert_ne!(offline_uuid("notch"), offline_uuid("Notch"));
+}
+
+#[tokio::test]
+async fn resolves_offline_players_without_looking_them_up() {
+    let player = resolve_player("Notch", false).await.unwrap();
+    assert_eq!(player.uuid, offline_uuid("Notch"));
+    assert_eq!(player.name, "Notch");
+
+    assert!(resolve_player("not a name", false).await.is_err());
+}
@@ -0,0 +1,134 @@
+//! Installed plugins as their jars describe them, and removing them.
+
+use server_manager::{list_installed_plugins, read_plugin_metadata, remove_plugin, PluginPlatform};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_jar(path: &Path, entries: &[(&str, &str)]) {
+    let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    for (name, contents) in entries {
+        zip.start_file(*name, options).unwrap();
+        zip.write_all(contents.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+const PLUGIN_YML: &str = r#"
+name: EssentialsX
+version: 2.0
+main: com.earth2me.essentials.Essentials
+api-version: 1.13
+description: Provides essential commands
+author: md_5
+authors: [snowleo, ementalo]
+depend: Vault
+softdepend: [LuckPerms, PlaceholderAPI]
+commands:
+  home:
+    description: Teleport home
+  spawn: {}
+"#;
+
+#[test]
+fn reads_bukkit_plugin_yml() {
+    let dir = TempDir::new().unwrap();
+    let jar = dir.path().join("EssentialsX-2.0.jar");
+    write_jar(&jar, &[("plugin.yml", PLUGIN_YML)]);
+
+    let metadata = read_plugin_metadata(&jar).unwrap().unwrap();
+    assert_eq!(metadata.platform, Some(PluginPlatform::Bukkit));
+    assert_eq!(metadata.name, "EssentialsX");
+    // Unquoted, so a float to YAML
+    assert_eq!(metadata.version.as_deref(), Some("2.0"));
+    assert_eq!(metadata.api_version.as_deref(), Some("1.13"));
+    assert_eq!(metadata.authors, vec!["md_5", "snowleo", "ementalo"]);
+    assert_eq!(metadata.depends, vec!["Vault"]);
+    assert_eq!(metadata.soft_depends, vec!["LuckPerms", "PlaceholderAPI"]);
+    assert_eq!(metadata.commands, vec!["home", "spawn"]);
+}
+
+#[test]
+fn prefers_paper_plugin_yml() {
+    let dir = TempDir::new().unwrap();
+    let jar = dir.path().join("both.jar");
+    let paper = r#"
+name: Chunky
+version: '1.4.10'
+api-version: '1.20'
+dependencies:
+  bootstrap:
+    Vault:
+      required: true
+  server:
+    Vault:
+      load: BEFORE
+    WorldBorder:
+      required: false
+"#;
+    write_jar(&jar, &[("plugin.yml", PLUGIN_YML), ("paper-plugin.yml", paper)]);
+
+    let metadata = read_plugin_metadata(&jar).unwrap().unwrap();
+    assert_eq!(metadata.platform, Some(PluginPlatform::Paper));
+    assert_eq!(metadata.name, "Chunky");
+    assert_eq!(metadata.version.as_deref(), Some("1.4.10"));
+    assert_eq!(metadata.depends, vec!["Vault"]);
+    assert_eq!(metadata.soft_depends, vec!["WorldBorder"]);
+}
+
+#[test]
+fn reads_proxy_descriptors() {
+    let dir = TempDir::new().unwrap();
+
+    let bungee = dir.path().join("bungee.jar");
+    write_jar(&bungee, &[("bungee.yml", "name: LiteBans\nversion: 2.15\nauthor: ruan\ndepends: [LuckPerms]\nsoftDepends: [Geyser]\n")]);
+    let metadata = read_plugin_metadata(&bungee).unwrap().unwrap();
+    assert_eq!(metadata.platform, Some(PluginPlatform::BungeeCord));
+    assert_eq!(metadata.depends, vec!["LuckPerms"]);
+    assert_eq!(metadata.soft_depends, vec!["Geyser"]);
+
+    let velocity = dir.path().join("velocity.jar");
+    let json = r#"{"id":"luckperms","name":"LuckPerms","version":"5.4.141","authors":["Luck"],
+        "dependencies":[{"id":"placeholderapi","optional":true},{"id":"signedvelocity"}],"main":"me.lucko.Velocity"}"#;
+    write_jar(&velocity, &[("velocity-plugin.json", json)]);
+    let metadata = read_plugin_metadata(&velocity).unwrap().unwrap();
+    assert_eq!(metadata.platform, Some(PluginPlatform::Velocity));
+    assert_eq!(metadata.name, "LuckPerms");
+    assert_eq!(metadata.authors, vec!["Luck"]);
+    assert_eq!(metadata.depends, vec!["signedvelocity"]);
+    assert_eq!(metadata.soft_depends, vec!["placeholderapi"]);
+
+    let library = dir.path().join("library.jar");
+    write_jar(&library, &[("META-INF/MANIFEST.MF", "Manifest-Version: 1.0\n")]);
+    assert!(read_plugin_metadata(&library).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn lists_and_removes_plugins_by_file_and_declared_name() {
+    let dir = TempDir::new().unwrap();
+    let plugins = dir.path().join("plugins");
+    fs::create_dir_all(&plugins).unwrap();
+    write_jar(&plugins.join("EssentialsX-2.0.jar"), &[("plugin.yml", PLUGIN_YML)]);
+    write_jar(&plugins.join("library.jar"), &[("META-INF/MANIFEST.MF", "Manifest-Version: 1.0\n")]);
+    fs::write(plugins.join("broken.jar"), "not a zip").unwrap();
+
+    let installed = list_installed_plugins(dir.path()).await.unwrap();
+    let names: Vec<_> = installed.iter().map(|p| (p.file_name.as_str(), p.metadata.name.as_str())).collect();
+    assert_eq!(
+        names,
+        vec![("EssentialsX-2.0.jar", "EssentialsX"), ("broken.jar", "broken"), ("library.jar", "library")]
+    );
+    assert!(installed[1].metadata.platform.is_none());
+
+    remove_plugin(dir.path(), "library.jar").await.unwrap();
+    assert!(!plugins.join("library.jar").exists());
+
+    // The declared name finds the versioned jar
+    remove_plugin(dir.path(), "essentialsx").await.unwrap();
+    assert!(!plugins.join("EssentialsX-2.0.jar").exists());
+
+    assert!(remove_plugin(dir.path(), "EssentialsX").await.is_err());
+    assert!(remove_plugin(dir.path(), "../server.properties").await.is_err());
+}
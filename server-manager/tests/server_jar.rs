@@ -0,0 +1,77 @@
+//! Uploaded server jars: checking they run, and keeping the one they replace.
+
+use server_manager::{
+    inspect_jar, install_server_jar, rollback_server_jar, runs_server_jar, ServerType, PREVIOUS_SERVER_JAR,
+    SERVER_JAR,
+};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use tempfile::TempDir;
+
+fn jar(manifest: &str, entries: &[&str]) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("META-INF/MANIFEST.MF", options).unwrap();
+    zip.write_all(manifest.as_bytes()).unwrap();
+    for entry in entries {
+        zip.start_file(*entry, options).unwrap();
+        zip.write_all(b"{\"id\": \"1.21.1\"}").unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+#[test]
+fn reads_main_class_and_version() {
+    // Long values wrap onto continuation lines
+    let manifest = "Manifest-Version: 1.0\r\nMain-Class: io.papermc.paperclip.Pape\r\n rclip\r\nCreated-By: Gradle\r\n";
+    let data = jar(manifest, &["io/papermc/paperclip/Paperclip.class", "version.json"]);
+
+    let info = inspect_jar(&data).unwrap();
+    assert_eq!(info.main_class, "io.papermc.paperclip.Paperclip");
+    assert_eq!(info.version.as_deref(), Some("1.21.1"));
+    assert_eq!(info.sha256, format!("{:x}", Sha256::digest(&data)));
+}
+
+#[test]
+fn rejects_jars_that_wont_run() {
+    let library = jar("Manifest-Version: 1.0\n", &[]);
+    assert!(inspect_jar(&library).unwrap_err().to_string().contains("Main-Class"));
+
+    let missing_class = jar("Main-Class: com.example.Main\n", &[]);
+    assert!(inspect_jar(&missing_class).unwrap_err().to_string().contains("com.example.Main"));
+
+    assert!(inspect_jar(b"not a zip").is_err());
+}
+
+#[tokio::test]
+async fn keeps_previous_jar_for_rollback() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join(SERVER_JAR), "downloaded").unwrap();
+
+    install_server_jar(dir.path(), b"uploaded").await.unwrap();
+    assert_eq!(fs::read(dir.path().join(SERVER_JAR)).unwrap(), b"uploaded");
+    assert_eq!(fs::read(dir.path().join(PREVIOUS_SERVER_JAR)).unwrap(), b"downloaded");
+
+    rollback_server_jar(dir.path()).await.unwrap();
+    assert_eq!(fs::read(dir.path().join(SERVER_JAR)).unwrap(), b"downloaded");
+    assert_eq!(fs::read(dir.path().join(PREVIOUS_SERVER_JAR)).unwrap(), b"uploaded");
+
+    fs::remove_file(dir.path().join(PREVIOUS_SERVER_JAR)).unwrap();
+    assert!(rollback_server_jar(dir.path()).await.is_err());
+    assert_eq!(fs::read(dir.path().join(SERVER_JAR)).unwrap(), b"downloaded");
+}
+
+#[test]
+fn knows_which_servers_launch_from_the_jar() {
+    let dir = TempDir::new().unwrap();
+    assert!(runs_server_jar(ServerType::Paper, dir.path()));
+    assert!(!runs_server_jar(ServerType::Bedrock, dir.path()));
+
+    fs::write(dir.path().join("run.sh"), "java @libraries/net/minecraftforge/forge/unix_args.txt").unwrap();
+    assert!(!runs_server_jar(ServerType::Forge, dir.path()));
+    assert!(!runs_server_jar(ServerType::NeoForge, dir.path()));
+
+    // An admin's own start script doesn't make a Paper server a Forge one
+    assert!(runs_server_jar(ServerType::Paper, dir.path()));
+}
@@ -0,0 +1,74 @@
+//! When console-line triggers fire.
+
+use server_manager::{MatchRate, RateLimit};
+use std::time::{Duration, Instant};
+
+fn limit(threshold: u32, window: u64, cooldown: u64) -> RateLimit {
+    RateLimit {
+        threshold,
+        window: Duration::from_secs(window),
+        cooldown: Duration::from_secs(cooldown),
+    }
+}
+
+#[test]
+fn fires_once_the_threshold_is_reached_within_the_window() {
+    let limit = limit(3, 10, 0);
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    let mut rate = MatchRate::default();
+
+    assert!(!rate.record(&limit, at(0)));
+    assert!(!rate.record(&limit, at(4)));
+    assert!(rate.record(&limit, at(8)));
+
+    // Firing starts the count over
+    assert!(!rate.record(&limit, at(9)));
+    assert!(!rate.record(&limit, at(10)));
+    assert!(rate.record(&limit, at(11)));
+}
+
+#[test]
+fn forgets_matches_older_than_the_window() {
+    let limit = limit(2, 10, 0);
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    let mut rate = MatchRate::default();
+
+    assert!(!rate.record(&limit, at(0)));
+    assert!(!rate.record(&limit, at(11)));
+    assert!(rate.record(&limit, at(21)));
+}
+
+#[test]
+fn holds_off_until_the_cooldown_passes() {
+    let limit = limit(1, 60, 30);
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    let mut rate = MatchRate::default();
+
+    assert!(rate.record(&limit, at(0)));
+    assert!(!rate.record(&limit, at(1)));
+    assert!(!rate.record(&limit, at(29)));
+    assert!(rate.record(&limit, at(30)));
+}
+
+#[test]
+fn stays_in_cooldown_after_forgetting_matches() {
+    let limit = limit(2, 60, 30);
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    let mut rate = MatchRate::default();
+
+    assert!(!rate.record(&limit, at(0)));
+    assert!(rate.record(&limit, at(1)));
+
+    assert!(!rate.record(&limit, at(40)));
+    rate.forget_matches();
+    assert!(!rate.record(&limit, at(41)));
+    assert!(rate.record(&limit, at(42)));
+
+    rate.forget_matches();
+    assert!(!rate.record(&limit, at(50)));
+    assert!(!rate.record(&limit, at(51)));
+}
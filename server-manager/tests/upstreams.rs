@@ -0,0 +1,1218 @@
+//! Downloads and plugin installs against a local stand-in for the Paper,
+//! Purpur, Mojang, Fabric, NeoForge, BungeeCord Jenkins, Bedrock and Modrinth
+//! APIs, and modpack imports that download from them.
+
+use server_manager::{
+    check_addon_compatibility, check_addon_updates, download_server_jar, get_available_builds, get_available_versions, get_newer_builds, import_bedrock_server, install_mod,
+    install_modpack, install_plugin, list_addon_versions, plan_addon_install, read_modpack, rollback_addon_update, search_mods, search_plugins,
+    update_addons, ArtifactCache,
+    Compatibility, Progress, ServerType, UpstreamConfig, Upstreams,
+};
+use std::io::Write;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::time::Duration;
+use tempfile::TempDir;
+use wiremock::matchers::{body_partial_json, header_regex, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const JAR: &[u8] = b"PK\x03\x04 not really a paper jar";
+const PAPER_JAR_NAME: &str = "paper-1.21.1-130.jar";
+
+fn upstreams(server: &MockServer) -> Upstreams {
+    Upstreams::new(UpstreamConfig {
+        paper_api: format!("{}/v2/", server.uri()),
+        purpur_api: format!("{}/purpur", server.uri()),
+        spigot_buildtools_url: format!("{}/BuildTools.jar", server.uri()),
+        spigot_versions: format!("{}/spigot/versions/", server.uri()),
+        mojang_version_manifest: format!("{}/mojang/version_manifest_v2.json", server.uri()),
+        fabric_meta: format!("{}/fabric", server.uri()),
+        forge_maven: format!("{}/forge", server.uri()),
+        forge_promotions: format!("{}/forge/promotions_slim.json", server.uri()),
+        neoforge_maven: format!("{}/neoforge", server.uri()),
+        bungeecord_ci: format!("{}/jenkins/job/BungeeCord", server.uri()),
+        bedrock_download_url: format!("{}/bedrock/bedrock-server-{{version}}.zip", server.uri()),
+        bedrock_versions: format!("{}/bedrock/links", server.uri()),
+        modrinth_api: format!("{}/modrinth", server.uri()),
+        proxy: None,
+        connect_timeout: Duration::from_secs(2),
+        read_timeout: Duration::from_secs(5),
+    })
+    .unwrap()
+}
+
+/// A mock server and upstreams that reach it, with throwaway server and cache
+/// directories
+async fn setup() -> (MockServer, Upstreams, TempDir, ArtifactCache) {
+    let server = MockServer::start().await;
+    let upstreams = upstreams(&server);
+    let dir = TempDir::new().unwrap();
+    let cache = ArtifactCache::new(dir.path().join("cache"));
+    (server, upstreams, dir, cache)
+}
+
+async fn mock_paper(server: &MockServer, sha256: &str, jar_downloads: u64) {
+    Mock::given(method("GET"))
+        .and(path("/v2/projects/paper"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "versions": ["1.20.6", "1.21.1"]
+        })))
+        .mount(server)
+        .await;
+
+    let builds: Vec<_> = [120, 125, 130]
+        .into_iter()
+        .map(|build| {
+            serde_json::json!({
+                "build": build,
+                "channel": "default",
+                "downloads": {
+                    "application": {
+                        "name": format!("paper-1.21.1-{}.jar", build),
+                        "sha256": sha256
+                    }
+                }
+            })
+        })
+        .collect();
+
+    Mock::given(method("GET"))
+        .and(path("/v2/projects/paper/versions/1.21.1/builds"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "builds": builds
+        })))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/v2/projects/paper/versions/1.21.1/builds/130/downloads/{}",
+            PAPER_JAR_NAME
+        )))
+        .and(header_regex("user-agent", "^mineserv-manager/"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(JAR))
+        .expect(jar_downloads)
+        .mount(server)
+        .await;
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+#[tokio::test]
+async fn lists_paper_versions() {
+    let (server, upstreams, _dir, _cache) = setup().await;
+    mock_paper(&server, &sha256_hex(JAR), 0).await;
+
+    let versions = get_available_versions(&upstreams, ServerType::Paper).await.unwrap();
+    assert_eq!(versions, vec!["1.20.6", "1.21.1"]);
+}
+
+#[tokio::test]
+async fn downloads_latest_paper_build_once() {
+    let (server, upstreams, dir, cache) = setup().await;
+    mock_paper(&server, &sha256_hex(JAR), 1).await;
+
+    for name in ["one", "two"] {
+        let server_dir = dir.path().join(name);
+        std::fs::create_dir_all(&server_dir).unwrap();
+        let jar = server_dir.join("server.jar");
+
+        let build = download_server_jar(ServerType::Paper, "1.21.1", None, &jar, &upstreams, &cache, &Progress::none())
+            .await
+            .unwrap();
+        assert_eq!(build, "130");
+        assert_eq!(std::fs::read(&jar).unwrap(), JAR);
+    }
+
+    let artifacts = cache.list().await.unwrap();
+    assert_eq!(artifacts.len(), 1);
+    assert_eq!(artifacts[0].key, "paper/1.21.1/130");
+    assert_eq!(artifacts[0].file_name, PAPER_JAR_NAME);
+    assert_eq!(artifacts[0].sha256, sha256_hex(JAR));
+}
+
+#[tokio::test]
+async fn keeps_cached_jars_apart_from_installed_copies() {
+    let (server, upstreams, dir, cache) = setup().await;
+    mock_paper(&server, &sha256_hex(JAR), 2).await;
+    let download = |jar: std::path::PathBuf| {
+        let (upstreams, cache) = (&upstreams, &cache);
+        async move {
+            download_server_jar(ServerType::Paper, "1.21.1", None, &jar, upstreams, cache, &Progress::none())
+                .await
+                .unwrap();
+            std::fs::read(&jar).unwrap()
+        }
+    };
+
+    // Writing an installed jar in place leaves the cache alone
+    let first = dir.path().join("first.jar");
+    download(first.clone()).await;
+    std::fs::write(&first, b"patched in place").unwrap();
+    assert_eq!(download(dir.path().join("second.jar")).await, JAR);
+
+    // A damaged object is fetched again rather than handed out
+    let object = cache.root().join("objects").join(sha256_hex(JAR));
+    std::fs::write(&object, b"bit rot").unwrap();
+    assert_eq!(download(dir.path().join("third.jar")).await, JAR);
+    assert_eq!(std::fs::read(&object).unwrap(), JAR);
+}
+
+#[tokio::test]
+async fn rejects_paper_jar_with_wrong_checksum() {
+    let (server, upstreams, dir, cache) = setup().await;
+    mock_paper(&server, &sha256_hex(b"something else"), 1).await;
+
+    let jar = dir.path().join("server.jar");
+    let err = download_server_jar(ServerType::Paper, "1.21.1", None, &jar, &upstreams, &cache, &Progress::none())
+        .await
+        .unwrap_err();
+
+    assert!(format!("{:#}", err).contains("Checksum mismatch"), "{:#}", err);
+    assert!(!jar.exists());
+    assert!(cache.list().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn falls_back_to_cache_when_paper_api_fails() {
+    let (server, upstreams, dir, cache) = setup().await;
+    mock_paper(&server, &sha256_hex(JAR), 1).await;
+
+    let first = dir.path().join("first.jar");
+    download_server_jar(ServerType::Paper, "1.21.1", None, &first, &upstreams, &cache, &Progress::none())
+        .await
+        .unwrap();
+
+    server.reset().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let second = dir.path().join("second.jar");
+    download_server_jar(ServerType::Paper, "1.21.1", None, &second, &upstreams, &cache, &Progress::none())
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&second).unwrap(), JAR);
+
+    // Nothing cached for this version
+    let err = download_server_jar(
+        ServerType::Paper,
+        "1.20.6",
+        None,
+        &dir.path().join("third.jar"),
+        &upstreams,
+        &cache,
+        &Progress::none(),
+    )
+    .await
+    .unwrap_err();
+    assert!(format!("{:#}", err).contains("503"), "{:#}", err);
+}
+
+#[tokio::test]
+async fn lists_paper_builds() {
+    let (server, upstreams, _dir, _cache) = setup().await;
+    mock_paper(&server, &sha256_hex(JAR), 0).await;
+
+    let builds = get_available_builds(&upstreams, ServerType::Paper, "1.21.1").await.unwrap();
+    let ids: Vec<_> = builds.iter().map(|b| b.id.as_str()).collect();
+    assert_eq!(ids, vec!["120", "125", "130"]);
+    assert_eq!(builds[0].channel.as_deref(), Some("default"));
+}
+
+#[tokio::test]
+async fn offers_newer_paper_builds_on_the_same_channel() {
+    let (server, upstreams, _dir, _cache) = setup().await;
+
+    let build = |build: u32, channel: &str, summary: &str| {
+        serde_json::json!({
+            "build": build,
+            "channel": channel,
+            "changes": [{ "commit": format!("{:040}", build), "summary": summary, "message": summary }],
+            "downloads": { "application": { "name": format!("paper-1.21.1-{}.jar", build), "sha256": "00" } }
+        })
+    };
+    Mock::given(method("GET"))
+        .and(path("/v2/projects/paper/versions/1.21.1/builds"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "builds": [
+                build(120, "DEFAULT", "Update upstream"),
+                build(125, "EXPERIMENTAL", "Try a new chunk system"),
+                build(130, "DEFAULT", "Fix dupe"),
+            ]
+        })))
+        .mount(&server)
+        .await;
+
+    let newer = get_newer_builds(&upstreams, ServerType::Paper, "1.21.1", "120").await.unwrap();
+    assert_eq!(newer.channel.as_deref(), Some("default"));
+    let ids: Vec<_> = newer.builds.iter().map(|b| b.id.as_str()).collect();
+    assert_eq!(ids, vec!["130"]);
+    assert_eq!(newer.builds[0].changes[0].summary, "Fix dupe");
+
+    let newer = get_newer_builds(&upstreams, ServerType::Paper, "1.21.1", "125").await.unwrap();
+    assert_eq!(newer.channel.as_deref(), Some("experimental"));
+    assert_eq!(newer.builds.len(), 1);
+    assert!(get_newer_builds(&upstreams, ServerType::Paper, "1.21.1", "130").await.unwrap().builds.is_empty());
+
+    // Purpur's builds aren't tracked, so nothing is fetched
+    assert!(get_newer_builds(&upstreams, ServerType::Purpur, "1.21.1", "2300").await.unwrap().builds.is_empty());
+}
+
+#[tokio::test]
+async fn downloads_pinned_purpur_build() {
+    let (server, upstreams, dir, cache) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/purpur/1.21.1/2300"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "build": "2300",
+            "md5": format!("{:x}", Md5::digest(JAR))
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/purpur/1.21.1/2300/download"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(JAR))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let jar = dir.path().join("server.jar");
+    download_server_jar(ServerType::Purpur, "1.21.1", Some("2300"), &jar, &upstreams, &cache, &Progress::none())
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&jar).unwrap(), JAR);
+
+    let artifacts = cache.list().await.unwrap();
+    assert_eq!(artifacts[0].key, "purpur/1.21.1/2300");
+    assert_eq!(artifacts[0].file_name, "purpur-1.21.1-2300.jar");
+}
+
+#[tokio::test]
+async fn lists_spigot_versions_from_index() {
+    let (server, upstreams, _dir, _cache) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/spigot/versions/"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<html><body><pre><a href="../">../</a>
+<a href="1.10.2.json">1.10.2.json</a>
+<a href="1.21.1.json">1.21.1.json</a>
+<a href="1.21.json">1.21.json</a>
+<a href="1.9.json">1.9.json</a>
+<a href="1.21-pre1.json">1.21-pre1.json</a>
+<a href="4271.json">4271.json</a>
+</pre></body></html>"#,
+        ))
+        .mount(&server)
+        .await;
+
+    let versions = get_available_versions(&upstreams, ServerType::Spigot).await.unwrap();
+    assert_eq!(versions, vec!["1.9", "1.10.2", "1.21", "1.21.1"]);
+
+    let builds = get_available_builds(&upstreams, ServerType::Spigot, "1.21.1").await.unwrap();
+    let ids: Vec<_> = builds.iter().map(|b| b.id.as_str()).collect();
+    assert_eq!(ids, vec!["craftbukkit", "spigot"]);
+}
+
+#[tokio::test]
+async fn downloads_vanilla_release_from_manifest() {
+    let (server, upstreams, dir, cache) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/mojang/version_manifest_v2.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "versions": [
+                { "id": "24w33a", "type": "snapshot", "url": format!("{}/mojang/24w33a.json", server.uri()) },
+                { "id": "1.21.1", "type": "release", "url": format!("{}/mojang/1.21.1.json", server.uri()) },
+                { "id": "1.21", "type": "release", "url": format!("{}/mojang/1.21.json", server.uri()) }
+            ]
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/mojang/1.21.1.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "downloads": {
+                "server": {
+                    "sha1": format!("{:x}", Sha1::digest(JAR)),
+                    "url": format!("{}/mojang/server.jar", server.uri())
+                }
+            }
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/mojang/server.jar"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(JAR))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let versions = get_available_versions(&upstreams, ServerType::Vanilla).await.unwrap();
+    assert_eq!(versions, vec!["1.21", "1.21.1"]);
+
+    let jar = dir.path().join("server.jar");
+    download_server_jar(ServerType::Vanilla, "1.21.1", None, &jar, &upstreams, &cache, &Progress::none())
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&jar).unwrap(), JAR);
+    assert_eq!(cache.list().await.unwrap()[0].key, "vanilla/1.21.1/1.21.1");
+}
+
+#[tokio::test]
+async fn downloads_latest_stable_fabric_launcher() {
+    let (server, upstreams, dir, cache) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/fabric/versions/loader/1.21.1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "loader": { "version": "0.16.6-beta.1", "stable": false } },
+            { "loader": { "version": "0.16.5", "stable": true } },
+            { "loader": { "version": "0.16.4", "stable": true } }
+        ])))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/fabric/versions/installer"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "version": "1.0.1", "stable": true }
+        ])))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/fabric/versions/loader/1.21.1/0.16.5/1.0.1/server/jar"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(JAR))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let jar = dir.path().join("server.jar");
+    download_server_jar(ServerType::Fabric, "1.21.1", None, &jar, &upstreams, &cache, &Progress::none())
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&jar).unwrap(), JAR);
+    assert_eq!(cache.list().await.unwrap()[0].key, "fabric/1.21.1/0.16.5");
+
+    let builds = get_available_builds(&upstreams, ServerType::Fabric, "1.21.1").await.unwrap();
+    assert_eq!(builds.last().unwrap().id, "0.16.6-beta.1");
+    assert_eq!(builds.last().unwrap().channel.as_deref(), Some("beta"));
+}
+
+#[tokio::test]
+async fn downloads_last_successful_bungeecord_build() {
+    let (server, upstreams, dir, cache) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/jenkins/job/BungeeCord/api/json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "builds": [
+                { "number": 1902, "result": null },
+                { "number": 1901, "result": "SUCCESS" },
+                { "number": 1900, "result": "FAILURE" },
+                { "number": 1899, "result": "SUCCESS" }
+            ]
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/jenkins/job/BungeeCord/lastSuccessfulBuild/api/json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "number": 1901, "result": "SUCCESS"
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/jenkins/job/BungeeCord/1901/artifact/bootstrap/target/BungeeCord.jar"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(JAR))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let builds = get_available_builds(&upstreams, ServerType::BungeeCord, "latest").await.unwrap();
+    let ids: Vec<_> = builds.iter().map(|b| b.id.as_str()).collect();
+    assert_eq!(ids, ["1899", "1901"]);
+
+    let jar = dir.path().join("server.jar");
+    download_server_jar(ServerType::BungeeCord, "latest", None, &jar, &upstreams, &cache, &Progress::none())
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&jar).unwrap(), JAR);
+    assert_eq!(cache.list().await.unwrap()[0].key, "bungeecord/latest/1901");
+}
+
+/// A BDS zip with the server binary and its stock config
+fn bedrock_zip() -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    for (path, contents) in [
+        ("bedrock_server", b"\x7fELF".as_slice()),
+        ("server.properties", b"server-name=Dedicated Server\nserver-port=19132\n"),
+        ("allowlist.json", b"[]"),
+        ("behavior_packs/vanilla/manifest.json", b"{}"),
+    ] {
+        zip.start_file(path, options).unwrap();
+        zip.write_all(contents).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+#[tokio::test]
+async fn downloads_current_bedrock_server() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (server, upstreams, dir, cache) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/bedrock/links"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "result": { "links": [
+                { "downloadType": "serverBedrockWindows", "downloadUrl": "https://example.com/bin-win/bedrock-server-1.21.51.02.zip" },
+                { "downloadType": "serverBedrockLinux", "downloadUrl": "https://example.com/bin-linux/bedrock-server-1.21.51.02.zip" }
+            ] }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/bedrock/bedrock-server-1.21.51.02.zip"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(bedrock_zip()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let versions = get_available_versions(&upstreams, ServerType::Bedrock).await.unwrap();
+    assert_eq!(versions, ["1.21.51.02"]);
+
+    // Reinstalling over a configured server keeps its settings
+    let server_dir = dir.path().join("server");
+    std::fs::create_dir_all(&server_dir).unwrap();
+    std::fs::write(server_dir.join("server.properties"), "server-name=Mine\n").unwrap();
+
+    download_server_jar(
+        ServerType::Bedrock,
+        "1.21.51.02",
+        None,
+        &server_dir.join("server.jar"),
+        &upstreams,
+        &cache,
+        &Progress::none(),
+    )
+    .await
+    .unwrap();
+
+    let binary = server_dir.join("bedrock_server");
+    assert_eq!(std::fs::metadata(&binary).unwrap().permissions().mode() & 0o777, 0o755);
+    assert!(server_dir.join("behavior_packs/vanilla/manifest.json").exists());
+    assert!(server_dir.join("allowlist.json").exists());
+    assert!(!server_dir.join("bedrock-server.zip").exists());
+    assert_eq!(
+        std::fs::read_to_string(server_dir.join("server.properties")).unwrap(),
+        "server-name=Mine\n"
+    );
+}
+
+#[tokio::test]
+async fn installs_uploaded_bedrock_server_offline() {
+    let (_server, upstreams, dir, cache) = setup().await;
+
+    let error = import_bedrock_server(&cache, "1.20.81.01", mrpack(serde_json::json!({}), &[]))
+        .await
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("bedrock_server"));
+    assert!(import_bedrock_server(&cache, "../1.20", bedrock_zip()).await.is_err());
+
+    import_bedrock_server(&cache, "1.20.81.01", bedrock_zip()).await.unwrap();
+
+    let server_dir = dir.path().join("server");
+    std::fs::create_dir_all(&server_dir).unwrap();
+    download_server_jar(
+        ServerType::Bedrock,
+        "1.20.81.01",
+        None,
+        &server_dir.join("server.jar"),
+        &upstreams,
+        &cache,
+        &Progress::none(),
+    )
+    .await
+    .unwrap();
+
+    assert!(server_dir.join("bedrock_server").exists());
+    assert!(std::fs::read_to_string(server_dir.join("server.properties"))
+        .unwrap()
+        .contains("server-port=19132"));
+}
+
+#[tokio::test]
+async fn maps_neoforge_versions_to_minecraft() {
+    let (server, upstreams, _dir, _cache) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/neoforge/api/maven/versions/releases/net/neoforged/neoforge"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "isSnapshot": false,
+            "versions": ["20.4.237", "21.0.167", "21.1.9-beta", "21.1.77", "20.2.3-beta"]
+        })))
+        .mount(&server)
+        .await;
+
+    let versions = get_available_versions(&upstreams, ServerType::NeoForge).await.unwrap();
+    assert_eq!(versions, vec!["1.20.2", "1.20.4", "1.21", "1.21.1"]);
+
+    let builds = get_available_builds(&upstreams, ServerType::NeoForge, "1.21.1").await.unwrap();
+    let ids: Vec<_> = builds.iter().map(|b| (b.id.as_str(), b.channel.as_deref())).collect();
+    assert_eq!(ids, vec![("21.1.9-beta", Some("beta")), ("21.1.77", Some("release"))]);
+}
+
+#[tokio::test]
+async fn searches_mods_for_loader_and_version() {
+    let (server, upstreams, _dir, _cache) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/modrinth/search"))
+        .and(query_param(
+            "facets",
+            r#"[["categories:fabric"],["versions:1.21.1"],["project_type:mod"]]"#,
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "hits": [{
+                "title": "Lithium",
+                "description": "Server optimizations",
+                "author": "jellysquid3",
+                "project_id": "gvQqBUqZ"
+            }]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mods = search_mods(&upstreams, "lithium", ServerType::Fabric, "1.21.1").await.unwrap();
+    assert_eq!(mods[0].name, "Lithium");
+}
+
+#[tokio::test]
+async fn keeps_mods_and_plugins_apart() {
+    let (_server, upstreams, dir, cache) = setup().await;
+
+    let err = install_mod(&upstreams, dir.path(), "Lithium", None, "1.21.1", ServerType::Paper, &cache)
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "paper servers don't support mods");
+
+    let err = install_plugin(&upstreams, dir.path(), "LuckPerms", None, "1.21.1", ServerType::Forge, &cache)
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "forge servers don't support plugins");
+}
+
+/// LuckPerms, whose Bukkit release for 1.21.1 needs Vault, and Vault
+async fn mock_modrinth(server: &MockServer, plugin: &[u8]) {
+    let vault = b"Vault";
+    let file = |name: &str, data: &[u8]| {
+        serde_json::json!({
+            "url": format!("{}/files/{}", server.uri(), name),
+            "filename": name,
+            "hashes": { "sha512": format!("{:x}", Sha512::digest(data)) },
+            "primary": true
+        })
+    };
+
+    for (project, id, title) in [("luckperms", "Vebnzrzj", "LuckPerms"), ("vault", "vault-id", "Vault")] {
+        for key in [project, id] {
+            Mock::given(method("GET"))
+                .and(path(format!("/modrinth/project/{}", key)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": id, "title": title })))
+                .mount(server)
+                .await;
+        }
+    }
+
+    Mock::given(method("GET"))
+        .and(path("/modrinth/project/Vebnzrzj/version"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            {
+                "id": "lp-fabric",
+                "project_id": "Vebnzrzj",
+                "version_number": "5.4.140",
+                "game_versions": ["1.21.1"],
+                "loaders": ["fabric"],
+                "files": [file("LuckPerms-Fabric.jar", b"00")]
+            },
+            {
+                "id": "lp-bukkit",
+                "project_id": "Vebnzrzj",
+                "version_number": "5.4.140",
+                "game_versions": ["1.21.1"],
+                "loaders": ["bukkit", "paper", "spigot"],
+                "dependencies": [
+                    { "project_id": "vault-id", "version_id": null, "dependency_type": "required" },
+                    { "project_id": "placeholderapi", "version_id": null, "dependency_type": "optional" }
+                ],
+                "files": [file("LuckPerms-Bukkit.jar", plugin)]
+            },
+            {
+                "id": "lp-old",
+                "project_id": "Vebnzrzj",
+                "version_number": "5.3.0",
+                "game_versions": ["1.20.1"],
+                "loaders": ["bukkit"],
+                "files": [file("LuckPerms-Bukkit-5.3.jar", b"old")]
+            }
+        ])))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/modrinth/project/vault-id/version"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+            "id": "vault-1.7",
+            "project_id": "vault-id",
+            "version_number": "1.7.3",
+            "game_versions": ["1.21.1"],
+            "loaders": ["bukkit"],
+            "files": [file("Vault.jar", vault)]
+        }])))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/files/LuckPerms-Bukkit.jar"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(plugin.to_vec()))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/files/Vault.jar"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vault.to_vec()))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn searches_plugins() {
+    let (server, upstreams, _dir, _cache) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/modrinth/search"))
+        .and(query_param("query", "perms"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "hits": [{
+                "title": "LuckPerms",
+                "description": "A permissions plugin",
+                "author": "Luck",
+                "project_id": "Vebnzrzj"
+            }]
+        })))
+        .mount(&server)
+        .await;
+
+    let plugins = search_plugins(&upstreams, "perms", "paper").await.unwrap();
+    assert_eq!(plugins.len(), 1);
+    assert_eq!(plugins[0].name, "LuckPerms");
+    assert_eq!(plugins[0].author.as_deref(), Some("Luck"));
+}
+
+#[tokio::test]
+async fn installs_plugin_with_required_dependencies_through_cache() {
+    let (server, upstreams, dir, cache) = setup().await;
+    let plugin = b"LuckPerms for Bukkit";
+    mock_modrinth(&server, plugin).await;
+
+    for name in ["one", "two"] {
+        let server_dir = dir.path().join(name);
+        let plan = install_plugin(&upstreams, &server_dir, "luckperms", None, "1.21.1", ServerType::Paper, &cache)
+            .await
+            .unwrap();
+        let files: Vec<_> = plan.install.iter().map(|a| (a.file_name.as_str(), a.dependency)).collect();
+        assert_eq!(files, vec![("LuckPerms-Bukkit.jar", false), ("Vault.jar", true)]);
+
+        let installed = server_dir.join("plugins").join("LuckPerms-Bukkit.jar");
+        assert_eq!(std::fs::read(installed).unwrap(), plugin);
+        assert!(server_dir.join("plugins").join("Vault.jar").exists());
+    }
+
+    let artifacts = cache.list().await.unwrap();
+    assert_eq!(artifacts.len(), 2);
+    assert!(artifacts.iter().all(|a| a.key.starts_with("modrinth/")));
+}
+
+#[tokio::test]
+async fn installs_exact_plugin_version() {
+    let (server, upstreams, dir, cache) = setup().await;
+    mock_modrinth(&server, b"LuckPerms for Bukkit").await;
+
+    Mock::given(method("GET"))
+        .and(path("/modrinth/version/lp-old"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "lp-old",
+            "project_id": "Vebnzrzj",
+            "version_number": "5.3.0",
+            "game_versions": ["1.20.1"],
+            "loaders": ["bukkit"],
+            "files": [{
+                "url": format!("{}/files/LuckPerms-Bukkit-5.3.jar", server.uri()),
+                "filename": "LuckPerms-Bukkit-5.3.jar",
+                "hashes": { "sha512": format!("{:x}", Sha512::digest(b"old")) }
+            }]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/files/LuckPerms-Bukkit-5.3.jar"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(&b"old"[..]))
+        .mount(&server)
+        .await;
+
+    // Only releases for the server's loaders and version are offered...
+    let versions = list_addon_versions(&upstreams, "luckperms", ServerType::Paper, "1.21.1").await.unwrap();
+    let ids: Vec<_> = versions.iter().map(|v| v.id.as_str()).collect();
+    assert_eq!(ids, vec!["lp-bukkit"]);
+
+    // ...but any release can be asked for by id
+    install_plugin(&upstreams, dir.path(), "Vebnzrzj", Some("lp-old"), "1.21.1", ServerType::Paper, &cache)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(dir.path().join("plugins").join("LuckPerms-Bukkit-5.3.jar")).unwrap(), b"old");
+
+    let err = install_plugin(&upstreams, dir.path(), "vault", Some("lp-old"), "1.21.1", ServerType::Paper, &cache)
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Version lp-old isn't a release of Vault");
+}
+
+#[tokio::test]
+async fn detects_conflicts_with_installed_plugins() {
+    let (server, upstreams, dir, cache) = setup().await;
+    mock_modrinth(&server, b"LuckPerms for Bukkit").await;
+    let plugins = dir.path().join("plugins");
+    std::fs::create_dir_all(&plugins).unwrap();
+    std::fs::write(plugins.join("LuckPerms-5.3.jar"), b"old").unwrap();
+    std::fs::write(plugins.join("Vault-1.7.2.jar"), b"older vault").unwrap();
+
+    Mock::given(method("POST"))
+        .and(path("/modrinth/version_files"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            format!("{:x}", Sha512::digest(b"old")): {
+                "id": "lp-old", "project_id": "Vebnzrzj", "version_number": "5.3.0", "game_versions": ["1.20.1"]
+            },
+            format!("{:x}", Sha512::digest(b"older vault")): {
+                "id": "vault-1.7.2", "project_id": "vault-id", "version_number": "1.7.2", "game_versions": ["1.21.1"]
+            }
+        })))
+        .mount(&server)
+        .await;
+
+    let plan = plan_addon_install(&upstreams, dir.path(), "luckperms", None, "1.21.1", ServerType::Paper)
+        .await
+        .unwrap();
+    // Any Vault release will do for a dependency
+    assert_eq!(plan.satisfied, vec!["Vault-1.7.2.jar"]);
+    assert_eq!(plan.conflicts, vec!["LuckPerms-5.3.jar is 5.3.0 of Vebnzrzj, not 5.4.140; remove it first"]);
+
+    let err = install_plugin(&upstreams, dir.path(), "luckperms", None, "1.21.1", ServerType::Paper, &cache)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().starts_with("Can't install: "));
+    assert!(!plugins.join("LuckPerms-Bukkit.jar").exists());
+}
+
+#[tokio::test]
+async fn installs_nothing_when_a_dependency_fails_to_download() {
+    let (server, upstreams, dir, cache) = setup().await;
+    let plugin = b"LuckPerms for Bukkit";
+    let file = |name: &str, data: &[u8]| {
+        serde_json::json!({
+            "url": format!("{}/files/{}", server.uri(), name),
+            "filename": name,
+            "hashes": { "sha512": format!("{:x}", Sha512::digest(data)) }
+        })
+    };
+
+    // Vault's file is gone, but LuckPerms would download fine
+    Mock::given(method("GET"))
+        .and(path("/files/Vault.jar"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/modrinth/project/luckperms"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": "Vebnzrzj", "title": "LuckPerms" })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/modrinth/project/Vebnzrzj/version"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+            "id": "lp-bukkit", "project_id": "Vebnzrzj", "version_number": "5.4.140",
+            "game_versions": ["1.21.1"], "loaders": ["paper"],
+            "dependencies": [{ "project_id": "vault-id", "dependency_type": "required" }],
+            "files": [file("LuckPerms-Bukkit.jar", plugin)]
+        }])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/modrinth/project/vault-id/version"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+            "id": "vault-1.7", "project_id": "vault-id", "version_number": "1.7.3",
+            "game_versions": ["1.21.1"], "loaders": ["bukkit"],
+            "files": [file("Vault.jar", b"Vault")]
+        }])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/files/LuckPerms-Bukkit.jar"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(plugin.to_vec()))
+        .mount(&server)
+        .await;
+
+    assert!(install_plugin(&upstreams, dir.path(), "luckperms", None, "1.21.1", ServerType::Paper, &cache)
+        .await
+        .is_err());
+    assert!(!dir.path().join("plugins").join("LuckPerms-Bukkit.jar").exists());
+}
+
+#[tokio::test]
+async fn refuses_addon_file_names_outside_the_plugins_directory() {
+    let (server, upstreams, dir, cache) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/modrinth/project/evil"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": "evil-id", "title": "Evil" })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/modrinth/project/evil-id/version"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+            "id": "evil-1", "project_id": "evil-id", "version_number": "1.0",
+            "game_versions": ["1.21.1"], "loaders": ["paper"],
+            "files": [{
+                "url": format!("{}/files/evil.jar", server.uri()),
+                "filename": "../server.jar",
+                "hashes": { "sha512": null }
+            }]
+        }])))
+        .mount(&server)
+        .await;
+
+    let err = install_plugin(&upstreams, dir.path(), "evil", None, "1.21.1", ServerType::Paper, &cache)
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Refusing add-on file name '../server.jar'");
+    assert!(!dir.path().join("server.jar").exists());
+}
+
+#[tokio::test]
+async fn reports_missing_plugin() {
+    let (server, upstreams, dir, cache) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/modrinth/project/Nope"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let err = install_plugin(&upstreams, dir.path(), "Nope", None, "1.21.1", ServerType::Paper, &cache)
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Plugin 'Nope' not found");
+}
+
+#[tokio::test]
+async fn refuses_plugins_on_vanilla() {
+    let (_server, upstreams, dir, cache) = setup().await;
+
+    let err = install_plugin(&upstreams, dir.path(), "LuckPerms", None, "1.21.1", ServerType::Vanilla, &cache)
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "vanilla servers don't support plugins");
+}
+
+#[tokio::test]
+async fn checks_installed_plugins_against_new_version() {
+    let (server, upstreams, dir, _cache) = setup().await;
+    let plugins = dir.path().join("plugins");
+    std::fs::create_dir_all(&plugins).unwrap();
+    let [luckperms, old_plugin, homemade] = [&b"LuckPerms"[..], b"Abandoned", b"Homemade"];
+    std::fs::write(plugins.join("LuckPerms.jar"), luckperms).unwrap();
+    std::fs::write(plugins.join("Abandoned.jar"), old_plugin).unwrap();
+    std::fs::write(plugins.join("Homemade.jar"), homemade).unwrap();
+    let sha512 = |data: &[u8]| format!("{:x}", Sha512::digest(data));
+
+    Mock::given(method("POST"))
+        .and(path("/modrinth/version_files"))
+        .and(body_partial_json(serde_json::json!({ "algorithm": "sha512" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            sha512(luckperms): { "project_id": "Vebnzrzj", "version_number": "5.4.130", "game_versions": ["1.21"] },
+            sha512(old_plugin): { "project_id": "AbCdEf12", "version_number": "1.0", "game_versions": ["1.20.4"] },
+        })))
+        .mount(&server)
+        .await;
+
+    // Modrinth falls back to the newest release for other versions too
+    Mock::given(method("POST"))
+        .and(path("/modrinth/version_files/update"))
+        .and(body_partial_json(serde_json::json!({ "game_versions": ["1.21.1"] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            sha512(luckperms): { "project_id": "Vebnzrzj", "version_number": "5.4.140", "game_versions": ["1.21", "1.21.1"] },
+            sha512(old_plugin): { "project_id": "AbCdEf12", "version_number": "1.0", "game_versions": ["1.20.4"] },
+        })))
+        .mount(&server)
+        .await;
+
+    let addons = check_addon_compatibility(&upstreams, dir.path(), ServerType::Paper, "1.21.1")
+        .await
+        .unwrap();
+    let by_name = |name: &str| addons.iter().find(|a| a.file_name == name).unwrap();
+
+    let luckperms = by_name("LuckPerms.jar");
+    assert_eq!(luckperms.compatibility, Compatibility::Compatible);
+    assert_eq!(luckperms.installed_version.as_deref(), Some("5.4.130"));
+    assert_eq!(luckperms.compatible_version.as_deref(), Some("5.4.140"));
+    assert_eq!(by_name("Abandoned.jar").compatibility, Compatibility::Incompatible);
+    assert_eq!(by_name("Homemade.jar").compatibility, Compatibility::Unknown);
+}
+
+#[tokio::test]
+async fn updates_plugins_by_hash_and_rolls_back() {
+    let (server, upstreams, dir, cache) = setup().await;
+    let plugins = dir.path().join("plugins");
+    std::fs::create_dir_all(&plugins).unwrap();
+    let [old_lp, new_lp, vault, homemade] = [&b"LuckPerms 5.4.130"[..], b"LuckPerms 5.4.140", b"Vault", b"Homemade"];
+    std::fs::write(plugins.join("LuckPerms-5.4.130.jar"), old_lp).unwrap();
+    std::fs::write(plugins.join("Vault.jar"), vault).unwrap();
+    std::fs::write(plugins.join("Homemade.jar"), homemade).unwrap();
+    let sha512 = |data: &[u8]| format!("{:x}", Sha512::digest(data));
+    let release = |id: &str, project: &str, number: &str, name: &str, data: &[u8]| {
+        serde_json::json!({
+            "id": id, "project_id": project, "version_number": number,
+            "game_versions": ["1.21.1"], "loaders": ["paper"],
+            "files": [{
+                "url": format!("{}/files/{}", server.uri(), name),
+                "filename": name,
+                "hashes": { "sha512": sha512(data) },
+                "primary": true
+            }]
+        })
+    };
+
+    Mock::given(method("POST"))
+        .and(path("/modrinth/version_files"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            sha512(old_lp): release("lp-130", "Vebnzrzj", "5.4.130", "LuckPerms-5.4.130.jar", old_lp),
+            sha512(new_lp): release("lp-140", "Vebnzrzj", "5.4.140", "LuckPerms-5.4.140.jar", new_lp),
+            sha512(vault): release("vault-1.7", "vault-id", "1.7.3", "Vault.jar", vault),
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/modrinth/version_files/update"))
+        .and(body_partial_json(serde_json::json!({ "game_versions": ["1.21.1"] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            sha512(old_lp): release("lp-140", "Vebnzrzj", "5.4.140", "LuckPerms-5.4.140.jar", new_lp),
+            sha512(new_lp): release("lp-140", "Vebnzrzj", "5.4.140", "LuckPerms-5.4.140.jar", new_lp),
+            sha512(vault): release("vault-1.7", "vault-id", "1.7.3", "Vault.jar", vault),
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/files/LuckPerms-5.4.140.jar"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(new_lp.to_vec()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let updates = check_addon_updates(&upstreams, dir.path(), ServerType::Paper, "1.21.1").await.unwrap();
+    let summary: Vec<_> = updates
+        .iter()
+        .map(|u| (u.file_name.as_str(), u.installed_version.as_deref(), u.latest_version.as_deref(), u.update_available))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Homemade.jar", None, None, false),
+            ("LuckPerms-5.4.130.jar", Some("5.4.130"), Some("5.4.140"), true),
+            ("Vault.jar", Some("1.7.3"), Some("1.7.3"), false),
+        ]
+    );
+
+    let err = update_addons(
+        &upstreams,
+        &cache,
+        dir.path(),
+        ServerType::Paper,
+        "1.21.1",
+        Some("Homemade.jar"),
+        &Progress::none(),
+    )
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Homemade.jar isn't a Modrinth release for paper 1.21.1");
+
+    let updated = update_addons(
+        &upstreams,
+        &cache,
+        dir.path(),
+        ServerType::Paper,
+        "1.21.1",
+        None,
+        &Progress::none(),
+    )
+        .await
+        .unwrap();
+    assert_eq!(updated.len(), 1);
+    assert_eq!(updated[0].previous_file, "LuckPerms-5.4.130.jar");
+    assert_eq!(updated[0].version, "5.4.140");
+    assert_eq!(std::fs::read(plugins.join("LuckPerms-5.4.140.jar")).unwrap(), new_lp);
+    assert!(!plugins.join("LuckPerms-5.4.130.jar").exists());
+
+    let updates = check_addon_updates(&upstreams, dir.path(), ServerType::Paper, "1.21.1").await.unwrap();
+    let lp = updates.iter().find(|u| u.file_name == "LuckPerms-5.4.140.jar").unwrap();
+    assert!(!lp.update_available);
+    assert!(lp.can_rollback);
+
+    // A release whose file would land outside plugins/ is refused
+    server.reset().await;
+    let mut evil = release("vault-1.8", "vault-id", "1.8.0", "Vault.jar", b"Vault 1.8");
+    evil["files"][0]["filename"] = "../server.jar".into();
+    Mock::given(method("POST"))
+        .and(path("/modrinth/version_files"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            sha512(vault): release("vault-1.7", "vault-id", "1.7.3", "Vault.jar", vault),
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/modrinth/version_files/update"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ sha512(vault): evil })))
+        .mount(&server)
+        .await;
+    let err = update_addons(
+        &upstreams,
+        &cache,
+        dir.path(),
+        ServerType::Paper,
+        "1.21.1",
+        Some("Vault.jar"),
+        &Progress::none(),
+    )
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Refusing add-on file name '../server.jar'");
+    assert!(plugins.join("Vault.jar").exists());
+
+    rollback_addon_update(dir.path(), ServerType::Paper, "LuckPerms-5.4.140.jar").await.unwrap();
+    assert_eq!(std::fs::read(plugins.join("LuckPerms-5.4.130.jar")).unwrap(), old_lp);
+    assert!(!plugins.join("LuckPerms-5.4.140.jar").exists());
+    assert!(rollback_addon_update(dir.path(), ServerType::Paper, "LuckPerms-5.4.140.jar").await.is_err());
+}
+
+/// A `.mrpack` with the given index and `(path, contents)` entries
+fn mrpack(index: serde_json::Value, entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("modrinth.index.json", options).unwrap();
+    zip.write_all(index.to_string().as_bytes()).unwrap();
+    for (path, contents) in entries {
+        zip.start_file(*path, options).unwrap();
+        zip.write_all(contents).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+#[tokio::test]
+async fn installs_modpack_files_and_overrides() {
+    let (server, upstreams, dir, cache) = setup().await;
+    let sodium = b"client only";
+    let lithium = b"PK\x03\x04 lithium";
+
+    Mock::given(method("GET"))
+        .and(path("/cdn/lithium.jar"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(lithium.as_slice()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let index = serde_json::json!({
+        "formatVersion": 1,
+        "game": "minecraft",
+        "versionId": "1.2.0",
+        "name": "Speedy",
+        "files": [
+            {
+                "path": "mods/lithium.jar",
+                "hashes": { "sha1": "unused", "sha512": format!("{:x}", Sha512::digest(lithium)) },
+                "env": { "client": "required", "server": "required" },
+                "downloads": [
+                    format!("{}/mirror-down/lithium.jar", server.uri()),
+                    format!("{}/cdn/lithium.jar", server.uri())
+                ],
+                "fileSize": lithium.len()
+            },
+            {
+                "path": "mods/sodium.jar",
+                "hashes": { "sha1": "unused", "sha512": format!("{:x}", Sha512::digest(sodium)) },
+                "env": { "client": "required", "server": "unsupported" },
+                "downloads": [format!("{}/cdn/sodium.jar", server.uri())],
+                "fileSize": sodium.len()
+            }
+        ],
+        "dependencies": { "minecraft": "1.21.1", "fabric-loader": "0.16.5" }
+    });
+    let data = mrpack(index, &[
+        ("overrides/config/lithium.properties", b"mixin.ai=true"),
+        ("overrides/server.properties", b"motd=Speedy"),
+        ("server-overrides/server.properties", b"motd=Speedy server"),
+    ]);
+
+    let modpack = read_modpack(data).unwrap();
+    assert_eq!(modpack.server_type, ServerType::Fabric);
+    assert_eq!(modpack.minecraft_version, "1.21.1");
+    assert_eq!(modpack.loader_version.as_deref(), Some("0.16.5"));
+
+    let server_dir = dir.path().join("server");
+    install_modpack(&upstreams, &cache, &server_dir, &modpack, &Progress::none())
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(server_dir.join("mods/lithium.jar")).unwrap(), lithium);
+    assert!(!server_dir.join("mods/sodium.jar").exists());
+    assert_eq!(
+        std::fs::read_to_string(server_dir.join("config/lithium.properties")).unwrap(),
+        "mixin.ai=true"
+    );
+    assert_eq!(
+        std::fs::read_to_string(server_dir.join("server.properties")).unwrap(),
+        "motd=Speedy server"
+    );
+}
+
+#[tokio::test]
+async fn rejects_modpack_files_outside_server() {
+    let index = serde_json::json!({
+        "formatVersion": 1,
+        "game": "minecraft",
+        "versionId": "1.0.0",
+        "name": "Sneaky",
+        "files": [{
+            "path": "../../.ssh/authorized_keys",
+            "hashes": { "sha1": "unused", "sha512": "00" },
+            "downloads": ["https://cdn.modrinth.com/data/x/keys"],
+            "fileSize": 1
+        }],
+        "dependencies": { "minecraft": "1.21.1" }
+    });
+
+    let error = read_modpack(mrpack(index, &[])).err().unwrap();
+    assert!(error.to_string().contains("outside the server"));
+}
@@ -0,0 +1,30 @@
+//! World backups and restoring them.
+
+use server_manager::{backup_world, restore_world, Progress};
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn restores_world_from_backup() {
+    let dir = TempDir::new().unwrap();
+    let world = dir.path().join("world");
+    fs::create_dir_all(world.join("region")).unwrap();
+    fs::write(world.join("level.dat"), "1.21.1").unwrap();
+    fs::write(world.join("region").join("r.0.0.mca"), "chunks").unwrap();
+
+    let backup = backup_world(dir.path(), "world", &Progress::none()).await.unwrap();
+
+    // What a newer version would rewrite
+    fs::write(world.join("level.dat"), "1.21.4").unwrap();
+    fs::write(world.join("region").join("r.1.0.mca"), "more chunks").unwrap();
+
+    restore_world(dir.path(), "world", &backup, &Progress::none()).unwrap();
+    assert_eq!(fs::read_to_string(world.join("level.dat")).unwrap(), "1.21.1");
+    assert_eq!(fs::read_to_string(world.join("region").join("r.0.0.mca")).unwrap(), "chunks");
+    assert!(!world.join("region").join("r.1.0.mca").exists());
+    assert!(!dir.path().join(".restore-world").exists());
+
+    // A backup of something else leaves the world alone
+    assert!(restore_world(dir.path(), "world_nether", &backup, &Progress::none()).is_err());
+    assert!(world.join("level.dat").exists());
+}